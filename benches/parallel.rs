@@ -0,0 +1,38 @@
+//! Compares sequential and parallel extraction of a synthetic jar comparable to rt.jar, with
+//! 20000 compressible entries of up to 16 KiB.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use jars::{jar, JarOptionBuilder, JarWriter};
+
+fn synthetic_jar() -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("jars-bench-{}.jar", std::process::id()));
+    let mut writer = JarWriter::create(&path).unwrap();
+
+    for i in 0..20_000usize {
+        let content = format!("class Entry{} {{ int value = {}; }}\n", i, i).repeat(i % 512);
+        writer.write_entry(&format!("pkg{}/Entry{}.class", i % 100, i), content.as_bytes()).unwrap();
+    }
+
+    writer.finish().unwrap();
+    path
+}
+
+fn parallel_extraction(c: &mut Criterion) {
+    let path = synthetic_jar();
+    let mut group = c.benchmark_group("extraction");
+
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(std::fs::metadata(&path).unwrap().len()));
+
+    for threads in [1, 2, 4, 8] {
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, threads| {
+            b.iter(|| jar(&path, JarOptionBuilder::builder().parallel(*threads).build()).unwrap());
+        });
+    }
+
+    group.finish();
+    std::fs::remove_file(path).ok();
+}
+
+criterion_group!(benches, parallel_extraction);
+criterion_main!(benches);
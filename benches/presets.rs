@@ -0,0 +1,45 @@
+//! Compares the default extraction options against the `fast` and `safe` presets on a synthetic
+//! jar with 10000 compressible entries of up to 16 KiB.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use jars::{jar, JarOptionBuilder, JarWriter};
+
+fn synthetic_jar() -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("jars-bench-presets-{}.jar", std::process::id()));
+    let mut writer = JarWriter::create(&path).unwrap();
+
+    for i in 0..10_000usize {
+        // Varying values keep the compression ratio realistic, below the limit of the safe preset.
+        let content = (0..i % 512)
+            .map(|j| format!("class Entry{} {{ int value = {}; }}\n", i, (i * 512 + j).wrapping_mul(2654435761) % 1000003))
+            .collect::<String>();
+        writer.write_entry(&format!("pkg{}/Entry{}.class", i % 100, i), content.as_bytes()).unwrap();
+    }
+
+    writer.finish().unwrap();
+    path
+}
+
+fn presets(c: &mut Criterion) {
+    let path = synthetic_jar();
+    let mut group = c.benchmark_group("presets");
+
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(std::fs::metadata(&path).unwrap().len()));
+
+    group.bench_function("default", |b| {
+        b.iter(|| jar(&path, JarOptionBuilder::default()).unwrap());
+    });
+    group.bench_function("fast", |b| {
+        b.iter(|| jar(&path, JarOptionBuilder::fast().build()).unwrap());
+    });
+    group.bench_function("safe", |b| {
+        b.iter(|| jar(&path, JarOptionBuilder::safe().build()).unwrap());
+    });
+
+    group.finish();
+    std::fs::remove_file(path).ok();
+}
+
+criterion_group!(benches, presets);
+criterion_main!(benches);
@@ -0,0 +1,53 @@
+//! Compares reading large entries through the jar read path, which reserves declared sizes and
+//! reads in bulk, against collecting bytes one at a time with `Read::bytes`.
+
+use std::fs::File;
+use std::io::Read;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use jars::{jar, JarOptionBuilder, JarWriter};
+use zip::ZipArchive;
+
+const ENTRY_SIZE: usize = 4 * 1024 * 1024;
+
+fn large_jar() -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("jars-bench-read-{}.jar", std::process::id()));
+    let mut writer = JarWriter::create(&path).unwrap();
+
+    for i in 0..8usize {
+        let content = (0..ENTRY_SIZE).map(|j| (j * 31 + i) as u8 % 64).collect::<Vec<_>>();
+        writer.write_entry(&format!("assets/blob{}.bin", i), &content).unwrap();
+    }
+
+    writer.finish().unwrap();
+    path
+}
+
+fn large_entries(c: &mut Criterion) {
+    let path = large_jar();
+    let mut group = c.benchmark_group("large_entries");
+
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(8 * ENTRY_SIZE as u64));
+
+    group.bench_function("jar", |b| {
+        b.iter(|| jar(&path, JarOptionBuilder::default()).unwrap());
+    });
+    // The read path this crate used before reserving buffers, kept as the baseline.
+    #[allow(clippy::unbuffered_bytes)]
+    group.bench_function("bytes", |b| {
+        b.iter(|| {
+            let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+
+            (0..archive.len())
+                .map(|index| archive.by_index(index).unwrap().bytes().collect::<Result<Vec<_>, _>>().unwrap())
+                .collect::<Vec<_>>()
+        });
+    });
+
+    group.finish();
+    std::fs::remove_file(path).ok();
+}
+
+criterion_group!(benches, large_entries);
+criterion_main!(benches);
@@ -0,0 +1,194 @@
+//! Layouts of Android libraries (`.aar`) and application packages (`.apk`).
+
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::{jar_from_bytes, Jar, JarError, JarOption};
+#[cfg(feature = "fs")]
+use crate::{jar, JarOptionBuilder};
+
+/// Path of the manifest in both Android libraries and application packages. The manifest of an
+/// application package is compiled into binary XML.
+pub const ANDROID_MANIFEST_PATH: &str = "AndroidManifest.xml";
+
+/// An Android library, whose classes are packaged in a nested `classes.jar` next to `res`
+/// resources and the Android manifest.
+///
+/// # Example
+///
+/// ```rs
+/// let aar = aar("library.aar")?;
+/// let classes = aar.classes_jar(JarOptionBuilder::builder().ext("class").build())?;
+/// ```
+pub struct AndroidLibrary {
+    archive: Jar,
+}
+
+impl AndroidLibrary {
+    /// Wraps an extracted Android library.
+    pub fn new(archive: Jar) -> Self {
+        Self {
+            archive,
+        }
+    }
+
+    /// Returns the extracted archive with all files under their original paths.
+    pub fn archive(&self) -> &Jar {
+        &self.archive
+    }
+
+    /// Extracts the nested `classes.jar`.
+    pub fn classes_jar(&self, option: JarOption) -> Result<Jar, JarError> {
+        self.library("classes.jar", option)
+    }
+
+    /// Iterates over paths of additional library jars under `libs`, sorted.
+    pub fn libraries(&self) -> impl Iterator<Item = &str> {
+        let mut paths = self.archive.files.keys()
+            .filter(|path| path.starts_with("libs/") && path.ends_with(".jar"))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        paths.sort();
+        paths.into_iter()
+    }
+
+    /// Extracts the nested jar at full qualified path `path`, e.g. `classes.jar` or a path from
+    /// [AndroidLibrary::libraries].
+    pub fn library(&self, path: &str, option: JarOption) -> Result<Jar, JarError> {
+        let content = self.archive.files.get(path).ok_or_else(|| JarError::EntryNotFound(path.to_string()))?;
+
+        jar_from_bytes(content, option)
+    }
+
+    /// Iterates over Android resources under `res`, with full qualified paths.
+    pub fn resources(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        prefixed(&self.archive, "res/")
+    }
+
+    /// Returns the plain text Android manifest.
+    pub fn android_manifest(&self) -> Option<&[u8]> {
+        self.archive.files.get(ANDROID_MANIFEST_PATH).map(Vec::as_slice)
+    }
+}
+
+/// An Android application package, whose classes are compiled into `classes.dex`,
+/// `classes2.dex`, ... files.
+///
+/// # Example
+///
+/// ```rs
+/// let apk = apk("app.apk", JarOptionBuilder::builder().ext("dex").target("assets").build())?;
+///
+/// for (name, dex) in apk.dex_files() {
+///     // ...
+/// }
+/// ```
+pub struct AndroidPackage {
+    archive: Jar,
+}
+
+impl AndroidPackage {
+    /// Wraps an extracted Android application package.
+    pub fn new(archive: Jar) -> Self {
+        Self {
+            archive,
+        }
+    }
+
+    /// Returns the extracted archive with all files under their original paths.
+    pub fn archive(&self) -> &Jar {
+        &self.archive
+    }
+
+    /// Iterates over `classes*.dex` files at the package root in loading order, i.e. `classes.dex`
+    /// first and then `classes2.dex`, `classes3.dex`, ... numerically.
+    pub fn dex_files(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        let mut dex_files = self.archive.files.iter()
+            .filter_map(|(path, content)| Some((dex_index(path)?, path.as_str(), content.as_slice())))
+            .collect::<Vec<_>>();
+
+        dex_files.sort_by_key(|(index, _, _)| *index);
+        dex_files.into_iter().map(|(_, path, content)| (path, content))
+    }
+
+    /// Iterates over raw assets under `assets`, with full qualified paths.
+    pub fn assets(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        prefixed(&self.archive, "assets/")
+    }
+
+    /// Returns the Android manifest in binary XML.
+    pub fn android_manifest(&self) -> Option<&[u8]> {
+        self.archive.files.get(ANDROID_MANIFEST_PATH).map(Vec::as_slice)
+    }
+}
+
+/// Extracts the Android library at `path` with all its files.
+#[cfg(feature = "fs")]
+pub fn aar<P>(path: P) -> Result<AndroidLibrary, JarError> where P: AsRef<Path> {
+    jar(path, JarOptionBuilder::default()).map(AndroidLibrary::new)
+}
+
+/// Extracts the Android application package at `path`. The extraction behaviour is defined by
+/// parameter `option`, see [jar].
+#[cfg(feature = "fs")]
+pub fn apk<P>(path: P, option: JarOption) -> Result<AndroidPackage, JarError> where P: AsRef<Path> {
+    jar(path, option).map(AndroidPackage::new)
+}
+
+fn prefixed<'a>(archive: &'a Jar, prefix: &'a str) -> impl Iterator<Item = (&'a str, &'a [u8])> {
+    archive.files.iter()
+        .filter(move |(path, _)| path.starts_with(prefix))
+        .map(|(path, content)| (path.as_str(), content.as_slice()))
+}
+
+/// Returns the loading index of a root level dex file, where `classes.dex` is 1.
+fn dex_index(path: &str) -> Option<u32> {
+    let index = path.strip_prefix("classes")?.strip_suffix(".dex")?;
+
+    if index.is_empty() {
+        Some(1)
+    } else {
+        index.parse().ok().filter(|index| *index > 1)
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use crate::fixture::{jar_bytes, jar_file};
+    use crate::{aar, apk, JarOptionBuilder};
+
+    #[test]
+    fn test_aar() {
+        let aar = aar(jar_file(&[
+            ("AndroidManifest.xml", b"<manifest/>"),
+            ("classes.jar", &jar_bytes(&[("a/A.class", b"a")])),
+            ("libs/extra.jar", &jar_bytes(&[])),
+            ("res/values/values.xml", b"<resources/>"),
+        ])).unwrap();
+
+        assert_eq!(aar.classes_jar(JarOptionBuilder::default()).unwrap().files["a/A.class"], b"a");
+        assert_eq!(aar.libraries().collect::<Vec<_>>(), vec!["libs/extra.jar"]);
+        assert_eq!(aar.resources().collect::<Vec<_>>(), vec![("res/values/values.xml", b"<resources/>".as_slice())]);
+        assert_eq!(aar.android_manifest(), Some(b"<manifest/>".as_slice()));
+    }
+
+    #[test]
+    fn test_apk() {
+        let path = jar_file(&[
+            ("classes10.dex", b"10"),
+            ("classes2.dex", b"2"),
+            ("classes.dex", b"1"),
+            ("lib/x86/libnative.so", b""),
+            ("assets/data.bin", b"data"),
+        ]);
+
+        let package = apk(&path, JarOptionBuilder::default()).unwrap();
+        assert_eq!(package.dex_files().map(|(name, _)| name).collect::<Vec<_>>(), vec!["classes.dex", "classes2.dex", "classes10.dex"]);
+        assert_eq!(package.assets().collect::<Vec<_>>(), vec![("assets/data.bin", b"data".as_slice())]);
+
+        let package = apk(&path, JarOptionBuilder::builder().target("assets").build()).unwrap();
+        assert_eq!(package.dex_files().count(), 0);
+        assert_eq!(package.assets().count(), 1);
+    }
+}
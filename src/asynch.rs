@@ -0,0 +1,64 @@
+//! Async counterparts of the extraction entry points, available with the `async` feature. Extraction
+//! runs on tokio's blocking thread pool so file IO and decompression never block the async runtime.
+//!
+//! # Example
+//!
+//! ```rs
+//! let jar = jars::asynch::jar("sample/rt.jar", JarOptionBuilder::default()).await?;
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::{Jar, JarError, JarOption};
+
+/// Extracts a jar file without blocking the async runtime. See [crate::jar] for the extraction
+/// behaviour.
+pub async fn jar<P>(path: P, option: JarOption) -> Result<Jar, JarError> where P: AsRef<Path> {
+    let path = path.as_ref().to_path_buf();
+
+    spawn_blocking(move || crate::jar(path, option)).await
+}
+
+/// Extracts a jar held in memory without blocking the async runtime. See [crate::jar] for the
+/// extraction behaviour.
+pub async fn jar_from_bytes(bytes: Vec<u8>, option: JarOption) -> Result<Jar, JarError> {
+    spawn_blocking(move || crate::jar_from_bytes(&bytes, option)).await
+}
+
+/// Extracts a jar file onto the file system without blocking the async runtime. See
+/// [crate::extract_to_dir] for the extraction behaviour.
+pub async fn extract_to_dir<P, D>(path: P, dest_dir: D, option: JarOption) -> Result<Vec<PathBuf>, JarError> where P: AsRef<Path>, D: AsRef<Path> {
+    let path = path.as_ref().to_path_buf();
+    let dest_dir = dest_dir.as_ref().to_path_buf();
+
+    spawn_blocking(move || crate::extract_to_dir(path, dest_dir, option)).await
+}
+
+/// Runs `f` on the blocking thread pool, resuming a panic of `f` on the awaiting task.
+async fn spawn_blocking<T, F>(f: F) -> Result<T, JarError>
+    where T: Send + 'static, F: FnOnce() -> Result<T, JarError> + Send + 'static {
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(err) => Err(JarError::Io(std::io::Error::new(std::io::ErrorKind::Interrupted, err))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::{jar_bytes, jar_file};
+    use crate::JarOptionBuilder;
+
+    #[tokio::test]
+    async fn test_async_jar() {
+        let path = jar_file(&[("java/lang/Object.class", b"object"), ("java/lang/messages.properties", b"")]);
+        let jar = crate::asynch::jar(&path, JarOptionBuilder::builder().ext("class").build()).await.unwrap();
+
+        assert_eq!(jar.files.keys().collect::<Vec<_>>(), vec!["java/lang/Object.class"]);
+
+        let bytes = jar_bytes(&[("a.txt", b"a")]);
+        let jar = crate::asynch::jar_from_bytes(bytes, JarOptionBuilder::default()).await.unwrap();
+
+        assert_eq!(jar.files["a.txt"], b"a");
+    }
+}
@@ -0,0 +1,104 @@
+//! Extraction of many jars at once, e.g. all dependencies of a classpath.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{jar, Jar, JarError, JarOption};
+
+/// Extracts every jar of `paths` concurrently, returning the result of each jar keyed by its path,
+/// so one unreadable jar does not fail the others. Up to `option.threads` jars are extracted at
+/// once as set with [crate::JarOptionBuilder::parallel], or one per available core when unset,
+/// while each jar is extracted on a single thread.
+///
+/// # Example
+///
+/// ```rs
+/// let results = jars_all(&classpath, JarOptionBuilder::builder().ext("class").build());
+///
+/// for (path, result) in results {
+///     match result {
+///         Ok(jar) => println!("{}: {} classes", path.display(), jar.files.len()),
+///         Err(err) => eprintln!("{}: {}", path.display(), err),
+///     }
+/// }
+/// ```
+pub fn jars_all<I, P>(paths: I, mut option: JarOption) -> HashMap<PathBuf, Result<Jar, JarError>>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut paths = paths.into_iter().map(|path| path.as_ref().to_path_buf()).collect::<Vec<_>>();
+    let threads = match option.threads {
+        0 | 1 => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+        threads => threads,
+    };
+
+    paths.sort();
+    paths.dedup();
+    option.threads = 1;
+
+    let next = AtomicUsize::new(0);
+
+    std::thread::scope(|scope| {
+        let workers = (0..threads.min(paths.len()))
+            .map(|_| scope.spawn(|| {
+                let mut results = Vec::new();
+
+                while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    results.push((path.clone(), jar(path, option.clone())));
+                }
+
+                results
+            }))
+            .collect::<Vec<_>>();
+
+        workers.into_iter()
+            .flat_map(|worker| worker.join().expect("extraction thread panicked"))
+            .collect()
+    })
+}
+
+/// Extracts every `.jar` file directly inside `dir`, e.g. a `lib` directory, like [jars_all].
+pub fn jars_in_dir<P>(dir: P, option: JarOption) -> Result<HashMap<PathBuf, Result<Jar, JarError>>, JarError> where P: AsRef<Path> {
+    let mut paths = Vec::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_file() && path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("jar")) {
+            paths.push(path);
+        }
+    }
+
+    Ok(jars_all(paths, option))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::{jar_bytes, temp_path};
+    use crate::{jars_all, jars_in_dir, JarError, JarOptionBuilder};
+
+    #[test]
+    fn test_jars_all() {
+        let dir = temp_path("lib");
+
+        std::fs::create_dir_all(&dir).unwrap();
+        for index in 0..8 {
+            std::fs::write(dir.join(format!("lib{}.jar", index)), jar_bytes(&[(&format!("a/A{}.class", index), b"a")])).unwrap();
+        }
+        std::fs::write(dir.join("broken.jar"), b"not a jar").unwrap();
+        std::fs::write(dir.join("README.txt"), b"readme").unwrap();
+
+        let results = jars_in_dir(&dir, JarOptionBuilder::builder().parallel(3).build()).unwrap();
+
+        assert_eq!(results.len(), 9);
+        assert!(results[&dir.join("lib5.jar")].as_ref().unwrap().files.contains_key("a/A5.class"));
+        assert!(matches!(results[&dir.join("broken.jar")], Err(JarError::Zip(_))));
+
+        let results = jars_all([dir.join("lib0.jar"), dir.join("lib0.jar"), dir.join("missing.jar")], JarOptionBuilder::default());
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[&dir.join("missing.jar")], Err(JarError::Io(_))));
+    }
+}
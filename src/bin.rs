@@ -0,0 +1,199 @@
+//! `jars` command line tool, built with feature `cli`.
+//!
+//! ```sh
+//! jars list rt.jar --target java/lang
+//! jars extract rt.jar --target java/lang --ext class -o outdir
+//! curl -s https://example.com/app.jar | jars extract - -o outdir
+//! jars manifest rt.jar
+//! jars grep 'java/lang/Object' rt.jar --ext class
+//! jars stats rt.jar --largest 5
+//! jars explain rt.jar --target java/lang --ext class
+//! jars list rt.jar --config jars.json
+//! ```
+
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use jars::{
+    extract_pipe_to_dir, extract_to_dir, jar, jar_explain, jar_list, jar_open, jar_stats, Decision, JarConfig, JarError, JarOption, JarOptionBuilder, MatchRule,
+    MANIFEST_PATH,
+};
+use regex::bytes::Regex;
+
+fn main() -> ExitCode {
+    let matches = command().get_matches();
+    let result = match matches.subcommand() {
+        Some(("list", matches)) => list(matches),
+        Some(("extract", matches)) => extract(matches),
+        Some(("manifest", matches)) => manifest(matches),
+        Some(("grep", matches)) => grep(matches),
+        Some(("stats", matches)) => stats(matches),
+        Some(("explain", matches)) => explain(matches),
+        _ => unreachable!("subcommand is required"),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("jars: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn command() -> Command {
+    let jar_arg = Arg::new("jar").required(true).help("Path of the jar file");
+
+    Command::new("jars")
+        .about("Lists, extracts and searches jar files")
+        .subcommand_required(true)
+        .subcommand(filter_args(Command::new("list").about("Lists entries with their sizes").arg(jar_arg.clone())))
+        .subcommand(filter_args(Command::new("extract").about("Extracts entries into a directory")
+            .arg(jar_arg.clone().help("Path of the jar file, or `-` to read it from standard input")))
+            .arg(Arg::new("output").short('o').long("output").default_value(".").help("Destination directory")))
+        .subcommand(Command::new("manifest").about("Prints META-INF/MANIFEST.MF").arg(jar_arg.clone()))
+        .subcommand(filter_args(Command::new("grep").about("Prints lines of entries matching a regex")
+            .arg(Arg::new("pattern").required(true).help("Regular expression to search for"))
+            .arg(jar_arg.clone())))
+        .subcommand(filter_args(Command::new("stats").about("Prints size and compression statistics").arg(jar_arg.clone()))
+            .arg(Arg::new("largest").long("largest").default_value("10").value_parser(clap::value_parser!(usize))
+                .help("Number of largest entries to print")))
+        .subcommand(filter_args(Command::new("explain").about("Prints which filter includes or skips each entry").arg(jar_arg)))
+}
+
+fn filter_args(command: Command) -> Command {
+    command
+        .arg(Arg::new("config").long("config").help("Read extraction rules from this JSON file, extended by the other filters"))
+        .arg(Arg::new("target").long("target").action(ArgAction::Append).help("Only include entries under this path"))
+        .arg(Arg::new("ext").long("ext").action(ArgAction::Append).help("Only include entries with this extension"))
+        .arg(Arg::new("exclude").long("exclude").action(ArgAction::Append).help("Exclude entries under this path"))
+        .arg(Arg::new("glob").long("glob").action(ArgAction::Append).help("Only include entries matching this glob"))
+        .arg(Arg::new("max-depth").long("max-depth").value_parser(clap::value_parser!(usize))
+            .help("Only include entries at most this many path segments deep"))
+        .arg(Arg::new("keep-meta-info").long("keep-meta-info").action(ArgAction::SetTrue).help("Include META-INF entries"))
+        .arg(Arg::new("ignore-case").long("ignore-case").action(ArgAction::SetTrue).help("Match filters ignoring case"))
+        .arg(Arg::new("normalize-paths").long("normalize-paths").action(ArgAction::SetTrue)
+            .help("Match filters treating `\\` as `/` and ignoring leading `./`"))
+}
+
+fn option(matches: &ArgMatches) -> Result<JarOption, JarError> {
+    let mut builder = match matches.get_one::<String>("config") {
+        Some(config) => JarConfig::from_json(config)?.builder()?,
+        None => JarOptionBuilder::builder(),
+    };
+
+    for target in matches.get_many::<String>("target").unwrap_or_default() {
+        builder = builder.target(target);
+    }
+    for ext in matches.get_many::<String>("ext").unwrap_or_default() {
+        builder = builder.ext(ext);
+    }
+    for exclude in matches.get_many::<String>("exclude").unwrap_or_default() {
+        builder = builder.exclude_target(exclude);
+    }
+    for glob in matches.get_many::<String>("glob").unwrap_or_default() {
+        builder = builder.target_glob(glob);
+    }
+    if let Some(max_depth) = matches.get_one::<usize>("max-depth") {
+        builder = builder.max_depth(*max_depth);
+    }
+    if matches.get_flag("keep-meta-info") {
+        builder = builder.keep_meta_info();
+    }
+    if matches.get_flag("ignore-case") {
+        builder = builder.case_insensitive();
+    }
+    if matches.get_flag("normalize-paths") {
+        builder = builder.normalize_paths();
+    }
+
+    builder.try_build()
+}
+
+fn jar_path(matches: &ArgMatches) -> &str {
+    matches.get_one::<String>("jar").expect("jar is required")
+}
+
+fn list(matches: &ArgMatches) -> Result<(), JarError> {
+    let mut stdout = io::stdout().lock();
+
+    for entry in jar_list(jar_path(matches), option(matches)?)? {
+        writeln!(stdout, "{:>10}  {}", entry.size, entry.name)?;
+    }
+
+    Ok(())
+}
+
+fn extract(matches: &ArgMatches) -> Result<(), JarError> {
+    let output = matches.get_one::<String>("output").expect("output has a default value");
+    let paths = match jar_path(matches) {
+        "-" => extract_pipe_to_dir(io::stdin().lock(), output, option(matches)?)?,
+        path => extract_to_dir(path, output, option(matches)?)?,
+    };
+
+    eprintln!("extracted {} files into {}", paths.len(), output);
+    Ok(())
+}
+
+fn manifest(matches: &ArgMatches) -> Result<(), JarError> {
+    let content = jar_open(jar_path(matches), JarOptionBuilder::builder().keep_meta_info().build())?.get(MANIFEST_PATH)?;
+
+    io::stdout().write_all(&content)?;
+    Ok(())
+}
+
+fn grep(matches: &ArgMatches) -> Result<(), JarError> {
+    let pattern = matches.get_one::<String>("pattern").expect("pattern is required");
+    let regex = Regex::new(pattern).map_err(|err| JarError::InvalidPattern(err.to_string()))?;
+    let jar = jar(jar_path(matches), option(matches)?)?;
+    let mut stdout = io::stdout().lock();
+
+    for (entry, content) in jar.ordered_entries() {
+        for (line_number, line) in content.split(|byte| *byte == b'\n').enumerate() {
+            if regex.is_match(line) {
+                writeln!(stdout, "{}:{}:{}", entry.name, line_number + 1, String::from_utf8_lossy(line).trim_end())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn stats(matches: &ArgMatches) -> Result<(), JarError> {
+    let largest = *matches.get_one::<usize>("largest").expect("largest has a default value");
+    let stats = jar_stats(jar_path(matches), option(matches)?)?;
+    let mut stdout = io::stdout().lock();
+
+    writeln!(stdout, "{} entries, {} bytes, {} compressed ({:.1}%)", stats.total.entries, stats.total.size,
+        stats.total.compressed_size, stats.total.compression_ratio() * 100.0)?;
+    writeln!(stdout, "\nby extension:")?;
+    for (extension, totals) in &stats.extensions {
+        writeln!(stdout, "{:>8} {:>12}  .{}", totals.entries, totals.size, extension)?;
+    }
+    writeln!(stdout, "\nlargest entries:")?;
+    for entry in stats.largest(largest) {
+        writeln!(stdout, "{:>12}  {}", entry.size, entry.name)?;
+    }
+
+    Ok(())
+}
+
+fn explain(matches: &ArgMatches) -> Result<(), JarError> {
+    let mut stdout = io::stdout().lock();
+
+    for explanation in jar_explain(jar_path(matches), option(matches)?)? {
+        let decision = match explanation.decision {
+            Decision::Extracted(MatchRule::All) => "extracted".to_string(),
+            Decision::Extracted(MatchRule::Target(target)) => format!("extracted, under target `{}`", target),
+            Decision::Extracted(MatchRule::Pattern(pattern)) => format!("extracted, matches `{}`", pattern),
+            Decision::Extracted(MatchRule::Extension(ext)) => format!("extracted, has extension `{}`", ext),
+            Decision::Skipped(reason) => format!("skipped, {}", reason),
+            Decision::LimitExceeded(message) => format!("fails, limit exceeded, {}", message),
+        };
+
+        writeln!(stdout, "{}  {}", explanation.name, decision)?;
+    }
+
+    Ok(())
+}
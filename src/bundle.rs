@@ -0,0 +1,302 @@
+//! Java resource bundles packaged as `*.properties` files, grouped by base name and locale, and a
+//! parser for the properties format.
+
+use std::collections::BTreeMap;
+
+use crate::Jar;
+
+/// A resource bundle family: the properties files sharing a base name, one per locale.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ResourceBundle {
+    /// Base name as passed to `ResourceBundle.getBundle`, e.g. `com.example.messages`.
+    pub base_name: String,
+    /// Paths of the properties files by locale, e.g. `de_DE`, with an empty locale for the base
+    /// file without locale suffix.
+    pub locales: BTreeMap<String, String>,
+}
+
+impl ResourceBundle {
+    /// Parses the properties file of each locale of the bundle in `jar` with [parse_properties].
+    /// Locales whose file is not extracted in `jar` are left out.
+    pub fn properties(&self, jar: &Jar) -> BTreeMap<String, BTreeMap<String, String>> {
+        self.locales.iter()
+            .filter_map(|(locale, path)| Some((locale.clone(), parse_properties(jar.files.get(path)?))))
+            .collect()
+    }
+}
+
+impl Jar {
+    /// Groups `*.properties` entries outside `META-INF` into resource bundles sorted by base name,
+    /// e.g. `messages.properties` and `messages_de_DE.properties` into bundle `messages` with the
+    /// base locale and `de_DE`.
+    ///
+    /// A file name is split into base name and locale following `ResourceBundle` naming: a
+    /// language of 2 or 3 lowercase letters, optionally followed by a script of 4 letters, a
+    /// country of 2 uppercase letters or 3 digits and a variant, e.g. `zh_Hant_TW`. Names without
+    /// such a suffix form the base file.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("app.jar", JarOptionBuilder::builder().ext("properties").build())?;
+    ///
+    /// for bundle in jar.resource_bundles() {
+    ///     let properties = bundle.properties(&jar);
+    ///
+    ///     for (locale, translations) in &properties {
+    ///         let missing = properties[""].keys().filter(|key| !translations.contains_key(*key)).count();
+    ///     }
+    /// }
+    /// ```
+    pub fn resource_bundles(&self) -> Vec<ResourceBundle> {
+        let mut bundles = BTreeMap::<String, BTreeMap<String, String>>::new();
+
+        for path in self.files.keys() {
+            let Some(stem) = path.strip_suffix(".properties") else {
+                continue;
+            };
+            if path.starts_with("META-INF/") {
+                continue;
+            }
+
+            let (base, locale) = split_locale(stem);
+
+            bundles.entry(base.replace('/', ".")).or_default().insert(locale.to_string(), path.clone());
+        }
+
+        bundles.into_iter()
+            .map(|(base_name, locales)| ResourceBundle {
+                base_name,
+                locales,
+            })
+            .collect()
+    }
+}
+
+/// Splits a properties file path without extension into base name and locale suffix, the locale
+/// being empty if the file name does not end with one.
+fn split_locale(stem: &str) -> (&str, &str) {
+    let name_start = stem.rfind('/').map_or(0, |index| index + 1);
+    let mut separators = stem[name_start..].match_indices('_').map(|(index, _)| name_start + index);
+
+    // The leftmost valid suffix is the longest locale, e.g. `en_US` rather than `US`.
+    separators
+        .find(|index| *index > name_start && is_locale(&stem[index + 1..]))
+        .map_or((stem, ""), |index| (&stem[..index], &stem[index + 1..]))
+}
+
+fn is_locale(locale: &str) -> bool {
+    let mut parts = locale.split('_').peekable();
+    let language = parts.next().unwrap_or_default();
+
+    if !(2..=3).contains(&language.len()) || !language.bytes().all(|byte| byte.is_ascii_lowercase()) {
+        return false;
+    }
+
+    parts.next_if(|script| script.len() == 4
+        && script.starts_with(|first: char| first.is_ascii_uppercase())
+        && script.bytes().skip(1).all(|byte| byte.is_ascii_lowercase()));
+
+    let Some(country) = parts.next() else {
+        return true;
+    };
+    let is_country = (country.len() == 2 && country.bytes().all(|byte| byte.is_ascii_uppercase()))
+        || (country.len() == 3 && country.bytes().all(|byte| byte.is_ascii_digit()));
+
+    // The variant may contain further underscores.
+    is_country && parts.all(|variant| !variant.is_empty())
+}
+
+/// Parses the content of a Java properties file into key/value pairs, following
+/// `java.util.Properties.load`: comment lines start with `#` or `!`, keys end at the first
+/// unescaped `=`, `:` or whitespace, lines ending with an odd number of backslashes continue on
+/// the next line, and `\t`, `\n`, `\r`, `\f` and `\uXXXX` escapes are decoded, including surrogate
+/// pairs. Later duplicate keys win.
+///
+/// Content is decoded as UTF-8, falling back to ISO-8859-1 if it is not valid UTF-8, like
+/// `PropertyResourceBundle` does since Java 9.
+///
+/// # Example
+///
+/// ```rs
+/// let properties = parse_properties(b"greeting = Gr\\u00FC\\u00DF \\\n    Gott\n");
+///
+/// assert_eq!(properties["greeting"], "Grüß Gott");
+/// ```
+pub fn parse_properties(content: &[u8]) -> BTreeMap<String, String> {
+    let content = match std::str::from_utf8(content) {
+        Ok(content) => content.to_string(),
+        Err(_) => content.iter().map(|byte| char::from(*byte)).collect(),
+    };
+    let mut properties = BTreeMap::new();
+
+    for line in logical_lines(&content) {
+        let mut chars = line.chars().peekable();
+        let mut key = Unescaper::default();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '\\' => key.escape(&mut chars),
+                '=' | ':' => break,
+                ' ' | '\t' | '\x0C' => {
+                    while chars.next_if(|char| matches!(char, ' ' | '\t' | '\x0C')).is_some() {}
+                    chars.next_if(|char| matches!(char, '=' | ':'));
+                    break;
+                }
+                _ => key.push(char),
+            }
+        }
+        while chars.next_if(|char| matches!(char, ' ' | '\t' | '\x0C')).is_some() {}
+
+        let mut value = Unescaper::default();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '\\' => value.escape(&mut chars),
+                _ => value.push(char),
+            }
+        }
+
+        properties.insert(key.finish(), value.finish());
+    }
+
+    properties
+}
+
+/// Joins continued natural lines into logical lines, with leading whitespace and the trailing
+/// continuation backslashes removed, skipping blank and comment lines.
+fn logical_lines(content: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current: Option<String> = None;
+
+    for natural in content.replace("\r\n", "\n").split(['\n', '\r']) {
+        let natural = natural.trim_start_matches([' ', '\t', '\x0C']);
+
+        if current.is_none() && (natural.is_empty() || natural.starts_with(['#', '!'])) {
+            continue;
+        }
+
+        let backslashes = natural.len() - natural.trim_end_matches('\\').len();
+        let line = current.get_or_insert_with(String::new);
+
+        if !backslashes.is_multiple_of(2) {
+            line.push_str(&natural[..natural.len() - 1]);
+        } else {
+            line.push_str(natural);
+            lines.extend(current.take());
+        }
+    }
+
+    lines.extend(current);
+    lines
+}
+
+/// Builds a string from characters and escapes, combining `\uXXXX` surrogate pairs.
+#[derive(Default)]
+struct Unescaper {
+    string: String,
+    units: Vec<u16>,
+}
+
+impl Unescaper {
+    fn push(&mut self, char: char) {
+        self.flush();
+        self.string.push(char);
+    }
+
+    /// Decodes the escape following a backslash taken from `chars`.
+    fn escape(&mut self, chars: &mut impl Iterator<Item = char>) {
+        match chars.next() {
+            Some('u') => {
+                let digits = chars.take(4).collect::<String>();
+
+                match u16::from_str_radix(&digits, 16) {
+                    Ok(unit) if digits.len() == 4 => self.units.push(unit),
+                    // Malformed escapes are kept verbatim rather than failing the whole file.
+                    _ => {
+                        self.push('u');
+                        self.string.push_str(&digits);
+                    }
+                }
+            }
+            Some('t') => self.push('\t'),
+            Some('n') => self.push('\n'),
+            Some('r') => self.push('\r'),
+            Some('f') => self.push('\x0C'),
+            Some(char) => self.push(char),
+            None => {}
+        }
+    }
+
+    fn flush(&mut self) {
+        self.string.extend(char::decode_utf16(self.units.drain(..)).map(|char| char.unwrap_or(char::REPLACEMENT_CHARACTER)));
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.string
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, parse_properties, JarOptionBuilder, ResourceBundle};
+
+    #[test]
+    fn test_parse_properties() {
+        let content = b"# comment\n  ! other\n\nplain=value\nspaced : a value  \nbare\nwith\\ space\\=s = x\\ty\r\n\
+            multi = one, \\\n    two, \\\r\n\tthree\nunicode=\\u00FC\\uD83D\\uDE00\\u00\nescaped=\\\\\nplain=later";
+        let properties = parse_properties(content);
+        let expected = [
+            ("bare", ""),
+            ("escaped", "\\"),
+            ("multi", "one, two, three"),
+            ("plain", "later"),
+            ("spaced", "a value  "),
+            ("unicode", "ü😀u00"),
+            ("with space=s", "x\ty"),
+        ];
+
+        assert_eq!(properties, expected.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect());
+        assert_eq!(parse_properties(b"name=Gr\xFC\xDF")["name"], "Grüß");
+        assert_eq!(parse_properties("name=Grüß".as_bytes())["name"], "Grüß");
+    }
+
+    #[test]
+    fn test_resource_bundles() {
+        let bytes = jar_bytes(&[
+            ("com/example/messages.properties", b"hello=Hello\nbye=Bye"),
+            ("com/example/messages_de_DE.properties", b"hello=Hallo"),
+            ("com/example/messages_zh_Hant_TW.properties", b""),
+            ("com/example/error_codes_fr.properties", b""),
+            ("com/example/app_config.properties", b""),
+            ("META-INF/maven/com.example/app/pom.properties", b"version=1"),
+            ("com/example/Main.class", b""),
+        ]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().target("com").target("META-INF").build()).unwrap();
+        let bundle = |base_name: &str, locales: &[(&str, &str)]| ResourceBundle {
+            base_name: base_name.to_string(),
+            locales: locales.iter().map(|(locale, path)| (locale.to_string(), path.to_string())).collect(),
+        };
+        let bundles = jar.resource_bundles();
+
+        assert_eq!(bundles, vec![
+            bundle("com.example.app_config", &[("", "com/example/app_config.properties")]),
+            bundle("com.example.error_codes", &[("fr", "com/example/error_codes_fr.properties")]),
+            bundle("com.example.messages", &[
+                ("", "com/example/messages.properties"),
+                ("de_DE", "com/example/messages_de_DE.properties"),
+                ("zh_Hant_TW", "com/example/messages_zh_Hant_TW.properties"),
+            ]),
+        ]);
+
+        let properties = bundles[2].properties(&jar);
+
+        assert_eq!(properties["de_DE"], BTreeMap::from([("hello".to_string(), "Hallo".to_string())]));
+        assert_eq!(properties[""].len(), 2);
+        assert!(properties["zh_Hant_TW"].is_empty());
+    }
+}
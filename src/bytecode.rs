@@ -0,0 +1,115 @@
+//! Class file versions of the classes in a jar, e.g. to check the Java release an artifact targets.
+
+use std::collections::BTreeMap;
+
+use crate::{Jar, VERSIONS_PREFIX};
+
+/// Class file versions of a jar's classes, see [Jar::bytecode_summary]. Versions are major class
+/// file versions, e.g. 55 for Java 11.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BytecodeSummary {
+    /// Number of classes per major version.
+    pub versions: BTreeMap<u16, usize>,
+    /// Paths of classes depending on preview features, sorted.
+    pub preview_classes: Vec<String>,
+    /// Paths of malformed class files, sorted.
+    pub invalid_classes: Vec<String>,
+}
+
+impl BytecodeSummary {
+    /// Returns the lowest major version, [None] if there are no valid classes.
+    pub fn min_version(&self) -> Option<u16> {
+        self.versions.keys().next().copied()
+    }
+
+    /// Returns the highest major version, [None] if there are no valid classes.
+    pub fn max_version(&self) -> Option<u16> {
+        self.versions.keys().next_back().copied()
+    }
+
+    /// Returns the Java release required to load all classes, e.g. 11 when the highest major
+    /// version is 55.
+    pub fn required_java_version(&self) -> Option<u16> {
+        self.max_version().map(|version| version.saturating_sub(44))
+    }
+
+    pub fn has_preview(&self) -> bool {
+        !self.preview_classes.is_empty()
+    }
+
+    /// Checks whether all classes load on given Java release without preview features, e.g. to
+    /// assert in CI that an artifact targets Java 11.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let summary = jar("app.jar", JarOptionBuilder::builder().ext("class").build())?.bytecode_summary();
+    ///
+    /// assert!(summary.runs_on(11), "requires Java {:?}", summary.required_java_version());
+    /// ```
+    pub fn runs_on(&self, java_version: u16) -> bool {
+        !self.has_preview() && self.required_java_version().is_none_or(|required| required <= java_version)
+    }
+}
+
+impl Jar {
+    /// Summarizes the class file versions of all extracted classes. Versioned classes of
+    /// multi-release jars under `META-INF/versions/` target newer releases by design and are
+    /// skipped.
+    pub fn bytecode_summary(&self) -> BytecodeSummary {
+        let mut summary = BytecodeSummary::default();
+
+        for (entry, header) in self.class_entries().filter(|(entry, _)| !entry.name.starts_with(VERSIONS_PREFIX)) {
+            match header {
+                Ok(header) => {
+                    *summary.versions.entry(header.major_version).or_default() += 1;
+                    if header.is_preview() {
+                        summary.preview_classes.push(entry.name.clone());
+                    }
+                }
+                Err(_) => summary.invalid_classes.push(entry.name.clone()),
+            }
+        }
+
+        summary.preview_classes.sort();
+        summary.invalid_classes.sort();
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::classfile::tests::{class_bytes, reference, utf8};
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, JarOptionBuilder};
+
+    #[test]
+    fn test_bytecode_summary() {
+        let class = |major_version| class_bytes(major_version, &[utf8("a/A"), reference(7, 1)], 2, 0, &[]);
+        let mut preview = class(61);
+
+        preview[4..6].copy_from_slice(&[0xFF, 0xFF]);
+
+        let bytes = jar_bytes(&[
+            ("a/A.class", &class(52)),
+            ("a/B.class", &class(55)),
+            ("a/C.class", &class(55)),
+            ("META-INF/versions/21/a/A.class", &class(65)),
+            ("a/Broken.class", b"broken"),
+        ]);
+        let summary = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap().bytecode_summary();
+
+        assert_eq!(summary.versions.iter().map(|(version, count)| (*version, *count)).collect::<Vec<_>>(), vec![(52, 1), (55, 2)]);
+        assert_eq!((summary.min_version(), summary.max_version()), (Some(52), Some(55)));
+        assert_eq!(summary.required_java_version(), Some(11));
+        assert_eq!(summary.invalid_classes, vec!["a/Broken.class"]);
+        assert!(summary.runs_on(11) && summary.runs_on(17) && !summary.runs_on(8));
+
+        let bytes = jar_bytes(&[("a/A.class", &preview)]);
+        let summary = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap().bytecode_summary();
+
+        assert_eq!(summary.preview_classes, vec!["a/A.class"]);
+        assert!(!summary.runs_on(17));
+    }
+}
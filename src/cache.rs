@@ -0,0 +1,317 @@
+//! Persistent cache of extraction results, so unchanged jars are not extracted again across runs.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use ring::digest::{digest, SHA256};
+
+use crate::{jar, jar_list, CompressionMethod, DateTime, Jar, JarEntry, JarError, JarOption};
+
+const MAGIC: &[u8] = b"JARSCACHE\x02";
+
+/// A cache of extracted jars and listings in a directory, keyed by the jar's canonical path, size
+/// and modification time, and the extraction options. A jar modified since it was cached is
+/// extracted again.
+///
+/// Options with custom filters, name decoders or content mappers cannot be keyed, so jars
+/// extracted with them bypass the cache. Jars with entries skipped by
+/// [crate::JarOptionBuilder::skip_corrupt] or with warnings, see [Jar::warnings], are not cached
+/// either.
+///
+/// # Example
+///
+/// ```rs
+/// let cache = JarCache::new("target/jars-cache");
+///
+/// for path in classpath {
+///     let jar = cache.jar(path, JarOptionBuilder::builder().ext("class").build())?;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct JarCache {
+    dir: PathBuf,
+}
+
+impl JarCache {
+    /// Creates a cache storing results in `dir`, which is created on demand.
+    pub fn new<P>(dir: P) -> Self where P: AsRef<Path> {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Extracts the jar at `path` like [crate::jar], or loads it from the cache.
+    pub fn jar<P>(&self, path: P, option: JarOption) -> Result<Jar, JarError> where P: AsRef<Path> {
+        let path = path.as_ref();
+        let Some(key) = cache_key(path, &option, "jar")? else {
+            return jar(path, option);
+        };
+        let cache_path = self.cache_path(&key);
+
+        if let Some((entries, duplicates, comment)) = load(&cache_path, &key, true) {
+            let mut files = HashMap::with_capacity(entries.len());
+            let mut metadata = HashMap::with_capacity(entries.len());
+            let mut order = Vec::with_capacity(entries.len());
+
+            for (entry, content) in entries {
+                files.insert(entry.name.clone(), content.unwrap_or_default());
+                order.push(entry.name.clone());
+                metadata.insert(entry.name.clone(), entry);
+            }
+
+            return Ok(Jar {
+                files,
+                metadata,
+                order,
+                failures: Vec::new(),
+                duplicates,
+                unsafe_names: Vec::new(),
+                warnings: Vec::new(),
+                spilled: HashMap::new(),
+                comment,
+            });
+        }
+
+        let jar = jar(path, option)?;
+
+        if jar.failures.is_empty() && jar.warnings.is_empty() {
+            let entries = jar.ordered_entries().map(|(entry, content)| (entry, Some(content)));
+            self.store(&cache_path, &key, entries, &jar.duplicates, &jar.comment)?;
+        }
+
+        Ok(jar)
+    }
+
+    /// Lists entries of the jar at `path` like [crate::jar_list], or loads them from the cache.
+    pub fn jar_list<P>(&self, path: P, option: JarOption) -> Result<Vec<JarEntry>, JarError> where P: AsRef<Path> {
+        let path = path.as_ref();
+        let Some(key) = cache_key(path, &option, "list")? else {
+            return jar_list(path, option);
+        };
+        let cache_path = self.cache_path(&key);
+
+        if let Some((entries, _, _)) = load(&cache_path, &key, false) {
+            return Ok(entries.into_iter().map(|(entry, _)| entry).collect());
+        }
+
+        let entries = jar_list(path, option)?;
+
+        self.store(&cache_path, &key, entries.iter().map(|entry| (entry, None)), &[], &[])?;
+        Ok(entries)
+    }
+
+    /// Removes all cached results.
+    pub fn clear(&self) -> Result<(), JarError> {
+        match std::fs::remove_dir_all(&self.dir) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn cache_path(&self, key: &str) -> PathBuf {
+        let hash = digest(&SHA256, key.as_bytes()).as_ref()[..16].iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+
+        self.dir.join(format!("{}.bin", hash))
+    }
+
+    fn store<'a, I>(&self, cache_path: &Path, key: &str, entries: I, duplicates: &[String], comment: &[u8]) -> Result<(), JarError>
+    where I: IntoIterator<Item = (&'a JarEntry, Option<&'a [u8]>)> {
+        let temp_path = cache_path.with_extension(format!("{}.tmp", std::process::id()));
+
+        std::fs::create_dir_all(&self.dir)?;
+
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+
+        writer.write_all(MAGIC)?;
+        write_bytes(&mut writer, key.as_bytes())?;
+        write_u64(&mut writer, duplicates.len() as u64)?;
+        for name in duplicates {
+            write_bytes(&mut writer, name.as_bytes())?;
+        }
+        write_bytes(&mut writer, comment)?;
+
+        for (entry, content) in entries {
+            writer.write_all(&[1])?;
+            write_entry(&mut writer, entry)?;
+            if let Some(content) = content {
+                write_bytes(&mut writer, content)?;
+            }
+        }
+
+        writer.write_all(&[0])?;
+        writer.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+        std::fs::rename(&temp_path, cache_path)?;
+
+        Ok(())
+    }
+}
+
+type Entries = (Vec<(JarEntry, Option<Vec<u8>>)>, Vec<String>, Vec<u8>);
+
+/// Builds the cache key, or returns [None] if the options cannot be keyed or the file system does
+/// not record modification times.
+fn cache_key(path: &Path, option: &JarOption, kind: &str) -> Result<Option<String>, JarError> {
+    let Some(option_key) = option.cache_key() else {
+        return Ok(None);
+    };
+    let path = path.canonicalize()?;
+    let metadata = std::fs::metadata(&path)?;
+    let Some(modified) = metadata.modified().ok().and_then(|modified| modified.duration_since(UNIX_EPOCH).ok()) else {
+        return Ok(None);
+    };
+
+    Ok(Some(format!(
+        "{} {} path={} size={} modified={} {}",
+        env!("CARGO_PKG_VERSION"), kind, path.display(), metadata.len(), modified.as_nanos(), option_key,
+    )))
+}
+
+/// Loads cached entries, returning [None] if the file is missing, written for another key or
+/// corrupted, so the result is computed again.
+fn load(cache_path: &Path, key: &str, with_content: bool) -> Option<Entries> {
+    let mut reader = BufReader::new(File::open(cache_path).ok()?);
+    let mut magic = [0; MAGIC.len()];
+
+    reader.read_exact(&mut magic).ok()?;
+    if magic != MAGIC || read_bytes(&mut reader).ok()? != key.as_bytes() {
+        return None;
+    }
+
+    let duplicates = (0..read_u64(&mut reader).ok()?)
+        .map(|_| String::from_utf8(read_bytes(&mut reader).ok()?).ok())
+        .collect::<Option<Vec<_>>>()?;
+    let comment = read_bytes(&mut reader).ok()?;
+    let mut entries = Vec::new();
+
+    while read_array::<1, _>(&mut reader).ok()? == [1] {
+        let entry = read_entry(&mut reader).ok()?;
+        let content = if with_content { Some(read_bytes(&mut reader).ok()?) } else { None };
+
+        entries.push((entry, content));
+    }
+
+    Some((entries, duplicates, comment))
+}
+
+fn write_entry<W>(writer: &mut W, entry: &JarEntry) -> Result<(), JarError> where W: Write {
+    let DateTime { year, month, day, hour, minute, second } = entry.last_modified;
+
+    write_bytes(writer, entry.name.as_bytes())?;
+    write_u64(writer, entry.size)?;
+    write_u64(writer, entry.compressed_size)?;
+    writer.write_all(&entry.compression.id().to_le_bytes())?;
+    writer.write_all(&year.to_le_bytes())?;
+    writer.write_all(&[month, day, hour, minute, second])?;
+    writer.write_all(&entry.crc32.to_le_bytes())?;
+    match entry.unix_mode {
+        Some(mode) => {
+            writer.write_all(&[1])?;
+            writer.write_all(&mode.to_le_bytes())?;
+        }
+        None => writer.write_all(&[0])?,
+    }
+
+    write_bytes(writer, &entry.extra)
+}
+
+fn read_entry<R>(reader: &mut R) -> Result<JarEntry, JarError> where R: Read {
+    let name = String::from_utf8(read_bytes(reader)?).map_err(|err| JarError::InvalidEntryName(err.to_string()))?;
+    let size = read_u64(reader)?;
+    let compressed_size = read_u64(reader)?;
+    let compression = CompressionMethod::from_id(u16::from_le_bytes(read_array(reader)?));
+    let year = u16::from_le_bytes(read_array(reader)?);
+    let [month, day, hour, minute, second] = read_array(reader)?;
+    let crc32 = u32::from_le_bytes(read_array(reader)?);
+    let unix_mode = match read_array::<1, _>(reader)? {
+        [0] => None,
+        _ => Some(u32::from_le_bytes(read_array(reader)?)),
+    };
+    let extra = read_bytes(reader)?;
+
+    Ok(JarEntry {
+        name,
+        size,
+        compressed_size,
+        compression,
+        last_modified: DateTime { year, month, day, hour, minute, second },
+        crc32,
+        unix_mode,
+        extra,
+    })
+}
+
+fn write_u64<W>(writer: &mut W, value: u64) -> Result<(), JarError> where W: Write {
+    Ok(writer.write_all(&value.to_le_bytes())?)
+}
+
+fn write_bytes<W>(writer: &mut W, bytes: &[u8]) -> Result<(), JarError> where W: Write {
+    write_u64(writer, bytes.len() as u64)?;
+    Ok(writer.write_all(bytes)?)
+}
+
+fn read_array<const N: usize, R>(reader: &mut R) -> Result<[u8; N], JarError> where R: Read {
+    let mut bytes = [0; N];
+
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u64<R>(reader: &mut R) -> Result<u64, JarError> where R: Read {
+    Ok(u64::from_le_bytes(read_array(reader)?))
+}
+
+fn read_bytes<R>(reader: &mut R) -> Result<Vec<u8>, JarError> where R: Read {
+    let len = read_u64(reader)?;
+    let mut bytes = Vec::new();
+
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::{jar_bytes, jar_file, temp_path};
+    use crate::{JarCache, JarOptionBuilder};
+
+    #[test]
+    fn test_cache() {
+        let path = jar_file(&[("a/A.class", b"a"), ("b.txt", b"b")]);
+        let cache_dir = temp_path("cache");
+        let cache = JarCache::new(&cache_dir);
+        let option = || JarOptionBuilder::builder().ext("class").build();
+
+        let extracted = cache.jar(&path, option()).unwrap();
+        let cached = cache.jar(&path, option()).unwrap();
+
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 1);
+        assert_eq!(cached.files, extracted.files);
+        assert_eq!(cached.entry("a/A.class"), extracted.entry("a/A.class"));
+        assert_eq!(cache.jar_list(&path, option()).unwrap(), cache.jar_list(&path, option()).unwrap());
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 2);
+
+        // A different option is a different key.
+        assert_eq!(cache.jar(&path, JarOptionBuilder::default()).unwrap().files.len(), 2);
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 3);
+
+        // Custom filters bypass the cache.
+        let filtered = cache.jar(&path, JarOptionBuilder::builder().filter(|entry| entry.size > 0).build()).unwrap();
+        assert_eq!(filtered.files.len(), 2);
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 3);
+
+        // A modified jar is extracted again.
+        std::fs::write(&path, jar_bytes(&[("a/A.class", b"modified")])).unwrap();
+        assert_eq!(cache.jar(&path, option()).unwrap().files["a/A.class"], b"modified");
+
+        cache.clear().unwrap();
+        assert!(!cache_dir.exists());
+    }
+}
@@ -0,0 +1,106 @@
+//! Extraction on a background thread feeding a bounded channel, so entries can be processed while
+//! later ones are still decompressed.
+
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread::JoinHandle;
+
+use crate::option::map_content;
+use crate::{jar_visit_reader, JarEntry, JarError, JarOption};
+
+/// Handle of the extracting thread, returning the number of entries sent, and the receiving end of
+/// the entry channel.
+type Channel = (JoinHandle<Result<usize, JarError>>, Receiver<(String, Vec<u8>)>);
+
+/// Extracts the jar at `path` on a background thread, sending each entry matching `option` as its
+/// full qualified path and content over a channel holding up to `capacity` entries. Extraction
+/// blocks while the channel is full and stops early once the receiver is dropped.
+///
+/// The jar is opened before returning. Other failures are returned by the join handle, which
+/// otherwise returns the number of entries sent. Contents are held in memory, so
+/// [crate::JarOptionBuilder::spill_to_disk] does not apply.
+///
+/// # Example
+///
+/// ```rs
+/// let (handle, receiver) = jar_channel("sample/rt.jar", JarOptionBuilder::builder().ext("class").build(), 16)?;
+///
+/// for (name, content) in receiver {
+///     index.add(&name, parse_class(&content)?);
+/// }
+/// handle.join().unwrap()?;
+/// ```
+pub fn jar_channel<P>(path: P, option: JarOption, capacity: usize) -> Result<Channel, JarError>
+    where P: AsRef<Path> {
+    Ok(jar_channel_reader(File::open(path)?, option, capacity))
+}
+
+/// Extracts a jar from any seekable source on a background thread into a channel. See
+/// [jar_channel].
+pub fn jar_channel_reader<R>(reader: R, option: JarOption, capacity: usize) -> Channel
+    where R: Read + Seek + Send + 'static {
+    let (sender, receiver) = sync_channel(capacity);
+    let handle = std::thread::spawn(move || {
+        let content_mapper = option.content_mapper.clone();
+        let mut sent = 0;
+
+        jar_visit_reader(reader, option, &mut |entry: &JarEntry, reader: &mut dyn Read| {
+            let mut content = Vec::new();
+
+            reader.read_to_end(&mut content)?;
+
+            let Some(content) = map_content(&content_mapper, &entry.name, content) else {
+                return Ok(ControlFlow::Continue(()));
+            };
+
+            if sender.send((entry.name.clone(), content)).is_err() {
+                return Ok(ControlFlow::Break(()));
+            }
+
+            sent += 1;
+            Ok(ControlFlow::Continue(()))
+        })?;
+
+        Ok(sent)
+    });
+
+    (handle, receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::fixture::{jar_bytes, jar_file};
+    use crate::{jar_channel, jar_channel_reader, JarError, JarOptionBuilder};
+
+    #[test]
+    fn test_jar_channel() {
+        let path = jar_file(&[("a/A.class", b"a"), ("a/B.class", b"b"), ("c.txt", b"c")]);
+        let (handle, receiver) = jar_channel(&path, JarOptionBuilder::builder().ext("class").build(), 1).unwrap();
+
+        assert_eq!(receiver.iter().collect::<Vec<_>>(), vec![("a/A.class".to_string(), b"a".to_vec()), ("a/B.class".to_string(), b"b".to_vec())]);
+        assert_eq!(handle.join().unwrap().unwrap(), 2);
+
+        let (handle, receiver) = jar_channel(&path, JarOptionBuilder::default(), 0).unwrap();
+
+        assert_eq!(receiver.recv().unwrap().0, "a/A.class");
+        drop(receiver);
+        assert_eq!(handle.join().unwrap().unwrap(), 1);
+
+        let (handle, receiver) = jar_channel_reader(Cursor::new(b"not a jar".to_vec()), JarOptionBuilder::default(), 1);
+
+        assert!(receiver.recv().is_err());
+        assert!(matches!(handle.join().unwrap(), Err(JarError::Zip(_))));
+
+        let bytes = jar_bytes(&[("a.txt", b"a")]);
+        let (handle, receiver) = jar_channel_reader(Cursor::new(bytes), JarOptionBuilder::builder().map_content(|_, content| Some([content, b"!".to_vec()].concat())).build(), 1);
+
+        assert_eq!(receiver.recv().unwrap().1, b"a!");
+        assert_eq!(handle.join().unwrap().unwrap(), 1);
+        assert!(jar_channel(path.with_extension("missing"), JarOptionBuilder::default(), 1).is_err());
+    }
+}
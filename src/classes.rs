@@ -0,0 +1,242 @@
+//! Conversions between entry paths and binary class names, e.g. `java/lang/String.class` and
+//! `java.lang.String`.
+
+use crate::classfile::{read_constant_pool, ClassFile, ClassReader, MAGIC};
+use crate::{Jar, JarEntry, JarError};
+
+const CLASS_SUFFIX: &str = ".class";
+
+const ACC_INTERFACE: u16 = 0x0200;
+const ACC_ANNOTATION: u16 = 0x2000;
+const ACC_ENUM: u16 = 0x4000;
+const ACC_MODULE: u16 = 0x8000;
+
+/// Minor version marking class files which depend on preview features of their Java release.
+const PREVIEW_MINOR_VERSION: u16 = 0xFFFF;
+
+/// Checks whether a class file of given version depends on preview features, which exist since
+/// Java 12.
+pub(crate) fn is_preview_version(major_version: u16, minor_version: u16) -> bool {
+    major_version >= 56 && minor_version == PREVIEW_MINOR_VERSION
+}
+
+/// The header of a class file, read up to the super class without parsing fields, methods or
+/// attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassHeader {
+    pub minor_version: u16,
+    /// Class file version, e.g. 52 for Java 8 and 61 for Java 17.
+    pub major_version: u16,
+    /// Access flags of the class, e.g. `0x0021` for `public` classes.
+    pub access_flags: u16,
+    /// Internal name of the class, e.g. `java/lang/String`.
+    pub this_class: String,
+    /// Internal name of the super class, [None] for `java/lang/Object` and `module-info`.
+    pub super_class: Option<String>,
+}
+
+impl ClassHeader {
+    /// Reads the header of a class file, failing with [JarError::ClassFormat] if the magic number
+    /// is wrong or the file ends early.
+    pub fn parse(bytes: &[u8]) -> Result<Self, JarError> {
+        let mut reader = ClassReader::new(bytes);
+
+        if reader.u4()? != MAGIC {
+            return Err(JarError::ClassFormat("invalid magic number".to_string()));
+        }
+
+        let minor_version = reader.u2()?;
+        let major_version = reader.u2()?;
+        let class_file = ClassFile {
+            constant_pool: read_constant_pool(&mut reader)?,
+            attributes: Vec::new(),
+        };
+        let access_flags = reader.u2()?;
+        let this_class = reader.u2()?;
+        let super_class = reader.u2()?;
+        let this_class = if access_flags & ACC_MODULE != 0 {
+            // `module-info` names itself by a class constant as well, but may omit its Utf8 name.
+            class_file.class_name(this_class).unwrap_or("module-info").to_string()
+        } else {
+            class_file.class_name(this_class)?.to_string()
+        };
+        let super_class = match super_class {
+            0 => None,
+            index => Some(class_file.class_name(index)?.to_string()),
+        };
+
+        Ok(Self {
+            minor_version,
+            major_version,
+            access_flags,
+            this_class,
+            super_class,
+        })
+    }
+
+    /// Returns the Java release the class file version belongs to, e.g. 8 for major version 52.
+    /// Releases before Java 5 are numbered by their minor release, e.g. 4 for Java 1.4.
+    pub fn java_version(&self) -> u16 {
+        self.major_version.saturating_sub(44)
+    }
+
+    /// Checks whether the class depends on preview features of its Java release, so it only loads
+    /// on exactly that release with `--enable-preview`.
+    pub fn is_preview(&self) -> bool {
+        is_preview_version(self.major_version, self.minor_version)
+    }
+
+    pub fn is_interface(&self) -> bool {
+        self.access_flags & ACC_INTERFACE != 0
+    }
+
+    pub fn is_annotation(&self) -> bool {
+        self.access_flags & ACC_ANNOTATION != 0
+    }
+
+    pub fn is_enum(&self) -> bool {
+        self.access_flags & ACC_ENUM != 0
+    }
+
+    /// Checks whether the class file is a `module-info` descriptor.
+    pub fn is_module(&self) -> bool {
+        self.access_flags & ACC_MODULE != 0
+    }
+}
+
+impl Jar {
+    /// Iterates over binary names of all classes in the jar, in no particular order. Entries under
+    /// `META-INF`, including versioned classes of multi-release jars, are not classes on the
+    /// classpath and are skipped. `module-info` and `package-info` descriptors are skipped unless
+    /// `include_descriptors` is set.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("sample/rt.jar", JarOptionBuilder::builder().ext("class").build())?;
+    ///
+    /// for class in jar.classes(false) {
+    ///     // "java.lang.String", "java.util.Map$Entry", ...
+    /// }
+    /// ```
+    pub fn classes(&self, include_descriptors: bool) -> impl Iterator<Item = String> + '_ {
+        self.files.keys()
+            .filter(|path| !path.starts_with("META-INF/"))
+            .filter_map(|path| binary_name(path))
+            .filter(move |name| include_descriptors || !is_descriptor(name))
+    }
+
+    /// Iterates over all extracted `.class` entries in central directory order, including
+    /// versioned classes and descriptors, along with their [ClassHeader]. Malformed class files
+    /// yield an error instead of a header.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("app.jar", JarOptionBuilder::builder().ext("class").build())?;
+    ///
+    /// for (entry, header) in jar.class_entries() {
+    ///     println!("{} Java {}", entry.name, header?.java_version());
+    /// }
+    /// ```
+    pub fn class_entries(&self) -> impl Iterator<Item = (&JarEntry, Result<ClassHeader, JarError>)> {
+        self.ordered_entries()
+            .filter(|(entry, _)| entry.name.ends_with(CLASS_SUFFIX))
+            .map(|(entry, content)| (entry, ClassHeader::parse(content)))
+    }
+}
+
+/// Converts a class entry path to its binary name, e.g. `java/util/Map$Entry.class` to
+/// `java.util.Map$Entry`. Returns [None] if `path` is not a class file.
+pub fn binary_name(path: &str) -> Option<String> {
+    let name = path.strip_suffix(CLASS_SUFFIX)?;
+
+    if name.is_empty() || name.ends_with('/') {
+        None
+    } else {
+        Some(name.replace('/', "."))
+    }
+}
+
+/// Converts a binary class name to its entry path, e.g. `java.util.Map$Entry` to
+/// `java/util/Map$Entry.class`.
+pub fn class_path(binary_name: &str) -> String {
+    format!("{}{}", binary_name.replace('.', "/"), CLASS_SUFFIX)
+}
+
+/// Checks whether a binary name is a `module-info` or `package-info` descriptor rather than a class.
+fn is_descriptor(binary_name: &str) -> bool {
+    let simple_name = binary_name.rsplit('.').next().unwrap_or(binary_name);
+
+    simple_name == "module-info" || simple_name == "package-info"
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::classfile::tests::{class_bytes, reference, utf8};
+    use crate::fixture::jar_bytes;
+    use crate::{binary_name, class_path, jar_from_bytes, ClassHeader, JarError, JarOptionBuilder};
+
+    #[test]
+    fn test_classes() {
+        let bytes = jar_bytes(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n"),
+            ("META-INF/versions/11/a/A.class", b""),
+            ("module-info.class", b""),
+            ("a/package-info.class", b""),
+            ("a/A.class", b""),
+            ("a/A$1.class", b""),
+            ("a/a.properties", b""),
+        ]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().keep_meta_info().ext("class").ext("properties").build()).unwrap();
+
+        let mut classes = jar.classes(false).collect::<Vec<_>>();
+        classes.sort();
+        assert_eq!(classes, vec!["a.A", "a.A$1"]);
+
+        let mut classes = jar.classes(true).collect::<Vec<_>>();
+        classes.sort();
+        assert_eq!(classes, vec!["a.A", "a.A$1", "a.package-info", "module-info"]);
+    }
+
+    #[test]
+    fn test_binary_name_conversions() {
+        assert_eq!(binary_name("java/util/Map$Entry.class").as_deref(), Some("java.util.Map$Entry"));
+        assert_eq!(binary_name("Main.class").as_deref(), Some("Main"));
+        assert_eq!(binary_name("java/lang/"), None);
+        assert_eq!(binary_name("a/.class"), None);
+        assert_eq!(class_path("java.util.Map$Entry"), "java/util/Map$Entry.class");
+    }
+
+    #[test]
+    fn test_class_entries() {
+        let class = class_bytes(52, &[utf8("a/A"), reference(7, 1), utf8("java/lang/Object"), reference(7, 3)], 2, 4, &[]);
+        let mut preview = class_bytes(65, &[utf8("b/B"), reference(7, 1)], 2, 0, &[]);
+
+        preview[4..6].copy_from_slice(&[0xFF, 0xFF]);
+        // Access flags are followed by this class, super class, interface, field and method counts
+        // and attribute count.
+        let flags = preview.len() - 14;
+        preview[flags..flags + 2].copy_from_slice(&0x2601u16.to_be_bytes());
+
+        let bytes = jar_bytes(&[("a/A.class", &class), ("b/B.class", &preview), ("c/C.class", b"broken"), ("a/a.txt", b"")]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap();
+        let entries = jar.class_entries().collect::<Vec<_>>();
+
+        assert_eq!(entries.len(), 3);
+
+        let header = entries[0].1.as_ref().unwrap();
+        assert_eq!(entries[0].0.name, "a/A.class");
+        assert_eq!((header.this_class.as_str(), header.super_class.as_deref()), ("a/A", Some("java/lang/Object")));
+        assert_eq!(header.java_version(), 8);
+        assert!(!header.is_preview() && !header.is_interface());
+
+        let header = entries[1].1.as_ref().unwrap();
+        assert_eq!(header.java_version(), 21);
+        assert!(header.is_preview() && header.is_interface() && header.is_annotation());
+        assert_eq!(header.super_class, None);
+
+        assert!(matches!(entries[2].1, Err(JarError::ClassFormat(_))));
+        assert_eq!(ClassHeader::parse(&class[..20]).unwrap_err().to_string(), "invalid class file, unexpected end of class file");
+    }
+}
@@ -0,0 +1,376 @@
+//! Minimal class file reader based on
+//! [The Java Virtual Machine Specification, Chapter 4](https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html),
+//! reading only the parts needed to inspect jars rather than method bodies.
+
+use crate::JarError;
+
+/// Magic number every class file starts with.
+pub(crate) const MAGIC: u32 = 0xCAFEBABE;
+
+/// A constant pool entry. Constants not needed by this crate are kept as [Constant::Other].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum Constant {
+    Utf8(String),
+    Class(u16),
+    Module(u16),
+    Package(u16),
+    Other,
+}
+
+/// A parsed class file, with fields and methods skipped.
+#[derive(Debug, Clone)]
+pub(crate) struct ClassFile<'a> {
+    /// Constant pool indexed as in the class file, index 0 and the second slot of long and double
+    /// constants are [Constant::Other].
+    pub(crate) constant_pool: Vec<Constant>,
+    pub(crate) attributes: Vec<(u16, &'a [u8])>,
+}
+
+impl<'a> ClassFile<'a> {
+    pub(crate) fn parse(bytes: &'a [u8]) -> Result<Self, JarError> {
+        let mut reader = ClassReader::new(bytes);
+
+        if reader.u4()? != MAGIC {
+            return Err(JarError::ClassFormat("invalid magic number".to_string()));
+        }
+
+        // Skips versions.
+        reader.u4()?;
+        let constant_pool = read_constant_pool(&mut reader)?;
+        // Skips access flags, this class and super class.
+        reader.bytes(6)?;
+        let interfaces_count = reader.u2()? as usize;
+        reader.bytes(interfaces_count * 2)?;
+
+        // Skips fields, then methods.
+        for _ in 0..2 {
+            for _ in 0..reader.u2()? {
+                reader.bytes(6)?;
+                read_attributes(&mut reader)?;
+            }
+        }
+
+        let attributes = read_attributes(&mut reader)?;
+
+        Ok(Self {
+            constant_pool,
+            attributes,
+        })
+    }
+
+    pub(crate) fn utf8(&self, index: u16) -> Result<&str, JarError> {
+        match self.constant_pool.get(index as usize) {
+            Some(Constant::Utf8(value)) => Ok(value),
+            _ => Err(constant_error(index, "Utf8")),
+        }
+    }
+
+    /// Resolves a `CONSTANT_Class` to its internal name, e.g. `java/lang/Object`.
+    pub(crate) fn class_name(&self, index: u16) -> Result<&str, JarError> {
+        match self.constant_pool.get(index as usize) {
+            Some(Constant::Class(name)) => self.utf8(*name),
+            _ => Err(constant_error(index, "Class")),
+        }
+    }
+
+    pub(crate) fn module_name(&self, index: u16) -> Result<&str, JarError> {
+        match self.constant_pool.get(index as usize) {
+            Some(Constant::Module(name)) => self.utf8(*name),
+            _ => Err(constant_error(index, "Module")),
+        }
+    }
+
+    pub(crate) fn package_name(&self, index: u16) -> Result<&str, JarError> {
+        match self.constant_pool.get(index as usize) {
+            Some(Constant::Package(name)) => self.utf8(*name),
+            _ => Err(constant_error(index, "Package")),
+        }
+    }
+
+    /// Finds the content of the class attribute named `name`.
+    pub(crate) fn attribute(&self, name: &str) -> Option<&'a [u8]> {
+        self.attributes.iter()
+            .find(|(name_index, _)| self.utf8(*name_index).is_ok_and(|attribute| attribute == name))
+            .map(|(_, content)| *content)
+    }
+}
+
+/// A big-endian cursor over class file bytes.
+pub(crate) struct ClassReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ClassReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+        }
+    }
+
+    pub(crate) fn bytes(&mut self, length: usize) -> Result<&'a [u8], JarError> {
+        if length > self.bytes.len() {
+            return Err(JarError::ClassFormat("unexpected end of class file".to_string()));
+        }
+
+        let (bytes, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    pub(crate) fn u1(&mut self) -> Result<u8, JarError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u2(&mut self) -> Result<u16, JarError> {
+        Ok(u16::from_be_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u4(&mut self) -> Result<u32, JarError> {
+        Ok(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+}
+
+pub(crate) fn read_constant_pool(reader: &mut ClassReader) -> Result<Vec<Constant>, JarError> {
+    let count = reader.u2()? as usize;
+    let mut constant_pool = vec![Constant::Other];
+
+    while constant_pool.len() < count {
+        let tag = reader.u1()?;
+        let constant = match tag {
+            1 => {
+                let length = reader.u2()? as usize;
+                Constant::Utf8(decode_modified_utf8(reader.bytes(length)?))
+            }
+            7 => Constant::Class(reader.u2()?),
+            19 => Constant::Module(reader.u2()?),
+            20 => Constant::Package(reader.u2()?),
+            8 | 16 => {
+                reader.bytes(2)?;
+                Constant::Other
+            }
+            15 => {
+                reader.bytes(3)?;
+                Constant::Other
+            }
+            3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => {
+                reader.bytes(4)?;
+                Constant::Other
+            }
+            5 | 6 => {
+                reader.bytes(8)?;
+                // Long and double constants take up two slots.
+                constant_pool.push(Constant::Other);
+                Constant::Other
+            }
+            tag => return Err(JarError::ClassFormat(format!("unknown constant pool tag {}", tag))),
+        };
+
+        constant_pool.push(constant);
+    }
+
+    Ok(constant_pool)
+}
+
+/// Rewrites `CONSTANT_Utf8` values of a class file with `map`, which returns [None] to keep a value.
+/// Since the rest of the class file refers to constants by index, everything after the constant
+/// pool is copied unchanged.
+pub(crate) fn map_utf8_constants<F>(bytes: &[u8], mut map: F) -> Result<Vec<u8>, JarError> where F: FnMut(&str) -> Option<String> {
+    let mut reader = ClassReader::new(bytes);
+
+    if reader.u4()? != MAGIC {
+        return Err(JarError::ClassFormat("invalid magic number".to_string()));
+    }
+
+    reader.u4()?;
+    let count = reader.u2()? as usize;
+    let mut mapped = bytes[..10].to_vec();
+    let mut index = 1;
+
+    while index < count {
+        let tag = reader.u1()?;
+        mapped.push(tag);
+
+        let length = match tag {
+            1 => {
+                let length = reader.u2()? as usize;
+                let value = reader.bytes(length)?;
+
+                match map(&decode_modified_utf8(value)) {
+                    Some(value) => {
+                        let encoded = encode_modified_utf8(&value);
+                        let length = u16::try_from(encoded.len())
+                            .map_err(|_| JarError::ClassFormat("mapped constant exceeds 65535 bytes".to_string()))?;
+
+                        mapped.extend(length.to_be_bytes());
+                        mapped.extend(encoded);
+                    }
+                    None => {
+                        mapped.extend((length as u16).to_be_bytes());
+                        mapped.extend(value);
+                    }
+                }
+
+                0
+            }
+            7 | 8 | 16 | 19 | 20 => 2,
+            15 => 3,
+            3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => 4,
+            5 | 6 => {
+                // Long and double constants take up two slots.
+                index += 1;
+                8
+            }
+            tag => return Err(JarError::ClassFormat(format!("unknown constant pool tag {}", tag))),
+        };
+
+        mapped.extend(reader.bytes(length)?);
+        index += 1;
+    }
+
+    mapped.extend(reader.bytes);
+    Ok(mapped)
+}
+
+fn read_attributes<'a>(reader: &mut ClassReader<'a>) -> Result<Vec<(u16, &'a [u8])>, JarError> {
+    (0..reader.u2()?)
+        .map(|_| {
+            let name_index = reader.u2()?;
+            let length = reader.u4()? as usize;
+
+            Ok((name_index, reader.bytes(length)?))
+        })
+        .collect()
+}
+
+/// Decodes the modified UTF-8 used by class files, where NUL is encoded in two bytes and
+/// supplementary characters as surrogate pairs. Malformed sequences are replaced with U+FFFD.
+pub(crate) fn decode_modified_utf8(bytes: &[u8]) -> String {
+    if let Ok(string) = std::str::from_utf8(bytes) {
+        return string.to_string();
+    }
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let byte = bytes[i] as u16;
+        let continuation = |offset: usize| bytes.get(i + offset).map(|byte| (*byte & 0x3F) as u16).unwrap_or(0xFFFD);
+
+        if byte < 0x80 {
+            units.push(byte);
+            i += 1;
+        } else if byte & 0xE0 == 0xC0 {
+            units.push((byte & 0x1F) << 6 | continuation(1));
+            i += 2;
+        } else if byte & 0xF0 == 0xE0 {
+            units.push((byte & 0x0F) << 12 | continuation(1) << 6 | continuation(2));
+            i += 3;
+        } else {
+            units.push(0xFFFD);
+            i += 1;
+        }
+    }
+
+    String::from_utf16_lossy(&units)
+}
+
+/// Encodes a string in the modified UTF-8 used by class files, see [decode_modified_utf8].
+pub(crate) fn encode_modified_utf8(value: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(value.len());
+
+    for unit in value.encode_utf16() {
+        match unit {
+            0x01..=0x7F => bytes.push(unit as u8),
+            0x00 | 0x80..=0x7FF => bytes.extend([0xC0 | (unit >> 6) as u8, 0x80 | (unit & 0x3F) as u8]),
+            _ => bytes.extend([0xE0 | (unit >> 12) as u8, 0x80 | (unit >> 6 & 0x3F) as u8, 0x80 | (unit & 0x3F) as u8]),
+        }
+    }
+
+    bytes
+}
+
+fn constant_error(index: u16, expected: &str) -> JarError {
+    JarError::ClassFormat(format!("constant pool entry {} is not a {} constant", index, expected))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::classfile::{decode_modified_utf8, encode_modified_utf8, map_utf8_constants, ClassFile, MAGIC};
+
+    /// Builds a class file with given constant pool entries (tag and payload) and class attributes
+    /// (name index and content), declaring `this_class` and `super_class` by constant pool index.
+    pub(crate) fn class_bytes(
+        major_version: u16,
+        constants: &[(u8, Vec<u8>)],
+        this_class: u16,
+        super_class: u16,
+        attributes: &[(u16, Vec<u8>)],
+    ) -> Vec<u8> {
+        let mut bytes = MAGIC.to_be_bytes().to_vec();
+
+        bytes.extend(0u16.to_be_bytes());
+        bytes.extend(major_version.to_be_bytes());
+        bytes.extend((constants.len() as u16 + 1).to_be_bytes());
+        for (tag, payload) in constants {
+            bytes.push(*tag);
+            bytes.extend(payload);
+        }
+        bytes.extend(0x0021u16.to_be_bytes());
+        bytes.extend(this_class.to_be_bytes());
+        bytes.extend(super_class.to_be_bytes());
+        bytes.extend([0; 6]);
+        bytes.extend((attributes.len() as u16).to_be_bytes());
+        for (name_index, content) in attributes {
+            bytes.extend(name_index.to_be_bytes());
+            bytes.extend((content.len() as u32).to_be_bytes());
+            bytes.extend(content);
+        }
+
+        bytes
+    }
+
+    pub(crate) fn utf8(value: &str) -> (u8, Vec<u8>) {
+        let mut payload = (value.len() as u16).to_be_bytes().to_vec();
+        payload.extend(value.as_bytes());
+        (1, payload)
+    }
+
+    pub(crate) fn reference(tag: u8, index: u16) -> (u8, Vec<u8>) {
+        (tag, index.to_be_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_parse_class() {
+        let bytes = class_bytes(
+            61,
+            &[utf8("a/B"), reference(7, 1), utf8("java/lang/Object"), reference(7, 3)],
+            2,
+            4,
+            &[],
+        );
+        let class = ClassFile::parse(&bytes).unwrap();
+
+        assert_eq!(class.class_name(2).unwrap(), "a/B");
+        assert_eq!(class.class_name(4).unwrap(), "java/lang/Object");
+        assert!(class.class_name(1).is_err());
+        assert!(ClassFile::parse(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_decode_modified_utf8() {
+        assert_eq!(decode_modified_utf8(&[0x61, 0xC0, 0x80]), "a\0");
+        assert_eq!(decode_modified_utf8(&[0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]), "\u{1F600}");
+        assert_eq!(encode_modified_utf8("a\0\u{1F600}"), vec![0x61, 0xC0, 0x80, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]);
+    }
+
+    #[test]
+    fn test_map_utf8_constants() {
+        let bytes = class_bytes(61, &[utf8("a/B"), reference(7, 1), utf8("java/lang/Object"), reference(7, 3)], 2, 4, &[]);
+        let mapped = map_utf8_constants(&bytes, |value| value.strip_prefix("a/").map(|name| format!("shaded/a/{}", name))).unwrap();
+        let class = ClassFile::parse(&mapped).unwrap();
+
+        assert_eq!(class.class_name(2).unwrap(), "shaded/a/B");
+        assert_eq!(class.class_name(4).unwrap(), "java/lang/Object");
+        assert_eq!(mapped.len(), bytes.len() + "shaded/".len());
+    }
+}
@@ -0,0 +1,375 @@
+//! Class and resource resolution across multiple jars, and the `Class-Path` manifest attribute
+//! referencing other jars relative to the jar itself.
+
+#[cfg(feature = "fs")]
+use std::collections::{BTreeMap, HashMap, HashSet};
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+
+use crate::{Jar, JarError, Manifest};
+#[cfg(feature = "fs")]
+use crate::{class_path, jar, jar_open, JarOption, JarOptionBuilder, OpenJar, MANIFEST_PATH};
+
+/// An ordered list of jars resolving classes and resources like a JVM class loader: the first jar
+/// containing an entry wins, shadowing the same entry in later jars. Jars are kept open and entries
+/// are only read when requested.
+///
+/// # Example
+///
+/// ```rs
+/// let mut classpath = Classpath::new(&["app.jar", "lib/dependency.jar"], JarOptionBuilder::default())?;
+///
+/// if let Some(content) = classpath.find_class("com.example.Main")? {
+///     // ...
+/// }
+/// ```
+#[cfg(feature = "fs")]
+pub struct Classpath {
+    jars: Vec<(PathBuf, OpenJar<File>)>,
+    providers: HashMap<String, Vec<usize>>,
+}
+
+/// An entry found in more than one jar of a [Classpath].
+#[cfg(feature = "fs")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ShadowedEntry {
+    pub name: String,
+    /// Jar the entry is resolved from.
+    pub provider: PathBuf,
+    /// Later jars whose entry is shadowed, in classpath order.
+    pub shadowed: Vec<PathBuf>,
+}
+
+/// Entries of one jar of a [Classpath] hidden by an earlier jar, see [Classpath::shadowing].
+#[cfg(feature = "fs")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Shadowing {
+    /// Jar the entries are resolved from.
+    pub provider: PathBuf,
+    /// Later jar whose entries are hidden.
+    pub shadowed: PathBuf,
+    /// Hidden entries whose content differs from the resolved one by size or CRC-32, sorted.
+    pub conflicting: Vec<String>,
+    /// Hidden entries with the same content as the resolved one, sorted.
+    pub identical: Vec<String>,
+}
+
+#[cfg(feature = "fs")]
+impl Classpath {
+    /// Opens the jars at `paths` in classpath order, indexing entries passing the extraction rules
+    /// of `option`.
+    pub fn new<P>(paths: &[P], option: JarOption) -> Result<Self, JarError> where P: AsRef<Path> {
+        let mut jars = Vec::with_capacity(paths.len());
+        let mut providers = HashMap::<String, Vec<usize>>::new();
+
+        for (position, path) in paths.iter().enumerate() {
+            let jar = jar_open(path, option.clone())?;
+
+            for name in jar.names() {
+                providers.entry(name.to_string()).or_default().push(position);
+            }
+
+            jars.push((path.as_ref().to_path_buf(), jar));
+        }
+
+        Ok(Self {
+            jars,
+            providers,
+        })
+    }
+
+    /// Reads the class with given binary name, e.g. `java.util.Map$Entry`, from the first jar
+    /// containing it.
+    pub fn find_class(&mut self, binary_name: &str) -> Result<Option<Vec<u8>>, JarError> {
+        self.find_resource(&class_path(binary_name))
+    }
+
+    /// Reads the resource at full qualified path `path` from the first jar containing it.
+    pub fn find_resource(&mut self, path: &str) -> Result<Option<Vec<u8>>, JarError> {
+        match self.providers.get(path) {
+            Some(providers) => self.jars[providers[0]].1.get(path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the path of the jar `path` is resolved from.
+    pub fn locate(&self, path: &str) -> Option<&Path> {
+        self.providers.get(path).map(|providers| self.jars[providers[0]].0.as_path())
+    }
+
+    /// Reports entries found in more than one jar, sorted by name.
+    pub fn duplicates(&self) -> Vec<ShadowedEntry> {
+        let mut duplicates = self.providers.iter()
+            .filter(|(_, providers)| providers.len() > 1)
+            .map(|(name, providers)| ShadowedEntry {
+                name: name.clone(),
+                provider: self.jars[providers[0]].0.clone(),
+                shadowed: providers[1..].iter().map(|position| self.jars[*position].0.clone()).collect(),
+            })
+            .collect::<Vec<_>>();
+
+        duplicates.sort_by(|a, b| a.name.cmp(&b.name));
+        duplicates
+    }
+
+    /// Returns the effective view of the classpath, every entry name with the jar it is resolved
+    /// from, sorted by name.
+    pub fn resources(&self) -> Vec<(&str, &Path)> {
+        let mut resources = self.providers.iter()
+            .map(|(name, providers)| (name.as_str(), self.jars[providers[0]].0.as_path()))
+            .collect::<Vec<_>>();
+
+        resources.sort();
+        resources
+    }
+
+    /// Reports which jar hides which entries of which later jar, one [Shadowing] per pair of jars in
+    /// classpath order. Only the central directories are compared, so no content is read.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let classpath = Classpath::new(&classpath, JarOptionBuilder::default())?;
+    ///
+    /// for shadowing in classpath.shadowing() {
+    ///     for name in &shadowing.conflicting {
+    ///         eprintln!("{} from {} hides {}", name, shadowing.provider.display(), shadowing.shadowed.display());
+    ///     }
+    /// }
+    /// ```
+    pub fn shadowing(&self) -> Vec<Shadowing> {
+        let mut pairs = BTreeMap::<(usize, usize), (Vec<String>, Vec<String>)>::new();
+
+        for (name, providers) in self.providers.iter().filter(|(_, providers)| providers.len() > 1) {
+            let provider = self.jars[providers[0]].1.entry(name);
+
+            for position in &providers[1..] {
+                let shadowed = self.jars[*position].1.entry(name);
+                let (conflicting, identical) = pairs.entry((providers[0], *position)).or_default();
+                let same_content = matches!((provider, shadowed), (Some(a), Some(b)) if a.size == b.size && a.crc32 == b.crc32);
+
+                if same_content {
+                    identical.push(name.clone());
+                } else {
+                    conflicting.push(name.clone());
+                }
+            }
+        }
+
+        pairs.into_iter()
+            .map(|((provider, shadowed), (mut conflicting, mut identical))| {
+                conflicting.sort();
+                identical.sort();
+
+                Shadowing {
+                    provider: self.jars[provider].0.clone(),
+                    shadowed: self.jars[shadowed].0.clone(),
+                    conflicting,
+                    identical,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Jar {
+    /// Returns entries of the `Class-Path` manifest attribute, which are space-separated URLs
+    /// relative to the directory containing the jar, e.g. `lib/dependency.jar`. Returns an empty list
+    /// when the manifest or the attribute is absent.
+    pub fn class_path(&self) -> Result<Vec<String>, JarError> {
+        Ok(self.manifest()?.as_ref().map(class_path_of).unwrap_or_default())
+    }
+
+    /// Loads jars referenced by `Class-Path` relative to `base_dir`, the directory containing this
+    /// jar, with given extraction rules. Referenced jars are resolved recursively through their own
+    /// `Class-Path` and returned in resolution order with their paths, each jar only once. As the JVM
+    /// does, missing jars, directories and non-`file` URLs are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let app = jar("app/app.jar", JarOptionBuilder::default())?;
+    ///
+    /// for (path, dependency) in app.resolve_class_path("app", JarOptionBuilder::default())? {
+    ///     // ...
+    /// }
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn resolve_class_path<P>(&self, base_dir: P, option: JarOption) -> Result<Vec<(PathBuf, Jar)>, JarError>
+        where P: AsRef<Path> {
+        let mut resolved = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = self.class_path()?.into_iter()
+            .rev()
+            .map(|entry| (base_dir.as_ref().to_path_buf(), entry))
+            .collect::<Vec<_>>();
+
+        while let Some((base_dir, entry)) = pending.pop() {
+            let Some(path) = resolve_entry(&base_dir, &entry) else {
+                continue;
+            };
+
+            if !path.is_file() || !visited.insert(path.canonicalize()?) {
+                continue;
+            }
+
+            let class_path = read_manifest(&path)?.as_ref().map(class_path_of).unwrap_or_default();
+            let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+            pending.extend(class_path.into_iter().rev().map(|entry| (dir.clone(), entry)));
+            resolved.push((path.clone(), jar(&path, option.clone())?));
+        }
+
+        Ok(resolved)
+    }
+}
+
+fn class_path_of(manifest: &Manifest) -> Vec<String> {
+    manifest.main_attributes()
+        .get("Class-Path")
+        .map(|class_path| class_path.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(feature = "fs")]
+fn read_manifest(path: &Path) -> Result<Option<Manifest>, JarError> {
+    let mut jar = jar_open(path, JarOptionBuilder::builder().target(MANIFEST_PATH).build())?;
+
+    if jar.contains(MANIFEST_PATH) {
+        crate::parse_manifest(&jar.get(MANIFEST_PATH)?).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Resolves a `Class-Path` URL against `base_dir`, returning [None] for directories and URLs with a
+/// scheme other than `file`.
+#[cfg(feature = "fs")]
+fn resolve_entry(base_dir: &Path, entry: &str) -> Option<PathBuf> {
+    let path = match entry.split_once(':') {
+        Some((scheme, path)) if scheme.eq_ignore_ascii_case("file") => path,
+        // A single letter before `:` is a Windows drive rather than a scheme.
+        Some((scheme, _)) if scheme.len() > 1 => return None,
+        _ => entry,
+    };
+
+    if path.ends_with('/') {
+        return None;
+    }
+
+    Some(base_dir.join(percent_decode(path)?))
+}
+
+#[cfg(feature = "fs")]
+fn percent_decode(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::path::Path;
+
+    use crate::classpath::resolve_entry;
+    use crate::fixture::{jar_bytes, jar_file, temp_path};
+    use crate::{jar, Classpath, JarOptionBuilder, Shadowing};
+
+    #[test]
+    fn test_classpath() {
+        let first = jar_file(&[("a/A.class", b"first"), ("a/messages.properties", b"")]);
+        let second = jar_file(&[("a/A.class", b"second"), ("b/B.class", b"b")]);
+        let mut classpath = Classpath::new(&[&first, &second], JarOptionBuilder::default()).unwrap();
+
+        assert_eq!(classpath.find_class("a.A").unwrap().unwrap(), b"first");
+        assert_eq!(classpath.find_class("b.B").unwrap().unwrap(), b"b");
+        assert_eq!(classpath.find_resource("c/C.class").unwrap(), None);
+        assert_eq!(classpath.locate("b/B.class"), Some(second.as_path()));
+
+        let duplicates = classpath.duplicates();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].name, "a/A.class");
+        assert_eq!(duplicates[0].provider, first);
+        assert_eq!(duplicates[0].shadowed, vec![second]);
+    }
+
+    #[test]
+    fn test_shadowing() {
+        let first = jar_file(&[("logback.xml", b"first"), ("a/A.class", b"a")]);
+        let second = jar_file(&[("logback.xml", b"second"), ("a/A.class", b"a"), ("b/B.class", b"b")]);
+        let third = jar_file(&[("logback.xml", b"third"), ("b/B.class", b"other")]);
+        let classpath = Classpath::new(&[&first, &second, &third], JarOptionBuilder::default()).unwrap();
+
+        assert_eq!(classpath.resources(), vec![
+            ("a/A.class", first.as_path()),
+            ("b/B.class", second.as_path()),
+            ("logback.xml", first.as_path()),
+        ]);
+        assert_eq!(classpath.shadowing(), vec![
+            Shadowing {
+                provider: first.clone(),
+                shadowed: second.clone(),
+                conflicting: vec!["logback.xml".to_string()],
+                identical: vec!["a/A.class".to_string()],
+            },
+            Shadowing {
+                provider: first.clone(),
+                shadowed: third.clone(),
+                conflicting: vec!["logback.xml".to_string()],
+                identical: vec![],
+            },
+            Shadowing {
+                provider: second.clone(),
+                shadowed: third.clone(),
+                conflicting: vec!["b/B.class".to_string()],
+                identical: vec![],
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_resolve_class_path() {
+        let dir = temp_path("class-path");
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        std::fs::write(dir.join("app.jar"), jar_bytes(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nClass-Path: lib/a%20b.jar lib/missing.jar classes/\r\n"),
+        ])).unwrap();
+        std::fs::write(dir.join("lib/a b.jar"), jar_bytes(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nClass-Path: c.jar a%20b.jar\r\n"),
+            ("a/A.class", b"a"),
+        ])).unwrap();
+        std::fs::write(dir.join("lib/c.jar"), jar_bytes(&[("c/C.class", b"c")])).unwrap();
+
+        let app = jar(dir.join("app.jar"), JarOptionBuilder::default()).unwrap();
+        let resolved = app.resolve_class_path(&dir, JarOptionBuilder::builder().ext("class").build()).unwrap();
+
+        assert_eq!(app.class_path().unwrap(), vec!["lib/a%20b.jar", "lib/missing.jar", "classes/"]);
+        assert_eq!(resolved.iter().map(|(path, _)| path.clone()).collect::<Vec<_>>(), vec![dir.join("lib/a b.jar"), dir.join("lib/c.jar")]);
+        assert_eq!(resolved[1].1.files["c/C.class"], b"c");
+    }
+
+    #[test]
+    fn test_resolve_entry() {
+        let base = Path::new("base");
+
+        assert_eq!(resolve_entry(base, "file:lib/a.jar"), Some(base.join("lib/a.jar")));
+        assert_eq!(resolve_entry(base, "http://example.com/a.jar"), None);
+        assert_eq!(resolve_entry(base, "lib/"), None);
+    }
+}
@@ -0,0 +1,78 @@
+//! Compression methods of jar entries, decoupled from the underlying zip implementation.
+
+use std::collections::HashMap;
+use std::io::Read;
+
+use crate::option::Callback;
+
+/// Decompressor of [crate::JarOptionBuilder::decompressor], wrapping the raw content of an entry.
+pub(crate) type Decompressor = dyn for<'r> Fn(Box<dyn Read + 'r>) -> std::io::Result<Box<dyn Read + 'r>> + Send + Sync;
+
+/// Registered decompressors by method id.
+pub(crate) type Decompressors = HashMap<u16, Callback<Decompressor>>;
+
+/// Compression method of a jar entry.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompressionMethod {
+    /// Entry content is stored as is.
+    Stored,
+    /// Entry content is compressed with Deflate, which is what almost all jars use.
+    Deflated,
+    /// Entry content is compressed with BZIP2.
+    Bzip2,
+    /// Entry content is compressed with Zstandard.
+    Zstd,
+    /// Any other compression method, identified by its zip method id.
+    Other(u16),
+}
+
+impl CompressionMethod {
+    /// Returns the method id recorded in zip headers.
+    pub(crate) fn id(self) -> u16 {
+        match self {
+            Self::Stored => 0,
+            Self::Deflated => 8,
+            Self::Bzip2 => 12,
+            Self::Zstd => 93,
+            Self::Other(id) => id,
+        }
+    }
+
+    /// Checks whether entries compressed with this method are decompressed without a registered
+    /// decompressor. BZIP2 and Zstandard require features `bzip2` and `zstd`, which are enabled by
+    /// default.
+    pub(crate) fn is_supported(self) -> bool {
+        match self {
+            Self::Stored | Self::Deflated => true,
+            Self::Bzip2 => cfg!(feature = "bzip2"),
+            Self::Zstd => cfg!(feature = "zstd"),
+            Self::Other(_) => false,
+        }
+    }
+
+    /// Maps a method id recorded in zip headers to its method.
+    pub(crate) fn from_id(id: u16) -> Self {
+        match id {
+            0 => Self::Stored,
+            8 => Self::Deflated,
+            12 => Self::Bzip2,
+            93 => Self::Zstd,
+            id => Self::Other(id),
+        }
+    }
+}
+
+impl From<zip::CompressionMethod> for CompressionMethod {
+    #[allow(deprecated)]
+    fn from(method: zip::CompressionMethod) -> Self {
+        Self::from_id(method.to_u16())
+    }
+}
+
+impl From<CompressionMethod> for zip::CompressionMethod {
+    #[allow(deprecated)]
+    fn from(method: CompressionMethod) -> Self {
+        Self::from_u16(method.id())
+    }
+}
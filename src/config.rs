@@ -0,0 +1,245 @@
+//! Extraction rules stored in configuration files, so rule sets can be kept alongside a project and
+//! shared between the command line tool and library users.
+
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::{ContentKind, DuplicatePolicy, JarError, JarOptionBuilder, NameEncoding, SymlinkPolicy, UnsafeNamePolicy};
+#[cfg(feature = "fs")]
+use crate::JarOption;
+
+/// Extraction rules of a [JarOption] which can be described without code, deserialized from
+/// configuration files. Fields are named after the [JarOptionBuilder] methods setting them, and
+/// every field is optional.
+///
+/// # Example
+///
+/// ```json
+/// {
+///     "targets": ["com/example"],
+///     "extensions": ["class"],
+///     "exclude": ["com/example/internal"],
+///     "max_entry_size": 10485760,
+///     "duplicate_policy": "Error"
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JarConfig {
+    /// See [JarOptionBuilder::target].
+    pub targets: Vec<String>,
+    /// See [JarOptionBuilder::ext].
+    pub extensions: Vec<String>,
+    /// See [JarOptionBuilder::target_glob].
+    pub globs: Vec<String>,
+    /// See [JarOptionBuilder::target_regex].
+    pub regexes: Vec<String>,
+    /// See [JarOptionBuilder::exclude_target].
+    pub exclude: Vec<String>,
+    /// See [JarOptionBuilder::exclude_ext].
+    pub exclude_extensions: Vec<String>,
+    /// See [JarOptionBuilder::content_type].
+    pub content_types: Vec<ContentKind>,
+    /// See [JarOptionBuilder::strip_prefix].
+    pub strip_prefixes: Vec<String>,
+    /// See [JarOptionBuilder::keep_meta_info].
+    pub keep_meta_info: bool,
+    /// See [JarOptionBuilder::strip_signatures].
+    pub strip_signatures: bool,
+    /// See [JarOptionBuilder::min_size].
+    pub min_size: Option<u64>,
+    /// See [JarOptionBuilder::max_size].
+    pub max_size: Option<u64>,
+    /// See [JarOptionBuilder::max_depth].
+    pub max_depth: Option<usize>,
+    /// See [JarOptionBuilder::release].
+    pub release: Option<u32>,
+    /// See [JarOptionBuilder::max_total_size].
+    pub max_total_size: Option<u64>,
+    /// See [JarOptionBuilder::max_entry_size].
+    pub max_entry_size: Option<u64>,
+    /// See [JarOptionBuilder::max_entries].
+    pub max_entries: Option<usize>,
+    /// See [JarOptionBuilder::max_compression_ratio].
+    pub max_compression_ratio: Option<u64>,
+    /// See [JarOptionBuilder::skip_corrupt].
+    pub skip_corrupt: bool,
+    /// See [JarOptionBuilder::verify_crc].
+    pub verify_crc: Option<bool>,
+    /// See [JarOptionBuilder::parallel].
+    pub threads: Option<usize>,
+    /// See [JarOptionBuilder::name_encoding].
+    pub name_encoding: Option<NameEncoding>,
+    /// See [JarOptionBuilder::include_dirs].
+    pub include_dirs: bool,
+    /// See [JarOptionBuilder::duplicate_policy].
+    pub duplicate_policy: Option<DuplicatePolicy>,
+    /// See [JarOptionBuilder::unsafe_name_policy].
+    pub unsafe_name_policy: Option<UnsafeNamePolicy>,
+    /// See [JarOptionBuilder::symlink_policy].
+    pub symlink_policy: Option<SymlinkPolicy>,
+    /// See [JarOptionBuilder::case_insensitive].
+    pub case_insensitive: bool,
+    /// See [JarOptionBuilder::normalize_paths].
+    pub normalize_paths: bool,
+    /// See [JarOptionBuilder::prefix_targets].
+    pub prefix_targets: bool,
+}
+
+impl JarConfig {
+    /// Parses a JSON configuration, failing on unknown fields so misspelled rules are not
+    /// silently ignored.
+    pub fn from_json_str(json: &str) -> Result<Self, JarError> {
+        serde_json::from_str(json).map_err(|err| JarError::Config(err.to_string()))
+    }
+
+    /// Reads and parses the JSON configuration file at `path`, see [JarConfig::from_json_str].
+    #[cfg(feature = "fs")]
+    pub fn from_json<P>(path: P) -> Result<Self, JarError> where P: AsRef<Path> {
+        Self::from_json_str(&std::fs::read_to_string(path)?)
+    }
+
+    /// Creates a [JarOptionBuilder] with the rules of this configuration, so options only
+    /// expressible in code, e.g. [JarOptionBuilder::filter], can be added. Fails if a regex is
+    /// invalid.
+    pub fn builder(&self) -> Result<JarOptionBuilder, JarError> {
+        let mut builder = JarOptionBuilder::builder();
+
+        for target in &self.targets {
+            builder = builder.target(target);
+        }
+        for extension in &self.extensions {
+            builder = builder.ext(extension);
+        }
+        for glob in &self.globs {
+            builder = builder.target_glob(glob);
+        }
+        for regex in &self.regexes {
+            builder = builder.target_regex(regex)?;
+        }
+        for exclude in &self.exclude {
+            builder = builder.exclude_target(exclude);
+        }
+        for extension in &self.exclude_extensions {
+            builder = builder.exclude_ext(extension);
+        }
+        for kind in &self.content_types {
+            builder = builder.content_type(*kind);
+        }
+        for prefix in &self.strip_prefixes {
+            builder = builder.strip_prefix(prefix);
+        }
+
+        if self.keep_meta_info {
+            builder = builder.keep_meta_info();
+        }
+        if self.strip_signatures {
+            builder = builder.strip_signatures();
+        }
+        if let Some(min_size) = self.min_size {
+            builder = builder.min_size(min_size);
+        }
+        if let Some(max_size) = self.max_size {
+            builder = builder.max_size(max_size);
+        }
+        if let Some(max_depth) = self.max_depth {
+            builder = builder.max_depth(max_depth);
+        }
+        if let Some(max_total_size) = self.max_total_size {
+            builder = builder.max_total_size(max_total_size);
+        }
+        if let Some(max_entry_size) = self.max_entry_size {
+            builder = builder.max_entry_size(max_entry_size);
+        }
+        if let Some(max_compression_ratio) = self.max_compression_ratio {
+            builder = builder.max_compression_ratio(max_compression_ratio);
+        }
+        if self.skip_corrupt {
+            builder = builder.skip_corrupt();
+        }
+        if self.include_dirs {
+            builder = builder.include_dirs();
+        }
+        if self.case_insensitive {
+            builder = builder.case_insensitive();
+        }
+        if self.normalize_paths {
+            builder = builder.normalize_paths();
+        }
+        if self.prefix_targets {
+            builder = builder.prefix_targets();
+        }
+        if let Some(release) = self.release {
+            builder = builder.release(release);
+        }
+        if let Some(max_entries) = self.max_entries {
+            builder = builder.max_entries(max_entries);
+        }
+        if let Some(verify_crc) = self.verify_crc {
+            builder = builder.verify_crc(verify_crc);
+        }
+        if let Some(threads) = self.threads {
+            builder = builder.parallel(threads);
+        }
+        if let Some(encoding) = self.name_encoding {
+            builder = builder.name_encoding(encoding);
+        }
+        if let Some(policy) = self.duplicate_policy {
+            builder = builder.duplicate_policy(policy);
+        }
+        if let Some(policy) = self.unsafe_name_policy {
+            builder = builder.unsafe_name_policy(policy);
+        }
+        if let Some(policy) = self.symlink_policy {
+            builder = builder.symlink_policy(policy);
+        }
+
+        Ok(builder)
+    }
+}
+
+#[cfg(feature = "fs")]
+impl JarOption {
+    /// Builds extraction options from the JSON configuration file at `path`, see [JarConfig].
+    /// Contradictory rules are rejected, see [crate::JarOptionBuilder::try_build].
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("app.jar", JarOption::from_json("jars.json")?)?;
+    /// ```
+    pub fn from_json<P>(path: P) -> Result<JarOption, JarError> where P: AsRef<Path> {
+        JarConfig::from_json(path)?.builder()?.try_build()
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use crate::fixture::{jar_bytes, temp_path};
+    use crate::{jar_from_bytes, DuplicatePolicy, JarConfig, JarError, JarOption};
+
+    #[test]
+    fn test_from_json() {
+        let path = temp_path("jars.json");
+        std::fs::write(&path, r#"{
+            "targets": ["a"],
+            "exclude": ["a/internal"],
+            "globs": ["**/*.txt"],
+            "max_size": 4,
+            "duplicate_policy": "FirstWins"
+        }"#).unwrap();
+
+        let option = JarOption::from_json(&path).unwrap();
+        let bytes = jar_bytes(&[("a/A.class", b"a"), ("a/internal/B.class", b"b"), ("a/large.bin", b"large"), ("c.txt", b"c"), ("d.bin", b"d")]);
+        let mut names = jar_from_bytes(&bytes, option).unwrap().files.into_keys().collect::<Vec<_>>();
+
+        names.sort();
+        assert_eq!(names, vec!["a/A.class", "c.txt"]);
+
+        let config = JarConfig::from_json_str(r#"{"duplicate_policy": "Error"}"#).unwrap();
+        assert_eq!(config.duplicate_policy, Some(DuplicatePolicy::Error));
+        assert!(config.targets.is_empty());
+        assert!(matches!(JarConfig::from_json_str(r#"{"target": ["a"]}"#), Err(JarError::Config(_))));
+        assert!(matches!(JarConfig::from_json_str(r#"{"regexes": ["("]}"#).unwrap().builder(), Err(JarError::InvalidPattern(_))));
+    }
+}
@@ -0,0 +1,168 @@
+//! Content-level duplicate detection across a set of jars, finding resources and classes bundled by
+//! more than one artifact, e.g. on a classpath or in a distribution's `lib` directory.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::digest::DigestWriter;
+use crate::{jar_stream, DigestAlgorithm, JarError, JarOptionBuilder, MODULE_INFO_PATH, VERSIONS_PREFIX};
+
+/// Entries with identical content found in more than one jar, see [find_duplicates].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateContent {
+    /// SHA-256 digest of the content.
+    pub digest: Vec<u8>,
+    pub size: u64,
+    /// Jars and entry names holding the content, sorted. Names may differ between jars, e.g. for
+    /// relocated or renamed copies.
+    pub locations: Vec<(PathBuf, String)>,
+}
+
+/// A class found under the same name in more than one jar, see [find_duplicates].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateClass {
+    pub name: String,
+    /// Jars containing the class, in the order given to [find_duplicates].
+    pub jars: Vec<PathBuf>,
+    /// Whether every copy has the same content. Differing copies make the loaded class depend on
+    /// the classpath order.
+    pub identical: bool,
+}
+
+/// A package whose classes are spread over more than one jar, which the module system rejects, see
+/// [find_duplicates].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitPackage {
+    /// Package in internal form, e.g. `com/example`.
+    pub package: String,
+    /// Jars containing classes of the package, in the order given to [find_duplicates].
+    pub jars: Vec<PathBuf>,
+}
+
+/// Report of [find_duplicates].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DuplicateReport {
+    /// Content shared by more than one jar, sorted by the first location.
+    pub identical: Vec<DuplicateContent>,
+    /// Classes shared by more than one jar, sorted by name.
+    pub classes: Vec<DuplicateClass>,
+    /// Packages split over more than one jar, sorted by package.
+    pub split_packages: Vec<SplitPackage>,
+}
+
+impl DuplicateReport {
+    /// Checks whether no content, class or package is shared between jars.
+    pub fn is_clean(&self) -> bool {
+        self.identical.is_empty() && self.classes.is_empty() && self.split_packages.is_empty()
+    }
+
+    /// Returns the number of bytes taken by copies of identical content beyond the first one.
+    pub fn redundant_size(&self) -> u64 {
+        self.identical.iter()
+            .map(|duplicate| duplicate.size * (duplicate.locations.len() as u64 - 1))
+            .sum()
+    }
+}
+
+/// Hashes the entries of the jars at `paths` and reports content, classes and packages found in
+/// more than one of them. Entries are streamed, directories and empty files are ignored, and
+/// versioned classes of multi-release jars as well as `module-info.class` are not counted as
+/// classes.
+///
+/// # Example
+///
+/// ```rs
+/// let report = find_duplicates(&["lib/a.jar", "lib/b.jar", "lib/c.jar"])?;
+///
+/// for class in report.classes.iter().filter(|class| !class.identical) {
+///     eprintln!("{} differs between {:?}", class.name, class.jars);
+/// }
+/// ```
+pub fn find_duplicates<P>(paths: &[P]) -> Result<DuplicateReport, JarError> where P: AsRef<Path> {
+    let mut contents = BTreeMap::<(Vec<u8>, u64), BTreeSet<(usize, String)>>::new();
+    let mut classes = BTreeMap::<String, Vec<(usize, Vec<u8>)>>::new();
+    let mut packages = BTreeMap::<String, BTreeSet<usize>>::new();
+
+    for (position, path) in paths.iter().enumerate() {
+        let mut stream = jar_stream(path, JarOptionBuilder::default())?;
+
+        while let Some(next) = stream.next_match() {
+            let (index, entry) = next?;
+
+            if entry.size == 0 {
+                continue;
+            }
+
+            let mut digest = DigestWriter(DigestAlgorithm::Sha256.context());
+
+            std::io::copy(&mut stream.decode(index, &entry)?, &mut digest)?;
+
+            let digest = digest.0.finish().as_ref().to_vec();
+
+            if entry.name.ends_with(".class") && !entry.name.starts_with(VERSIONS_PREFIX) && entry.name != MODULE_INFO_PATH {
+                let package = entry.name.rsplit_once('/').map_or("", |(package, _)| package);
+
+                packages.entry(package.to_string()).or_default().insert(position);
+                classes.entry(entry.name.clone()).or_default().push((position, digest.clone()));
+            }
+
+            contents.entry((digest, entry.size)).or_default().insert((position, entry.name));
+        }
+    }
+
+    let jar = |position: usize| paths[position].as_ref().to_path_buf();
+    let mut identical = contents.into_iter()
+        .filter(|(_, locations)| locations.first().map(|(position, _)| position) != locations.last().map(|(position, _)| position))
+        .map(|((digest, size), locations)| DuplicateContent {
+            digest,
+            size,
+            locations: locations.into_iter().map(|(position, name)| (jar(position), name)).collect(),
+        })
+        .collect::<Vec<_>>();
+
+    identical.sort_by(|a, b| a.locations[0].cmp(&b.locations[0]));
+
+    Ok(DuplicateReport {
+        identical,
+        classes: classes.into_iter()
+            .filter(|(_, copies)| copies.len() > 1)
+            .map(|(name, copies)| DuplicateClass {
+                name,
+                identical: copies.iter().all(|(_, digest)| *digest == copies[0].1),
+                jars: copies.into_iter().map(|(position, _)| jar(position)).collect(),
+            })
+            .collect(),
+        split_packages: packages.into_iter()
+            .filter(|(_, jars)| jars.len() > 1)
+            .map(|(package, jars)| SplitPackage {
+                package,
+                jars: jars.into_iter().map(jar).collect(),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::find_duplicates;
+    use crate::fixture::jar_file;
+
+    #[test]
+    fn test_find_duplicates() {
+        let first = jar_file(&[("a/A.class", b"a"), ("a/B.class", b"b"), ("LICENSE", b"license"), ("empty.txt", b"")]);
+        let second = jar_file(&[("a/A.class", b"a"), ("a/B.class", b"other"), ("shaded/LICENSE.txt", b"license"), ("empty.txt", b"")]);
+        let third = jar_file(&[("b/C.class", b"c"), ("b/D.class", b"c")]);
+        let report = find_duplicates(&[&first, &second, &third]).unwrap();
+
+        assert_eq!(report.identical.len(), 2);
+        assert_eq!(report.identical[0].locations, vec![(first.clone(), "LICENSE".to_string()), (second.clone(), "shaded/LICENSE.txt".to_string())]);
+        assert_eq!(report.identical[1].locations, vec![(first.clone(), "a/A.class".to_string()), (second.clone(), "a/A.class".to_string())]);
+        assert_eq!(report.redundant_size(), 8);
+
+        let classes = report.classes.iter().map(|class| (class.name.as_str(), class.identical)).collect::<Vec<_>>();
+        assert_eq!(classes, vec![("a/A.class", true), ("a/B.class", false)]);
+        assert_eq!(report.split_packages.len(), 1);
+        assert_eq!((report.split_packages[0].package.as_str(), report.split_packages[0].jars.len()), ("a", 2));
+        assert!(find_duplicates(&[&third]).unwrap().is_clean());
+    }
+}
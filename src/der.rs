@@ -0,0 +1,164 @@
+//! Minimal DER reader and writer, sufficient for PKCS#7 signature blocks and X.509 certificates.
+
+/// Tags of universal DER types used by this crate.
+pub(crate) mod tag {
+    pub(crate) const INTEGER: u8 = 0x02;
+    pub(crate) const BIT_STRING: u8 = 0x03;
+    pub(crate) const OCTET_STRING: u8 = 0x04;
+    pub(crate) const NULL: u8 = 0x05;
+    pub(crate) const OID: u8 = 0x06;
+    pub(crate) const SEQUENCE: u8 = 0x30;
+    pub(crate) const SET: u8 = 0x31;
+    pub(crate) const CONTEXT_0: u8 = 0xA0;
+}
+
+pub(crate) type DerResult<T> = Result<T, &'static str>;
+
+/// A single decoded TLV element.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Der<'a> {
+    pub(crate) tag: u8,
+    /// Content octets of the element.
+    pub(crate) content: &'a [u8],
+    /// Full encoding of the element, header included.
+    pub(crate) raw: &'a [u8],
+}
+
+impl<'a> Der<'a> {
+    /// Returns a reader over the elements nested in this constructed element.
+    pub(crate) fn reader(&self) -> DerReader<'a> {
+        DerReader::new(self.content)
+    }
+}
+
+/// A sequential reader of DER elements.
+#[derive(Debug, Clone)]
+pub(crate) struct DerReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub(crate) fn peek_tag(&self) -> Option<u8> {
+        self.bytes.first().copied()
+    }
+
+    /// Reads the next element regardless of its tag.
+    pub(crate) fn read(&mut self) -> DerResult<Der<'a>> {
+        let tag = *self.bytes.first().ok_or("unexpected end of data")?;
+        let first_length = *self.bytes.get(1).ok_or("unexpected end of data")?;
+        let (length, header_length) = if first_length < 0x80 {
+            (first_length as usize, 2)
+        } else {
+            let length_bytes = (first_length & 0x7F) as usize;
+
+            if length_bytes == 0 {
+                return Err("indefinite length is not supported");
+            }
+            if length_bytes > 4 {
+                return Err("length is too large");
+            }
+
+            let length = self.bytes.get(2..2 + length_bytes)
+                .ok_or("unexpected end of data")?
+                .iter()
+                .fold(0usize, |length, byte| length << 8 | *byte as usize);
+
+            (length, 2 + length_bytes)
+        };
+        let end = header_length.checked_add(length).ok_or("length is too large")?;
+
+        if end > self.bytes.len() {
+            return Err("unexpected end of data");
+        }
+
+        let (raw, rest) = self.bytes.split_at(end);
+        self.bytes = rest;
+
+        Ok(Der {
+            tag,
+            content: &raw[header_length..],
+            raw,
+        })
+    }
+
+    /// Reads the next element, failing if it does not have the expected tag.
+    pub(crate) fn expect(&mut self, tag: u8) -> DerResult<Der<'a>> {
+        let der = self.read()?;
+
+        if der.tag == tag {
+            Ok(der)
+        } else {
+            Err("unexpected element")
+        }
+    }
+
+    /// Reads the next element only if it has the given tag.
+    pub(crate) fn optional(&mut self, tag: u8) -> DerResult<Option<Der<'a>>> {
+        if self.peek_tag() == Some(tag) {
+            self.read().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Encodes a single DER element with given content octets.
+pub(crate) fn encode(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![tag];
+    let length = content.len().to_be_bytes();
+    let significant = &length[length.iter().position(|byte| *byte != 0).unwrap_or(length.len() - 1)..];
+
+    if content.len() < 0x80 {
+        bytes.push(content.len() as u8);
+    } else {
+        bytes.push(0x80 | significant.len() as u8);
+        bytes.extend(significant);
+    }
+
+    bytes.extend(content);
+    bytes
+}
+
+/// Encodes a constructed DER element from already encoded elements.
+pub(crate) fn encode_all(tag: u8, elements: &[&[u8]]) -> Vec<u8> {
+    encode(tag, &elements.concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::der::{encode, encode_all, tag, DerReader};
+
+    #[test]
+    fn test_read_nested() {
+        // SEQUENCE { INTEGER 5, OCTET STRING (200 bytes) }
+        let mut bytes = vec![tag::SEQUENCE, 0x81, 0xCE, tag::INTEGER, 0x01, 0x05, tag::OCTET_STRING, 0x81, 0xC8];
+        bytes.extend([0xAB; 200]);
+
+        let sequence = DerReader::new(&bytes).expect(tag::SEQUENCE).unwrap();
+        let mut reader = sequence.reader();
+
+        assert_eq!(reader.expect(tag::INTEGER).unwrap().content, [5]);
+        assert!(reader.optional(tag::SET).unwrap().is_none());
+        assert_eq!(reader.expect(tag::OCTET_STRING).unwrap().content.len(), 200);
+        assert!(reader.is_empty());
+        assert!(DerReader::new(&bytes[..10]).read().is_err());
+    }
+
+    #[test]
+    fn test_encode() {
+        let bytes = encode_all(tag::SEQUENCE, &[&encode(tag::INTEGER, &[5]), &encode(tag::OCTET_STRING, &[0xAB; 200])]);
+
+        assert_eq!(&bytes[..9], [tag::SEQUENCE, 0x81, 0xCE, tag::INTEGER, 0x01, 0x05, tag::OCTET_STRING, 0x81, 0xC8]);
+        assert_eq!(encode(tag::OCTET_STRING, &[0; 300])[..4], [tag::OCTET_STRING, 0x82, 0x01, 0x2C]);
+    }
+}
@@ -0,0 +1,237 @@
+//! Structural comparison of two jars.
+
+use std::collections::BTreeSet;
+
+use crate::{binary_name, Attributes, Jar, JarError, Manifest, MODULE_INFO_PATH, VERSIONS_PREFIX};
+
+/// Differences between two jars, see [diff]. Entry names are sorted.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JarDiff {
+    /// Files only present in the new jar.
+    pub added: Vec<String>,
+    /// Files only present in the old jar.
+    pub removed: Vec<String>,
+    /// Files present in both jars with different contents.
+    pub modified: Vec<String>,
+    /// Changed manifest attributes, in main section then per-entry section order.
+    pub manifest: Vec<AttributeChange>,
+}
+
+impl JarDiff {
+    /// Checks whether both jars have identical files.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Classes changed between two jars, see [diff_classes]. Classes are named by binary name, e.g.
+/// `java.util.Map$Entry`, and sorted.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassDiff {
+    /// Classes only present in the new jar.
+    pub added: Vec<String>,
+    /// Classes only present in the old jar.
+    pub removed: Vec<String>,
+    /// Classes present in both jars with different bytecode.
+    pub modified: Vec<String>,
+}
+
+impl ClassDiff {
+    /// Checks whether both jars have identical classes.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// A manifest attribute added, removed or changed between two jars.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttributeChange {
+    /// Name of the per-entry section containing the attribute, [None] for the main section.
+    pub section: Option<String>,
+    pub name: String,
+    /// Value in the old jar, [None] if the attribute is added.
+    pub old: Option<String>,
+    /// Value in the new jar, [None] if the attribute is removed.
+    pub new: Option<String>,
+}
+
+/// Compares the extracted files of jar `old` against jar `new` by content, and their manifests by
+/// attribute. Fails if either manifest is malformed.
+///
+/// # Example
+///
+/// ```rs
+/// let option = JarOptionBuilder::default();
+/// let diff = diff(&jar("lib-1.0.jar", option.clone())?, &jar("lib-1.1.jar", option)?)?;
+///
+/// for name in &diff.modified {
+///     // ...
+/// }
+/// ```
+pub fn diff(old: &Jar, new: &Jar) -> Result<JarDiff, JarError> {
+    let mut diff = diff_files(old, new);
+    let (old_manifest, new_manifest) = (old.manifest()?.unwrap_or_default(), new.manifest()?.unwrap_or_default());
+
+    diff_attributes(&mut diff.manifest, None, old_manifest.main_attributes(), new_manifest.main_attributes());
+
+    let sections = section_names(&old_manifest).into_iter()
+        .chain(section_names(&new_manifest))
+        .collect::<BTreeSet<_>>();
+    let empty = Attributes::default();
+
+    for section in sections {
+        diff_attributes(
+            &mut diff.manifest,
+            Some(section),
+            old_manifest.entry(section).unwrap_or(&empty),
+            new_manifest.entry(section).unwrap_or(&empty),
+        );
+    }
+
+    Ok(diff)
+}
+
+/// Compares the classes of jar `old` against jar `new` by their decompressed bytecode, so classes
+/// only recompressed or with new timestamps, e.g. after rebuilding unchanged sources, are not
+/// reported. Versioned classes of multi-release jars and `module-info.class` are ignored; extract
+/// both jars with [crate::JarOptionBuilder::release] to compare the classes of a Java release.
+///
+/// # Example
+///
+/// ```rs
+/// let option = JarOptionBuilder::builder().ext("class").build();
+/// let diff = diff_classes(&jar("guava-32.0.jar", option.clone())?, &jar("guava-33.0.jar", option)?);
+///
+/// for class in &diff.removed {
+///     println!("removed {}", class);
+/// }
+/// ```
+pub fn diff_classes(old: &Jar, new: &Jar) -> ClassDiff {
+    let diff = diff_files(old, new);
+    let classes = |names: Vec<String>| {
+        names.iter()
+            .filter(|name| !name.starts_with(VERSIONS_PREFIX) && *name != MODULE_INFO_PATH)
+            .filter_map(|name| binary_name(name))
+            .collect::<Vec<_>>()
+    };
+
+    ClassDiff {
+        added: classes(diff.added),
+        removed: classes(diff.removed),
+        modified: classes(diff.modified),
+    }
+}
+
+/// Compares extracted files by content, leaving manifest changes empty.
+fn diff_files(old: &Jar, new: &Jar) -> JarDiff {
+    let names = old.files.keys().chain(new.files.keys()).collect::<BTreeSet<_>>();
+    let mut diff = JarDiff::default();
+
+    for name in names {
+        match (old.files.get(name), new.files.get(name)) {
+            (Some(_), None) => diff.removed.push(name.clone()),
+            (None, Some(_)) => diff.added.push(name.clone()),
+            (Some(old), Some(new)) if old != new => diff.modified.push(name.clone()),
+            _ => {}
+        }
+    }
+
+    diff
+}
+
+fn section_names(manifest: &Manifest) -> Vec<&str> {
+    manifest.entries().map(|(name, _)| name).collect()
+}
+
+fn diff_attributes(changes: &mut Vec<AttributeChange>, section: Option<&str>, old: &Attributes, new: &Attributes) {
+    let changed = old.iter()
+        .filter(|(name, value)| new.get(name) != Some(*value))
+        .map(|(name, value)| (name, Some(value), new.get(name)))
+        .chain(new.iter()
+            .filter(|(name, _)| !old.contains(name))
+            .map(|(name, value)| (name, None, Some(value))));
+
+    for (name, old, new) in changed {
+        changes.push(AttributeChange {
+            section: section.map(str::to_string),
+            name: name.to_string(),
+            old: old.map(str::to_string),
+            new: new.map(str::to_string),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::fixture::jar_bytes;
+    use crate::{diff, diff_classes, jar_from_bytes, AttributeChange, CompressionMethod, JarOptionBuilder, JarWriter};
+
+    #[test]
+    fn test_diff() {
+        let old = jar_from_bytes(&jar_bytes(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nImplementation-Version: 1.0\r\nX-Removed: a\r\n"),
+            ("a/A.class", b"a"),
+            ("a/B.class", b"b"),
+        ]), JarOptionBuilder::default()).unwrap();
+        let new = jar_from_bytes(&jar_bytes(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nImplementation-Version: 1.1\r\n\r\nName: a/\r\nSealed: true\r\n"),
+            ("a/A.class", b"a"),
+            ("a/B.class", b"b2"),
+            ("a/C.class", b"c"),
+        ]), JarOptionBuilder::default()).unwrap();
+        let diff = diff(&old, &new).unwrap();
+        let change = |section: Option<&str>, name: &str, old: Option<&str>, new: Option<&str>| AttributeChange {
+            section: section.map(str::to_string),
+            name: name.to_string(),
+            old: old.map(str::to_string),
+            new: new.map(str::to_string),
+        };
+
+        assert_eq!(diff.added, vec!["a/C.class"]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.modified, vec!["META-INF/MANIFEST.MF", "a/B.class"]);
+        assert_eq!(diff.manifest, vec![
+            change(None, "Implementation-Version", Some("1.0"), Some("1.1")),
+            change(None, "X-Removed", Some("a"), None),
+            change(Some("a/"), "Sealed", None, Some("true")),
+        ]);
+    }
+
+    #[test]
+    fn test_diff_classes() {
+        let old = jar_from_bytes(&jar_bytes(&[
+            ("module-info.class", b"module"),
+            ("a/A.class", b"a"),
+            ("a/B.class", b"b"),
+            ("a/B$Inner.class", b"inner"),
+            ("a/data.txt", b"data"),
+        ]), JarOptionBuilder::default()).unwrap();
+        let new = jar_from_bytes(&jar_bytes(&[
+            ("module-info.class", b"module2"),
+            ("META-INF/versions/11/a/A.class", b"a11"),
+            ("a/A.class", b"a"),
+            ("a/B.class", b"b2"),
+            ("a/C.class", b"c"),
+            ("a/data.txt", b"data2"),
+        ]), JarOptionBuilder::default()).unwrap();
+        let diff = diff_classes(&old, &new);
+
+        assert_eq!(diff.added, vec!["a.C"]);
+        assert_eq!(diff.removed, vec!["a.B$Inner"]);
+        assert_eq!(diff.modified, vec!["a.B"]);
+
+        let mut writer = JarWriter::new(Cursor::new(Vec::new())).compression(CompressionMethod::Stored);
+
+        for (entry, content) in old.ordered_entries() {
+            writer.write_entry(&entry.name, content).unwrap();
+        }
+
+        let recompressed = jar_from_bytes(&writer.finish().unwrap().into_inner(), JarOptionBuilder::default()).unwrap();
+        assert!(diff_classes(&old, &recompressed).is_empty());
+    }
+}
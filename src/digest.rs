@@ -0,0 +1,123 @@
+//! Message digest algorithms used by jar manifests and signature files.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use ring::digest;
+
+use crate::Jar;
+
+/// A message digest algorithm as named in manifest `<ALGORITHM>-Digest` attributes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum DigestAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    /// Parses an algorithm name used in manifests, e.g. `SHA-256` or the legacy `SHA1`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "SHA1" | "SHA-1" => Some(Self::Sha1),
+            "SHA-256" => Some(Self::Sha256),
+            "SHA-384" => Some(Self::Sha384),
+            "SHA-512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Returns the standard name of the algorithm, e.g. `SHA-256`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+            Self::Sha384 => "SHA-384",
+            Self::Sha512 => "SHA-512",
+        }
+    }
+
+    /// Computes the digest of `bytes`.
+    pub fn digest(&self, bytes: &[u8]) -> Vec<u8> {
+        digest::digest(self.algorithm(), bytes).as_ref().to_vec()
+    }
+
+    /// Starts an incremental digest, for content read in chunks.
+    pub(crate) fn context(&self) -> digest::Context {
+        digest::Context::new(self.algorithm())
+    }
+
+    fn algorithm(&self) -> &'static digest::Algorithm {
+        match self {
+            Self::Sha1 => &digest::SHA1_FOR_LEGACY_USE_ONLY,
+            Self::Sha256 => &digest::SHA256,
+            Self::Sha384 => &digest::SHA384,
+            Self::Sha512 => &digest::SHA512,
+        }
+    }
+}
+
+/// A [Write] sink feeding an incremental digest, e.g. to digest entries while streaming them.
+pub(crate) struct DigestWriter(pub(crate) digest::Context);
+
+impl Write for DigestWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Jar {
+    /// Computes the digest of every extracted file with given algorithm, keyed by full qualified
+    /// path.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let checksums = jar("sample/rt.jar", JarOptionBuilder::default())?.checksums(DigestAlgorithm::Sha1);
+    /// let hex = checksums["java/lang/Object.class"].iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    /// ```
+    pub fn checksums(&self, algorithm: DigestAlgorithm) -> HashMap<String, Vec<u8>> {
+        self.files.iter()
+            .map(|(file_path, content)| (file_path.clone(), algorithm.digest(content)))
+            .collect()
+    }
+}
+
+/// Iterates over digest attributes of a manifest section with given attribute name suffix, e.g.
+/// `-Digest` for `SHA-256-Digest`, yielding the algorithm and the base64-encoded digest. Attributes
+/// with unknown algorithms are skipped.
+pub(crate) fn digest_attributes<'a>(
+    attributes: &'a crate::Attributes,
+    suffix: &'a str,
+) -> impl Iterator<Item = (DigestAlgorithm, &'a str)> {
+    attributes.iter().filter_map(move |(name, value)| {
+        let algorithm = name.len()
+            .checked_sub(suffix.len())
+            .filter(|split| name.is_char_boundary(*split) && name[*split..].eq_ignore_ascii_case(suffix))
+            .and_then(|split| DigestAlgorithm::from_name(&name[..split]))?;
+
+        Some((algorithm, value.trim()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, DigestAlgorithm, JarOptionBuilder};
+
+    #[test]
+    fn test_checksums() {
+        let jar = jar_from_bytes(&jar_bytes(&[("a.txt", b"abc")]), JarOptionBuilder::default()).unwrap();
+        let checksums = jar.checksums(DigestAlgorithm::Sha1);
+        let hex = checksums["a.txt"].iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+
+        assert_eq!(hex, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(DigestAlgorithm::from_name("sha-256").map(|algorithm| algorithm.name()), Some("SHA-256"));
+    }
+}
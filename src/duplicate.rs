@@ -0,0 +1,165 @@
+//! Handling of entries sharing the same name, which zip allows and which can mask content, e.g.
+//! when a scanner inspects the first entry while the JVM loads another.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek};
+
+use zip::ZipArchive;
+
+use crate::encoding::extracted_name;
+use crate::{JarError, JarOption};
+
+/// How entries sharing a name are extracted, set with [crate::JarOptionBuilder::duplicate_policy].
+/// Names are compared after decoding and, for multi-release jars, after release resolution.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DuplicatePolicy {
+    /// Keeps the first entry in archive order.
+    FirstWins,
+    /// Keeps the last entry in archive order, as `java.util.zip.ZipFile` does.
+    #[default]
+    LastWins,
+    /// Fails extraction with [JarError::DuplicateEntry].
+    Error,
+    /// Keeps every entry, renaming the second and later ones with a `~<n>` suffix before the
+    /// extension, e.g. `a/A~1.class`.
+    KeepAll,
+}
+
+/// Duplicated names found before extraction and their resolution.
+#[derive(Debug, Default)]
+pub(crate) struct Duplicates {
+    /// Names shared by more than one entry, sorted.
+    pub(crate) names: Vec<String>,
+    /// Indices of entries dropped by the policy.
+    pub(crate) skipped: HashSet<usize>,
+    /// Indices of entries renamed by [DuplicatePolicy::KeepAll].
+    pub(crate) renamed: HashMap<usize, String>,
+}
+
+/// Finds entries sharing a name and resolves them by the policy of `option`. Only entries visible
+/// after release resolution are considered when `release_names` is given.
+pub(crate) fn resolve_duplicates<R>(
+    archive: &mut ZipArchive<R>,
+    option: &JarOption,
+    release_names: Option<&HashMap<usize, String>>,
+) -> Result<Duplicates, JarError> where R: Read + Seek {
+    let mut occurrences = HashMap::<String, Vec<usize>>::new();
+
+    for index in 0..archive.len() {
+        let name = match release_names {
+            Some(release_names) => match release_names.get(&index) {
+                Some(name) => name.clone(),
+                None => continue,
+            },
+            None => {
+                let file = archive.by_index_raw(index)?;
+
+                match extracted_name(&file, option) {
+                    Some(name) if !file.is_dir() => name,
+                    _ => continue,
+                }
+            }
+        };
+
+        occurrences.entry(name).or_default().push(index);
+    }
+
+    let mut duplicates = Duplicates::default();
+
+    for (name, indices) in &occurrences {
+        if indices.len() < 2 {
+            continue;
+        }
+
+        duplicates.names.push(name.clone());
+
+        match option.duplicate_policy {
+            DuplicatePolicy::FirstWins => duplicates.skipped.extend(&indices[1..]),
+            DuplicatePolicy::LastWins => duplicates.skipped.extend(&indices[..indices.len() - 1]),
+            DuplicatePolicy::Error => return Err(JarError::DuplicateEntry(name.clone())),
+            DuplicatePolicy::KeepAll => {
+                let mut suffix = 0;
+
+                for index in &indices[1..] {
+                    let renamed = loop {
+                        suffix += 1;
+                        let renamed = suffixed(name, suffix);
+
+                        if !occurrences.contains_key(&renamed) {
+                            break renamed;
+                        }
+                    };
+
+                    duplicates.renamed.insert(*index, renamed);
+                }
+            }
+        }
+    }
+
+    duplicates.names.sort();
+    Ok(duplicates)
+}
+
+pub(crate) fn suffixed(name: &str, suffix: usize) -> String {
+    let (dir, file_name) = name.rsplit_once('/').unwrap_or(("", name));
+    let renamed = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => format!("{}~{}.{}", stem, suffix, extension),
+        _ => format!("{}~{}", file_name, suffix),
+    };
+
+    if dir.is_empty() {
+        renamed
+    } else {
+        format!("{}/{}", dir, renamed)
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, jar_list, DuplicatePolicy, JarError, JarOptionBuilder};
+
+    /// Builds a jar where `a/A.class` appears twice, by renaming `a/B.class` in place.
+    fn duplicated_jar() -> Vec<u8> {
+        let mut bytes = jar_bytes(&[("a/A.class", b"first"), ("a/B.class", b"second"), ("b.txt", b"")]);
+
+        for position in 0..bytes.len() - 9 {
+            if &bytes[position..position + 9] == b"a/B.class" {
+                bytes[position + 2] = b'A';
+            }
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn test_duplicate_policies() {
+        let extract = |policy| jar_from_bytes(&duplicated_jar(), JarOptionBuilder::builder().duplicate_policy(policy).build());
+
+        let jar = extract(DuplicatePolicy::LastWins).unwrap();
+        assert_eq!(jar.files["a/A.class"], b"second");
+        assert_eq!(jar.duplicates(), ["a/A.class"]);
+        assert_eq!(jar.ordered_entries().count(), 2);
+
+        assert_eq!(extract(DuplicatePolicy::FirstWins).unwrap().files["a/A.class"], b"first");
+        assert!(matches!(extract(DuplicatePolicy::Error), Err(JarError::DuplicateEntry(name)) if name == "a/A.class"));
+
+        let jar = extract(DuplicatePolicy::KeepAll).unwrap();
+        assert_eq!(jar.files["a/A.class"], b"first");
+        assert_eq!(jar.files["a/A~1.class"], b"second");
+
+        let jar = jar_from_bytes(&duplicated_jar(), JarOptionBuilder::builder().parallel(2).build()).unwrap();
+        assert_eq!(jar.files["a/A.class"], b"second");
+        assert_eq!(jar.duplicates(), ["a/A.class"]);
+    }
+
+    #[test]
+    fn test_duplicates_listed_once() {
+        let path = crate::fixture::temp_path("duplicated.jar");
+        std::fs::write(&path, duplicated_jar()).unwrap();
+
+        let names = jar_list(&path, JarOptionBuilder::default()).unwrap().into_iter().map(|entry| entry.name).collect::<Vec<_>>();
+        assert_eq!(names, vec!["a/A.class", "b.txt"]);
+    }
+}
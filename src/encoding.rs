@@ -0,0 +1,244 @@
+//! Decoding of entry names, which zip stores as raw bytes in UTF-8 or IBM code page 437.
+
+use std::path::{Component, Path};
+
+use zip::read::ZipFile;
+
+use crate::JarOption;
+
+/// Characters of code page 437 from `0x80` to `0xFF`, the lower half being ASCII.
+const CP437_HIGH: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{A0}";
+
+pub(crate) type NameDecoder = dyn Fn(&[u8]) -> String + Send + Sync;
+
+/// Encoding of entry names, set with [crate::JarOptionBuilder::name_encoding].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NameEncoding {
+    /// Decodes names flagged as UTF-8 as UTF-8, and other names as UTF-8 when valid or code page
+    /// 437 otherwise. Java has always written UTF-8 names, but without the flag before Java 7.
+    #[default]
+    Auto,
+    /// Strictly follows the zip specification, decoding names flagged as UTF-8 as UTF-8 and other
+    /// names as code page 437.
+    Flag,
+    /// Decodes every name as UTF-8, replacing invalid sequences with `U+FFFD`.
+    Utf8,
+    /// Decodes every name as code page 437, e.g. for archives created by old DOS tools which set
+    /// the flag incorrectly.
+    Cp437,
+}
+
+/// How entries whose names are unsafe to use as relative paths are extracted, i.e. names containing
+/// NUL, absolute names, or names escaping the jar root with `..`. Set with
+/// [crate::JarOptionBuilder::unsafe_name_policy]; unsafe names are reported by
+/// [crate::Jar::unsafe_names] regardless of the policy.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnsafeNamePolicy {
+    /// Skips the entry.
+    #[default]
+    Skip,
+    /// Fails extraction with [crate::JarError::InvalidEntryName].
+    Error,
+    /// Extracts the entry under a sanitized name, dropping NUL characters as well as root, prefix,
+    /// `.` and `..` components, e.g. `../../etc/passwd` becomes `etc/passwd`. Entries whose
+    /// sanitized name is empty are skipped.
+    Sanitize,
+}
+
+/// An entry name rejected as unsafe, see [UnsafeNamePolicy].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsafeName {
+    /// Decoded name as stored in the archive.
+    pub name: String,
+    /// Name the entry is extracted under with [UnsafeNamePolicy::Sanitize], [None] if the entry is
+    /// not extracted.
+    pub sanitized: Option<String>,
+}
+
+/// Decodes the name of `file` according to `option`, returning an [UnsafeName] if the name is
+/// unsafe to use as a relative path, i.e. it contains NUL, is absolute, or escapes its root with
+/// `..`. The unsafe name carries its sanitized form with [UnsafeNamePolicy::Sanitize].
+pub(crate) fn decode_name(file: &ZipFile, option: &JarOption) -> Result<String, UnsafeName> {
+    decode_raw_name(file.name_raw(), is_flagged_utf8(file), option)
+}
+
+/// Decodes a raw entry name like [decode_name], with `flagged_utf8` being the UTF-8 flag of its
+/// header.
+pub(crate) fn decode_raw_name(raw: &[u8], flagged_utf8: bool, option: &JarOption) -> Result<String, UnsafeName> {
+    let name = match (&option.name_decoder, option.name_encoding) {
+        (Some(decoder), _) => decoder.0(raw),
+        (None, NameEncoding::Auto | NameEncoding::Flag) if flagged_utf8 => String::from_utf8_lossy(raw).to_string(),
+        (None, NameEncoding::Auto) => std::str::from_utf8(raw).map_or_else(|_| decode_cp437(raw), str::to_string),
+        (None, NameEncoding::Flag) => decode_cp437(raw),
+        (None, NameEncoding::Utf8) => String::from_utf8_lossy(raw).to_string(),
+        (None, NameEncoding::Cp437) => decode_cp437(raw),
+    };
+
+    if is_enclosed(&name) {
+        return Ok(name);
+    }
+
+    let sanitized = match option.unsafe_name_policy {
+        UnsafeNamePolicy::Sanitize => sanitize(&name),
+        UnsafeNamePolicy::Skip | UnsafeNamePolicy::Error => None,
+    };
+
+    Err(UnsafeName { name, sanitized })
+}
+
+/// Decodes the name of `file` like [decode_name], returning the name the entry is extracted under,
+/// or [None] if it is not extracted because of an unsafe name.
+pub(crate) fn extracted_name(file: &ZipFile, option: &JarOption) -> Option<String> {
+    decode_name(file, option).map_or_else(|unsafe_name| unsafe_name.sanitized, Some)
+}
+
+/// Checks whether the name of `file` is decoded as UTF-8 according to `option` although it is not
+/// valid UTF-8, so invalid sequences are replaced with `U+FFFD`.
+pub(crate) fn is_lossy(file: &ZipFile, option: &JarOption) -> bool {
+    is_raw_lossy(file.name_raw(), is_flagged_utf8(file), option)
+}
+
+/// Checks whether a raw entry name is decoded lossily, see [is_lossy].
+pub(crate) fn is_raw_lossy(raw: &[u8], flagged_utf8: bool, option: &JarOption) -> bool {
+    let utf8 = match (&option.name_decoder, option.name_encoding) {
+        (Some(_), _) | (None, NameEncoding::Cp437) => false,
+        (None, NameEncoding::Auto | NameEncoding::Flag) => flagged_utf8,
+        (None, NameEncoding::Utf8) => true,
+    };
+
+    utf8 && std::str::from_utf8(raw).is_err()
+}
+
+/// Checks whether the UTF-8 flag of `file` is set. [zip] does not expose the flag, but decodes
+/// flagged names as lossy UTF-8 and other names as code page 437, which never yields `U+FFFD`.
+fn is_flagged_utf8(file: &ZipFile) -> bool {
+    file.name() == String::from_utf8_lossy(file.name_raw())
+}
+
+fn decode_cp437(raw: &[u8]) -> String {
+    raw.iter()
+        .map(|byte| match byte {
+            0x00..=0x7F => *byte as char,
+            _ => CP437_HIGH.chars().nth(*byte as usize - 0x80).expect("code page 437 covers every byte"),
+        })
+        .collect()
+}
+
+pub(crate) fn is_enclosed(name: &str) -> bool {
+    if name.contains('\0') {
+        return false;
+    }
+
+    let mut depth = 0usize;
+
+    for component in Path::new(name).components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => return false,
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(parent) => depth = parent,
+                None => return false,
+            },
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+        }
+    }
+
+    true
+}
+
+/// Keeps only the normal components of `name`, or returns [None] if there are none.
+fn sanitize(name: &str) -> Option<String> {
+    let name = name.replace('\0', "");
+    let mut sanitized = Path::new(&name).components()
+        .filter_map(|component| match component {
+            Component::Normal(component) => Some(component.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if sanitized.is_empty() {
+        return None;
+    }
+    if name.ends_with('/') {
+        sanitized.push('/');
+    }
+
+    Some(sanitized)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, JarError, JarOptionBuilder, NameEncoding, UnsafeName, UnsafeNamePolicy};
+
+    use super::{is_enclosed, sanitize};
+
+    /// Builds a jar with a UTF-8 encoded non-ASCII name whose UTF-8 flag is cleared, as written
+    /// by Java 6 and earlier.
+    fn unflagged_jar() -> Vec<u8> {
+        let mut bytes = jar_bytes(&[("é.txt", b"e")]);
+
+        for signature in [b"PK\x03\x04", b"PK\x01\x02"] {
+            let header = bytes.windows(4).position(|window| window == signature).unwrap();
+            // General purpose flags are at offset 6 of local headers and 8 of central headers.
+            let flags = header + if signature == b"PK\x03\x04" { 6 } else { 8 };
+            bytes[flags + 1] &= !0x08;
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn test_name_encodings() {
+        let names = |bytes: &[u8], encoding: NameEncoding| {
+            let jar = jar_from_bytes(bytes, JarOptionBuilder::builder().name_encoding(encoding).build()).unwrap();
+            jar.files.into_keys().collect::<Vec<_>>()
+        };
+        let unflagged = unflagged_jar();
+        let flagged = jar_bytes(&[("é.txt", b"e")]);
+
+        assert_eq!(names(&unflagged, NameEncoding::Auto), vec!["é.txt"]);
+        assert_eq!(names(&unflagged, NameEncoding::Flag), vec!["├⌐.txt"]);
+        assert_eq!(names(&unflagged, NameEncoding::Utf8), vec!["é.txt"]);
+        assert_eq!(names(&flagged, NameEncoding::Auto), vec!["é.txt"]);
+        assert_eq!(names(&flagged, NameEncoding::Flag), vec!["é.txt"]);
+        assert_eq!(names(&flagged, NameEncoding::Cp437), vec!["├⌐.txt"]);
+    }
+
+    #[test]
+    fn test_name_decoder() {
+        let option = JarOptionBuilder::builder()
+            .name_decoder(|raw| String::from_utf8_lossy(raw).to_uppercase())
+            .target("A")
+            .build();
+        let jar = jar_from_bytes(&jar_bytes(&[("a/a.txt", b"a"), ("../escape.txt", b"")]), option).unwrap();
+
+        assert_eq!(jar.files.keys().collect::<Vec<_>>(), vec!["A/A.TXT"]);
+        assert!(!is_enclosed("/etc/passwd"));
+        assert!(is_enclosed("a/../b"));
+    }
+
+    #[test]
+    fn test_unsafe_name_policy() {
+        let bytes = jar_bytes(&[("a.txt", b"a"), ("../../etc/passwd", b"root"), ("/..", b"")]);
+        let extract = |policy| jar_from_bytes(&bytes, JarOptionBuilder::builder().unsafe_name_policy(policy).build());
+
+        let jar = extract(UnsafeNamePolicy::Skip).unwrap();
+        assert_eq!(jar.files.keys().collect::<Vec<_>>(), vec!["a.txt"]);
+        assert_eq!(jar.unsafe_names(), &[
+            UnsafeName { name: "../../etc/passwd".to_string(), sanitized: None },
+            UnsafeName { name: "/..".to_string(), sanitized: None },
+        ]);
+
+        let jar = extract(UnsafeNamePolicy::Sanitize).unwrap();
+        assert_eq!(jar.files["etc/passwd"], b"root");
+        assert_eq!(jar.unsafe_names()[0].sanitized.as_deref(), Some("etc/passwd"));
+        assert_eq!(jar.unsafe_names()[1].sanitized, None);
+
+        assert!(matches!(extract(UnsafeNamePolicy::Error), Err(JarError::InvalidEntryName(name)) if name == "../../etc/passwd"));
+        assert_eq!(sanitize("/a/./b\0c/"), Some("a/bc/".to_string()));
+    }
+}
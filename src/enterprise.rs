@@ -0,0 +1,204 @@
+//! Layouts of Java EE web archives (`.war`) and enterprise archives (`.ear`).
+
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::{jar_from_bytes, Jar, JarError, JarOption, JarOptionBuilder};
+#[cfg(feature = "fs")]
+use crate::jar;
+
+/// Directory of a web archive's classes.
+pub const WEB_INF_CLASSES: &str = "WEB-INF/classes/";
+/// Directory of a web archive's library jars.
+pub const WEB_INF_LIB: &str = "WEB-INF/lib/";
+
+/// A web archive, whose classes are under `WEB-INF/classes` and library jars under `WEB-INF/lib`.
+///
+/// # Example
+///
+/// ```rs
+/// let war = war("app.war")?;
+///
+/// for library in war.libraries() {
+///     let jar = war.library(library, JarOptionBuilder::builder().ext("class").build())?;
+/// }
+/// ```
+pub struct WebArchive {
+    archive: Jar,
+}
+
+impl WebArchive {
+    /// Wraps an extracted web archive.
+    pub fn new(archive: Jar) -> Self {
+        Self {
+            archive,
+        }
+    }
+
+    /// Returns the extracted archive with all files under their original paths.
+    pub fn archive(&self) -> &Jar {
+        &self.archive
+    }
+
+    /// Iterates over files under `WEB-INF/classes` with paths relative to it, as seen by the web
+    /// application's class loader, e.g. `com/example/Servlet.class`.
+    pub fn classes(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.archive.files.iter()
+            .filter_map(|(path, content)| Some((path.strip_prefix(WEB_INF_CLASSES)?, content.as_slice())))
+    }
+
+    /// Iterates over full qualified paths of library jars directly under `WEB-INF/lib`, sorted.
+    pub fn libraries(&self) -> impl Iterator<Item = &str> {
+        nested_jars(&self.archive, WEB_INF_LIB, &["jar"]).into_iter()
+    }
+
+    /// Extracts the library jar at full qualified path `path`, see [WebArchive::libraries].
+    pub fn library(&self, path: &str, option: JarOption) -> Result<Jar, JarError> {
+        nested_jar(&self.archive, path, option)
+    }
+
+    /// Returns the deployment descriptor `WEB-INF/web.xml`.
+    pub fn web_xml(&self) -> Option<&[u8]> {
+        self.archive.files.get("WEB-INF/web.xml").map(Vec::as_slice)
+    }
+}
+
+/// An enterprise archive, containing module archives at its root and shared library jars under
+/// the library directory, which is `lib` unless declared otherwise by `META-INF/application.xml`.
+///
+/// # Example
+///
+/// ```rs
+/// let ear = ear("app.ear")?;
+///
+/// for module in ear.modules() {
+///     if module.ends_with(".war") {
+///         let war = ear.web_module(module)?;
+///     }
+/// }
+/// ```
+pub struct EnterpriseArchive {
+    archive: Jar,
+    library_directory: String,
+}
+
+impl EnterpriseArchive {
+    /// Wraps an extracted enterprise archive.
+    pub fn new(archive: Jar) -> Self {
+        let library_directory = archive.files.get("META-INF/application.xml")
+            .and_then(|application_xml| xml_element(&String::from_utf8_lossy(application_xml), "library-directory"))
+            .map(|directory| format!("{}/", directory.trim_matches('/')))
+            .unwrap_or_else(|| "lib/".to_string());
+
+        Self {
+            archive,
+            library_directory,
+        }
+    }
+
+    /// Returns the extracted archive with all files under their original paths.
+    pub fn archive(&self) -> &Jar {
+        &self.archive
+    }
+
+    /// Iterates over paths of module archives (`.jar`, `.war` and `.rar`) at the archive root,
+    /// sorted.
+    pub fn modules(&self) -> impl Iterator<Item = &str> {
+        nested_jars(&self.archive, "", &["jar", "war", "rar"]).into_iter()
+    }
+
+    /// Iterates over paths of library jars directly under the library directory, sorted.
+    pub fn libraries(&self) -> impl Iterator<Item = &str> {
+        nested_jars(&self.archive, &self.library_directory, &["jar"]).into_iter()
+    }
+
+    /// Extracts the module or library at full qualified path `path`.
+    pub fn module(&self, path: &str, option: JarOption) -> Result<Jar, JarError> {
+        nested_jar(&self.archive, path, option)
+    }
+
+    /// Extracts the web module at full qualified path `path` with all its files.
+    pub fn web_module(&self, path: &str) -> Result<WebArchive, JarError> {
+        nested_jar(&self.archive, path, JarOptionBuilder::default()).map(WebArchive::new)
+    }
+
+    /// Returns the deployment descriptor `META-INF/application.xml`.
+    pub fn application_xml(&self) -> Option<&[u8]> {
+        self.archive.files.get("META-INF/application.xml").map(Vec::as_slice)
+    }
+}
+
+/// Extracts the web archive at `path` with all its files.
+#[cfg(feature = "fs")]
+pub fn war<P>(path: P) -> Result<WebArchive, JarError> where P: AsRef<Path> {
+    jar(path, JarOptionBuilder::default()).map(WebArchive::new)
+}
+
+/// Extracts the enterprise archive at `path` with all its files.
+#[cfg(feature = "fs")]
+pub fn ear<P>(path: P) -> Result<EnterpriseArchive, JarError> where P: AsRef<Path> {
+    jar(path, JarOptionBuilder::default()).map(EnterpriseArchive::new)
+}
+
+fn nested_jars<'a>(archive: &'a Jar, dir: &str, extensions: &[&str]) -> Vec<&'a str> {
+    let mut paths = archive.files.keys()
+        .filter(|path| path.strip_prefix(dir).is_some_and(|name| !name.contains('/')))
+        .filter(|path| path.rsplit_once('.').is_some_and(|(_, extension)| extensions.contains(&extension)))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    paths.sort();
+    paths
+}
+
+fn nested_jar(archive: &Jar, path: &str, option: JarOption) -> Result<Jar, JarError> {
+    let content = archive.files.get(path).ok_or_else(|| JarError::EntryNotFound(path.to_string()))?;
+
+    jar_from_bytes(content, option)
+}
+
+/// Returns the text of the first element named `name`, which is enough for simple descriptor values.
+fn xml_element(xml: &str, name: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", name))? + name.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", name))?;
+
+    Some(xml[start..end].trim().to_string())
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use crate::fixture::{jar_bytes, jar_file};
+    use crate::{ear, war, JarOptionBuilder};
+
+    #[test]
+    fn test_war() {
+        let library = jar_bytes(&[("lib/L.class", b"l")]);
+        let war = war(jar_file(&[
+            ("WEB-INF/web.xml", b"<web-app/>"),
+            ("WEB-INF/classes/a/Servlet.class", b"servlet"),
+            ("WEB-INF/lib/library.jar", &library),
+            ("index.html", b""),
+        ])).unwrap();
+
+        assert_eq!(war.classes().collect::<Vec<_>>(), vec![("a/Servlet.class", b"servlet".as_slice())]);
+        assert_eq!(war.libraries().collect::<Vec<_>>(), vec!["WEB-INF/lib/library.jar"]);
+        assert_eq!(war.library("WEB-INF/lib/library.jar", JarOptionBuilder::default()).unwrap().files["lib/L.class"], b"l");
+        assert_eq!(war.web_xml(), Some(b"<web-app/>".as_slice()));
+    }
+
+    #[test]
+    fn test_ear() {
+        let web = jar_bytes(&[("WEB-INF/classes/a/Servlet.class", b"servlet")]);
+        let ear = ear(jar_file(&[
+            ("META-INF/application.xml", b"<application><library-directory>shared</library-directory></application>"),
+            ("web.war", &web),
+            ("ejb.jar", &jar_bytes(&[])),
+            ("shared/common.jar", &jar_bytes(&[])),
+            ("lib/ignored.jar", &jar_bytes(&[])),
+        ])).unwrap();
+
+        assert_eq!(ear.modules().collect::<Vec<_>>(), vec!["ejb.jar", "web.war"]);
+        assert_eq!(ear.libraries().collect::<Vec<_>>(), vec!["shared/common.jar"]);
+        assert_eq!(ear.web_module("web.war").unwrap().classes().count(), 1);
+    }
+}
@@ -0,0 +1,354 @@
+//! Metadata of jar entries read from the central directory.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use zip::read::ZipFile;
+
+use crate::CompressionMethod;
+
+/// Metadata of a single jar entry, available before its content is decompressed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JarEntry {
+    /// Full qualified path of the entry.
+    pub name: String,
+    /// Uncompressed size in bytes.
+    pub size: u64,
+    /// Compressed size in bytes.
+    pub compressed_size: u64,
+    /// Compression method of the entry content.
+    pub compression: CompressionMethod,
+    /// Last modification time as recorded in the archive.
+    pub last_modified: DateTime,
+    /// CRC-32 checksum of the uncompressed content.
+    pub crc32: u32,
+    /// Unix mode of the entry, if the jar was created on a Unix-like system.
+    pub unix_mode: Option<u32>,
+    /// Raw extra field of the entry's central directory header, a sequence of fields tagged with
+    /// header IDs, see [JarEntry::extra_fields]. Some build systems store provenance data there.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub extra: Vec<u8>,
+}
+
+impl JarEntry {
+    pub(crate) fn new(name: String, file: &ZipFile) -> Self {
+        Self {
+            name,
+            size: file.size(),
+            compressed_size: file.compressed_size(),
+            compression: file.compression().into(),
+            last_modified: file.last_modified().into(),
+            crc32: file.crc32(),
+            unix_mode: file.unix_mode(),
+            extra: file.extra_data().to_vec(),
+        }
+    }
+
+    /// Iterates over the fields of [JarEntry::extra], parsing the known ones. Iteration stops at a
+    /// field whose declared length exceeds the remaining data.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// for field in entry.extra_fields() {
+    ///     if let ExtraField::Unknown { id: 0x4242, data } = field {
+    ///         println!("built by {}", String::from_utf8_lossy(data));
+    ///     }
+    /// }
+    /// ```
+    pub fn extra_fields(&self) -> impl Iterator<Item = ExtraField<'_>> {
+        let mut data = self.extra.as_slice();
+
+        std::iter::from_fn(move || {
+            let [id_low, id_high, len_low, len_high, rest @ ..] = data else {
+                return None;
+            };
+            let (id, len) = (u16::from_le_bytes([*id_low, *id_high]), u16::from_le_bytes([*len_low, *len_high]) as usize);
+            let field = rest.get(..len)?;
+
+            data = &rest[len..];
+            Some(ExtraField::parse(id, field))
+        })
+    }
+
+    /// Checks whether the entry is a directory, which is only extracted with
+    /// [crate::JarOptionBuilder::include_dirs].
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+
+    /// Checks whether the entry is a symbolic link by its Unix mode, in which case its content is
+    /// the link target. See [crate::JarOptionBuilder::symlink_policy].
+    pub fn is_symlink(&self) -> bool {
+        self.unix_mode.is_some_and(|mode| mode & 0o170000 == 0o120000)
+    }
+
+    /// Checks whether the entry is a file with an executable bit set in its Unix mode, e.g. a
+    /// launcher script, which [crate::extract_to_dir] restores with
+    /// [crate::JarOptionBuilder::preserve_permissions].
+    pub fn is_executable(&self) -> bool {
+        !self.is_dir() && !self.is_symlink() && self.unix_mode.is_some_and(|mode| mode & 0o111 != 0)
+    }
+}
+
+/// A field of [JarEntry::extra], see [JarEntry::extra_fields]. Known fields which are truncated
+/// or of an unsupported version are returned as [ExtraField::Unknown].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ExtraField<'a> {
+    /// ZIP64 extended information (`0x0001`), holding the 64-bit uncompressed size, compressed
+    /// size, local header offset and disk number, in this order, each present only if its header
+    /// field is saturated. The sizes are already resolved in [JarEntry].
+    Zip64(Vec<u64>),
+    /// Extended timestamp (`0x5455`) with second precision and no 2107 limit. Central directory
+    /// headers only record the modification time.
+    ExtendedTimestamp {
+        modified: Option<SystemTime>,
+        accessed: Option<SystemTime>,
+        created: Option<SystemTime>,
+    },
+    /// Info-ZIP Unix owner (`0x7875`).
+    UnixOwner {
+        uid: u32,
+        gid: u32,
+    },
+    /// Empty marker (`0xCAFE`) which the JDK's `jar` tool adds to the first entry, identifying the
+    /// archive as a jar for some operating systems.
+    JarMarker,
+    /// Any other field, with its header ID and data.
+    Unknown {
+        id: u16,
+        data: &'a [u8],
+    },
+}
+
+impl<'a> ExtraField<'a> {
+    fn parse(id: u16, data: &'a [u8]) -> Self {
+        let parsed = match id {
+            0x0001 if data.len().is_multiple_of(8) => Some(ExtraField::Zip64(data.chunks(8)
+                .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("chunk has 8 bytes")))
+                .collect())),
+            0x5455 => parse_extended_timestamp(data),
+            0x7875 => parse_unix_owner(data),
+            0xCAFE if data.is_empty() => Some(ExtraField::JarMarker),
+            _ => None,
+        };
+
+        parsed.unwrap_or(ExtraField::Unknown { id, data })
+    }
+}
+
+fn parse_extended_timestamp(data: &[u8]) -> Option<ExtraField<'_>> {
+    let (flags, mut times) = data.split_first()?;
+    let mut next = |bit: u8| {
+        if flags & bit == 0 || times.len() < 4 {
+            return None;
+        }
+
+        let (time, rest) = times.split_at(4);
+        let seconds = i32::from_le_bytes(time.try_into().expect("time has 4 bytes"));
+
+        times = rest;
+        Some(if seconds >= 0 {
+            UNIX_EPOCH + Duration::from_secs(seconds as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs() as u64)
+        })
+    };
+
+    Some(ExtraField::ExtendedTimestamp {
+        modified: next(1),
+        accessed: next(2),
+        created: next(4),
+    })
+}
+
+fn parse_unix_owner(data: &[u8]) -> Option<ExtraField<'_>> {
+    let [1, uid_size, rest @ ..] = data else {
+        return None;
+    };
+    let (uid, rest) = rest.split_at_checked(*uid_size as usize)?;
+    let (gid_size, gid) = rest.split_first()?;
+    let id = |bytes: &[u8]| -> Option<u32> {
+        let mut buffer = [0; 4];
+
+        buffer.get_mut(..bytes.len())?.copy_from_slice(bytes);
+        Some(u32::from_le_bytes(buffer))
+    };
+
+    (gid.len() == *gid_size as usize).then_some(())?;
+    Some(ExtraField::UnixOwner {
+        uid: id(uid)?,
+        gid: id(gid)?,
+    })
+}
+
+/// An MS-DOS timestamp as stored in zip headers, which has a 2-second resolution and no time zone.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl From<zip::DateTime> for DateTime {
+    fn from(date_time: zip::DateTime) -> Self {
+        Self {
+            year: date_time.year(),
+            month: date_time.month(),
+            day: date_time.day(),
+            hour: date_time.hour(),
+            minute: date_time.minute(),
+            second: date_time.second(),
+        }
+    }
+}
+
+impl From<DateTime> for SystemTime {
+    fn from(date_time: DateTime) -> Self {
+        date_time.to_system_time()
+    }
+}
+
+impl From<SystemTime> for DateTime {
+    fn from(time: SystemTime) -> Self {
+        DateTime::from_system_time(time)
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<DateTime> for time::OffsetDateTime {
+    type Error = time::error::ComponentRange;
+
+    /// Converts the timestamp interpreted as UTC, failing only for fields out of their range.
+    fn try_from(date_time: DateTime) -> Result<Self, Self::Error> {
+        let date = time::Date::from_calendar_date(date_time.year as i32, time::Month::try_from(date_time.month)?, date_time.day)?;
+        let time = time::Time::from_hms(date_time.hour, date_time.minute, date_time.second)?;
+
+        Ok(time::PrimitiveDateTime::new(date, time).assume_utc())
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for DateTime {
+    fn from(date_time: time::OffsetDateTime) -> Self {
+        DateTime::from_unix_timestamp(date_time.unix_timestamp())
+    }
+}
+
+impl DateTime {
+    /// Converts the timestamp to seconds since the Unix epoch, interpreting it as UTC since zip
+    /// timestamps carry no time zone.
+    pub fn unix_timestamp(&self) -> i64 {
+        // Days from civil date, see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+        let year = self.year as i64 - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146097 + day_of_era - 719468;
+
+        days * 86400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+    }
+
+    /// Converts seconds since the Unix epoch to a timestamp, interpreted as UTC. Times are rounded
+    /// down to even seconds and clamped to 1980-01-01 00:00:00 through 2107-12-31 23:59:58, the
+    /// range representable in zip headers.
+    pub fn from_unix_timestamp(seconds: i64) -> Self {
+        let min = DateTime { year: 1980, month: 1, day: 1, hour: 0, minute: 0, second: 0 }.unix_timestamp();
+        let max = DateTime { year: 2107, month: 12, day: 31, hour: 23, minute: 59, second: 58 }.unix_timestamp();
+        let seconds = seconds.clamp(min, max);
+        let (days, seconds_of_day) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+        // Civil date from days, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+
+        Self {
+            year: (year_of_era + era * 400 + (month <= 2) as i64) as u16,
+            month: month as u8,
+            day: (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8,
+            hour: (seconds_of_day / 3600) as u8,
+            minute: (seconds_of_day % 3600 / 60) as u8,
+            second: (seconds_of_day % 60 / 2 * 2) as u8,
+        }
+    }
+
+    /// Converts a [SystemTime] to a timestamp, see [DateTime::from_unix_timestamp].
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => elapsed.as_secs().min(i64::MAX as u64) as i64,
+            Err(err) => -(err.duration().as_secs().min(i64::MAX as u64) as i64),
+        };
+
+        Self::from_unix_timestamp(seconds)
+    }
+
+    /// Converts the timestamp to a [SystemTime], interpreting it as UTC.
+    pub fn to_system_time(&self) -> SystemTime {
+        let seconds = self.unix_timestamp();
+
+        if seconds >= 0 {
+            UNIX_EPOCH + Duration::from_secs(seconds as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+        }
+    }
+
+    /// Returns the current time, or 1980-01-01 00:00:00 on `wasm32-unknown-unknown`, where reading
+    /// the clock panics.
+    pub(crate) fn now() -> Self {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        return DateTime::from(SystemTime::now());
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        return DateTime::from_unix_timestamp(0);
+    }
+
+    pub(crate) fn to_zip(self) -> zip::DateTime {
+        zip::DateTime::from_date_and_time(self.year, self.month, self.day, self.hour, self.minute, self.second)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, ExtraField, JarOptionBuilder};
+
+    #[test]
+    fn test_extra_fields() {
+        let mut entry = jar_from_bytes(&jar_bytes(&[("a.txt", b"a")]), JarOptionBuilder::default()).unwrap().entry("a.txt").unwrap().clone();
+
+        entry.extra = [
+            &[0xFE, 0xCA, 0, 0][..],
+            &[0x55, 0x54, 5, 0, 3, 0x10, 0, 0, 0],
+            &[0x75, 0x78, 7, 0, 1, 2, 0xE8, 0x03, 2, 0xE9, 0x03],
+            &[0x01, 0, 8, 0, 0, 0, 0, 0, 1, 0, 0, 0],
+            &[0x42, 0x42, 3, 0, b'a', b'b', b'c'],
+            &[0x43, 0x43, 9, 0, b'x'],
+        ].concat();
+
+        assert_eq!(entry.extra_fields().collect::<Vec<_>>(), vec![
+            ExtraField::JarMarker,
+            ExtraField::ExtendedTimestamp {
+                modified: Some(UNIX_EPOCH + Duration::from_secs(16)),
+                accessed: None,
+                created: None,
+            },
+            ExtraField::UnixOwner { uid: 1000, gid: 1001 },
+            ExtraField::Zip64(vec![1 << 32]),
+            ExtraField::Unknown { id: 0x4242, data: b"abc" },
+        ]);
+    }
+}
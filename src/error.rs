@@ -0,0 +1,118 @@
+//! Error type returned by every public API of this crate.
+
+use std::fmt::{Display, Formatter};
+
+use zip::result::ZipError;
+
+use crate::CompressionMethod;
+
+/// An error occurred while reading, parsing or writing a jar.
+#[derive(Debug)]
+pub enum JarError {
+    /// An IO error from the underlying file system or reader.
+    Io(std::io::Error),
+    /// The archive is not a valid zip file, or uses an unsupported feature.
+    Zip(ZipError),
+    /// An entry name is unsafe to use as a file system path, e.g. it escapes the destination
+    /// directory.
+    InvalidEntryName(String),
+    /// The manifest does not follow the manifest format.
+    ManifestParse(String),
+    /// A path pattern failed to compile.
+    InvalidPattern(String),
+    /// An entry is written more than once.
+    DuplicateEntry(String),
+    /// A class file is malformed.
+    ClassFormat(String),
+    /// A requested entry does not exist or is excluded by extraction rules.
+    EntryNotFound(String),
+    /// A resource limit configured with [crate::JarOptionBuilder] is exceeded, e.g. by a zip bomb.
+    LimitExceeded(String),
+    /// Extraction is aborted by the caller.
+    Cancelled,
+    /// A jar cannot be signed, e.g. because the private key does not match the certificate.
+    Signing(String),
+    /// A remote jar cannot be downloaded, e.g. because the server returns an error status.
+    Fetch(String),
+    /// An entry is compressed with a method which is neither supported nor registered with
+    /// [crate::JarOptionBuilder::decompressor].
+    UnsupportedCompression(CompressionMethod),
+    /// A symbolic link entry is extracted with [crate::SymlinkPolicy::Error].
+    SymlinkEntry(String),
+    /// A configuration file is malformed or has unknown fields.
+    Config(String),
+    /// Options built with [crate::JarOptionBuilder::try_build] contradict each other or can never
+    /// match, e.g. a path both targeted and excluded.
+    InvalidOption(String),
+}
+
+impl Display for JarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JarError::Io(err) => write!(f, "io error: {}", err),
+            JarError::Zip(err) => write!(f, "zip error: {}", err),
+            JarError::InvalidEntryName(name) => write!(f, "invalid entry name `{}`", name),
+            JarError::ManifestParse(message) => write!(f, "invalid manifest, {}", message),
+            JarError::InvalidPattern(message) => write!(f, "invalid pattern, {}", message),
+            JarError::DuplicateEntry(name) => write!(f, "duplicate entry `{}`", name),
+            JarError::ClassFormat(message) => write!(f, "invalid class file, {}", message),
+            JarError::EntryNotFound(name) => write!(f, "entry `{}` not found", name),
+            JarError::LimitExceeded(message) => write!(f, "limit exceeded, {}", message),
+            JarError::Cancelled => write!(f, "extraction cancelled"),
+            JarError::Signing(message) => write!(f, "signing failed, {}", message),
+            JarError::Fetch(message) => write!(f, "fetching failed, {}", message),
+            JarError::UnsupportedCompression(method) => write!(f, "unsupported compression method {:?} (id {})", method, method.id()),
+            JarError::SymlinkEntry(name) => write!(f, "symbolic link entry `{}`", name),
+            JarError::Config(message) => write!(f, "invalid configuration, {}", message),
+            JarError::InvalidOption(message) => write!(f, "invalid option, {}", message),
+        }
+    }
+}
+
+impl JarError {
+    /// Checks whether the error is caused by corrupted archive content, e.g. a CRC-32 mismatch or a
+    /// malformed compressed stream, rather than by the caller.
+    pub(crate) fn is_corrupt(&self) -> bool {
+        matches!(self, JarError::Io(_) | JarError::Zip(_))
+    }
+}
+
+impl std::error::Error for JarError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JarError::Io(err) => Some(err),
+            JarError::Zip(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for JarError {
+    /// Unwraps a [JarError] which was converted into an IO error to pass through [std::io::Read].
+    fn from(err: std::io::Error) -> Self {
+        if err.get_ref().is_some_and(|inner| inner.is::<JarError>()) {
+            return *err.into_inner().unwrap().downcast::<JarError>().unwrap();
+        }
+
+        JarError::Io(err)
+    }
+}
+
+impl From<ZipError> for JarError {
+    fn from(err: ZipError) -> Self {
+        match err {
+            ZipError::Io(err) => JarError::Io(err),
+            err => JarError::Zip(err),
+        }
+    }
+}
+
+impl From<JarError> for std::io::Error {
+    fn from(err: JarError) -> Self {
+        match err {
+            JarError::Io(err) => err,
+            err @ JarError::EntryNotFound(_) => std::io::Error::new(std::io::ErrorKind::NotFound, err),
+            err => std::io::Error::new(std::io::ErrorKind::InvalidData, err),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Dry runs of extraction explaining which rule decides whether each entry is extracted.
+
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{Read, Seek};
+#[cfg(feature = "fs")]
+use std::io::BufReader;
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::limits::Limits;
+use crate::option::Callback;
+use crate::trace::{SkipReason, TraceEvent};
+use crate::{JarEntry, JarError, JarOption, JarStream};
+
+/// The rule an extracted entry matches, see [jar_explain] and [JarOption::matches].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum MatchRule {
+    /// No target, pattern or extension is set, so every entry not excluded matches.
+    All,
+    /// The entry is the target or lies under it.
+    Target(String),
+    /// The entry matches the glob or regular expression.
+    Pattern(String),
+    /// The entry has the extension.
+    Extension(String),
+}
+
+/// Whether an entry would be extracted, see [jar_explain].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Decision {
+    /// The entry is extracted because it matches the rule.
+    Extracted(MatchRule),
+    /// The entry is skipped for the reason.
+    Skipped(SkipReason),
+    /// The entry matches, but extraction fails with [JarError::LimitExceeded] at it with the message.
+    LimitExceeded(String),
+}
+
+/// The decision of [jar_explain] for an entry.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Explanation {
+    /// Index of the entry in the archive.
+    pub index: usize,
+    /// Effective name of the entry, e.g. without the versioned prefix of multi-release jars.
+    pub name: String,
+    pub decision: Decision,
+}
+
+/// Explains for every entry of the jar at `path`, in archive order, whether [crate::jar] extracts
+/// it with `option` and which rule decides it. Only the central directory is read, so no entry
+/// content is decompressed.
+///
+/// # Example
+///
+/// ```rs
+/// let option = JarOptionBuilder::builder().target("com/example").exclude_ext("properties").build();
+///
+/// for explanation in jar_explain("app.jar", option)? {
+///     println!("{}: {:?}", explanation.name, explanation.decision);
+/// }
+/// ```
+#[cfg(feature = "fs")]
+pub fn jar_explain<P>(path: P, option: JarOption) -> Result<Vec<Explanation>, JarError> where P: AsRef<Path> {
+    jar_explain_reader(BufReader::new(File::open(path)?), option)
+}
+
+/// Explains the extraction of a jar from any seekable source, see [jar_explain].
+pub fn jar_explain_reader<R>(reader: R, mut option: JarOption) -> Result<Vec<Explanation>, JarError> where R: Read + Seek {
+    let decisions = Arc::new(Mutex::new(Vec::new()));
+    let traced = decisions.clone();
+    let inner = option.trace.take();
+    let limits = std::mem::take(&mut option.limits);
+    let rules = option.clone();
+
+    option.trace = Some(Callback(Arc::new(move |event: &TraceEvent| {
+        if let Some(inner) = &inner {
+            (inner.0)(event);
+        }
+
+        let decision = match event {
+            TraceEvent::Matched { index, entry } => (*index, entry.name.clone(), Ok((*entry).clone())),
+            TraceEvent::Skipped { index, name, reason } => (*index, name.to_string(), Err(*reason)),
+            _ => return,
+        };
+        traced.lock().expect("explaining thread panicked").push(decision);
+    })));
+
+    let mut stream = JarStream::new(reader, option)?;
+
+    while let Some(next) = stream.next_match() {
+        next?;
+    }
+
+    let decisions = std::mem::take(&mut *decisions.lock().expect("explaining thread panicked"));
+
+    Ok(explain(decisions, &rules, &limits))
+}
+
+type Traced = (usize, String, Result<JarEntry, SkipReason>);
+
+fn explain(decisions: Vec<Traced>, option: &JarOption, limits: &Limits) -> Vec<Explanation> {
+    let mut matched = 0;
+    let mut matched_size = 0u64;
+
+    decisions.into_iter()
+        .map(|(index, name, entry)| {
+            let decision = match entry {
+                Err(reason) => Decision::Skipped(reason),
+                Ok(entry) => {
+                    matched += 1;
+                    matched_size = matched_size.saturating_add(entry.size);
+
+                    match limits.check(&entry, matched, matched_size) {
+                        Err(JarError::LimitExceeded(message)) => Decision::LimitExceeded(message),
+                        _ => option.matches(&entry.name),
+                    }
+                }
+            };
+
+            Explanation {
+                index,
+                name,
+                decision,
+            }
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::fixture::{jar_bytes, jar_file};
+    use crate::{jar_explain, jar_explain_reader, Decision, JarOptionBuilder, MatchRule, SkipReason};
+
+    #[test]
+    fn test_jar_explain() {
+        let path = jar_file(&[
+            ("a/", b""),
+            ("a/A.class", b"a"),
+            ("a/messages.properties", b""),
+            ("b/B.class", b"b"),
+            ("b/b.txt", b"b"),
+            ("c/Large.class", &[0; 64]),
+            ("d.txt", b"d"),
+        ]);
+        let option = JarOptionBuilder::builder()
+            .target("a")
+            .ext("class")
+            .target_glob("**/*.txt")
+            .exclude_ext("properties")
+            .max_size(16)
+            .build();
+        let decisions = jar_explain(&path, option).unwrap()
+            .into_iter()
+            .map(|explanation| (explanation.name, explanation.decision))
+            .collect::<Vec<_>>();
+
+        assert_eq!(decisions, vec![
+            ("a/".to_string(), Decision::Skipped(SkipReason::Directory)),
+            ("a/A.class".to_string(), Decision::Extracted(MatchRule::Target("a".to_string()))),
+            ("a/messages.properties".to_string(), Decision::Skipped(SkipReason::Excluded)),
+            ("b/B.class".to_string(), Decision::Extracted(MatchRule::Extension("class".to_string()))),
+            ("b/b.txt".to_string(), Decision::Extracted(MatchRule::Pattern("**/*.txt".to_string()))),
+            ("c/Large.class".to_string(), Decision::Skipped(SkipReason::Size)),
+            ("d.txt".to_string(), Decision::Extracted(MatchRule::Pattern("**/*.txt".to_string()))),
+        ]);
+    }
+
+    #[test]
+    fn test_jar_explain_limits() {
+        let bytes = jar_bytes(&[("a.txt", b"a"), ("b.txt", b"b"), ("c.txt", b"c")]);
+        let explanations = jar_explain_reader(Cursor::new(bytes), JarOptionBuilder::builder().max_entries(2).build()).unwrap();
+
+        assert_eq!(explanations[1].decision, Decision::Extracted(MatchRule::All));
+        assert!(matches!(&explanations[2].decision, Decision::LimitExceeded(message) if message.contains("more than 2 entries")));
+    }
+}
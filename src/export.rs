@@ -0,0 +1,204 @@
+//! Re-serialization of extracted jars into other archive formats.
+
+use std::io::{Seek, Write};
+
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use crate::{Jar, JarEntry, JarError};
+
+const BLOCK_SIZE: usize = 512;
+
+impl Jar {
+    /// Writes extracted files as a POSIX tar archive in central directory order, preserving paths,
+    /// modification times and Unix permissions. Directory entries are written as directories. Paths longer than the ustar format allows are
+    /// written with PAX extended headers.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("app.jar", JarOptionBuilder::builder().ext("class").build())?;
+    ///
+    /// jar.write_tar(File::create("layer.tar")?)?;
+    /// ```
+    pub fn write_tar<W>(&self, mut writer: W) -> Result<W, JarError> where W: Write {
+        for (entry, content) in self.ordered_entries() {
+            write_tar_entry(&mut writer, entry, content)?;
+        }
+
+        writer.write_all(&[0; BLOCK_SIZE * 2])?;
+        Ok(writer)
+    }
+
+    /// Writes extracted files as a zip archive in central directory order, preserving paths,
+    /// modification times, Unix permissions and compression methods. Unlike [crate::JarWriter], no
+    /// manifest is generated. The archive comment is kept if it is valid UTF-8.
+    pub fn write_zip<W>(&self, writer: W) -> Result<W, JarError> where W: Write + Seek {
+        let mut writer = ZipWriter::new(writer);
+
+        if let Ok(comment) = std::str::from_utf8(&self.comment) {
+            writer.set_comment(comment);
+        }
+
+        for (entry, content) in self.ordered_entries() {
+            let mut options = FileOptions::default()
+                .compression_method(entry.compression.into())
+                .last_modified_time(entry.last_modified.to_zip());
+
+            if let Some(mode) = entry.unix_mode {
+                options = options.unix_permissions(mode & 0o7777);
+            }
+
+            if entry.is_dir() {
+                writer.add_directory(entry.name.as_str(), options)?;
+            } else {
+                writer.start_file(entry.name.as_str(), options)?;
+                writer.write_all(content)?;
+            }
+        }
+
+        Ok(writer.finish()?)
+    }
+}
+
+fn write_tar_entry<W>(writer: &mut W, entry: &JarEntry, content: &[u8]) -> Result<(), JarError> where W: Write {
+    let mtime = entry.last_modified.unix_timestamp().max(0) as u64;
+    let (prefix, name) = match split_ustar_path(&entry.name) {
+        Some(split) => split,
+        None => {
+            let record = pax_record("path", &entry.name);
+            let pax_name = format!("PaxHeaders/{}", entry.name.rsplit('/').next().unwrap_or_default());
+
+            writer.write_all(&tar_header("", truncate(&pax_name, 100), 0o644, record.len() as u64, mtime, b'x'))?;
+            write_padded(writer, record.as_bytes())?;
+            ("", truncate(&entry.name, 100))
+        }
+    };
+    let (default_mode, typeflag) = if entry.is_dir() { (0o755, b'5') } else { (0o644, b'0') };
+    let mode = entry.unix_mode.map_or(default_mode, |mode| mode & 0o7777);
+
+    writer.write_all(&tar_header(prefix, name, mode, content.len() as u64, mtime, typeflag))?;
+    write_padded(writer, content)
+}
+
+/// Splits a path into the ustar `prefix` and `name` fields, which hold up to 155 and 100 bytes.
+fn split_ustar_path(path: &str) -> Option<(&str, &str)> {
+    if path.len() <= 100 {
+        return Some(("", path));
+    }
+
+    path.match_indices('/')
+        .map(|(index, _)| (&path[..index], &path[index + 1..]))
+        .find(|(prefix, name)| prefix.len() <= 155 && name.len() <= 100 && !name.is_empty())
+}
+
+fn truncate(value: &str, len: usize) -> &str {
+    let mut end = value.len().min(len);
+
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    &value[..end]
+}
+
+/// Formats a PAX extended header record, whose length prefix counts itself.
+fn pax_record(key: &str, value: &str) -> String {
+    let body_len = key.len() + value.len() + 3;
+    let mut len = body_len + 1;
+
+    while (body_len + len.to_string().len()) != len {
+        len = body_len + len.to_string().len();
+    }
+
+    format!("{} {}={}\n", len, key, value)
+}
+
+fn tar_header(prefix: &str, name: &str, mode: u32, size: u64, mtime: u64, typeflag: u8) -> [u8; BLOCK_SIZE] {
+    let mut header = [0; BLOCK_SIZE];
+
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], mode as u64);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], size);
+    write_octal(&mut header[136..148], mtime);
+    header[156] = typeflag;
+    header[257..265].copy_from_slice(b"ustar\x0000");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    // The checksum is computed with its own field filled with spaces.
+    header[148..156].fill(b' ');
+    let checksum = header.iter().map(|byte| *byte as u64).sum::<u64>();
+    write_octal(&mut header[148..155], checksum);
+    header
+}
+
+/// Writes a zero-padded, NUL-terminated octal number filling `field`.
+fn write_octal(field: &mut [u8], value: u64) {
+    let width = field.len() - 1;
+    let digits = format!("{:0width$o}", value, width = width);
+
+    field[..width].copy_from_slice(&digits.as_bytes()[digits.len() - width..]);
+    field[width] = 0;
+}
+
+fn write_padded<W>(writer: &mut W, content: &[u8]) -> Result<(), JarError> where W: Write {
+    writer.write_all(content)?;
+    writer.write_all(&[0; BLOCK_SIZE][..(BLOCK_SIZE - content.len() % BLOCK_SIZE) % BLOCK_SIZE])?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, DateTime, JarOptionBuilder};
+
+    use super::{pax_record, split_ustar_path};
+
+    fn tar_field(header: &[u8], range: std::ops::Range<usize>) -> &str {
+        std::str::from_utf8(&header[range]).unwrap().trim_end_matches('\0')
+    }
+
+    #[test]
+    fn test_write_tar() {
+        let long_name = format!("{}/Long.class", "a".repeat(200));
+        let jar = jar_from_bytes(&jar_bytes(&[("a/A.class", b"class a"), (&long_name, b"long")]), JarOptionBuilder::default()).unwrap();
+        let tar = jar.write_tar(Vec::new()).unwrap();
+
+        assert_eq!(tar.len(), 512 * 8);
+        assert_eq!(tar_field(&tar, 0..100), "a/A.class");
+        assert_eq!(tar_field(&tar, 124..135), "00000000007");
+        assert_eq!(tar_field(&tar, 257..263), "ustar");
+        assert_eq!(&tar[512..519], b"class a");
+
+        let checksum = tar[..512].iter().enumerate()
+            .map(|(index, byte)| if (148..156).contains(&index) { b' ' as u64 } else { *byte as u64 })
+            .sum::<u64>();
+        assert_eq!(u64::from_str_radix(tar_field(&tar, 148..155), 8).unwrap(), checksum);
+
+        assert_eq!(tar[1024 + 156], b'x');
+        assert!(tar[1536..2048].starts_with(pax_record("path", &long_name).as_bytes()));
+        assert!(tar[4096 - 1024..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn test_write_zip() {
+        let jar = jar_from_bytes(&jar_bytes(&[("b.txt", b"b"), ("a.txt", b"a")]), JarOptionBuilder::default()).unwrap();
+        let bytes = jar.write_zip(Cursor::new(Vec::new())).unwrap().into_inner();
+        let copy = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap();
+
+        assert_eq!(copy.ordered_entries().collect::<Vec<_>>(), jar.ordered_entries().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(pax_record("path", "a"), "9 path=a\n");
+        assert_eq!(pax_record("path", &"a".repeat(95)), format!("105 path={}\n", "a".repeat(95)));
+        assert_eq!(split_ustar_path(&format!("{}/{}", "p".repeat(150), "n".repeat(90))).map(|(prefix, _)| prefix.len()), Some(150));
+        assert_eq!(DateTime { year: 2000, month: 3, day: 1, hour: 0, minute: 0, second: 1 }.unix_timestamp(), 951868801);
+    }
+}
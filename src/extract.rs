@@ -0,0 +1,363 @@
+//! Extraction of jar entries onto the file system.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Error, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crate::option::map_content;
+use crate::progress::{report, Progress};
+use crate::symlink::Materialized;
+use crate::{jar_stream, JarError, JarOption, JarPipe, SymlinkPolicy};
+
+/// Extracts a jar file from given parameter `path` into directory `dest_dir`, creating it and any
+/// parent directories of entries as needed. Returns paths of all written files.
+///
+/// Entry paths are sanitized before writing: `.` components and leading root components are
+/// dropped, and entries containing `..` components or passing through a symbolic link in `dest_dir`
+/// are rejected with [JarError::InvalidEntryName], so no file can be written outside of `dest_dir`.
+/// Unix permissions stored in the jar are restored when
+/// [crate::JarOptionBuilder::preserve_permissions] is set. Directory entries included by
+/// [crate::JarOptionBuilder::include_dirs] are created as directories. Entries are streamed to disk
+/// unless [crate::JarOptionBuilder::map_content] is set, in which case each entry's content is
+/// buffered to be mapped, and entries dropped by it are not written. Symbolic link entries are
+/// created as links with [crate::SymlinkPolicy::Materialize].
+///
+/// # Example
+///
+/// ```rs
+/// extract_to_dir("sample/rt.jar", "out", JarOptionBuilder::builder().target("java/lang").build())?;
+/// ```
+pub fn extract_to_dir<P, D>(path: P, dest_dir: D, option: JarOption) -> Result<Vec<PathBuf>, JarError>
+    where P: AsRef<Path>, D: AsRef<Path> {
+    let dest_dir = dest_dir.as_ref();
+    let preserve_permissions = option.preserve_permissions;
+    let progress = option.progress.clone();
+    let content_mapper = option.content_mapper.clone();
+    let materialize = option.symlink_policy == SymlinkPolicy::Materialize;
+    let mut links = Materialized::default();
+    let mut stream = jar_stream(path, option)?;
+    let total = stream.len();
+    let mut written = Vec::new();
+    let mut dir_modes = Vec::new();
+
+    std::fs::create_dir_all(dest_dir)?;
+
+    while let Some(entry) = stream.next_entry() {
+        let (file_path, mut reader) = entry?;
+        let relative_path = sanitize_path(&file_path)?;
+        let dest_path = dest_dir.join(&relative_path);
+
+        links.check(&file_path, &relative_path)?;
+        check_no_links(dest_dir, &relative_path, &file_path)?;
+
+        if materialize && reader.entry().is_symlink() {
+            let mut target = String::new();
+
+            reader.read_to_string(&mut target)?;
+            written.push(links.create(&file_path, dest_dir, &relative_path, &target)?);
+            continue;
+        }
+
+        if reader.entry().is_dir() {
+            std::fs::create_dir_all(&dest_path)?;
+
+            // Applied once extraction is done, so read-only directories can still be filled.
+            if preserve_permissions {
+                if let Some(mode) = reader.unix_mode() {
+                    dir_modes.push((dest_path.clone(), mode & DIR_MODE_MASK));
+                }
+            }
+
+            written.push(dest_path);
+            continue;
+        }
+
+        let mapped = match content_mapper {
+            Some(_) => {
+                let mut content = Vec::new();
+
+                reader.read_to_end(&mut content)?;
+                match map_content(&content_mapper, &file_path, content) {
+                    Some(content) => Some(content),
+                    None => continue,
+                }
+            }
+            None => None,
+        };
+
+        if let Some(parent) = dest_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = File::create(&dest_path)?;
+        let bytes_read = match mapped {
+            Some(content) => {
+                file.write_all(&content)?;
+                reader.bytes_read()
+            }
+            None => std::io::copy(&mut reader, &mut file)?,
+        };
+
+        if preserve_permissions {
+            if let Some(mode) = reader.unix_mode() {
+                set_permissions(&file, mode & FILE_MODE_MASK)?;
+            }
+        }
+
+        written.push(dest_path);
+        report(&progress, Progress {
+            index: reader.index(),
+            total,
+            name: &file_path,
+            bytes_read,
+        })?;
+    }
+
+    // Deepest first, so no directory is made read-only before its subdirectories are updated.
+    dir_modes.sort_by_key(|(path, _)| std::cmp::Reverse(path.components().count()));
+    for (path, mode) in dir_modes {
+        set_permissions(&File::open(path)?, mode)?;
+    }
+
+    Ok(written)
+}
+
+/// Extracts a jar read from a source without random access, e.g. standard input, into directory
+/// `dest_dir` as [extract_to_dir] does, writing each entry as soon as it is read. Returns paths of
+/// all written files. See [crate::JarPipe] for the limitations of reading jars this way; notably
+/// permissions and symbolic links are not restored. Entries passing through a symbolic link in
+/// `dest_dir` are rejected with [JarError::InvalidEntryName] as well.
+///
+/// # Example
+///
+/// ```rs
+/// extract_pipe_to_dir(std::io::stdin().lock(), "out", JarOptionBuilder::builder().ext("class").build())?;
+/// ```
+pub fn extract_pipe_to_dir<R, D>(reader: R, dest_dir: D, option: JarOption) -> Result<Vec<PathBuf>, JarError>
+    where R: Read, D: AsRef<Path> {
+    let dest_dir = dest_dir.as_ref();
+    let content_mapper = option.content_mapper.clone();
+    let mut pipe = JarPipe::new(reader, option);
+    let mut written = Vec::new();
+    let mut written_paths = HashSet::new();
+
+    std::fs::create_dir_all(dest_dir)?;
+
+    while let Some(entry) = pipe.next_entry() {
+        let (entry, content) = entry?;
+        let relative_path = sanitize_path(&entry.name)?;
+        let dest_path = dest_dir.join(&relative_path);
+
+        check_no_links(dest_dir, &relative_path, &entry.name)?;
+
+        if entry.is_dir() {
+            std::fs::create_dir_all(&dest_path)?;
+            written.push(dest_path);
+            continue;
+        }
+
+        let Some(content) = map_content(&content_mapper, &entry.name, content) else {
+            continue;
+        };
+
+        if let Some(parent) = dest_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(&dest_path, content)?;
+
+        if written_paths.insert(dest_path.clone()) {
+            written.push(dest_path);
+        }
+    }
+
+    Ok(written)
+}
+
+/// Rejects `relative_path` if any of its components under `dest_dir`, including the path itself,
+/// is a symbolic link, e.g. one left in the destination by an earlier extraction, so nothing is
+/// ever written through a link.
+fn check_no_links(dest_dir: &Path, relative_path: &Path, file_path: &str) -> Result<(), JarError> {
+    let mut path = dest_dir.to_path_buf();
+
+    for component in relative_path.components() {
+        path.push(component);
+
+        match std::fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => return Err(JarError::InvalidEntryName(file_path.to_string())),
+            Ok(_) => {}
+            // Nothing below a missing component exists yet.
+            Err(_) => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts an entry path into a relative path which cannot escape the destination directory.
+pub(crate) fn sanitize_path(file_path: &str) -> Result<PathBuf, JarError> {
+    let mut sanitized = PathBuf::new();
+
+    for component in Path::new(file_path).components() {
+        match component {
+            Component::Normal(component) => sanitized.push(component),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+            Component::ParentDir => return Err(JarError::InvalidEntryName(file_path.to_string())),
+        }
+    }
+
+    if sanitized.as_os_str().is_empty() {
+        return Err(JarError::InvalidEntryName(file_path.to_string()));
+    }
+
+    Ok(sanitized)
+}
+
+/// Permission bits restored on files, leaving out setuid, setgid and sticky bits of untrusted jars.
+const FILE_MODE_MASK: u32 = 0o777;
+/// Permission bits restored on directories, which are never made writable for group or others.
+const DIR_MODE_MASK: u32 = 0o755;
+
+#[cfg(unix)]
+fn set_permissions(file: &File, mode: u32) -> Result<(), Error> {
+    use std::os::unix::fs::PermissionsExt;
+
+    file.set_permissions(std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_permissions(_file: &File, _mode: u32) -> Result<(), Error> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::extract::sanitize_path;
+    use crate::fixture::{jar_bytes, jar_file, temp_path};
+    use crate::{extract_pipe_to_dir, extract_to_dir, JarError, JarOptionBuilder};
+
+    #[test]
+    fn test_extract_to_dir() {
+        let path = jar_file(&[("a/b/C.class", b"c"), ("d.txt", b"d")]);
+        let dest = temp_path("extracted");
+        let written = extract_to_dir(&path, &dest, JarOptionBuilder::builder().ext("class").build()).unwrap();
+
+        assert_eq!(written, vec![dest.join("a/b/C.class")]);
+        assert_eq!(std::fs::read(dest.join("a/b/C.class")).unwrap(), b"c");
+        assert!(!dest.join("d.txt").exists());
+    }
+
+    #[test]
+    fn test_extract_dirs() {
+        let path = jar_file(&[("empty/", b""), ("a/b.txt", b"b")]);
+        let dest = temp_path("extracted-dirs");
+        let written = extract_to_dir(&path, &dest, JarOptionBuilder::builder().include_dirs().build()).unwrap();
+
+        assert_eq!(written, vec![dest.join("empty"), dest.join("a/b.txt")]);
+        assert!(dest.join("empty").is_dir());
+    }
+
+    #[test]
+    fn test_extract_map_content() {
+        let path = jar_file(&[("a.txt", b"a"), ("b.txt", b"b")]);
+        let dest = temp_path("extracted-mapped");
+        let option = JarOptionBuilder::builder().map_content(|name, content| (name == "a.txt").then(|| [content, b"!".to_vec()].concat())).build();
+        let written = extract_to_dir(&path, &dest, option).unwrap();
+
+        assert_eq!(written, vec![dest.join("a.txt")]);
+        assert_eq!(std::fs::read(dest.join("a.txt")).unwrap(), b"a!");
+    }
+
+    #[test]
+    fn test_extract_pipe_to_dir() {
+        let bytes = jar_bytes(&[("a/b/C.class", b"c"), ("d.txt", b"d"), ("empty/", b"")]);
+        let dest = temp_path("extracted-pipe");
+        let written = extract_pipe_to_dir(bytes.as_slice(), &dest, JarOptionBuilder::builder().ext("class").build()).unwrap();
+
+        assert_eq!(written, vec![dest.join("a/b/C.class")]);
+        assert_eq!(std::fs::read(dest.join("a/b/C.class")).unwrap(), b"c");
+        assert!(!dest.join("d.txt").exists());
+
+        let written = extract_pipe_to_dir(bytes.as_slice(), &dest, JarOptionBuilder::builder().include_dirs().build()).unwrap();
+
+        assert_eq!(written, vec![dest.join("a/b/C.class"), dest.join("d.txt"), dest.join("empty")]);
+        assert!(dest.join("empty").is_dir());
+        assert!(extract_pipe_to_dir(jar_bytes(&[("../escape", b"")]).as_slice(), &dest, JarOptionBuilder::default()).unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_permissions() {
+        use std::io::{Cursor, Write};
+        use std::os::unix::fs::PermissionsExt;
+
+        use zip::write::FileOptions;
+        use zip::ZipWriter;
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+        writer.add_directory("ro/", FileOptions::default().unix_permissions(0o577)).unwrap();
+        writer.start_file("ro/run.sh", FileOptions::default().unix_permissions(0o755)).unwrap();
+        writer.write_all(b"#!/bin/sh\n").unwrap();
+
+        let mut bytes = writer.finish().unwrap().into_inner();
+        // Sets the setuid bit in the external attributes of the last central header, ro/run.sh.
+        let central = bytes.windows(4).rposition(|window| window == b"PK\x01\x02").unwrap();
+        bytes[central + 41] |= 0o4000u16.to_le_bytes()[1];
+
+        let (path, dest) = (temp_path("permissions.jar"), temp_path("permissions"));
+        std::fs::write(&path, bytes).unwrap();
+        extract_to_dir(&path, &dest, JarOptionBuilder::builder().include_dirs().preserve_permissions().build()).unwrap();
+
+        let mode = |path: PathBuf| std::fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+
+        assert_eq!(mode(dest.join("ro/run.sh")), 0o755);
+        assert_eq!(mode(dest.join("ro")), 0o555);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_refuses_linked_parents() {
+        let (dest, target) = (temp_path("linked-parent"), temp_path("linked-parent-target"));
+        let bytes = jar_bytes(&[("a/b/C.class", b"c")]);
+
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+        std::os::unix::fs::symlink(&target, dest.join("a")).unwrap();
+
+        let path = temp_path("linked-parent.jar");
+        std::fs::write(&path, &bytes).unwrap();
+
+        assert!(matches!(extract_to_dir(&path, &dest, JarOptionBuilder::default()), Err(JarError::InvalidEntryName(name)) if name == "a/b/C.class"));
+        assert!(!target.join("b").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_pipe_refuses_links() {
+        let (dest, target) = (temp_path("pipe-linked"), temp_path("pipe-linked-target"));
+        let bytes = jar_bytes(&[("a/b/C.class", b"c")]);
+
+        std::fs::create_dir_all(dest.join("a/b")).unwrap();
+        std::os::unix::fs::symlink(&target, dest.join("a/b/C.class")).unwrap();
+
+        assert!(matches!(extract_pipe_to_dir(bytes.as_slice(), &dest, JarOptionBuilder::default()), Err(JarError::InvalidEntryName(name)) if name == "a/b/C.class"));
+        assert!(!target.exists());
+
+        std::fs::remove_dir_all(dest.join("a")).unwrap();
+        std::os::unix::fs::symlink(&target, dest.join("a")).unwrap();
+
+        assert!(matches!(extract_pipe_to_dir(bytes.as_slice(), &dest, JarOptionBuilder::default()), Err(JarError::InvalidEntryName(name)) if name == "a/b/C.class"));
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn test_sanitize_path() {
+        assert_eq!(sanitize_path("/abs/./file").unwrap(), PathBuf::from("abs/file"));
+        assert!(sanitize_path("a/../../escape").is_err());
+        assert!(sanitize_path("/").is_err());
+    }
+}
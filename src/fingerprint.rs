@@ -0,0 +1,140 @@
+//! Whole-jar digests telling rebuilds of unchanged content apart from real changes.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::digest::DigestWriter;
+use crate::writer::NONDETERMINISTIC_ATTRIBUTES;
+use crate::{jar_stream, parse_manifest, DigestAlgorithm, JarError, JarOptionBuilder, MANIFEST_PATH};
+
+/// Digests of a jar computed by [jar_fingerprint].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JarFingerprint {
+    /// SHA-256 digest of the jar file.
+    pub file_digest: Vec<u8>,
+    /// SHA-256 digest of entry names and contents, independent of timestamps, entry order,
+    /// compression and manifest attributes describing the build environment, e.g. `Build-Jdk`.
+    pub content_digest: Vec<u8>,
+    /// Number of file entries covered by [JarFingerprint::content_digest].
+    pub entries: usize,
+}
+
+impl JarFingerprint {
+    /// Checks whether both jars are byte-identical.
+    pub fn is_identical(&self, other: &JarFingerprint) -> bool {
+        self.file_digest == other.file_digest
+    }
+
+    /// Checks whether both jars have the same content, e.g. when one is a rebuild of unchanged
+    /// sources, even if their files differ.
+    pub fn same_content(&self, other: &JarFingerprint) -> bool {
+        self.content_digest == other.content_digest
+    }
+}
+
+/// Computes the raw and the content-normalized digest of the jar at `path`, so jars can be
+/// compared for byte-level as well as semantic identity. Entry contents are streamed, and
+/// directories are ignored.
+///
+/// # Example
+///
+/// ```rs
+/// let (before, after) = (jar_fingerprint("previous/app.jar")?, jar_fingerprint("target/app.jar")?);
+///
+/// if !before.is_identical(&after) && before.same_content(&after) {
+///     println!("rebuilt without changes");
+/// }
+/// ```
+pub fn jar_fingerprint<P>(path: P) -> Result<JarFingerprint, JarError> where P: AsRef<Path> {
+    let path = path.as_ref();
+    let mut file_digest = DigestWriter(DigestAlgorithm::Sha256.context());
+
+    std::io::copy(&mut File::open(path)?, &mut file_digest)?;
+
+    let mut stream = jar_stream(path, JarOptionBuilder::default())?;
+    let mut entries = Vec::new();
+
+    while let Some(next) = stream.next_match() {
+        let (index, entry) = next?;
+        let mut file = stream.decode(index, &entry)?;
+        let digest = if entry.name == MANIFEST_PATH {
+            let mut content = Vec::new();
+
+            file.read_to_end(&mut content)?;
+            DigestAlgorithm::Sha256.digest(&normalize_manifest(content))
+        } else {
+            let mut digest = DigestWriter(DigestAlgorithm::Sha256.context());
+
+            std::io::copy(&mut file, &mut digest)?;
+            digest.0.finish().as_ref().to_vec()
+        };
+
+        entries.push((entry.name, digest));
+    }
+
+    entries.sort();
+
+    let mut content_digest = DigestAlgorithm::Sha256.context();
+
+    for (name, digest) in &entries {
+        content_digest.update(&(name.len() as u64).to_be_bytes());
+        content_digest.update(name.as_bytes());
+        content_digest.update(digest);
+    }
+
+    Ok(JarFingerprint {
+        file_digest: file_digest.0.finish().as_ref().to_vec(),
+        content_digest: content_digest.finish().as_ref().to_vec(),
+        entries: entries.len(),
+    })
+}
+
+/// Removes build environment attributes from the main section of a manifest, keeping malformed
+/// manifests as they are.
+fn normalize_manifest(content: Vec<u8>) -> Vec<u8> {
+    let Ok(mut manifest) = parse_manifest(&content) else {
+        return content;
+    };
+
+    for name in NONDETERMINISTIC_ATTRIBUTES {
+        manifest.main_attributes_mut().remove(name);
+    }
+    manifest.to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::fixture::{jar_file, temp_path};
+    use crate::{jar_fingerprint, CompressionMethod, JarWriter};
+
+    #[test]
+    fn test_jar_fingerprint() {
+        let first = jar_file(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nBuild-Jdk: 17\r\n"),
+            ("a/", b""),
+            ("a/A.class", b"a"),
+            ("b.txt", b"b"),
+        ]);
+        let mut writer = JarWriter::new(Cursor::new(Vec::new())).compression(CompressionMethod::Stored);
+
+        writer.write_entry("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nBuild-Jdk: 21\r\n").unwrap();
+        writer.write_entry("b.txt", b"b").unwrap();
+        writer.write_entry("a/A.class", b"a").unwrap();
+
+        let second = temp_path("rebuilt.jar");
+        std::fs::write(&second, writer.finish().unwrap().into_inner()).unwrap();
+
+        let changed = jar_file(&[("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n"), ("a/A.class", b"changed"), ("b.txt", b"b")]);
+        let (first, second, changed) = (jar_fingerprint(&first).unwrap(), jar_fingerprint(&second).unwrap(), jar_fingerprint(&changed).unwrap());
+
+        assert_eq!(first.entries, 3);
+        assert_eq!(first.file_digest.len(), 32);
+        assert!(first.is_identical(&first));
+        assert!(!first.is_identical(&second));
+        assert!(first.same_content(&second));
+        assert!(!first.same_content(&changed));
+    }
+}
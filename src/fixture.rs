@@ -0,0 +1,109 @@
+//! Synthetic jar fixtures for unit tests, since a real rt.jar is rarely available.
+
+use std::io::{Cursor, Write};
+#[cfg(feature = "fs")]
+use std::path::PathBuf;
+#[cfg(feature = "fs")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+#[cfg(feature = "fs")]
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Builds an in-memory jar containing given entries in order. Entries ending with `/` are written as
+/// directories.
+pub(crate) fn jar_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for (name, content) in entries {
+        if let Some(dir) = name.strip_suffix('/') {
+            writer.add_directory(dir, FileOptions::default()).unwrap();
+        } else {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(content).unwrap();
+        }
+    }
+
+    writer.finish().unwrap().into_inner()
+}
+
+/// Returns a unique path inside the system temporary directory.
+#[cfg(feature = "fs")]
+pub(crate) fn temp_path(name: &str) -> PathBuf {
+    let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    std::env::temp_dir().join(format!("jars-test-{}-{}-{}", std::process::id(), id, name))
+}
+
+/// Writes an in-memory jar containing given entries to a unique temporary file.
+#[cfg(feature = "fs")]
+pub(crate) fn jar_file(entries: &[(&str, &[u8])]) -> PathBuf {
+    let path = temp_path("fixture.jar");
+
+    std::fs::write(&path, jar_bytes(entries)).unwrap();
+    path
+}
+
+/// Builds an in-memory jar of stored entries whose sizes and offsets are recorded only in ZIP64
+/// extra fields, with a ZIP64 end of central directory, as written for entries over 4 GiB or
+/// archives of more than 65535 entries.
+pub(crate) fn zip64_jar_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut central_directory = Vec::new();
+
+    for (name, content) in entries {
+        let offset = bytes.len() as u64;
+        let crc32 = crc32fast::hash(content);
+        let size = content.len() as u64;
+
+        bytes.extend(0x04034b50u32.to_le_bytes());
+        bytes.extend([45, 0, 0, 0, 0, 0, 0, 0, 0x21, 0]);
+        bytes.extend(crc32.to_le_bytes());
+        bytes.extend([0xFF; 8]);
+        bytes.extend((name.len() as u16).to_le_bytes());
+        bytes.extend(20u16.to_le_bytes());
+        bytes.extend(name.as_bytes());
+        bytes.extend([1, 0, 16, 0]);
+        bytes.extend(size.to_le_bytes());
+        bytes.extend(size.to_le_bytes());
+        bytes.extend(*content);
+
+        central_directory.extend(0x02014b50u32.to_le_bytes());
+        central_directory.extend([45, 3, 45, 0, 0, 0, 0, 0, 0, 0, 0x21, 0]);
+        central_directory.extend(crc32.to_le_bytes());
+        central_directory.extend([0xFF; 8]);
+        central_directory.extend((name.len() as u16).to_le_bytes());
+        central_directory.extend(28u16.to_le_bytes());
+        central_directory.extend([0; 6]);
+        central_directory.extend((0o100644u32 << 16).to_le_bytes());
+        central_directory.extend([0xFF; 4]);
+        central_directory.extend(name.as_bytes());
+        central_directory.extend([1, 0, 24, 0]);
+        central_directory.extend(size.to_le_bytes());
+        central_directory.extend(size.to_le_bytes());
+        central_directory.extend(offset.to_le_bytes());
+    }
+
+    let central_directory_offset = bytes.len() as u64;
+    bytes.extend(&central_directory);
+    let end_offset = bytes.len() as u64;
+
+    bytes.extend(0x06064b50u32.to_le_bytes());
+    bytes.extend(44u64.to_le_bytes());
+    bytes.extend([45, 0, 45, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    bytes.extend((entries.len() as u64).to_le_bytes());
+    bytes.extend((entries.len() as u64).to_le_bytes());
+    bytes.extend((central_directory.len() as u64).to_le_bytes());
+    bytes.extend(central_directory_offset.to_le_bytes());
+
+    bytes.extend(0x07064b50u32.to_le_bytes());
+    bytes.extend(0u32.to_le_bytes());
+    bytes.extend(end_offset.to_le_bytes());
+    bytes.extend(1u32.to_le_bytes());
+
+    bytes.extend(0x06054b50u32.to_le_bytes());
+    bytes.extend([0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0]);
+    bytes
+}
@@ -0,0 +1,202 @@
+//! The legacy jar index `META-INF/INDEX.LIST`, which maps packages to the jars of an application
+//! so class loaders open only the jars containing a requested class.
+
+use std::collections::BTreeSet;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::{Jar, JarError, MANIFEST_PATH, VERSIONS_PREFIX};
+#[cfg(feature = "fs")]
+use crate::{jar_list, JarOptionBuilder};
+
+/// Path of the jar index inside a jar.
+pub const INDEX_PATH: &str = "META-INF/INDEX.LIST";
+
+const VERSION_HEADER: &str = "JarIndex-Version:";
+
+/// A parsed or generated `META-INF/INDEX.LIST`, listing for each jar its packages as directories,
+/// e.g. `com/example`, and its files at the archive root.
+///
+/// # Example
+///
+/// ```rs
+/// let index = generate_index(&["app.jar", "lib/util.jar"])?;
+/// let mut writer = JarWriter::create("indexed.jar")?.index(index);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JarIndex {
+    /// Version of the index format, `1.0`.
+    pub version: String,
+    jars: Vec<(String, BTreeSet<String>)>,
+}
+
+impl Default for JarIndex {
+    fn default() -> Self {
+        Self {
+            version: "1.0".to_string(),
+            jars: Vec::new(),
+        }
+    }
+}
+
+impl JarIndex {
+    /// Parses an index, failing with [JarError::ManifestParse] if the version header is missing.
+    pub fn parse(bytes: &[u8]) -> Result<Self, JarError> {
+        let content = String::from_utf8_lossy(bytes).replace("\r\n", "\n");
+        let mut sections = content.split("\n\n");
+        let version = sections.next()
+            .and_then(|header| header.trim().strip_prefix(VERSION_HEADER))
+            .ok_or_else(|| JarError::ManifestParse(format!("{} does not start with `{}`", INDEX_PATH, VERSION_HEADER)))?;
+        let mut index = Self {
+            version: version.trim().to_string(),
+            jars: Vec::new(),
+        };
+
+        for section in sections {
+            let mut lines = section.lines().map(str::trim).filter(|line| !line.is_empty());
+
+            if let Some(jar) = lines.next() {
+                index.add(jar, lines);
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Adds a jar with given package directories and root file names, merging with an existing jar
+    /// of the same name.
+    pub fn add<'a, I>(&mut self, jar: &str, paths: I) where I: IntoIterator<Item = &'a str> {
+        let position = match self.jars.iter().position(|(name, _)| name == jar) {
+            Some(position) => position,
+            None => {
+                self.jars.push((jar.to_string(), BTreeSet::new()));
+                self.jars.len() - 1
+            }
+        };
+
+        self.jars[position].1.extend(paths.into_iter().map(str::to_string));
+    }
+
+    /// Adds a jar by the names of its entries, indexing the directory of every entry, or the name
+    /// itself for entries at the archive root, like `jar --generate-index`. The manifest, the index
+    /// and versioned entries of multi-release jars are skipped.
+    pub fn add_entries<'a, I>(&mut self, jar: &str, names: I) where I: IntoIterator<Item = &'a str> {
+        let paths = names.into_iter()
+            .filter(|name| !matches!(*name, "META-INF/" | MANIFEST_PATH | INDEX_PATH) && !name.starts_with(VERSIONS_PREFIX))
+            .map(|name| name.trim_end_matches('/'))
+            .filter(|name| !name.is_empty())
+            .map(|name| name.rsplit_once('/').map_or(name, |(directory, _)| directory))
+            .collect::<Vec<_>>();
+
+        self.add(jar, paths);
+    }
+
+    /// Iterates over jar names in index order, each with its indexed paths sorted.
+    pub fn jars(&self) -> impl Iterator<Item = (&str, impl Iterator<Item = &str>)> {
+        self.jars.iter().map(|(jar, paths)| (jar.as_str(), paths.iter().map(String::as_str)))
+    }
+
+    /// Returns the jars which may contain the entry at `path`, e.g. `com/example/Main.class`, in
+    /// index order.
+    pub fn locate(&self, path: &str) -> Vec<&str> {
+        let key = path.rsplit_once('/').map_or(path, |(directory, _)| directory);
+
+        self.jars.iter()
+            .filter(|(_, paths)| paths.contains(key))
+            .map(|(jar, _)| jar.as_str())
+            .collect()
+    }
+
+    /// Serializes the index in the format read by class loaders.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut content = format!("{} {}\n\n", VERSION_HEADER, self.version);
+
+        for (jar, paths) in &self.jars {
+            content.push_str(jar);
+            content.push('\n');
+            for path in paths {
+                content.push_str(path);
+                content.push('\n');
+            }
+            content.push('\n');
+        }
+
+        content.into_bytes()
+    }
+}
+
+impl Jar {
+    /// Parses `META-INF/INDEX.LIST`, returning [None] if it is absent or not extracted.
+    pub fn index(&self) -> Result<Option<JarIndex>, JarError> {
+        self.files.get(INDEX_PATH)
+            .map(|content| JarIndex::parse(content))
+            .transpose()
+    }
+}
+
+/// Generates an index of the jars at `paths`, named by their file names. Like `jar
+/// --generate-index`, the first jar is usually the one the index is written into, followed by
+/// the jars of its `Class-Path`. Only central directories are read.
+#[cfg(feature = "fs")]
+pub fn generate_index<P>(paths: &[P]) -> Result<JarIndex, JarError> where P: AsRef<Path> {
+    let mut index = JarIndex::default();
+
+    for path in paths {
+        let path = path.as_ref();
+        let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        let entries = jar_list(path, JarOptionBuilder::builder().include_dirs().build())?;
+
+        index.add_entries(&name, entries.iter().map(|entry| entry.name.as_str()));
+    }
+
+    Ok(index)
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::fixture::jar_file;
+    use crate::{generate_index, jar_from_bytes, JarIndex, JarOptionBuilder, JarWriter, INDEX_PATH};
+
+    #[test]
+    fn test_generate_index() {
+        let app = jar_file(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n"),
+            ("META-INF/versions/11/a/A.class", b""),
+            ("a/", b""),
+            ("a/A.class", b""),
+            ("a/b/B.class", b""),
+            ("log4j.properties", b""),
+        ]);
+        let lib = jar_file(&[("c/C.class", b"")]);
+        let index = generate_index(&[&app, &lib]).unwrap();
+        let app_name = app.file_name().unwrap().to_str().unwrap();
+        let lib_name = lib.file_name().unwrap().to_str().unwrap();
+
+        assert_eq!(
+            index.jars().map(|(jar, paths)| (jar, paths.collect::<Vec<_>>())).collect::<Vec<_>>(),
+            vec![(app_name, vec!["a", "a/b", "log4j.properties"]), (lib_name, vec!["c"])],
+        );
+        assert_eq!(index.locate("a/b/B.class"), vec![app_name]);
+        assert_eq!(index.locate("log4j.properties"), vec![app_name]);
+        assert!(index.locate("d/D.class").is_empty());
+        assert_eq!(JarIndex::parse(&index.to_bytes()).unwrap(), index);
+
+        let mut writer = JarWriter::new(Cursor::new(Vec::new())).index(index.clone());
+        writer.write_entry("a/A.class", b"").unwrap();
+
+        let written = jar_from_bytes(&writer.finish().unwrap().into_inner(), JarOptionBuilder::default()).unwrap();
+        assert_eq!(written.index().unwrap(), Some(index));
+        assert_eq!(written.ordered_entries().nth(1).unwrap().0.name, INDEX_PATH);
+    }
+
+    #[test]
+    fn test_parse_index() {
+        let index = JarIndex::parse(b"JarIndex-Version: 1.0\r\n\r\napp.jar\r\na\r\na/b\r\n\r\nlib.jar\r\nc\r\n").unwrap();
+
+        assert_eq!(index.version, "1.0");
+        assert_eq!(index.locate("c/C.class"), vec!["lib.jar"]);
+        assert!(JarIndex::parse(b"app.jar\na\n").is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! CRC-32 validation of entry contents.
+
+use std::io::{Read, Seek};
+
+use flate2::read::DeflateDecoder;
+use zip::read::ZipFile;
+use zip::ZipArchive;
+
+use crate::compression::Decompressors;
+use crate::{CompressionMethod, Jar, JarError};
+
+/// Result of [Jar::integrity_report].
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct IntegrityReport {
+    /// Number of files checked against their stored CRC-32.
+    pub checked: usize,
+    /// Files whose content does not match their stored CRC-32.
+    pub mismatches: Vec<CrcMismatch>,
+}
+
+impl IntegrityReport {
+    /// Checks whether every checked file matches its stored CRC-32.
+    pub fn is_intact(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// A file whose content does not match the CRC-32 stored in the archive.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CrcMismatch {
+    pub name: String,
+    /// CRC-32 stored in the central directory.
+    pub expected: u32,
+    /// CRC-32 of the extracted content.
+    pub actual: u32,
+}
+
+impl Jar {
+    /// Checks every extracted file against the CRC-32 stored in the archive, e.g. after extracting
+    /// with [crate::JarOptionBuilder::verify_crc] disabled. Files added to [Jar::files] after
+    /// extraction have no stored CRC-32 and are not checked.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("sample/rt.jar", JarOptionBuilder::builder().verify_crc(false).build())?;
+    ///
+    /// assert!(jar.integrity_report().is_intact());
+    /// ```
+    pub fn integrity_report(&self) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+
+        for (entry, content) in self.entries() {
+            let actual = crc32fast::hash(content);
+
+            report.checked += 1;
+            if actual != entry.crc32 {
+                report.mismatches.push(CrcMismatch {
+                    name: entry.name.clone(),
+                    expected: entry.crc32,
+                    actual,
+                });
+            }
+        }
+
+        report.mismatches.sort_by(|a, b| a.name.cmp(&b.name));
+        report
+    }
+}
+
+/// Decompressed content of an entry. Entries stored or deflated are decompressed from the raw
+/// content without CRC-32 validation unless required, while other compression methods are always
+/// validated by [zip]. Entries of methods with a registered decompressor are decompressed by it
+/// instead, and fail with [JarError::UnsupportedCompression] if [zip] does not support the method
+/// either.
+pub(crate) enum EntryDecoder<'a> {
+    Checked(ZipFile<'a>),
+    Stored(ZipFile<'a>),
+    Deflated(DeflateDecoder<ZipFile<'a>>),
+    Custom(Box<dyn Read + 'a>),
+}
+
+impl<'a> EntryDecoder<'a> {
+    pub(crate) fn open<R>(
+        archive: &'a mut ZipArchive<R>,
+        index: usize,
+        compression: CompressionMethod,
+        verify_crc: bool,
+        decompressors: &Decompressors,
+    ) -> Result<Self, JarError> where R: Read + Seek {
+        if let Some(decompressor) = decompressors.get(&compression.id()) {
+            let raw = archive.by_index_raw(index)?;
+            let expected = raw.crc32();
+            let content = decompressor.0(Box::new(raw))?;
+
+            if verify_crc {
+                return Ok(EntryDecoder::Custom(Box::new(CrcReader { inner: content, hasher: crc32fast::Hasher::new(), expected })));
+            }
+            return Ok(EntryDecoder::Custom(content));
+        }
+        if !compression.is_supported() {
+            return Err(JarError::UnsupportedCompression(compression));
+        }
+
+        Ok(match compression {
+            _ if verify_crc => EntryDecoder::Checked(archive.by_index(index)?),
+            CompressionMethod::Stored => EntryDecoder::Stored(archive.by_index_raw(index)?),
+            CompressionMethod::Deflated => EntryDecoder::Deflated(DeflateDecoder::new(archive.by_index_raw(index)?)),
+            _ => EntryDecoder::Checked(archive.by_index(index)?),
+        })
+    }
+}
+
+impl Read for EntryDecoder<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            EntryDecoder::Checked(file) | EntryDecoder::Stored(file) => file.read(buf),
+            EntryDecoder::Deflated(decoder) => decoder.read(buf),
+            EntryDecoder::Custom(content) => content.read(buf),
+        }
+    }
+}
+
+/// Validates the CRC-32 of content decompressed by a registered decompressor once it is read to
+/// the end, as [zip] does for the methods it supports.
+struct CrcReader<'a> {
+    inner: Box<dyn Read + 'a>,
+    hasher: crc32fast::Hasher,
+    expected: u32,
+}
+
+impl Read for CrcReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+
+        if read == 0 && !buf.is_empty() && self.hasher.clone().finalize() != self.expected {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid checksum"));
+        }
+
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, CompressionMethod, JarError, JarOptionBuilder, JarWriter};
+
+    #[test]
+    fn test_integrity_report() {
+        let mut bytes = jar_bytes(&[("a.txt", b"aaaa"), ("b.txt", b"bbbb")]);
+        let central_directory = bytes.windows(4).position(|window| window == b"PK\x01\x02").unwrap();
+        // Corrupts the CRC-32 of `a.txt` declared in the central directory.
+        bytes[central_directory + 16] ^= 0xFF;
+
+        assert!(jar_from_bytes(&bytes, JarOptionBuilder::default()).is_err());
+
+        for option in [JarOptionBuilder::builder().verify_crc(false), JarOptionBuilder::builder().verify_crc(false).parallel(2)] {
+            let jar = jar_from_bytes(&bytes, option.build()).unwrap();
+            let report = jar.integrity_report();
+
+            assert_eq!(jar.files["a.txt"], b"aaaa");
+            assert_eq!(report.checked, 2);
+            assert_eq!(report.mismatches.iter().map(|mismatch| mismatch.name.as_str()).collect::<Vec<_>>(), vec!["a.txt"]);
+            assert!(!report.is_intact());
+        }
+    }
+
+    #[test]
+    fn test_decompressor() {
+        let mut writer = JarWriter::new(Cursor::new(Vec::new())).compression(CompressionMethod::Stored);
+        writer.write_entry("a.txt", b"hello").unwrap();
+        let mut bytes = writer.finish().unwrap().into_inner();
+
+        // Relabels stored entries with an unknown method id in the local and central headers.
+        for (signature, offset) in [(b"PK\x03\x04", 8), (b"PK\x01\x02", 10)] {
+            let headers = bytes.windows(4).enumerate().filter(|(_, window)| window == signature).map(|(header, _)| header).collect::<Vec<_>>();
+
+            for header in headers {
+                bytes[header + offset..header + offset + 2].copy_from_slice(&200u16.to_le_bytes());
+            }
+        }
+
+        let unsupported = jar_from_bytes(&bytes, JarOptionBuilder::default());
+        assert!(matches!(unsupported, Err(JarError::UnsupportedCompression(CompressionMethod::Other(200)))));
+
+        let identity = JarOptionBuilder::builder().decompressor(200, |raw| Ok(raw)).build();
+        assert_eq!(jar_from_bytes(&bytes, identity).unwrap().files["a.txt"], b"hello");
+
+        let uppercase = |verify| JarOptionBuilder::builder()
+            .decompressor(200, |mut raw| {
+                let mut content = Vec::new();
+                raw.read_to_end(&mut content)?;
+                Ok(Box::new(Cursor::new(content.to_ascii_uppercase())))
+            })
+            .verify_crc(verify)
+            .parallel(2)
+            .build();
+
+        assert!(jar_from_bytes(&bytes, uppercase(true)).is_err());
+        assert_eq!(jar_from_bytes(&bytes, uppercase(false)).unwrap().files["a.txt"], b"HELLO");
+    }
+}
@@ -0,0 +1,198 @@
+//! Identity and content summary of a jar, as needed per component by SBOM generators.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::Path;
+
+use crate::classes::is_preview_version;
+use crate::classfile::MAGIC;
+use crate::maven::maven_coordinates;
+use crate::module::parse_module_info;
+use crate::{
+    automatic_module_name, jar_stream, parse_manifest, BytecodeSummary, JarError, JarOptionBuilder, MavenCoordinates, ModuleKind,
+    NativeLibrary, MANIFEST_PATH, MAVEN_PREFIX, MODULE_INFO_PATH, VERSIONS_PREFIX,
+};
+
+/// Main manifest attributes identifying a jar, collected into [Inventory::identity].
+pub const IDENTITY_ATTRIBUTES: [&str; 12] = [
+    "Implementation-Title",
+    "Implementation-Version",
+    "Implementation-Vendor",
+    "Implementation-Vendor-Id",
+    "Specification-Title",
+    "Specification-Version",
+    "Specification-Vendor",
+    "Bundle-SymbolicName",
+    "Bundle-Version",
+    "Bundle-Vendor",
+    "Bundle-License",
+    "Automatic-Module-Name",
+];
+
+/// Report of [inventory].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    /// File name of the jar, e.g. `guava-32.1.2-jre.jar`.
+    pub file_name: String,
+    /// Coordinates of the Maven artifacts packaged in the jar, see [crate::Jar::maven_coordinates].
+    pub maven: Vec<MavenCoordinates>,
+    /// [IDENTITY_ATTRIBUTES] present in the main section of the manifest.
+    pub identity: BTreeMap<String, String>,
+    /// Name of the module, declared by `module-info.class`, named by `Automatic-Module-Name` or
+    /// derived from the file name, [None] if no valid name can be derived.
+    pub module_name: Option<String>,
+    pub module_kind: Option<ModuleKind>,
+    /// Number of classes, not counting `module-info.class` and versioned classes of multi-release
+    /// jars.
+    pub class_count: usize,
+    /// Class file versions, see [crate::Jar::bytecode_summary].
+    pub bytecode: BytecodeSummary,
+    /// Paths of jars nested in the jar, e.g. `BOOT-INF/lib/` dependencies, sorted.
+    pub embedded_jars: Vec<String>,
+    /// Native libraries, sorted by path, see [crate::Jar::native_libraries].
+    pub native_libraries: Vec<NativeLibrary>,
+}
+
+/// Gathers the identity and content summary of the jar at `path` in a single pass: entries are
+/// classified by name, only the manifest, Maven build metadata and `module-info.class` are read
+/// in full, and classes only up to their version. Fails on an invalid manifest or module
+/// descriptor.
+///
+/// # Example
+///
+/// ```rs
+/// let inventory = inventory("lib/guava-32.1.2-jre.jar")?;
+///
+/// for coordinates in &inventory.maven {
+///     println!("pkg:maven/{}/{}@{}", coordinates.group_id, coordinates.artifact_id, coordinates.version.as_deref().unwrap_or(""));
+/// }
+/// println!("{:?} requires Java {:?}", inventory.module_name, inventory.bytecode.required_java_version());
+/// ```
+pub fn inventory<P>(path: P) -> Result<Inventory, JarError> where P: AsRef<Path> {
+    let path = path.as_ref();
+    let mut stream = jar_stream(path, JarOptionBuilder::default())?;
+    let mut metadata = HashMap::new();
+    let mut inventory = Inventory {
+        file_name: path.file_name().map(|file_name| file_name.to_string_lossy().into_owned()).unwrap_or_default(),
+        maven: Vec::new(),
+        identity: BTreeMap::new(),
+        module_name: None,
+        module_kind: None,
+        class_count: 0,
+        bytecode: BytecodeSummary::default(),
+        embedded_jars: Vec::new(),
+        native_libraries: Vec::new(),
+    };
+
+    while let Some(next) = stream.next_match() {
+        let (index, entry) = next?;
+        let name = entry.name.clone();
+
+        if name == MANIFEST_PATH || name == MODULE_INFO_PATH || name.starts_with(MAVEN_PREFIX) && is_pom(&name) {
+            let mut content = Vec::new();
+
+            stream.decode(index, &entry)?.read_to_end(&mut content)?;
+            metadata.insert(name, content);
+        } else if name.ends_with(".class") {
+            if name.starts_with(VERSIONS_PREFIX) {
+                continue;
+            }
+
+            let mut header = Vec::with_capacity(8);
+
+            stream.decode(index, &entry)?.take(8).read_to_end(&mut header)?;
+            inventory.class_count += 1;
+            count_version(&mut inventory.bytecode, name, &header);
+        } else if name.to_ascii_lowercase().ends_with(".jar") {
+            inventory.embedded_jars.push(name);
+        } else if let Some(library) = NativeLibrary::classify(&name) {
+            inventory.native_libraries.push(library);
+        }
+    }
+
+    if let Some(content) = metadata.get(MANIFEST_PATH) {
+        let manifest = parse_manifest(content)?;
+
+        inventory.identity = IDENTITY_ATTRIBUTES.iter()
+            .filter_map(|name| Some((name.to_string(), manifest.main_attributes().get(name)?.trim().to_string())))
+            .collect();
+    }
+
+    let (module_name, module_kind) = match metadata.get(MODULE_INFO_PATH) {
+        Some(module_info) => (Some(parse_module_info(module_info)?.name), Some(ModuleKind::Explicit)),
+        None => {
+            let name = inventory.identity.get("Automatic-Module-Name")
+                .cloned()
+                .or_else(|| automatic_module_name(&inventory.file_name).map(|(name, _)| name));
+
+            (name.clone(), name.map(|_| ModuleKind::Automatic))
+        }
+    };
+
+    inventory.module_name = module_name;
+    inventory.module_kind = module_kind;
+    inventory.maven = maven_coordinates(&metadata);
+    inventory.bytecode.preview_classes.sort();
+    inventory.bytecode.invalid_classes.sort();
+    inventory.embedded_jars.sort();
+    inventory.native_libraries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(inventory)
+}
+
+fn is_pom(name: &str) -> bool {
+    name.ends_with("/pom.properties") || name.ends_with("/pom.xml")
+}
+
+/// Counts the class file version of the class at `name` from the first 8 bytes of its content.
+fn count_version(summary: &mut BytecodeSummary, name: String, header: &[u8]) {
+    let word = |offset: usize| u16::from_be_bytes([header[offset], header[offset + 1]]);
+
+    if header.len() < 8 || u32::from_be_bytes([header[0], header[1], header[2], header[3]]) != MAGIC {
+        summary.invalid_classes.push(name);
+        return;
+    }
+
+    let (minor_version, major_version) = (word(4), word(6));
+
+    *summary.versions.entry(major_version).or_default() += 1;
+    if is_preview_version(major_version, minor_version) {
+        summary.preview_classes.push(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::classfile::tests::{class_bytes, reference, utf8};
+    use crate::fixture::jar_file;
+    use crate::{inventory, Arch, ModuleKind, Os};
+
+    #[test]
+    fn test_inventory() {
+        let class = |major_version| class_bytes(major_version, &[utf8("a/A"), reference(7, 1)], 2, 0, &[]);
+        let manifest = b"Manifest-Version: 1.0\r\nImplementation-Title: sample\r\nImplementation-Version: 1.2\r\n\
+            Automatic-Module-Name: com.example.sample\r\nBuilt-By: someone\r\n";
+        let path = jar_file(&[
+            ("META-INF/MANIFEST.MF", manifest),
+            ("META-INF/maven/com.example/sample/pom.properties", b"groupId=com.example\nartifactId=sample\nversion=1.2\n"),
+            ("a/A.class", &class(52)),
+            ("a/B.class", &class(55)),
+            ("META-INF/versions/21/a/A.class", &class(65)),
+            ("a/Broken.class", b"CAFE"),
+            ("BOOT-INF/lib/dependency.jar", b""),
+            ("linux-x86-64/libsample.so", b""),
+        ]);
+        let inventory = inventory(&path).unwrap();
+
+        assert_eq!(inventory.maven.len(), 1);
+        assert_eq!((inventory.maven[0].artifact_id.as_str(), inventory.maven[0].version.as_deref()), ("sample", Some("1.2")));
+        assert_eq!(inventory.identity.keys().collect::<Vec<_>>(), vec!["Automatic-Module-Name", "Implementation-Title", "Implementation-Version"]);
+        assert_eq!((inventory.module_name.as_deref(), inventory.module_kind), (Some("com.example.sample"), Some(ModuleKind::Automatic)));
+        assert_eq!(inventory.class_count, 3);
+        assert_eq!(inventory.bytecode.versions.iter().map(|(version, count)| (*version, *count)).collect::<Vec<_>>(), vec![(52, 1), (55, 1)]);
+        assert_eq!(inventory.bytecode.invalid_classes, vec!["a/Broken.class"]);
+        assert_eq!(inventory.embedded_jars, vec!["BOOT-INF/lib/dependency.jar"]);
+        assert_eq!(inventory.native_libraries.len(), 1);
+        assert_eq!((inventory.native_libraries[0].os, inventory.native_libraries[0].arch), (Os::Linux, Some(Arch::X86_64)));
+    }
+}
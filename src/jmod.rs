@@ -0,0 +1,173 @@
+//! JDK module files (`.jmod`), which are zip archives preceded by a 4-byte header and organized in
+//! sections, e.g. `classes/java/lang/Object.class` and `lib/libjava.so`.
+
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use zip::result::ZipError;
+
+use crate::{jar_from_bytes, Jar, JarError, JarOption};
+
+const JMOD_MAGIC: &[u8] = b"JM";
+
+/// A section of a jmod file, see [Jmod::section].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JmodSection {
+    /// Class files and resources, `classes`.
+    Classes,
+    /// Configuration files, `conf`.
+    Config,
+    /// C header files, `include`.
+    HeaderFiles,
+    /// Legal notices, `legal`.
+    LegalNotices,
+    /// Native commands, `bin`.
+    NativeCommands,
+    /// Native libraries, `lib`.
+    NativeLibraries,
+    /// Man pages, `man`.
+    ManPages,
+}
+
+impl JmodSection {
+    /// All sections in the order the `jmod` tool writes them.
+    pub const ALL: [JmodSection; 7] = [
+        JmodSection::Classes,
+        JmodSection::Config,
+        JmodSection::HeaderFiles,
+        JmodSection::LegalNotices,
+        JmodSection::NativeCommands,
+        JmodSection::NativeLibraries,
+        JmodSection::ManPages,
+    ];
+
+    /// Returns the directory of the section in the jmod file, e.g. `classes/`.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            JmodSection::Classes => "classes/",
+            JmodSection::Config => "conf/",
+            JmodSection::HeaderFiles => "include/",
+            JmodSection::LegalNotices => "legal/",
+            JmodSection::NativeCommands => "bin/",
+            JmodSection::NativeLibraries => "lib/",
+            JmodSection::ManPages => "man/",
+        }
+    }
+
+    /// Returns the section containing full qualified path `path` and the path within the section.
+    pub fn of(path: &str) -> Option<(JmodSection, &str)> {
+        JmodSection::ALL.iter()
+            .find_map(|section| Some((*section, path.strip_prefix(section.prefix())?)))
+    }
+}
+
+/// An extracted jmod file.
+///
+/// # Example
+///
+/// ```rs
+/// let jmod = jmod("/usr/lib/jvm/java-17/jmods/java.base.jmod", JarOptionBuilder::builder().target("classes/java/lang").build())?;
+///
+/// for (path, content) in jmod.section(JmodSection::Classes) {
+///     // "java/lang/Object.class", ...
+/// }
+/// ```
+pub struct Jmod {
+    archive: Jar,
+}
+
+impl Jmod {
+    /// Returns the extracted archive with all files under their full qualified paths.
+    pub fn archive(&self) -> &Jar {
+        &self.archive
+    }
+
+    /// Iterates over files of `section` with paths relative to the section.
+    pub fn section(&self, section: JmodSection) -> impl Iterator<Item = (&str, &[u8])> {
+        self.archive.files.iter()
+            .filter_map(move |(path, content)| Some((path.strip_prefix(section.prefix())?, content.as_slice())))
+    }
+
+    /// Converts the jmod into a [Jar] holding only its `classes` section, with the same paths as a
+    /// jar on the classpath.
+    pub fn into_classes(self) -> Jar {
+        let mut archive = self.archive;
+        let prefix = JmodSection::Classes.prefix();
+
+        archive.files = archive.files.into_iter()
+            .filter_map(|(path, content)| Some((path.strip_prefix(prefix)?.to_string(), content)))
+            .collect();
+        archive.metadata = archive.metadata.into_iter()
+            .filter_map(|(path, mut entry)| {
+                entry.name = path.strip_prefix(prefix)?.to_string();
+                Some((entry.name.clone(), entry))
+            })
+            .collect();
+        archive.order = archive.order.into_iter()
+            .filter_map(|path| Some(path.strip_prefix(prefix)?.to_string()))
+            .collect();
+
+        archive
+    }
+}
+
+/// Extracts a jmod file from given parameter `path`. Rules of `option` match full qualified paths
+/// including the section, e.g. `classes/java/lang/Object.class`.
+#[cfg(feature = "fs")]
+pub fn jmod<P>(path: P, option: JarOption) -> Result<Jmod, JarError> where P: AsRef<Path> {
+    jmod_from_bytes(&fs::read(path)?, option)
+}
+
+/// Extracts a jmod file held entirely in memory. See [jmod] for the extraction behaviour.
+pub fn jmod_from_bytes(bytes: &[u8], option: JarOption) -> Result<Jmod, JarError> {
+    if bytes.len() < 4 || !bytes.starts_with(JMOD_MAGIC) {
+        return Err(JarError::Zip(ZipError::InvalidArchive("missing jmod header")));
+    }
+
+    Ok(Jmod {
+        archive: jar_from_bytes(&bytes[4..], option)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jmod_from_bytes, JarOptionBuilder, JmodSection};
+
+    fn jmod_bytes() -> Vec<u8> {
+        let mut bytes = b"JM\x01\x00".to_vec();
+        bytes.extend(jar_bytes(&[
+            ("classes/module-info.class", b"module"),
+            ("classes/java/lang/Object.class", b"object"),
+            ("lib/libjava.so", b"elf"),
+            ("legal/LICENSE", b"license"),
+        ]));
+        bytes
+    }
+
+    #[test]
+    fn test_jmod_sections() {
+        let jmod = jmod_from_bytes(&jmod_bytes(), JarOptionBuilder::default()).unwrap();
+        let mut classes = jmod.section(JmodSection::Classes).map(|(path, _)| path).collect::<Vec<_>>();
+        classes.sort();
+
+        assert_eq!(classes, vec!["java/lang/Object.class", "module-info.class"]);
+        assert_eq!(jmod.section(JmodSection::NativeLibraries).collect::<Vec<_>>(), vec![("libjava.so", b"elf".as_slice())]);
+        assert_eq!(JmodSection::of("legal/LICENSE"), Some((JmodSection::LegalNotices, "LICENSE")));
+
+        let classes = jmod.into_classes();
+        assert_eq!(classes.files.len(), 2);
+        assert_eq!(classes.entry("java/lang/Object.class").unwrap().name, "java/lang/Object.class");
+        assert_eq!(classes.classes(false).collect::<Vec<_>>(), vec!["java.lang.Object"]);
+    }
+
+    #[test]
+    fn test_jmod_filters_and_header() {
+        let jmod = jmod_from_bytes(&jmod_bytes(), JarOptionBuilder::builder().target("classes/java").build()).unwrap();
+        assert_eq!(jmod.archive().files.keys().collect::<Vec<_>>(), vec!["classes/java/lang/Object.class"]);
+
+        assert!(jmod_from_bytes(&jar_bytes(&[]), JarOptionBuilder::default()).is_err());
+    }
+}
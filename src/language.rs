@@ -0,0 +1,210 @@
+//! Heuristic detection of the JVM languages a jar's classes are compiled from.
+
+use std::collections::BTreeMap;
+
+use regex::bytes::Regex;
+
+use crate::Jar;
+
+/// A JVM language other than Java detected by [Jar::language_fingerprint].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JvmLanguage {
+    Kotlin,
+    Scala,
+    Groovy,
+    Clojure,
+}
+
+impl JvmLanguage {
+    /// Package of the language's runtime library, e.g. `kotlin/`.
+    fn runtime_package(self) -> &'static str {
+        match self {
+            JvmLanguage::Kotlin => "kotlin/",
+            JvmLanguage::Scala => "scala/",
+            JvmLanguage::Groovy => "groovy/",
+            JvmLanguage::Clojure => "clojure/",
+        }
+    }
+}
+
+/// Traces of a [JvmLanguage] in a jar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LanguageUsage {
+    pub language: JvmLanguage,
+    /// Version of the bundled runtime or the compiler, if recorded, e.g. `2.13.12`.
+    pub version: Option<String>,
+    /// Number of classes outside the runtime package carrying markers of the language's compiler,
+    /// e.g. `kotlin.Metadata` annotations or Scala signatures.
+    pub compiled_classes: usize,
+    /// Whether the jar bundles the language's runtime library.
+    pub bundles_runtime: bool,
+    /// Paths of marker files, e.g. `META-INF/main.kotlin_module`, sorted.
+    pub markers: Vec<String>,
+}
+
+/// JVM languages and build tools which produced a jar, see [Jar::language_fingerprint].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LanguageFingerprint {
+    /// Detected languages, sorted.
+    pub languages: Vec<LanguageUsage>,
+    /// `Created-By` manifest attribute, e.g. `Apache Maven 3.9.6`.
+    pub created_by: Option<String>,
+    /// `Build-Jdk` or `Build-Jdk-Spec` manifest attribute, e.g. `17`.
+    pub build_jdk: Option<String>,
+}
+
+impl LanguageFingerprint {
+    pub fn get(&self, language: JvmLanguage) -> Option<&LanguageUsage> {
+        self.languages.iter().find(|usage| usage.language == language)
+    }
+
+    pub fn contains(&self, language: JvmLanguage) -> bool {
+        self.get(language).is_some()
+    }
+}
+
+/// Byte patterns in class files left by the compilers of each language.
+const CLASS_MARKERS: [(JvmLanguage, &str); 4] = [
+    (JvmLanguage::Kotlin, r"Lkotlin/Metadata;"),
+    (JvmLanguage::Scala, r"ScalaSig|Lscala/reflect/ScalaSignature;|TASTY"),
+    (JvmLanguage::Groovy, r"groovy/lang/GroovyObject|Lgroovy/transform/Generated;"),
+    (JvmLanguage::Clojure, r"clojure/lang/IFn|clojure/lang/RT"),
+];
+
+impl Jar {
+    /// Detects JVM languages other than Java which produced the extracted classes and resources,
+    /// e.g. for a software bill of materials. Compiled classes are recognized by markers their
+    /// compilers leave in class files, bundled runtimes by their packages, and versions by
+    /// `library.properties` of Scala, `META-INF/groovy-release-info.properties` of Groovy and the
+    /// manifest of Kotlin's standard library. Classes and marker files must be extracted.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let fingerprint = jar("app.jar", JarOptionBuilder::default())?.language_fingerprint();
+    ///
+    /// if let Some(kotlin) = fingerprint.get(JvmLanguage::Kotlin) {
+    ///     println!("Kotlin {:?}, {} classes", kotlin.version, kotlin.compiled_classes);
+    /// }
+    /// ```
+    pub fn language_fingerprint(&self) -> LanguageFingerprint {
+        let markers = CLASS_MARKERS.map(|(language, pattern)| (language, Regex::new(pattern).expect("class markers are valid regexes")));
+        let mut usages = BTreeMap::<JvmLanguage, LanguageUsage>::new();
+        let mut has_tasty = false;
+
+        for (path, content) in &self.files {
+            if let Some((runtime, _)) = markers.iter().find(|(language, _)| path.starts_with(language.runtime_package())) {
+                if path.ends_with(".class") {
+                    usage(&mut usages, *runtime).bundles_runtime = true;
+                }
+            } else if path.ends_with(".class") {
+                if let Some((language, _)) = markers.iter().find(|(_, marker)| marker.is_match(content)) {
+                    usage(&mut usages, *language).compiled_classes += 1;
+                }
+            } else if path.ends_with(".tasty") {
+                has_tasty = true;
+            } else if path.ends_with(".clj") || path.ends_with(".cljc") {
+                usage(&mut usages, JvmLanguage::Clojure).markers.push(path.clone());
+            } else if path.starts_with("META-INF/") && path.ends_with(".kotlin_module") {
+                usage(&mut usages, JvmLanguage::Kotlin).markers.push(path.clone());
+            } else if path == "library.properties" || path == "META-INF/groovy-release-info.properties" {
+                let (language, key) = match path.as_str() {
+                    "library.properties" => (JvmLanguage::Scala, "version.number"),
+                    _ => (JvmLanguage::Groovy, "ImplementationVersion"),
+                };
+                let usage = usage(&mut usages, language);
+
+                usage.markers.push(path.clone());
+                if let Some(version) = property(content, key) {
+                    usage.version = Some(version);
+                }
+            }
+        }
+
+        // TASTy files are only emitted by Scala 3, whose exact version is not recorded in them.
+        if has_tasty {
+            usage(&mut usages, JvmLanguage::Scala).version.get_or_insert_with(|| "3".to_string());
+        }
+
+        let manifest = self.manifest().ok().flatten().unwrap_or_default();
+        let attributes = manifest.main_attributes();
+
+        if let Some(kotlin) = usages.get_mut(&JvmLanguage::Kotlin) {
+            let stdlib_version = attributes.get("Implementation-Title")
+                .filter(|title| title.starts_with("kotlin"))
+                .and(attributes.get("Implementation-Version"));
+
+            kotlin.version = stdlib_version.or(attributes.get("Kotlin-Version")).map(str::to_string);
+        }
+
+        let mut languages = usages.into_values().collect::<Vec<_>>();
+
+        for usage in &mut languages {
+            usage.markers.sort();
+        }
+
+        LanguageFingerprint {
+            languages,
+            created_by: attributes.get("Created-By").map(str::to_string),
+            build_jdk: attributes.get("Build-Jdk").or(attributes.get("Build-Jdk-Spec")).map(str::to_string),
+        }
+    }
+}
+
+fn usage(usages: &mut BTreeMap<JvmLanguage, LanguageUsage>, language: JvmLanguage) -> &mut LanguageUsage {
+    usages.entry(language).or_insert_with(|| LanguageUsage {
+        language,
+        version: None,
+        compiled_classes: 0,
+        bundles_runtime: false,
+        markers: Vec::new(),
+    })
+}
+
+/// Reads the value of `key` from a Java properties file, supporting `=` and `:` separators.
+fn property(content: &[u8], key: &str) -> Option<String> {
+    String::from_utf8_lossy(content).lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.starts_with('!'))
+        .find_map(|line| {
+            let (name, value) = line.split_once(['=', ':'])?;
+
+            (name.trim() == key).then(|| value.trim().to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, JarOptionBuilder, JvmLanguage};
+
+    #[test]
+    fn test_language_fingerprint() {
+        let bytes = jar_bytes(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nCreated-By: Apache Maven 3.9.6\r\nBuild-Jdk-Spec: 17\r\n"),
+            ("META-INF/main.kotlin_module", b""),
+            ("com/example/Main.class", b"\xCA\xFE\xBA\xBE...Lkotlin/Metadata;..."),
+            ("com/example/Util.class", b"\xCA\xFE\xBA\xBE...Lkotlin/Metadata;..."),
+            ("com/example/Legacy.class", b"\xCA\xFE\xBA\xBE...Ljava/lang/Object;..."),
+            ("com/example/Model.class", b"\xCA\xFE\xBA\xBE...Lscala/reflect/ScalaSignature;..."),
+            ("scala/Predef.class", b"\xCA\xFE\xBA\xBE...ScalaSig..."),
+            ("library.properties", b"# Scala library\nversion.number=2.13.12\n"),
+        ]);
+        let fingerprint = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap().language_fingerprint();
+
+        assert_eq!(fingerprint.languages.iter().map(|usage| usage.language).collect::<Vec<_>>(), vec![JvmLanguage::Kotlin, JvmLanguage::Scala]);
+
+        let kotlin = fingerprint.get(JvmLanguage::Kotlin).unwrap();
+        assert_eq!((kotlin.compiled_classes, kotlin.bundles_runtime, kotlin.version.as_deref()), (2, false, None));
+        assert_eq!(kotlin.markers, vec!["META-INF/main.kotlin_module"]);
+
+        let scala = fingerprint.get(JvmLanguage::Scala).unwrap();
+        assert_eq!((scala.compiled_classes, scala.bundles_runtime, scala.version.as_deref()), (1, true, Some("2.13.12")));
+        assert!(!fingerprint.contains(JvmLanguage::Groovy));
+        assert_eq!(fingerprint.created_by.as_deref(), Some("Apache Maven 3.9.6"));
+        assert_eq!(fingerprint.build_jdk.as_deref(), Some("17"));
+    }
+}
@@ -0,0 +1,138 @@
+//! Detection of how a jar is launched from its manifest attributes.
+
+use crate::{Jar, JarError};
+
+/// How a jar is meant to be run, see [LauncherInfo::kind].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JarKind {
+    /// A Spring Boot fat jar, whose `Main-Class` is a Spring Boot launcher starting `Start-Class`.
+    SpringBoot,
+    /// An executable jar run with `java -jar`, declaring `Main-Class`.
+    Executable,
+    /// A Java agent declaring `Premain-Class` or `Agent-Class`, loaded with `-javaagent` or
+    /// attached to a running JVM.
+    Agent,
+    /// A jar without entry points.
+    Library,
+}
+
+/// Entry points of a jar declared in its manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LauncherInfo {
+    /// Classification of the jar. A jar with several entry points is classified by the first
+    /// matching kind in [JarKind] declaration order; the entry points remain available below.
+    pub kind: JarKind,
+    /// `Main-Class`, the class run by `java -jar`.
+    pub main_class: Option<String>,
+    /// `Start-Class`, the application class of a Spring Boot jar.
+    pub start_class: Option<String>,
+    /// `Premain-Class`, the agent class loaded with `-javaagent`.
+    pub premain_class: Option<String>,
+    /// `Agent-Class`, the agent class attached to a running JVM.
+    pub agent_class: Option<String>,
+    /// `Launcher-Agent-Class`, an agent started before the main class of an executable jar.
+    pub launcher_agent_class: Option<String>,
+}
+
+impl Jar {
+    /// Returns the `Main-Class` manifest attribute. See [Jar::manifest] for extraction
+    /// requirements.
+    pub fn main_class(&self) -> Result<Option<String>, JarError> {
+        self.main_attribute("Main-Class")
+    }
+
+    /// Returns the `Premain-Class` manifest attribute of a Java agent.
+    pub fn premain_class(&self) -> Result<Option<String>, JarError> {
+        self.main_attribute("Premain-Class")
+    }
+
+    /// Returns the `Agent-Class` manifest attribute of a Java agent.
+    pub fn agent_class(&self) -> Result<Option<String>, JarError> {
+        self.main_attribute("Agent-Class")
+    }
+
+    /// Reads all entry points declared in the manifest and classifies the jar. A jar without a
+    /// manifest is a [JarKind::Library].
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let info = jar("app.jar", JarOptionBuilder::builder().keep_meta_info().build())?.launcher_info()?;
+    ///
+    /// if info.kind == JarKind::SpringBoot {
+    ///     println!("runs {}", info.start_class.unwrap());
+    /// }
+    /// ```
+    pub fn launcher_info(&self) -> Result<LauncherInfo, JarError> {
+        let manifest = self.manifest()?.unwrap_or_default();
+        let attribute = |name: &str| manifest.main_attributes().get(name)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string);
+
+        let main_class = attribute("Main-Class");
+        let start_class = attribute("Start-Class");
+        let premain_class = attribute("Premain-Class");
+        let agent_class = attribute("Agent-Class");
+        let kind = if start_class.is_some() && main_class.as_deref().is_some_and(is_spring_boot_launcher) {
+            JarKind::SpringBoot
+        } else if main_class.is_some() {
+            JarKind::Executable
+        } else if premain_class.is_some() || agent_class.is_some() {
+            JarKind::Agent
+        } else {
+            JarKind::Library
+        };
+
+        Ok(LauncherInfo {
+            kind,
+            main_class,
+            start_class,
+            premain_class,
+            agent_class,
+            launcher_agent_class: attribute("Launcher-Agent-Class"),
+        })
+    }
+
+    fn main_attribute(&self, name: &str) -> Result<Option<String>, JarError> {
+        Ok(self.manifest()?.and_then(|manifest| Some(manifest.main_attributes().get(name)?.trim().to_string())))
+    }
+}
+
+/// Checks whether a main class is one of Spring Boot's launchers, e.g.
+/// `org.springframework.boot.loader.JarLauncher` or, since Spring Boot 3.2,
+/// `org.springframework.boot.loader.launch.JarLauncher`.
+fn is_spring_boot_launcher(main_class: &str) -> bool {
+    main_class.starts_with("org.springframework.boot.loader.")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, Jar, JarKind, JarOptionBuilder};
+
+    fn jar_with_manifest(manifest: &[u8]) -> Jar {
+        jar_from_bytes(&jar_bytes(&[("META-INF/MANIFEST.MF", manifest)]), JarOptionBuilder::builder().keep_meta_info().build()).unwrap()
+    }
+
+    #[test]
+    fn test_launcher_info() {
+        let spring_boot = jar_with_manifest(b"Main-Class: org.springframework.boot.loader.launch.JarLauncher\r\nStart-Class: com.example.App\r\n");
+        let info = spring_boot.launcher_info().unwrap();
+        assert_eq!(info.kind, JarKind::SpringBoot);
+        assert_eq!(info.start_class.as_deref(), Some("com.example.App"));
+
+        let executable = jar_with_manifest(b"Main-Class: com.example.Main\r\nPremain-Class: com.example.Agent\r\n");
+        assert_eq!(executable.launcher_info().unwrap().kind, JarKind::Executable);
+        assert_eq!(executable.main_class().unwrap().as_deref(), Some("com.example.Main"));
+        assert_eq!(executable.premain_class().unwrap().as_deref(), Some("com.example.Agent"));
+
+        let agent = jar_with_manifest(b"Agent-Class: com.example.Agent\r\n");
+        assert_eq!(agent.launcher_info().unwrap().kind, JarKind::Agent);
+        assert_eq!(agent.agent_class().unwrap().as_deref(), Some("com.example.Agent"));
+
+        let library = jar_from_bytes(&jar_bytes(&[("a/A.class", b"")]), JarOptionBuilder::default()).unwrap();
+        assert_eq!(library.launcher_info().unwrap().kind, JarKind::Library);
+        assert_eq!(library.main_class().unwrap(), None);
+    }
+}
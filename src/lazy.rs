@@ -0,0 +1,156 @@
+//! Map-like access to jar entries decompressed on first access and cached afterwards.
+
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{Read, Seek};
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::{JarEntry, JarError, JarOption, OpenJar};
+
+/// Content of an entry of a [LazyJar], decompressed by the first call to [LazyEntry::get].
+pub struct LazyEntry<R> {
+    entry: JarEntry,
+    content: OnceLock<Vec<u8>>,
+    jar: Arc<Mutex<OpenJar<R>>>,
+}
+
+impl<R> LazyEntry<R> where R: Read + Seek {
+    /// Returns the decompressed content, decompressing it on the first call. Failures are not
+    /// cached, so a later call retries.
+    pub fn get(&self) -> Result<&[u8], JarError> {
+        if let Some(content) = self.content.get() {
+            return Ok(content);
+        }
+
+        let content = self.jar.lock().expect("decompressing thread panicked").get(&self.entry.name)?;
+
+        Ok(self.content.get_or_init(|| content))
+    }
+
+    /// Returns the metadata of the entry, available without decompressing it.
+    pub fn entry(&self) -> &JarEntry {
+        &self.entry
+    }
+
+    /// Checks whether the content has been decompressed already.
+    pub fn is_loaded(&self) -> bool {
+        self.content.get().is_some()
+    }
+}
+
+/// A jar whose entries are indexed when opened but only decompressed when first read, then cached,
+/// so callers with unpredictable access patterns pay only for the entries they touch. Entries can
+/// be read through shared references, also from several threads, decompressing one at a time.
+///
+/// Unlike [crate::jar], [crate::JarOptionBuilder::map_content] is not applied, as with [OpenJar].
+///
+/// # Example
+///
+/// ```rs
+/// let jar = jar_lazy("sample/rt.jar", JarOptionBuilder::builder().ext("class").build())?;
+///
+/// for name in dependencies_of("java/lang/Object.class") {
+///     let content = jar.get(&name)?;
+/// }
+/// ```
+pub struct LazyJar<R> {
+    /// Entries by full qualified path.
+    pub files: HashMap<String, LazyEntry<R>>,
+}
+
+impl<R> LazyJar<R> where R: Read + Seek {
+    /// Indexes a jar from any seekable source. See [crate::jar] for the extraction rules.
+    pub fn new(reader: R, option: JarOption) -> Result<Self, JarError> {
+        let open = OpenJar::new(reader, option)?;
+        let entries = open.names()
+            .filter_map(|name| open.entry(name).cloned())
+            .collect::<Vec<_>>();
+        let jar = Arc::new(Mutex::new(open));
+        let files = entries.into_iter()
+            .map(|entry| (entry.name.clone(), LazyEntry {
+                entry,
+                content: OnceLock::new(),
+                jar: jar.clone(),
+            }))
+            .collect();
+
+        Ok(Self {
+            files,
+        })
+    }
+
+    /// Returns the content of entry `name`, decompressing it on first access, or
+    /// [JarError::EntryNotFound] if the entry does not exist or is excluded.
+    pub fn get(&self, name: &str) -> Result<&[u8], JarError> {
+        self.files.get(name)
+            .ok_or_else(|| JarError::EntryNotFound(name.to_string()))?
+            .get()
+    }
+
+    /// Returns the metadata of entry `name`.
+    pub fn entry(&self, name: &str) -> Option<&JarEntry> {
+        self.files.get(name).map(LazyEntry::entry)
+    }
+
+    /// Checks whether entry `name` exists and passes the extraction rules.
+    pub fn contains(&self, name: &str) -> bool {
+        self.files.contains_key(name)
+    }
+
+    /// Returns the number of entries decompressed so far.
+    pub fn loaded(&self) -> usize {
+        self.files.values().filter(|entry| entry.is_loaded()).count()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Opens the jar file at `path` as a [LazyJar], decompressing entries on first access.
+///
+/// # Example
+///
+/// ```rs
+/// let jar = jar_lazy("sample/rt.jar", JarOptionBuilder::default())?;
+/// ```
+#[cfg(feature = "fs")]
+pub fn jar_lazy<P>(path: P, option: JarOption) -> Result<LazyJar<File>, JarError> where P: AsRef<Path> {
+    LazyJar::new(File::open(path)?, option)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::fixture::jar_bytes;
+    use crate::{JarError, JarOptionBuilder, LazyJar};
+
+    #[test]
+    fn test_lazy_jar() {
+        let bytes = jar_bytes(&[("a/A.class", b"a"), ("a/B.class", b"b"), ("c.txt", b"c")]);
+        let jar = LazyJar::new(Cursor::new(bytes), JarOptionBuilder::builder().ext("class").build()).unwrap();
+
+        assert_eq!((jar.len(), jar.loaded()), (2, 0));
+        assert_eq!(jar.entry("a/B.class").unwrap().size, 1);
+        assert_eq!(jar.get("a/B.class").unwrap(), b"b");
+        assert_eq!(jar.get("a/B.class").unwrap(), b"b");
+        assert!(jar.files["a/B.class"].is_loaded());
+        assert!(!jar.files["a/A.class"].is_loaded());
+        assert_eq!(jar.loaded(), 1);
+        assert!(!jar.contains("c.txt"));
+        assert!(matches!(jar.get("c.txt"), Err(JarError::EntryNotFound(_))));
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| assert_eq!(jar.files["a/A.class"].get().unwrap(), b"a"));
+        });
+        assert_eq!(jar.loaded(), 2);
+    }
+}
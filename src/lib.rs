@@ -11,145 +11,545 @@
 //! // ...
 //! }
 //! ```
+//!
+//! ## Large jars
+//!
+//! ZIP64 archives, with more than 65535 entries or entries of 4 GiB and larger, are supported and
+//! all sizes in [JarEntry] are 64-bit. Note that [jar] holds the decompressed content of every
+//! extracted entry in memory at once, so jars whose content does not fit in memory should be read
+//! with [jar_stream], [jar_visit] or [extract_to_dir], which hold one entry or a fixed-size buffer
+//! at a time, or filtered with [JarOptionBuilder::max_entry_size]. Entries larger than the address
+//! space cannot be extracted into memory on 32-bit targets. [JarWriter::large_files] is required
+//! to write entries of 4 GiB or larger.
+//!
+//! ## Non-seekable sources
+//!
+//! Jars are normally read through their central directory at the end of the file. Sources which
+//! cannot seek, e.g. standard input, can be read front to back with [jar_from_pipe], [JarPipe] or
+//! [extract_pipe_to_dir] instead, relying on local file headers only: unix modes and symbolic
+//! links are unavailable, entries removed from the central directory are still read, and entries
+//! with sizes deferred to a data descriptor must be deflated. See [JarPipe] for details.
+//!
+//! ## Presets
+//!
+//! [JarOptionBuilder::fast] skips CRC-32 checks and extracts on all cores, for trusted jars, while
+//! [JarOptionBuilder::safe] verifies checksums, rejects duplicate, unsafe and symlink entries, and
+//! bounds sizes and compression ratios, for untrusted jars. Both return a builder, so targets and
+//! other rules can be added. `cargo bench --bench presets` measures them against the defaults.
+//!
+//! ## Compression methods
+//!
+//! Stored and deflated entries are always supported, BZIP2 and Zstandard entries with the default
+//! features `bzip2` and `zstd`. Decompressors for other methods can be registered with
+//! [JarOptionBuilder::decompressor]; entries of unsupported methods fail with
+//! [JarError::UnsupportedCompression].
+//!
+//! ## WebAssembly
+//!
+//! APIs reading or writing the file system, e.g. [jar], [extract_to_dir] and [JarCache], require
+//! the default feature `fs`. Without it, and without the default features `bzip2` and `zstd` linking
+//! C libraries, the crate builds for `wasm32-unknown-unknown`, reading jars from memory with
+//! [jar_from_bytes], [jar_from_reader], [jar_visit_reader] or [OpenJar::new].
+//! [JarOptionBuilder::parallel] must not be set there, since threads cannot be spawned, and APIs
+//! spawning threads, e.g. `jar_channel`, also require `fs`. Entries written there without a
+//! timestamp are dated 1980-01-01, since the target has no clock.
+//!
+//! ## Serialization
+//!
+//! With feature `serde`, [JarEntry], [Manifest], [JarDiff] and [JarStats] implement `Serialize` and
+//! `Deserialize`, e.g. to emit JSON reports of jar contents.
+//!
+//! ## Shared contents
+//!
+//! [Jar::into_shared] moves extracted contents behind an `Arc` so they can be fanned out to
+//! several threads without copying. With feature `bytes`, `Jar::into_bytes` moves them into
+//! `bytes::Bytes` instead, which can also be sliced without copying.
+//!
+//! ## Configuration files
+//!
+//! With feature `config`, extraction rules can be loaded from JSON files with
+//! `JarOption::from_json`, see `JarConfig`, so rule sets can be shared between projects and the
+//! `jars` binary, which reads them with `--config`.
+//!
+//! ## Remote jars
+//!
+//! With feature `remote`, `jar_from_url` and `jar_from_maven` download jars into an on-disk cache
+//! before extracting them. Only `http://` and `file://` URLs are supported out of the box, other
+//! schemes require a `Fetcher` wrapping an HTTP client, see `Remote::fetcher`. Maven artifacts are
+//! verified against their published checksums.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
 use std::fs::File;
-use std::io::{Error, Read};
+use std::io::{Cursor, Read, Seek};
+#[cfg(feature = "fs")]
 use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
 
-use zip::ZipArchive;
+#[cfg(feature = "async")]
+pub mod asynch;
 
-/// An option that indicates the extraction behaviour used in [jar].
-#[derive(Debug, Default, Clone, Eq, PartialEq)]
-pub struct JarOption {
-    extract_targets: HashSet<String>,
-    extension_targets: HashSet<String>,
+pub use android::{AndroidLibrary, AndroidPackage, ANDROID_MANIFEST_PATH};
+#[cfg(feature = "fs")]
+pub use android::{aar, apk};
+#[cfg(feature = "fs")]
+pub use batch::{jars_all, jars_in_dir};
+pub use bundle::{parse_properties, ResourceBundle};
+pub use bytecode::BytecodeSummary;
+#[cfg(feature = "fs")]
+pub use cache::JarCache;
+#[cfg(feature = "fs")]
+pub use channel::{jar_channel, jar_channel_reader};
+pub use classes::{binary_name, class_path, ClassHeader};
+#[cfg(feature = "fs")]
+pub use classpath::{Classpath, ShadowedEntry, Shadowing};
+pub use compression::CompressionMethod;
+#[cfg(feature = "config")]
+pub use config::JarConfig;
+#[cfg(feature = "fs")]
+pub use dedup::{find_duplicates, DuplicateClass, DuplicateContent, DuplicateReport, SplitPackage};
+pub use diff::{diff, diff_classes, AttributeChange, ClassDiff, JarDiff};
+pub use digest::DigestAlgorithm;
+pub use duplicate::DuplicatePolicy;
+pub use encoding::{NameEncoding, UnsafeName, UnsafeNamePolicy};
+pub use enterprise::{EnterpriseArchive, WebArchive, WEB_INF_CLASSES, WEB_INF_LIB};
+#[cfg(feature = "fs")]
+pub use enterprise::{ear, war};
+pub use entry::{DateTime, ExtraField, JarEntry};
+pub use error::JarError;
+pub use explain::{jar_explain_reader, Decision, Explanation, MatchRule};
+#[cfg(feature = "fs")]
+pub use explain::jar_explain;
+#[cfg(feature = "fs")]
+pub use extract::{extract_pipe_to_dir, extract_to_dir};
+#[cfg(feature = "fs")]
+pub use fingerprint::{jar_fingerprint, JarFingerprint};
+pub use index::{JarIndex, INDEX_PATH};
+#[cfg(feature = "fs")]
+pub use index::generate_index;
+pub use integrity::{CrcMismatch, IntegrityReport};
+#[cfg(feature = "fs")]
+pub use inventory::{inventory, Inventory, IDENTITY_ATTRIBUTES};
+pub use jmod::{jmod_from_bytes, Jmod, JmodSection};
+#[cfg(feature = "fs")]
+pub use jmod::jmod;
+pub use language::{JvmLanguage, LanguageFingerprint, LanguageUsage};
+pub use launcher::{JarKind, LauncherInfo};
+pub use lazy::{LazyEntry, LazyJar};
+#[cfg(feature = "fs")]
+pub use lazy::jar_lazy;
+pub use manifest::{parse_manifest, Attributes, Manifest, MANIFEST_PATH};
+pub use maven::{MavenCoordinates, MAVEN_PREFIX};
+#[cfg(feature = "fs")]
+pub use merge::{merge, MergeOptions, MergeStrategy};
+#[cfg(all(unix, feature = "mmap"))]
+pub use mmap::{jar_mmap, MappedJar};
+pub use module::{
+    automatic_module_name, GraphModule, MissingModule, ModuleDescriptor, ModuleGraph, ModuleKind, PackageAccess, Provides, Requires,
+    MODULE_INFO_PATH,
+};
+#[cfg(feature = "fs")]
+pub use module::{module_descriptor, module_graph};
+pub use natives::{Arch, NativeLibrary, Os};
+pub use nested::{NestedClasspath, NestedLauncher};
+pub use open::OpenJar;
+#[cfg(feature = "fs")]
+pub use open::jar_open;
+pub use option::{JarOption, JarOptionBuilder, SAFE_MAX_ENTRIES, SAFE_MAX_ENTRY_SIZE, SAFE_MAX_TOTAL_SIZE};
+pub use osgi::{
+    parse_osgi_header, Clause, ExportedPackage, ImportedPackage, OsgiBundle, OsgiVersion, VersionRange,
+};
+pub use package::{sealing_violations, Package, SealingViolation};
+pub use pipe::{jar_from_pipe, JarPipe};
+pub use progress::Progress;
+pub use range::{jar_open_range, RangeReader, RangeSource, DEFAULT_BLOCK_SIZE};
+pub use raw::RawEntry;
+pub use release::VERSIONS_PREFIX;
+#[cfg(feature = "remote")]
+pub use remote::{jar_from_maven, jar_from_url, maven_url, Fetcher, Remote, CACHE_DIR_VAR};
+pub use relocate::Relocator;
+#[cfg(feature = "fs")]
+pub use relocate::relocate_jar;
+#[cfg(feature = "fs")]
+pub use repack::repack;
+pub use search::{SearchMatch, Searcher};
+#[cfg(feature = "fs")]
+pub use search::jar_search;
+pub use services::{ServiceDescriptorMerger, ServiceWarning, SERVICES_PREFIX};
+pub use sign::JarSigner;
+pub use signature::{EntryStatus, ManifestDigestReport, SignatureStatus, Signer, VerificationReport};
+pub use sink::{jar_visit_reader, EntrySink};
+#[cfg(feature = "fs")]
+pub use sink::jar_visit;
+pub use sniff::ContentKind;
+pub use spill::EntryContent;
+pub use spring::{Layer, SpringBootJar, BOOT_INF_CLASSES, BOOT_INF_LIB};
+#[cfg(feature = "fs")]
+pub use spring::spring_boot;
+#[cfg(feature = "fs")]
+pub use split::{split, SplitRules, WrittenJar};
+pub use stats::{JarStats, SizeTotals};
+#[cfg(feature = "fs")]
+pub use stats::jar_stats;
+pub use stream::{JarEntryReader, JarStream};
+#[cfg(feature = "fs")]
+pub use stream::{jar_list, jar_stream};
+pub use symlink::SymlinkPolicy;
+pub use text::is_probably_text;
+pub use trace::{SkipReason, TraceEvent};
+#[cfg(feature = "fs")]
+pub use update::JarUpdater;
+pub use validate::{validate_reader, ValidationIssue, ValidationReport, HIGH_COMPRESSION_RATIO};
+#[cfg(feature = "fs")]
+pub use validate::validate;
+pub use warning::JarWarning;
+pub use writer::JarWriter;
+
+mod android;
+#[cfg(feature = "fs")]
+mod batch;
+mod bundle;
+mod bytecode;
+#[cfg(feature = "fs")]
+mod cache;
+#[cfg(feature = "fs")]
+mod channel;
+mod classes;
+mod classfile;
+mod classpath;
+mod compression;
+#[cfg(feature = "config")]
+mod config;
+mod der;
+#[cfg(feature = "fs")]
+mod dedup;
+mod diff;
+mod digest;
+mod duplicate;
+mod encoding;
+mod enterprise;
+mod entry;
+mod error;
+mod explain;
+mod export;
+#[cfg(feature = "fs")]
+mod extract;
+#[cfg(feature = "fs")]
+mod fingerprint;
+mod index;
+mod integrity;
+#[cfg(feature = "fs")]
+mod inventory;
+mod jmod;
+mod language;
+mod launcher;
+mod lazy;
+mod limits;
+mod manifest;
+mod maven;
+#[cfg(feature = "fs")]
+mod merge;
+#[cfg(all(unix, feature = "mmap"))]
+mod mmap;
+mod module;
+mod natives;
+mod nested;
+mod open;
+mod option;
+mod osgi;
+mod package;
+mod parallel;
+mod pattern;
+mod pipe;
+mod progress;
+mod range;
+mod raw;
+mod release;
+mod relocate;
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(feature = "fs")]
+mod repack;
+mod search;
+mod services;
+mod sign;
+mod signature;
+mod sink;
+mod sniff;
+mod spill;
+#[cfg(feature = "fs")]
+mod split;
+mod spring;
+mod stats;
+mod stream;
+mod symlink;
+mod text;
+mod trace;
+#[cfg(feature = "fs")]
+mod update;
+mod validate;
+mod warning;
+mod writer;
+#[cfg(test)]
+mod fixture;
+
+/// Simple [Jar] data representation stores files with a single [HashMap], key of files are full
+/// qualified path while entry of files are read data in vector of u8.
+pub struct Jar {
+    pub files: HashMap<String, Vec<u8>>,
+    metadata: HashMap<String, JarEntry>,
+    order: Vec<String>,
+    failures: Vec<(String, JarError)>,
+    duplicates: Vec<String>,
+    unsafe_names: Vec<UnsafeName>,
+    warnings: Vec<JarWarning>,
+    spilled: HashMap<String, PathBuf>,
+    comment: Vec<u8>,
 }
 
-impl JarOption {
-    fn target_match(&self, qualified_target_path: &str) -> bool {
-        if self.extract_targets.is_empty() {
-            true
-        } else {
-            self.extract_targets.iter().any(|target| qualified_target_path.starts_with(target))
-        }
+impl Jar {
+    /// Iterates over metadata and content of all files extracted into memory, in no particular
+    /// order. Files spilled by [JarOptionBuilder::spill_to_disk] are left out, see [Jar::spilled]
+    /// or [Jar::into_contents] for them.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("sample/rt.jar", JarOptionBuilder::default())?;
+    ///
+    /// for (entry, content) in jar.entries() {
+    ///     println!("{} {} {:08x}", entry.name, entry.size, entry.crc32);
+    /// }
+    /// ```
+    pub fn entries(&self) -> impl Iterator<Item = (&JarEntry, &[u8])> {
+        self.metadata.iter()
+            .filter_map(|(file_path, entry)| Some((entry, self.files.get(file_path)?.as_slice())))
     }
 
-    fn ext_match(&self, qualified_target_path: &str) -> bool {
-        if self.extension_targets.is_empty() {
-            true
-        } else {
-            let extension = qualified_target_path.rsplit_once(".");
-            
-            if let Some((_, extension)) = extension {
-                self.extension_targets.iter().any(|ext| {
-                    extension.ends_with(ext)
-                })
-            } else {
-                false
-            }
-        }
+    /// Iterates over metadata and content of all files extracted into memory in central directory
+    /// order, which usually has the manifest first. Spilled files are left out as by [Jar::entries].
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// for (entry, content) in jar("sample/rt.jar", JarOptionBuilder::default())?.ordered_entries() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn ordered_entries(&self) -> impl Iterator<Item = (&JarEntry, &[u8])> {
+        self.order.iter()
+            .filter_map(|file_path| Some((self.metadata.get(file_path)?, self.files.get(file_path)?.as_slice())))
     }
-}
 
-/// A simple option builder for [JarOption] to build in a easy way.
-#[derive(Debug)]
-pub struct JarOptionBuilder {
-    extract_targets: HashSet<String>,
-    extension_targets: HashSet<String>,
-}
+    /// Moves every file's content behind an [Arc] without copying it, so contents can be cloned
+    /// cheaply and shared across threads or tasks, e.g. to fan classes out to several analyzers.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let files = jar("app.jar", JarOptionBuilder::builder().ext("class").build())?.into_shared();
+    ///
+    /// for (name, content) in &files {
+    ///     let content = content.clone();
+    ///     std::thread::spawn(move || analyze(&content));
+    /// }
+    /// ```
+    pub fn into_shared(self) -> HashMap<String, Arc<Vec<u8>>> {
+        self.files.into_iter()
+            .map(|(file_path, content)| (file_path, Arc::new(content)))
+            .collect()
+    }
 
-impl JarOptionBuilder {
-    /// Creates a [JarOption] which allows any file extraction by default.
-    pub fn default() -> JarOption {
-        JarOption::default()
+    /// Moves every file's content into [bytes::Bytes] without copying it, so contents can be
+    /// cloned and sliced cheaply and handed to libraries built on `bytes`, e.g. `tokio` or `hyper`.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let files = jar("app.jar", JarOptionBuilder::builder().ext("class").build())?.into_bytes();
+    ///
+    /// for (name, content) in &files {
+    ///     tokio::spawn(analyze(content.clone()));
+    /// }
+    /// ```
+    #[cfg(feature = "bytes")]
+    pub fn into_bytes(self) -> HashMap<String, bytes::Bytes> {
+        self.files.into_iter()
+            .map(|(file_path, content)| (file_path, bytes::Bytes::from(content)))
+            .collect()
     }
 
-    /// Creates a [JarOptionBuilder] to build up extraction options.
-    pub fn builder() -> Self {
-        Self {
-            extract_targets: HashSet::new(),
-            extension_targets: HashSet::new(),
-        }
+    /// Returns entries skipped because of corrupted content along with their errors, which is only
+    /// non-empty with [JarOptionBuilder::skip_corrupt].
+    pub fn failures(&self) -> &[(String, JarError)] {
+        &self.failures
     }
 
-    /// Keeps `META-INF` folder on extraction.
-    pub fn keep_meta_info(mut self) -> Self {
-        self.extract_targets.insert("META-INF".to_string());
-        self
+    /// Returns names shared by more than one entry in the archive, sorted, which may indicate an
+    /// attempt to mask content. See [JarOptionBuilder::duplicate_policy].
+    pub fn duplicates(&self) -> &[String] {
+        &self.duplicates
     }
 
-    /// Filters extraction target with providing target path. Note that [jar] extracts all files when
-    /// there's no extraction target specified.
-    /// 
+    /// Returns entry names rejected as unsafe to use as relative paths, e.g. `../../etc/passwd`, in
+    /// archive order, which indicates a malicious jar. See [JarOptionBuilder::unsafe_name_policy].
+    pub fn unsafe_names(&self) -> &[UnsafeName] {
+        &self.unsafe_names
+    }
+
+    /// Returns non-fatal oddities found during extraction in archive order, e.g. unsafe names or
+    /// dropped duplicate entries, so they can be surfaced instead of silently ignored.
+    ///
     /// # Example
-    /// 
+    ///
     /// ```rs
-    /// JarOptionBuilder::builder().target("java/lang").build();
+    /// let jar = jar("app.jar", JarOptionBuilder::default())?;
+    ///
+    /// for warning in jar.warnings() {
+    ///     eprintln!("warning: {}", warning);
+    /// }
     /// ```
-    pub fn target(mut self, target: &str) -> Self {
-        self.extract_targets.insert(target.to_string());
-        self
+    pub fn warnings(&self) -> &[JarWarning] {
+        &self.warnings
+    }
+
+    /// Returns the archive comment stored at the end of the jar, empty if there is none. Some build
+    /// systems store provenance data there.
+    pub fn comment(&self) -> &[u8] {
+        &self.comment
     }
 
-    /// Filters multiple extraction targets with providing target path. Note that [jar] extracts all 
-    /// files when there's no extraction target specified.
-    /// 
+    /// Returns the metadata of extracted file `file_path`.
+    pub fn entry(&self, file_path: &str) -> Option<&JarEntry> {
+        self.metadata.get(file_path)
+    }
+
+    /// Groups the metadata of extracted files by package, i.e. their parent directory such as
+    /// `java/lang`, with files at the root under the empty string. Each group lists its files in
+    /// central directory order. Directory entries are left out, and versioned entries stay under
+    /// their `META-INF/versions/<N>/...` directory.
+    ///
     /// # Example
-    /// 
+    ///
     /// ```rs
-    /// JarOptionBuilder::builder().targets(vec!["java/lang"]).build();
+    /// let jar = jar("sample/rt.jar", JarOptionBuilder::builder().ext("class").build())?;
+    ///
+    /// for (package, entries) in jar.by_package() {
+    ///     println!("{} {} classes", package, entries.len());
+    /// }
     /// ```
-    pub fn targets(mut self, targets: &Vec<&str>) -> Self {
-        for target in targets {
-            self.extract_targets.insert(target.to_string());
-        }
-        self
+    pub fn by_package(&self) -> HashMap<String, Vec<&JarEntry>> {
+        self.packages_matching("")
     }
 
-    /// Filters extraction targets with providing file extension. Note that [jar] extracts all 
-    /// files when there's no extraction target specified.
+    /// Groups the metadata of extracted files in package `prefix` or any of its subpackages as
+    /// [Jar::by_package] does. `prefix` is slash separated and matches whole components, e.g.
+    /// `java/lang` matches `java/lang/reflect` but not `java/language`.
     ///
     /// # Example
-    /// 
+    ///
     /// ```rs
-    /// JarOptionBuilder::builder().ext("class").build();
+    /// let reflection = jar.packages_matching("java/lang/reflect");
     /// ```
-    pub fn ext(mut self, ext: &str) -> Self {
-        self.extension_targets.insert(ext.to_string());
-        self
+    pub fn packages_matching(&self, prefix: &str) -> HashMap<String, Vec<&JarEntry>> {
+        let prefix = prefix.trim_end_matches('/');
+        let mut packages = HashMap::<String, Vec<&JarEntry>>::new();
+
+        for entry in self.order.iter().filter_map(|file_path| self.metadata.get(file_path)) {
+            let package = entry.name.rsplit_once('/').map_or("", |(package, _)| package);
+            let matched = prefix.is_empty()
+                || package.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+
+            if matched && !entry.is_dir() {
+                packages.entry(package.to_string()).or_default().push(entry);
+            }
+        }
+
+        packages
     }
 
-    /// Filters multiple extraction targets with providing file extension. Note that [jar] extracts 
-    /// all files when there's no extraction target specified.
+    /// Parses `META-INF/MANIFEST.MF` into a [Manifest]. Returns [None] when the manifest is absent
+    /// or excluded by extraction rules, e.g. when `META-INF` is not kept by
+    /// [JarOptionBuilder::keep_meta_info].
+    pub fn manifest(&self) -> Result<Option<Manifest>, JarError> {
+        self.files.get(MANIFEST_PATH)
+            .map(|content| parse_manifest(content))
+            .transpose()
+    }
+
+    /// Replaces `META-INF/MANIFEST.MF` with `manifest`, updating its metadata so the jar can be
+    /// written back with [Jar::write_zip]. An absent manifest is added as a stored entry right
+    /// after a leading `META-INF/` directory. Replacing the manifest of a signed jar invalidates its
+    /// signatures.
     ///
     /// # Example
     ///
     /// ```rs
-    /// JarOptionBuilder::builder().exts(vec!["class"]).build();
+    /// let jar = jar("app.jar", JarOptionBuilder::builder().keep_meta_info().build())?;
+    /// let mut manifest = jar.manifest()?.unwrap_or_default();
+    ///
+    /// manifest.set_main_attribute("Implementation-Version", env!("CARGO_PKG_VERSION"));
+    /// jar.with_manifest(manifest).write_zip(File::create("app-stamped.jar")?)?;
     /// ```
-    pub fn exts(mut self, exts: &Vec<&str>) -> Self {
-        for ext in exts {
-            self.extension_targets.insert(ext.to_string());
+    pub fn with_manifest(mut self, manifest: Manifest) -> Self {
+        let content = manifest.to_bytes();
+        let (size, crc32) = (content.len() as u64, crc32fast::hash(&content));
+
+        match self.metadata.get_mut(MANIFEST_PATH) {
+            Some(entry) => {
+                if entry.compression == CompressionMethod::Stored {
+                    entry.compressed_size = size;
+                }
+                entry.size = size;
+                entry.crc32 = crc32;
+            }
+            None => {
+                let index = self.order.first().map_or(0, |first| (first == "META-INF/") as usize);
+
+                self.order.insert(index, MANIFEST_PATH.to_string());
+                self.metadata.insert(MANIFEST_PATH.to_string(), JarEntry {
+                    name: MANIFEST_PATH.to_string(),
+                    size,
+                    compressed_size: size,
+                    compression: CompressionMethod::Stored,
+                    last_modified: DateTime::now(),
+                    crc32,
+                    unix_mode: None,
+                    extra: Vec::new(),
+                });
+            }
         }
+
+        self.spilled.remove(MANIFEST_PATH);
+        self.files.insert(MANIFEST_PATH.to_string(), content);
         self
     }
 
-    /// Finalize current [JarOptionBuilder] and construct a [JarOption] from current builder.
-    pub fn build(self) -> JarOption {
-        JarOption {
-            extract_targets: self.extract_targets,
-            extension_targets: self.extension_targets,
-        }
+    /// Verifies the jar's signatures, checking entry contents against manifest digests, manifest
+    /// sections against signature files, and signature files against their signature blocks. The
+    /// manifest and signature files must be extracted, e.g. with [JarOptionBuilder::keep_meta_info].
+    /// Files spilled to disk are digested from their spill files.
+    ///
+    /// Only extracted files are checked: entries listed in the manifest but left out by extraction
+    /// rules are reported as [EntryStatus::Unchecked]. Content rewritten by
+    /// [JarOptionBuilder::map_content] or [JarOptionBuilder::content_type] is digested after
+    /// mapping, so rewritten entries are reported as [EntryStatus::Tampered].
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let report = jar("signed.jar", JarOptionBuilder::default())?.verify()?;
+    ///
+    /// for name in report.tampered() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn verify(&self) -> Result<VerificationReport, JarError> {
+        signature::verify(&self.files, &self.spilled)
     }
 }
 
-/// Simple [Jar] data representation stores files with a single [HashMap], key of files are full
-/// qualified path while entry of files are read data in vector of u8.
-pub struct Jar {
-    pub files: HashMap<String, Vec<u8>>,
-}
-
 /// Extracts a jar file from given parameter `path`. The extraction behaviour is defined by parameter
 /// `option` which can build from [JarOptionBuilder::default] with all defaulted options, or 
 /// [JarOptionBuilder::builder] with multiple options provided.
@@ -159,43 +559,330 @@ pub struct Jar {
 /// ```rs
 /// let jar = jar("sample/rt.jar", JarOptionBuilder::default())?;
 /// ```
-pub fn jar<P>(path: P, option: JarOption) -> Result<Jar, Error> where P: AsRef<Path> {
+#[cfg(feature = "fs")]
+pub fn jar<P>(path: P, option: JarOption) -> Result<Jar, JarError> where P: AsRef<Path> {
+    if option.threads > 1 {
+        let path = path.as_ref();
+
+        parallel::jar_parallel(|| File::open(path), option)
+    } else {
+        jar_from_reader(File::open(path)?, option)
+    }
+}
+
+/// Extracts a jar from any seekable source, e.g. a jar downloaded into memory or embedded in
+/// another archive. See [jar] for the extraction behaviour.
+///
+/// # Example
+///
+/// ```rs
+/// let jar = jar_from_reader(Cursor::new(downloaded), JarOptionBuilder::default())?;
+/// ```
+pub fn jar_from_reader<R>(reader: R, option: JarOption) -> Result<Jar, JarError> where R: Read + Seek {
     let mut files = HashMap::new();
-    let mut jar_zip = File::open(path).map(ZipArchive::new)??;
+    let mut metadata = HashMap::new();
+    let mut order = Vec::new();
+    let mut failures = Vec::new();
+    let progress = option.progress.clone();
+    let skip_corrupt = option.skip_corrupt;
+    let content_mapper = option.content_mapper.clone();
+    let tee = option.tee.clone();
+    let spill = option.spill.clone();
+    let mut spilled = HashMap::new();
+    let mut stream = JarStream::new(reader, option)?;
+    let total = stream.len();
+    let duplicates = stream.duplicates().to_vec();
 
-    for i in 0..jar_zip.len() {
-        let file = jar_zip.by_index(i)?;
-        let file_path = match file.enclosed_name() {
-            Some(file_path) => file_path.to_string_lossy().to_string(),
-            None => continue,
-        };
+    loop {
+        let (file_path, err) = match stream.next_entry() {
+            None => break,
+            Some(Ok((file_path, mut reader))) => {
+                let entry = reader.entry().clone();
+                let index = reader.index();
 
-        if file.is_dir() {
-            continue;
-        }
+                match spill::read_entry(&spill, &entry, &mut sink::TeeReader::new(&tee, &entry, &mut reader)?, false) {
+                    Ok((content, bytes_read)) => {
+                        progress::report(&progress, Progress {
+                            index,
+                            total,
+                            name: &file_path,
+                            bytes_read,
+                        })?;
 
-        if !option.target_match(&file_path) && !option.ext_match(&file_path) {
-            continue;
-        }
+                        match content {
+                            EntryContent::InMemory(content) => {
+                                let Some(content) = option::map_content(&content_mapper, &file_path, content) else {
+                                    continue;
+                                };
 
-        files.insert(file_path, file.bytes().collect::<Result<Vec<_>, _>>()?);
+                                files.insert(file_path.clone(), content);
+                            }
+                            EntryContent::OnDisk(path) => {
+                                spilled.insert(file_path.clone(), path);
+                            }
+                        }
+
+                        metadata.insert(file_path.clone(), entry);
+                        order.push(file_path);
+                        continue;
+                    }
+                    Err(err) => (Some(file_path), err),
+                }
+            }
+            Some(Err(err)) => (None, err),
+        };
+
+        match file_path.or_else(|| stream.take_failed_name()) {
+            Some(file_path) if skip_corrupt && err.is_corrupt() => failures.push((file_path, err)),
+            _ => return Err(err),
+        }
     }
 
     Ok(Jar {
-        files
+        files,
+        metadata,
+        order,
+        failures,
+        duplicates,
+        unsafe_names: stream.unsafe_names().to_vec(),
+        warnings: stream.warnings().to_vec(),
+        spilled,
+        comment: stream.comment().to_vec(),
     })
 }
 
+/// Extracts a jar held entirely in memory. See [jar] for the extraction behaviour.
+///
+/// # Example
+///
+/// ```rs
+/// let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().ext("class").build())?;
+/// ```
+pub fn jar_from_bytes(bytes: &[u8], option: JarOption) -> Result<Jar, JarError> {
+    if option.threads > 1 {
+        parallel::jar_parallel(|| Ok(Cursor::new(bytes)), option)
+    } else {
+        jar_from_reader(Cursor::new(bytes), option)
+    }
+}
+
 /// Warning! Only tests when you have your own rt.jar, which can be copied from $JAVA_HOME/lib/rt.java
 /// below java 8, for java 9 and later, do not test it since it's not possible to obtain rt.jar.
 #[cfg(test)]
 mod tests {
-    use crate::{jar, JarOptionBuilder};
+    use std::collections::HashMap;
+    use std::io::{Cursor, Write};
 
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    use crate::fixture::{jar_bytes, zip64_jar_bytes};
+    use crate::{jar_from_bytes, CompressionMethod, ExtraField, JarEntry, JarOptionBuilder, JarWriter};
+
+    #[cfg(feature = "fs")]
     #[test]
     fn test_rt_jar_folders() {
-        let jar = jar("../sample/rt.jar", JarOptionBuilder::builder().target("java/lang").build());
+        let jar = crate::jar("../sample/rt.jar", JarOptionBuilder::builder().target("java/lang").build());
         
         assert!(jar.is_ok());
     }
+
+    #[test]
+    fn test_jar_from_bytes_filters() {
+        let bytes = jar_bytes(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n"),
+            ("java/lang/Object.class", b"object"),
+            ("java/lang/messages.properties", b"key=value"),
+            ("java/util/List.class", b"list"),
+        ]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().keep_meta_info().ext("class").build()).unwrap();
+        let mut paths = jar.files.keys().collect::<Vec<_>>();
+        paths.sort();
+
+        assert_eq!(paths, vec!["META-INF/MANIFEST.MF", "java/lang/Object.class", "java/util/List.class"]);
+    }
+
+    #[test]
+    fn test_into_shared() {
+        let bytes = jar_bytes(&[("a.txt", b"a"), ("b.txt", &[1; 1024])]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap();
+        let pointer = jar.files["b.txt"].as_ptr();
+        let files = jar.into_shared();
+        let shared = files["b.txt"].clone();
+
+        assert_eq!(shared.as_ptr(), pointer);
+        assert_eq!(std::thread::spawn(move || shared.len()).join().unwrap(), 1024);
+        assert_eq!(files["a.txt"].as_slice(), b"a");
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_into_bytes() {
+        let bytes = jar_bytes(&[("a.txt", b"a"), ("b.txt", &[1; 1024])]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap();
+        let pointer = jar.files["b.txt"].as_ptr();
+        let files = jar.into_bytes();
+        let shared = files["b.txt"].slice(512..);
+
+        assert_eq!(files["b.txt"].as_ptr(), pointer);
+        assert_eq!(std::thread::spawn(move || shared.len()).join().unwrap(), 512);
+        assert_eq!(files["a.txt"].as_ref(), b"a");
+    }
+
+    #[test]
+    fn test_skip_corrupt() {
+        let mut bytes = jar_bytes(&[("a.txt", b"aaaa"), ("b.txt", b"bbbb")]);
+        let central_directory = bytes.windows(4).position(|window| window == b"PK\x01\x02").unwrap();
+        // Corrupts the CRC-32 of `a.txt` declared in the central directory.
+        bytes[central_directory + 16] ^= 0xFF;
+
+        assert!(jar_from_bytes(&bytes, JarOptionBuilder::default()).is_err());
+
+        for option in [JarOptionBuilder::builder().skip_corrupt(), JarOptionBuilder::builder().skip_corrupt().parallel(2)] {
+            let jar = jar_from_bytes(&bytes, option.build()).unwrap();
+
+            assert_eq!(jar.files.keys().collect::<Vec<_>>(), vec!["b.txt"]);
+            assert_eq!(jar.failures().iter().map(|(file_path, _)| file_path.as_str()).collect::<Vec<_>>(), vec!["a.txt"]);
+        }
+    }
+
+    #[test]
+    fn test_ordered_entries() {
+        let entries: [(&str, &[u8]); 4] = [("META-INF/MANIFEST.MF", b""), ("z.txt", b"z"), ("a.txt", b"a"), ("m.txt", b"m")];
+
+        for option in [JarOptionBuilder::builder(), JarOptionBuilder::builder().parallel(2)] {
+            let jar = jar_from_bytes(&jar_bytes(&entries), option.build()).unwrap();
+            let names = jar.ordered_entries().map(|(entry, _)| entry.name.as_str()).collect::<Vec<_>>();
+
+            assert_eq!(names, vec!["META-INF/MANIFEST.MF", "z.txt", "a.txt", "m.txt"]);
+        }
+    }
+
+    #[test]
+    fn test_with_manifest() {
+        let option = || JarOptionBuilder::builder().keep_meta_info().build();
+        let bytes = jar_bytes(&[("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nMain-Class: Main\r\n"), ("Main.class", b"main")]);
+        let jar = jar_from_bytes(&bytes, option()).unwrap();
+        let mut manifest = jar.manifest().unwrap().unwrap();
+
+        manifest.set_main_attribute("Implementation-Version", "1.2.3");
+
+        let jar = jar_from_bytes(&jar.with_manifest(manifest.clone()).write_zip(Cursor::new(Vec::new())).unwrap().into_inner(), option()).unwrap();
+
+        assert_eq!(jar.manifest().unwrap(), Some(manifest.clone()));
+        assert_eq!(jar.entry("META-INF/MANIFEST.MF").unwrap().crc32, crc32fast::hash(&manifest.to_bytes()));
+
+        let jar = jar_from_bytes(&jar_bytes(&[("META-INF/", b""), ("Main.class", b"main")]), JarOptionBuilder::builder().include_dirs().build()).unwrap();
+        let names = jar.with_manifest(manifest).ordered_entries().map(|(entry, _)| entry.name.clone()).collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["META-INF/", "META-INF/MANIFEST.MF", "Main.class"]);
+    }
+
+    #[test]
+    fn test_by_package() {
+        let bytes = jar_bytes(&[
+            ("Main.class", b"main"),
+            ("java/lang/Object.class", b"object"),
+            ("java/lang/reflect/", b""),
+            ("java/lang/reflect/Method.class", b"method"),
+            ("java/lang/String.class", b"string"),
+            ("java/language/Word.class", b"word"),
+        ]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().include_dirs().build()).unwrap();
+        let names = |packages: HashMap<String, Vec<&JarEntry>>| {
+            let mut names = packages.into_iter()
+                .map(|(package, entries)| (package, entries.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+
+            names.sort();
+            names.into_iter().map(|(package, entries)| format!("{}: {}", package, entries.join(" "))).collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(jar.by_package()), vec![
+            ": Main.class",
+            "java/lang: java/lang/Object.class java/lang/String.class",
+            "java/lang/reflect: java/lang/reflect/Method.class",
+            "java/language: java/language/Word.class",
+        ]);
+        assert_eq!(names(jar.packages_matching("java/lang/")), vec![
+            "java/lang: java/lang/Object.class java/lang/String.class",
+            "java/lang/reflect: java/lang/reflect/Method.class",
+        ]);
+        assert!(jar.packages_matching("javax").is_empty());
+    }
+
+    #[test]
+    fn test_comment_and_extra_fields() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+        writer.set_comment("built-by=ci #42");
+        writer.start_file_with_extra_data("a.txt", FileOptions::default()).unwrap();
+        writer.write_all(&[0x42, 0x42, 3, 0, b'a', b'b', b'c']).unwrap();
+        writer.end_extra_data().unwrap();
+        writer.write_all(b"a").unwrap();
+
+        let bytes = writer.finish().unwrap().into_inner();
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap();
+
+        assert_eq!(jar.comment(), b"built-by=ci #42");
+        assert_eq!(jar.entry("a.txt").unwrap().extra_fields().collect::<Vec<_>>(), vec![ExtraField::Unknown { id: 0x4242, data: b"abc" }]);
+        assert_eq!(jar_from_bytes(&jar.write_zip(Cursor::new(Vec::new())).unwrap().into_inner(), JarOptionBuilder::default()).unwrap().comment(), jar.comment());
+        assert_eq!(crate::jar_from_pipe(bytes.as_slice(), JarOptionBuilder::default()).unwrap().entry("a.txt").unwrap().extra, jar.entry("a.txt").unwrap().extra);
+    }
+
+    #[test]
+    fn test_entry_metadata() {
+        let bytes = jar_bytes(&[("java/lang/Object.class", b"object")]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap();
+        let entry = jar.entry("java/lang/Object.class").unwrap();
+
+        assert_eq!(entry.size, 6);
+        assert_eq!(entry.crc32, 0xA8AD_ABEC);
+        assert_eq!(entry.compression, CompressionMethod::Deflated);
+        assert!(entry.last_modified.year >= 1980);
+        assert_eq!(jar.entries().map(|(entry, content)| (entry.name.as_str(), content)).collect::<Vec<_>>(), vec![
+            ("java/lang/Object.class", b"object".as_slice()),
+        ]);
+    }
+
+    #[test]
+    fn test_zip64_extra_fields() {
+        let jar = jar_from_bytes(&zip64_jar_bytes(&[("a.txt", b"aaaa"), ("data/b.bin", b"bb")]), JarOptionBuilder::default()).unwrap();
+        let entry = jar.entry("data/b.bin").unwrap();
+
+        assert_eq!(jar.files["a.txt"], b"aaaa");
+        assert_eq!(jar.files["data/b.bin"], b"bb");
+        assert_eq!((entry.size, entry.compressed_size), (2, 2));
+    }
+
+    #[test]
+    fn test_zip64_entry_count() {
+        let mut writer = JarWriter::new(Cursor::new(Vec::new())).compression(CompressionMethod::Stored);
+
+        for i in 0..70_000 {
+            writer.write_entry(&format!("{}.txt", i), b"").unwrap();
+        }
+
+        let bytes = writer.finish().unwrap().into_inner();
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().ext("txt").build()).unwrap();
+
+        assert_eq!(jar.files.len(), 70_000);
+        assert!(jar.files.contains_key("69999.txt"));
+    }
+
+    #[test]
+    fn test_include_dirs() {
+        let bytes = jar_bytes(&[("META-INF/MANIFEST.MF", b""), ("empty/", b""), ("a/", b""), ("a/A.class", b"a")]);
+
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap();
+        assert!(!jar.files.contains_key("empty/"));
+
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().include_dirs().build()).unwrap();
+        let dirs = jar.ordered_entries().filter(|(entry, _)| entry.is_dir()).map(|(entry, _)| entry.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(dirs, vec!["empty/", "a/"]);
+        assert_eq!(jar.files["empty/"], b"");
+
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().include_dirs().ext("class").build()).unwrap();
+        assert_eq!(jar.files.keys().collect::<Vec<_>>(), vec!["a/A.class"]);
+    }
 }
@@ -0,0 +1,118 @@
+//! Resource limits guarding against zip bombs when extracting untrusted jars.
+
+use std::io::Read;
+
+use crate::{JarEntry, JarError};
+
+/// Limits configured with [crate::JarOptionBuilder]. Sizes declared in the central directory are
+/// checked before an entry is read, and when any limit is set, entries are additionally cut off at
+/// their declared size so a lying header cannot inflate the output.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub(crate) struct Limits {
+    pub(crate) max_total_size: Option<u64>,
+    pub(crate) max_entry_size: Option<u64>,
+    pub(crate) max_entries: Option<usize>,
+    pub(crate) max_compression_ratio: Option<u64>,
+}
+
+impl Limits {
+    pub(crate) fn is_set(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// Checks the `count`-th matching entry against the limits, with `total_size` being the declared
+    /// size of all matching entries so far, this entry included.
+    pub(crate) fn check(&self, entry: &JarEntry, count: usize, total_size: u64) -> Result<(), JarError> {
+        if self.max_entries.is_some_and(|max| count > max) {
+            return Err(limit_error(format!("more than {} entries", count - 1)));
+        }
+
+        if self.max_entry_size.is_some_and(|max| entry.size > max) {
+            return Err(limit_error(format!("entry `{}` has {} bytes", entry.name, entry.size)));
+        }
+
+        if self.max_total_size.is_some_and(|max| total_size > max) {
+            return Err(limit_error(format!("entries have more than {} bytes in total", total_size - entry.size)));
+        }
+
+        if let Some(max) = self.max_compression_ratio {
+            if entry.size > entry.compressed_size.max(1).saturating_mul(max) {
+                return Err(limit_error(format!(
+                    "entry `{}` expands from {} to {} bytes",
+                    entry.name, entry.compressed_size, entry.size,
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Upper bound of buffers allocated up front from declared entry sizes, since a forged central
+/// directory can declare sizes far beyond the actual content.
+const MAX_PREALLOCATION: u64 = 16 * 1024 * 1024;
+
+/// Allocates a buffer for an entry's content with its declared size reserved, so reading does not
+/// repeatedly grow the buffer.
+pub(crate) fn content_buffer(entry: &JarEntry) -> Vec<u8> {
+    Vec::with_capacity(entry.size.min(MAX_PREALLOCATION) as usize)
+}
+
+/// Reads an entry's content, failing if it exceeds its declared size when `capped` is set.
+pub(crate) fn read_content<R>(reader: &mut R, entry: &JarEntry, capped: bool) -> Result<Vec<u8>, JarError> where R: Read {
+    let mut content = content_buffer(entry);
+
+    if capped {
+        reader.take(entry.size + 1).read_to_end(&mut content)?;
+        check_declared_size(entry, content.len() as u64)?;
+    } else {
+        reader.read_to_end(&mut content)?;
+    }
+
+    Ok(content)
+}
+
+pub(crate) fn check_declared_size(entry: &JarEntry, read: u64) -> Result<(), JarError> {
+    if read > entry.size {
+        Err(limit_error(format!("entry `{}` is larger than its declared {} bytes", entry.name, entry.size)))
+    } else {
+        Ok(())
+    }
+}
+
+fn limit_error(message: String) -> JarError {
+    JarError::LimitExceeded(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, JarError, JarOptionBuilder};
+
+    #[test]
+    fn test_limits() {
+        let bytes = jar_bytes(&[("a.txt", b"abc"), ("zeros.bin", &[0; 64 * 1024])]);
+        let exceeds = |builder: JarOptionBuilder| {
+            matches!(jar_from_bytes(&bytes, builder.build()), Err(JarError::LimitExceeded(_)))
+        };
+
+        assert!(exceeds(JarOptionBuilder::builder().max_entries(1)));
+        assert!(exceeds(JarOptionBuilder::builder().max_entry_size(1024)));
+        assert!(exceeds(JarOptionBuilder::builder().max_total_size(64 * 1024)));
+        assert!(exceeds(JarOptionBuilder::builder().max_compression_ratio(100)));
+        assert!(!exceeds(JarOptionBuilder::builder().ext("txt").max_entries(1).max_compression_ratio(100)));
+        assert!(!exceeds(JarOptionBuilder::builder().max_total_size(64 * 1024 + 3).parallel(2)));
+    }
+
+    #[test]
+    fn test_understated_size() {
+        let mut bytes = jar_bytes(&[("zeros.bin", &[0; 1024])]);
+        let central_directory = bytes.windows(4).position(|window| window == b"PK\x01\x02").unwrap();
+        // Declares 16 uncompressed bytes in the central directory.
+        bytes[central_directory + 24..central_directory + 28].copy_from_slice(&16u32.to_le_bytes());
+
+        let option = JarOptionBuilder::builder().max_entry_size(1024 * 1024).build();
+
+        assert!(matches!(jar_from_bytes(&bytes, option), Err(JarError::LimitExceeded(_))));
+    }
+}
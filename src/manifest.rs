@@ -0,0 +1,439 @@
+//! Parsing of `META-INF/MANIFEST.MF` based on the
+//! [JAR File Specification](https://docs.oracle.com/en/java/javase/17/docs/specs/jar/jar.html#jar-manifest).
+
+use crate::JarError;
+
+/// Full qualified path of the manifest inside a jar.
+pub const MANIFEST_PATH: &str = "META-INF/MANIFEST.MF";
+
+/// An ordered collection of manifest attributes. Attribute names are matched case-insensitively
+/// as required by the specification, while the original order and spelling are preserved.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Attributes {
+    attributes: Vec<(String, String)>,
+}
+
+impl Attributes {
+    /// Returns the value of attribute `name`, ignoring ASCII case.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Checks whether attribute `name` is present, ignoring ASCII case.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Iterates over all attributes as `(name, value)` pairs in declaration order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attributes.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.attributes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) -> Option<String> {
+        let index = self.attributes.iter().position(|(key, _)| key.eq_ignore_ascii_case(name))?;
+
+        Some(self.attributes.remove(index).1)
+    }
+
+    pub(crate) fn insert(&mut self, name: String, value: String) {
+        match self.attributes.iter_mut().find(|(key, _)| key.eq_ignore_ascii_case(&name)) {
+            Some((_, old_value)) => *old_value = value,
+            None => self.attributes.push((name, value)),
+        }
+    }
+}
+
+/// Structured representation of a jar manifest, consisting of main attributes and per-entry
+/// sections keyed by their `Name` attribute.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Manifest {
+    main_attributes: Attributes,
+    entries: Vec<(String, Attributes)>,
+}
+
+impl Manifest {
+    /// Returns the main section's attributes.
+    pub fn main_attributes(&self) -> &Attributes {
+        &self.main_attributes
+    }
+
+    /// Returns the attributes of per-entry section `name`, the `Name` attribute itself excluded.
+    pub fn entry(&self, name: &str) -> Option<&Attributes> {
+        self.entries.iter()
+            .find(|(entry_name, _)| entry_name == name)
+            .map(|(_, attributes)| attributes)
+    }
+
+    /// Iterates over all per-entry sections as `(name, attributes)` pairs in declaration order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Attributes)> {
+        self.entries.iter().map(|(name, attributes)| (name.as_str(), attributes))
+    }
+
+    /// Serializes the manifest with `CRLF` line endings, wrapping headers longer than 72 bytes
+    /// into continuation lines without splitting multibyte characters.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        for (name, value) in self.main_attributes.iter() {
+            write_header(&mut bytes, name, value);
+        }
+        bytes.extend_from_slice(b"\r\n");
+
+        for (name, attributes) in self.entries() {
+            write_header(&mut bytes, "Name", name);
+            for (name, value) in attributes.iter() {
+                write_header(&mut bytes, name, value);
+            }
+            bytes.extend_from_slice(b"\r\n");
+        }
+
+        bytes
+    }
+
+    /// Sets main attribute `name` to `value`. An existing attribute, matched ignoring ASCII case,
+    /// keeps its position and spelling, while a new one is appended.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let mut manifest = jar.manifest()?.unwrap_or_default();
+    ///
+    /// manifest.set_main_attribute("Implementation-Version", env!("CARGO_PKG_VERSION"));
+    /// let jar = jar.with_manifest(manifest);
+    /// ```
+    pub fn set_main_attribute(&mut self, name: &str, value: &str) {
+        self.main_attributes.insert(name.to_string(), value.to_string());
+    }
+
+    /// Removes main attribute `name`, ignoring ASCII case, returning its value.
+    pub fn remove_main_attribute(&mut self, name: &str) -> Option<String> {
+        self.main_attributes.remove(name)
+    }
+
+    /// Sets attribute `name` of per-entry section `section` to `value`, appending the section if
+    /// absent. Attribute order is preserved as with [Manifest::set_main_attribute].
+    pub fn set_entry_attribute(&mut self, section: &str, name: &str, value: &str) {
+        self.entry_mut(section).insert(name.to_string(), value.to_string());
+    }
+
+    /// Removes per-entry section `name`, returning its attributes.
+    pub fn remove_section(&mut self, name: &str) -> Option<Attributes> {
+        let index = self.entries.iter().position(|(entry_name, _)| entry_name == name)?;
+
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Removes the digest attributes signers add to per-entry sections, e.g. `SHA-256-Digest`,
+    /// dropping sections left empty, so a modified jar whose signature files are removed is not
+    /// mistaken for a signed one. Returns whether any attribute was removed.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let mut manifest = jar.manifest()?.unwrap_or_default();
+    ///
+    /// if manifest.strip_signatures() {
+    ///     jar = jar.with_manifest(manifest);
+    /// }
+    /// ```
+    pub fn strip_signatures(&mut self) -> bool {
+        let mut stripped = false;
+
+        for (_, attributes) in &mut self.entries {
+            let len = attributes.attributes.len();
+
+            attributes.attributes.retain(|(name, _)| !is_digest_attribute(name));
+            stripped |= attributes.attributes.len() != len;
+        }
+
+        if stripped {
+            self.entries.retain(|(_, attributes)| !attributes.is_empty());
+        }
+
+        stripped
+    }
+
+    pub(crate) fn main_attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.main_attributes
+    }
+
+    /// Returns the attributes of per-entry section `name`, appending an empty section if absent.
+    pub(crate) fn entry_mut(&mut self, name: &str) -> &mut Attributes {
+        let index = match self.entries.iter().position(|(entry_name, _)| entry_name == name) {
+            Some(index) => index,
+            None => {
+                self.entries.push((name.to_string(), Attributes::default()));
+                self.entries.len() - 1
+            }
+        };
+
+        &mut self.entries[index].1
+    }
+}
+
+/// Checks whether a per-entry attribute holds a digest added by a signer, e.g. `SHA-256-Digest`.
+fn is_digest_attribute(name: &str) -> bool {
+    let name = name.as_bytes();
+
+    name.len() > b"-Digest".len() && name[name.len() - b"-Digest".len()..].eq_ignore_ascii_case(b"-Digest")
+}
+
+/// Strips signer digests from manifest `content` as [Manifest::strip_signatures] does, returning
+/// `content` unchanged if it has none or cannot be parsed.
+pub(crate) fn strip_manifest_signatures(content: Vec<u8>) -> Vec<u8> {
+    let Ok(mut manifest) = parse_manifest(&content) else {
+        return content;
+    };
+
+    if manifest.strip_signatures() {
+        manifest.to_bytes()
+    } else {
+        content
+    }
+}
+
+/// Maximum length in bytes of a manifest line, line terminator excluded.
+const MAX_LINE_LENGTH: usize = 72;
+
+fn write_header(bytes: &mut Vec<u8>, name: &str, value: &str) {
+    let header = format!("{}: {}", name, value);
+    let mut line_length = 0;
+
+    for char in header.chars() {
+        if line_length + char.len_utf8() > MAX_LINE_LENGTH {
+            bytes.extend_from_slice(b"\r\n ");
+            line_length = 1;
+        }
+
+        let mut buffer = [0; 4];
+        bytes.extend_from_slice(char.encode_utf8(&mut buffer).as_bytes());
+        line_length += char.len_utf8();
+    }
+
+    bytes.extend_from_slice(b"\r\n");
+}
+
+/// Parses manifest content from raw bytes. Lines may be terminated by `CRLF`, `LF` or `CR`, and
+/// continuation lines (starting with a single space) are joined before decoding so multibyte
+/// characters split at the 72-byte line limit are decoded correctly.
+///
+/// # Example
+///
+/// ```rs
+/// let manifest = parse_manifest(b"Manifest-Version: 1.0\r\nMain-Class: Main\r\n")?;
+///
+/// assert_eq!(manifest.main_attributes().get("Main-Class"), Some("Main"));
+/// ```
+pub fn parse_manifest(bytes: &[u8]) -> Result<Manifest, JarError> {
+    let mut manifest = Manifest::default();
+    let mut sections = vec![Vec::new()];
+
+    for (line_number, line) in split_lines(bytes).into_iter().enumerate() {
+        if line.is_empty() {
+            if !sections.last().is_some_and(Vec::is_empty) {
+                sections.push(Vec::new());
+            }
+        } else if let Some(continuation) = line.strip_prefix(b" ") {
+            let header: &mut Vec<u8> = sections.last_mut()
+                .and_then(|section| section.last_mut())
+                .ok_or_else(|| parse_error(format!("line {}: continuation line without a header", line_number + 1)))?;
+
+            header.extend_from_slice(continuation);
+        } else {
+            sections.last_mut().unwrap().push(line.to_vec());
+        }
+    }
+
+    for (index, section) in sections.into_iter().enumerate() {
+        if section.is_empty() {
+            continue;
+        }
+
+        let mut attributes = Attributes::default();
+
+        for header in section {
+            let header = String::from_utf8(header)
+                .map_err(|_| parse_error(format!("section {}: header is not valid UTF-8", index)))?;
+            let (name, value) = header.split_once(':')
+                .ok_or_else(|| parse_error(format!("section {}: header `{}` is missing `:`", index, header)))?;
+
+            attributes.insert(name.to_string(), value.strip_prefix(' ').unwrap_or(value).to_string());
+        }
+
+        if index == 0 {
+            manifest.main_attributes = attributes;
+        } else {
+            let name = attributes.get("Name")
+                .ok_or_else(|| parse_error(format!("section {}: per-entry section is missing `Name`", index)))?
+                .to_string();
+
+            attributes.attributes.retain(|(key, _)| !key.eq_ignore_ascii_case("Name"));
+            manifest.entries.push((name, attributes));
+        }
+    }
+
+    Ok(manifest)
+}
+
+/// Splits raw manifest content into the exact bytes of each section, including the empty line
+/// terminating it, as digested by signature files.
+pub(crate) fn raw_sections(bytes: &[u8]) -> Vec<&[u8]> {
+    let mut sections = Vec::new();
+    let mut section_start = None;
+
+    for (start, line, end) in line_ranges(bytes) {
+        match section_start {
+            None if !line.is_empty() => section_start = Some(start),
+            Some(section) if line.is_empty() => {
+                sections.push(&bytes[section..end]);
+                section_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(section) = section_start {
+        sections.push(&bytes[section..]);
+    }
+
+    sections
+}
+
+fn split_lines(bytes: &[u8]) -> Vec<&[u8]> {
+    line_ranges(bytes).into_iter().map(|(_, line, _)| line).collect()
+}
+
+/// Splits content into lines as `(start, line, end)`, where `line` excludes the line terminator
+/// while `end` is the offset after it.
+fn line_ranges(bytes: &[u8]) -> Vec<(usize, &[u8], usize)> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\r' => {
+                let line = &bytes[start..i];
+                if bytes.get(i + 1) == Some(&b'\n') {
+                    i += 1;
+                }
+                lines.push((start, line, i + 1));
+                start = i + 1;
+            }
+            b'\n' => {
+                lines.push((start, &bytes[start..i], i + 1));
+                start = i + 1;
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    if start < bytes.len() {
+        lines.push((start, &bytes[start..], bytes.len()));
+    }
+
+    lines
+}
+
+fn parse_error(message: String) -> JarError {
+    JarError::ManifestParse(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::manifest::strip_manifest_signatures;
+    use crate::{parse_manifest, JarError};
+
+    #[test]
+    fn test_parse_sections_and_continuations() {
+        let manifest = parse_manifest(
+            "Manifest-Version: 1.0\r\nImplementation-Title: a very long \r\n title\r\n\r\n\
+            Name: java/lang/\r\nSealed: true\r\n\r\nName: caf\u{e9}\r\n Name.class\r\nSHA-256-Digest: abc\r\n"
+                .as_bytes(),
+        ).unwrap();
+
+        assert_eq!(manifest.main_attributes().get("manifest-version"), Some("1.0"));
+        assert_eq!(manifest.main_attributes().get("Implementation-Title"), Some("a very long title"));
+        assert_eq!(manifest.entry("java/lang/").and_then(|attrs| attrs.get("Sealed")), Some("true"));
+        assert_eq!(manifest.entry("caf\u{e9}Name.class").map(|attrs| attrs.len()), Some(1));
+    }
+
+    #[test]
+    fn test_to_bytes_round_trip() {
+        let long_value = "\u{e9}".repeat(80);
+        let content = format!("Manifest-Version: 1.0\r\nX-Long: {}\r\n\r\nName: a/B.class\r\nX: y\r\n", long_value);
+        let manifest = parse_manifest(content.as_bytes()).unwrap();
+        let bytes = manifest.to_bytes();
+
+        assert!(bytes.split(|byte| *byte == b'\n').all(|line| line.len() <= 73));
+        assert_eq!(parse_manifest(&bytes).unwrap(), manifest);
+    }
+
+    #[test]
+    fn test_edit() {
+        let mut manifest = parse_manifest(b"Manifest-Version: 1.0\r\nMain-Class: Main\r\n\r\nName: a/A.class\r\nX: y\r\n\r\nName: b/\r\nSealed: true\r\n").unwrap();
+        let version = "1.0.0-".to_string() + &"x".repeat(80);
+
+        manifest.set_main_attribute("manifest-version", "1.1");
+        manifest.set_main_attribute("Implementation-Version", &version);
+        manifest.set_entry_attribute("a/A.class", "X", "z");
+        manifest.set_entry_attribute("c/", "Sealed", "false");
+
+        assert_eq!(manifest.remove_main_attribute("main-class"), Some("Main".to_string()));
+        assert_eq!(manifest.remove_section("b/").map(|attrs| attrs.len()), Some(1));
+        assert_eq!(manifest.remove_section("b/"), None);
+
+        let attributes = manifest.main_attributes().iter().collect::<Vec<_>>();
+        assert_eq!(attributes, vec![("Manifest-Version", "1.1"), ("Implementation-Version", version.as_str())]);
+        assert_eq!(manifest.entries().map(|(name, _)| name).collect::<Vec<_>>(), vec!["a/A.class", "c/"]);
+
+        let bytes = manifest.to_bytes();
+        assert!(bytes.split(|byte| *byte == b'\n').all(|line| line.len() <= 73));
+        assert_eq!(parse_manifest(&bytes).unwrap(), manifest);
+    }
+
+    #[test]
+    fn test_strip_signatures() {
+        let bytes = b"Manifest-Version: 1.0\r\n\r\nName: a/A.class\r\nSHA-256-Digest: YQ==\r\n\r\nName: b/B.class\r\nsha1-digest: Yg==\r\nX: y\r\n\r\n";
+        let mut manifest = parse_manifest(bytes).unwrap();
+
+        assert!(manifest.strip_signatures());
+        assert!(!manifest.strip_signatures());
+        assert_eq!(manifest.entries().map(|(name, attributes)| (name, attributes.iter().collect::<Vec<_>>())).collect::<Vec<_>>(), vec![
+            ("b/B.class", vec![("X", "y")]),
+        ]);
+        assert_eq!(strip_manifest_signatures(bytes.to_vec()), manifest.to_bytes());
+        assert_eq!(strip_manifest_signatures(b"Manifest-Version: 1.0\n".to_vec()), b"Manifest-Version: 1.0\n");
+    }
+
+    #[test]
+    fn test_parse_malformed_header() {
+        assert!(matches!(parse_manifest(b"Manifest-Version 1.0\n"), Err(JarError::ManifestParse(_))));
+        assert!(matches!(parse_manifest(b" leading continuation\n"), Err(JarError::ManifestParse(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let manifest = parse_manifest(b"Manifest-Version: 1.0\r\n\r\nName: a/A.class\r\nSHA-256-Digest: abc\r\n").unwrap();
+        let json = serde_json::to_string(&manifest).unwrap();
+
+        assert_eq!(json, r#"{"main_attributes":[["Manifest-Version","1.0"]],"entries":[["a/A.class",[["SHA-256-Digest","abc"]]]]}"#);
+        assert_eq!(serde_json::from_str::<crate::Manifest>(&json).unwrap(), manifest);
+    }
+}
@@ -0,0 +1,133 @@
+//! Maven coordinates embedded by Maven builds under `META-INF/maven`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{parse_properties, Jar};
+
+/// Directory of Maven build metadata inside a jar.
+pub const MAVEN_PREFIX: &str = "META-INF/maven/";
+
+/// Coordinates identifying a Maven artifact.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MavenCoordinates {
+    pub group_id: String,
+    pub artifact_id: String,
+    /// Version of the artifact, [None] if neither `pom.properties` nor `pom.xml` declares it.
+    pub version: Option<String>,
+}
+
+impl Jar {
+    /// Returns coordinates of Maven artifacts packaged in the jar, sorted, parsed from
+    /// `META-INF/maven/<group>/<artifact>/pom.properties`. Artifacts without `pom.properties` fall
+    /// back to the directory names and the version in `pom.xml`. Shaded jars usually contain
+    /// coordinates of each bundled artifact. `META-INF` must be extracted, e.g. with
+    /// [crate::JarOptionBuilder::keep_meta_info].
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("guava.jar", JarOptionBuilder::builder().keep_meta_info().build())?;
+    ///
+    /// for coordinates in jar.maven_coordinates() {
+    ///     // "com.google.guava", "guava", Some("32.1.2-jre")
+    /// }
+    /// ```
+    pub fn maven_coordinates(&self) -> Vec<MavenCoordinates> {
+        maven_coordinates(&self.files)
+    }
+}
+
+/// Reads coordinates of Maven artifacts from the build metadata among `files`, see
+/// [Jar::maven_coordinates].
+pub(crate) fn maven_coordinates(files: &HashMap<String, Vec<u8>>) -> Vec<MavenCoordinates> {
+    let mut artifacts = BTreeMap::new();
+
+    for path in files.keys() {
+        let Some(relative) = path.strip_prefix(MAVEN_PREFIX) else {
+            continue;
+        };
+        let mut components = relative.splitn(3, '/');
+
+        if let (Some(group_id), Some(artifact_id), Some("pom.properties" | "pom.xml")) =
+            (components.next(), components.next(), components.next()) {
+            artifacts.insert((group_id, artifact_id), format!("{}{}/{}/", MAVEN_PREFIX, group_id, artifact_id));
+        }
+    }
+
+    artifacts.into_iter()
+        .map(|((group_id, artifact_id), dir)| {
+            let properties = files.get(&format!("{}pom.properties", dir))
+                .map(|content| parse_properties(content))
+                .unwrap_or_default();
+            let version = properties.get("version").cloned()
+                .or_else(|| pom_version(&String::from_utf8_lossy(files.get(&format!("{}pom.xml", dir))?)));
+
+            MavenCoordinates {
+                group_id: properties.get("groupId").cloned().unwrap_or_else(|| group_id.to_string()),
+                artifact_id: properties.get("artifactId").cloned().unwrap_or_else(|| artifact_id.to_string()),
+                version,
+            }
+        })
+        .collect()
+}
+
+/// Finds the project version in a `pom.xml`, inheriting the parent's version if absent.
+fn pom_version(pom: &str) -> Option<String> {
+    let parent = element(pom, "parent");
+    let mut project = pom.to_string();
+
+    // Versions of the parent, dependencies and plugins are not the project's own version.
+    for name in ["parent", "dependencies", "dependencyManagement", "build", "profiles"] {
+        if let Some((start, end)) = element_range(&project, name) {
+            project.replace_range(start..end, "");
+        }
+    }
+
+    element(&project, "version")
+        .or_else(|| element(parent?, "version"))
+        .map(str::to_string)
+        .filter(|version| !version.is_empty())
+}
+
+fn element<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let (start, end) = element_range(xml, name)?;
+
+    Some(xml[start + name.len() + 2..end - name.len() - 3].trim())
+}
+
+/// Returns the byte range of the first element named `name`, including its tags.
+fn element_range(xml: &str, name: &str) -> Option<(usize, usize)> {
+    let start = xml.find(&format!("<{}>", name))?;
+    let close = format!("</{}>", name);
+    let end = start + xml[start..].find(&close)? + close.len();
+
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, JarOptionBuilder, MavenCoordinates};
+
+    #[test]
+    fn test_maven_coordinates() {
+        let bytes = jar_bytes(&[
+            ("META-INF/maven/com.example/app/pom.properties", b"#Generated by Maven\nartifactId=app\ngroupId=com.example\nversion=1.2.3\n"),
+            ("META-INF/maven/com.example/app/pom.xml", b"<project/>"),
+            ("META-INF/maven/org.shaded/lib/pom.xml", b"<project>\n<parent><version>9</version></parent>\n<artifactId>lib</artifactId>\n<dependencies><dependency><version>1</version></dependency></dependencies>\n<version>2.0</version>\n</project>"),
+            ("META-INF/maven/org.shaded/child/pom.xml", b"<project><parent><version>9</version></parent></project>"),
+        ]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().keep_meta_info().build()).unwrap();
+        let coordinates = |group_id: &str, artifact_id: &str, version: &str| MavenCoordinates {
+            group_id: group_id.to_string(),
+            artifact_id: artifact_id.to_string(),
+            version: Some(version.to_string()),
+        };
+
+        assert_eq!(jar.maven_coordinates(), vec![
+            coordinates("com.example", "app", "1.2.3"),
+            coordinates("org.shaded", "child", "9"),
+            coordinates("org.shaded", "lib", "2.0"),
+        ]);
+    }
+}
@@ -0,0 +1,289 @@
+//! Merging multiple jars into one, e.g. to build an uber-jar.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use crate::manifest::strip_manifest_signatures;
+use crate::pattern::Pattern;
+use crate::relocate::write_relocated;
+use crate::signature::is_signature_file;
+use crate::{
+    jar_stream, DateTime, JarEntry, JarError, JarOption, JarOptionBuilder, JarStream, Relocator, ServiceDescriptorMerger, MANIFEST_PATH,
+    SERVICES_PREFIX,
+};
+
+/// How to resolve an entry found in more than one jar being merged.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MergeStrategy {
+    /// Keeps the entry of the first jar containing it.
+    FirstWins,
+    /// Keeps the entry of the last jar containing it.
+    LastWins,
+    /// Fails with [JarError::DuplicateEntry] unless all duplicates have identical content.
+    Error,
+    /// Concatenates contents of all duplicates in jar order, separated by line breaks.
+    Concatenate,
+    /// Merges `META-INF/services` provider-configuration files with the [ServiceDescriptorMerger]
+    /// of [MergeOptions::service_merger], which also checks files found in a single jar.
+    Services,
+}
+
+/// Options of [merge]. By default duplicates are resolved with [MergeStrategy::FirstWins], except
+/// `META-INF/services/*` which are merged with [MergeStrategy::Services].
+///
+/// # Example
+///
+/// ```rs
+/// let options = MergeOptions::default()
+///     .strategy(MergeStrategy::Error)
+///     .strategy_for("META-INF/spring.*", MergeStrategy::Concatenate);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MergeOptions {
+    option: JarOption,
+    strategy: MergeStrategy,
+    rules: Vec<(Pattern, MergeStrategy)>,
+    relocator: Relocator,
+    service_merger: ServiceDescriptorMerger,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            option: JarOptionBuilder::default(),
+            strategy: MergeStrategy::FirstWins,
+            rules: vec![(Pattern::glob("META-INF/services/*"), MergeStrategy::Services)],
+            relocator: Relocator::default(),
+            service_merger: ServiceDescriptorMerger::default(),
+        }
+    }
+}
+
+impl MergeOptions {
+    /// Only merges entries passing the extraction rules of `option`.
+    pub fn option(mut self, option: JarOption) -> Self {
+        self.option = option;
+        self
+    }
+
+    /// Sets the strategy for duplicates not matching any rule added by [MergeOptions::strategy_for].
+    pub fn strategy(mut self, strategy: MergeStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Resolves duplicates whose full qualified path matches `glob` with `strategy`, see
+    /// [JarOptionBuilder::target_glob] for the glob syntax. Rules added later take precedence.
+    pub fn strategy_for(mut self, glob: &str, strategy: MergeStrategy) -> Self {
+        self.rules.push((Pattern::glob(glob), strategy));
+        self
+    }
+
+    /// Relocates merged entries with `relocator`, e.g. to shade dependencies into the merged jar.
+    /// Duplicates are detected and resolved by their relocated paths.
+    pub fn relocator(mut self, relocator: Relocator) -> Self {
+        self.relocator = relocator;
+        self
+    }
+
+    /// Merges provider-configuration files with `merger`, e.g. to strip comments or report
+    /// conflicting providers.
+    pub fn service_merger(mut self, merger: ServiceDescriptorMerger) -> Self {
+        self.service_merger = merger;
+        self
+    }
+
+    fn strategy_of(&self, name: &str) -> MergeStrategy {
+        self.rules.iter()
+            .rev()
+            .find(|(pattern, _)| pattern.is_match(name))
+            .map(|(_, strategy)| *strategy)
+            .unwrap_or(self.strategy)
+    }
+}
+
+/// Merges the jars at `paths` into a new jar at `dest`, resolving entries found in more than one
+/// jar by [MergeOptions]. Entries are written in order of first appearance, and entries taken from a
+/// single jar are copied in compressed form. Signatures are removed as by
+/// [JarOptionBuilder::strip_signatures], since the merged jar's content no longer matches them. Returns the names of all entries found in more than one jar.
+///
+/// # Example
+///
+/// ```rs
+/// let duplicates = merge(&["app.jar", "lib/dependency.jar"], "app-all.jar", MergeOptions::default())?;
+/// ```
+pub fn merge<P, D>(paths: &[P], dest: D, options: MergeOptions) -> Result<Vec<String>, JarError>
+    where P: AsRef<Path>, D: AsRef<Path> {
+    let mut streams = paths.iter()
+        .map(|path| jar_stream(path, options.option.clone()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut names = Vec::<String>::new();
+    let mut sources = HashMap::<String, Vec<(usize, usize, JarEntry)>>::new();
+
+    for (source, stream) in streams.iter_mut().enumerate() {
+        while let Some(next) = stream.next_match() {
+            let (index, entry) = next?;
+
+            if is_signature_file(&entry.name) && entry.name != MANIFEST_PATH {
+                continue;
+            }
+
+            let name = options.relocator.relocate_path(&entry.name).unwrap_or_else(|| entry.name.clone());
+            let duplicates = sources.entry(name.clone()).or_default();
+            if duplicates.is_empty() {
+                names.push(name);
+            }
+            duplicates.push((source, index, entry));
+        }
+    }
+
+    let mut writer = ZipWriter::new(File::create(dest)?);
+    let mut duplicated = Vec::new();
+    let merged_names = names.iter().map(String::as_str).collect::<HashSet<_>>();
+
+    for name in &names {
+        let name = name.clone();
+        let duplicates = &sources[&name];
+        let chosen = match options.strategy_of(&name) {
+            MergeStrategy::Services if name.starts_with(SERVICES_PREFIX) => {
+                let mut descriptors = Vec::with_capacity(duplicates.len());
+
+                for (source, index, entry) in duplicates {
+                    let mut content = Vec::new();
+
+                    streams[*source].decode(*index, entry)?.read_to_end(&mut content)?;
+                    descriptors.push((paths[*source].as_ref(), content));
+                }
+
+                let service = &name[SERVICES_PREFIX.len()..];
+                let content = options.service_merger.merge(service, &descriptors);
+                let content = options.relocator.relocate_content(&duplicates[0].2.name, &name, &content)?.unwrap_or(content);
+
+                options.service_merger.check_providers(service, &content, &merged_names);
+                writer.start_file(name.as_str(), FileOptions::default().last_modified_time(DateTime::now().to_zip()))?;
+                writer.write_all(&content)?;
+                if duplicates.len() > 1 {
+                    duplicated.push(name);
+                }
+                continue;
+            }
+            _ if duplicates.len() == 1 => &duplicates[0],
+            MergeStrategy::FirstWins | MergeStrategy::Services => &duplicates[0],
+            MergeStrategy::LastWins => &duplicates[duplicates.len() - 1],
+            MergeStrategy::Error => {
+                let (_, _, first) = &duplicates[0];
+
+                if duplicates.iter().any(|(_, _, entry)| entry.crc32 != first.crc32 || entry.size != first.size) {
+                    return Err(JarError::DuplicateEntry(name));
+                }
+
+                &duplicates[0]
+            }
+            MergeStrategy::Concatenate => {
+                let content = concatenate(&mut streams, duplicates)?;
+                let content = options.relocator.relocate_content(&duplicates[0].2.name, &name, &content)?.unwrap_or(content);
+
+                writer.start_file(name.as_str(), FileOptions::default().last_modified_time(DateTime::now().to_zip()))?;
+                writer.write_all(&content)?;
+                duplicated.push(name);
+                continue;
+            }
+        };
+        let (source, index, entry) = chosen;
+
+        if name == MANIFEST_PATH {
+            let mut content = Vec::new();
+
+            streams[*source].decode(*index, entry)?.read_to_end(&mut content)?;
+
+            let content = options.relocator.relocate_content(&entry.name, &name, &content)?.unwrap_or(content);
+
+            writer.start_file(name.as_str(), FileOptions::default().last_modified_time(DateTime::now().to_zip()))?;
+            writer.write_all(&strip_manifest_signatures(content))?;
+        } else {
+            write_relocated(&mut writer, &mut streams[*source], *index, entry, &options.relocator)?;
+        }
+        if duplicates.len() > 1 {
+            duplicated.push(name);
+        }
+    }
+
+    writer.finish()?;
+    Ok(duplicated)
+}
+
+fn concatenate(streams: &mut [JarStream<File>], duplicates: &[(usize, usize, JarEntry)]) -> Result<Vec<u8>, JarError> {
+    let mut content = Vec::new();
+
+    for (source, index, entry) in duplicates {
+        if !content.is_empty() && !content.ends_with(b"\n") {
+            content.push(b'\n');
+        }
+
+        streams[*source].decode(*index, entry)?.read_to_end(&mut content)?;
+    }
+
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::{jar_file, temp_path};
+    use crate::{jar, merge, JarError, JarOptionBuilder, MergeOptions, MergeStrategy, Relocator};
+
+    #[test]
+    fn test_merge() {
+        let first = jar_file(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n\r\nName: a/A.class\r\nSHA-256-Digest: YQ==\r\n"),
+            ("META-INF/SIGNER.SF", b"signature"),
+            ("META-INF/services/a.Service", b"a.FirstImpl"),
+            ("a/A.class", b"first"),
+        ]);
+        let second = jar_file(&[
+            ("META-INF/services/a.Service", b"a.SecondImpl\n"),
+            ("a/A.class", b"second"),
+            ("b/B.class", b"b"),
+        ]);
+        let dest = temp_path("merged.jar");
+
+        let duplicates = merge(&[&first, &second], &dest, MergeOptions::default()).unwrap();
+        let merged = jar(&dest, JarOptionBuilder::default()).unwrap();
+        let mut paths = merged.files.keys().collect::<Vec<_>>();
+        paths.sort();
+
+        assert_eq!(duplicates, vec!["META-INF/services/a.Service", "a/A.class"]);
+        assert_eq!(paths, vec!["META-INF/MANIFEST.MF", "META-INF/services/a.Service", "a/A.class", "b/B.class"]);
+        assert_eq!(merged.files["a/A.class"], b"first");
+        assert_eq!(merged.files["META-INF/MANIFEST.MF"], b"Manifest-Version: 1.0\r\n\r\n");
+        assert_eq!(merged.services()["a.Service"], vec!["a.FirstImpl", "a.SecondImpl"]);
+
+        merge(&[&first, &second], &dest, MergeOptions::default().strategy(MergeStrategy::LastWins)).unwrap();
+        assert_eq!(jar(&dest, JarOptionBuilder::default()).unwrap().files["a/A.class"], b"second");
+
+        let result = merge(&[&first, &second], &dest, MergeOptions::default().strategy(MergeStrategy::Error));
+        assert!(matches!(result, Err(JarError::DuplicateEntry(name)) if name == "a/A.class"));
+        assert!(merge(&[&first, &first], &dest, MergeOptions::default().strategy(MergeStrategy::Error)).is_ok());
+    }
+
+    #[test]
+    fn test_merge_relocated() {
+        let first = jar_file(&[("app/main.txt", b"main")]);
+        let second = jar_file(&[
+            ("META-INF/services/com.lib.Service", b"com.lib.Impl\n"),
+            ("com/lib/data.txt", b"data"),
+        ]);
+        let dest = temp_path("merged-relocated.jar");
+        let options = MergeOptions::default().relocator(Relocator::new().relocate("com.lib", "shaded.lib"));
+
+        merge(&[&first, &second], &dest, options).unwrap();
+
+        let merged = jar(&dest, JarOptionBuilder::builder().keep_meta_info().ext("class").ext("txt").build()).unwrap();
+        assert_eq!(merged.files["shaded/lib/data.txt"], b"data");
+        assert_eq!(merged.services()["shaded.lib.Service"], vec!["shaded.lib.Impl"]);
+    }
+}
@@ -0,0 +1,296 @@
+//! Memory-mapped jars whose stored entries are borrowed from the mapping instead of copied, built
+//! with feature `mmap` on unix.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Cursor;
+use std::ops::Range;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr::NonNull;
+
+use zip::result::ZipError;
+
+use crate::limits::read_content;
+use crate::option::map_content;
+use crate::{CompressionMethod, JarEntry, JarError, JarOption, JarStream};
+
+/// A read-only private mapping of a whole file.
+struct Mmap {
+    ptr: NonNull<libc::c_void>,
+    len: usize,
+}
+
+// The mapping is read-only and never remapped, so it can be shared like a `&[u8]`.
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+impl Mmap {
+    unsafe fn map(file: &File) -> Result<Self, JarError> {
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| JarError::LimitExceeded("file does not fit in the address space".to_string()))?;
+
+        if len == 0 {
+            return Err(JarError::Zip(ZipError::InvalidArchive("empty file")));
+        }
+
+        let ptr = libc::mmap(std::ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0);
+
+        if ptr == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        Ok(Self {
+            ptr: NonNull::new(ptr).expect("mmap never returns null on success"),
+            len,
+        })
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr() as *const u8, self.len) }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr.as_ptr(), self.len);
+        }
+    }
+}
+
+/// Content of an entry in a [MappedJar].
+enum MappedContent {
+    /// Range of a stored entry's content in the mapping.
+    Mapped(Range<usize>),
+    /// Decompressed content of a compressed entry.
+    Owned(Vec<u8>),
+}
+
+/// A memory-mapped jar. Stored entries, common for large resources, are slices of the mapped file
+/// so their content is neither copied nor held on the heap; compressed entries are decompressed
+/// when the jar is opened, like [crate::jar]. Content mapped with
+/// [crate::JarOptionBuilder::map_content] and entries of a method with a registered decompressor
+/// are held on the heap as well.
+///
+/// # Example
+///
+/// ```rs
+/// let jar = unsafe { jar_mmap("resources.jar", JarOptionBuilder::default())? };
+///
+/// for (entry, content) in jar.entries() {
+///     // ...
+/// }
+/// ```
+pub struct MappedJar {
+    map: Mmap,
+    entries: Vec<JarEntry>,
+    contents: HashMap<String, (usize, MappedContent)>,
+    failures: Vec<(String, JarError)>,
+}
+
+impl MappedJar {
+    /// Returns the content of entry `name`, borrowed from the mapping if the entry is stored.
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        self.contents.get(name).map(|(_, content)| self.content(content))
+    }
+
+    /// Checks whether entry `name` is borrowed from the mapping rather than decompressed.
+    pub fn is_mapped(&self, name: &str) -> bool {
+        matches!(self.contents.get(name), Some((_, MappedContent::Mapped(_))))
+    }
+
+    /// Returns the metadata of entry `name`.
+    pub fn entry(&self, name: &str) -> Option<&JarEntry> {
+        self.contents.get(name).map(|(position, _)| &self.entries[*position])
+    }
+
+    /// Iterates over metadata and content of all entries in central directory order.
+    pub fn entries(&self) -> impl Iterator<Item = (&JarEntry, &[u8])> {
+        self.entries.iter()
+            .filter_map(|entry| Some((entry, self.get(&entry.name)?)))
+    }
+
+    /// Returns entries skipped because of corrupted content along with their errors, which is only
+    /// non-empty with [crate::JarOptionBuilder::skip_corrupt].
+    pub fn failures(&self) -> &[(String, JarError)] {
+        &self.failures
+    }
+
+    /// Returns the number of extracted entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks whether no entry is extracted.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn content<'a>(&'a self, content: &'a MappedContent) -> &'a [u8] {
+        match content {
+            MappedContent::Mapped(range) => &self.map.as_slice()[range.clone()],
+            MappedContent::Owned(content) => content,
+        }
+    }
+}
+
+/// Maps the jar file at `path` into memory. See [crate::jar] for the extraction rules; CRC-32 of
+/// stored entries are validated against the mapping unless disabled by
+/// [crate::JarOptionBuilder::verify_crc].
+///
+/// # Safety
+///
+/// The file must not be modified or truncated while the returned [MappedJar] is alive, since its
+/// stored entries are read directly from the file's pages.
+pub unsafe fn jar_mmap<P>(path: P, option: JarOption) -> Result<MappedJar, JarError> where P: AsRef<Path> {
+    let map = Mmap::map(&File::open(path)?)?;
+    let capped = option.limits.is_set();
+    let verify_crc = !option.skip_crc;
+    let skip_corrupt = option.skip_corrupt;
+    let content_mapper = option.content_mapper.clone();
+    let maps_stored = !option.decompressors.contains_key(&CompressionMethod::Stored.id());
+    let mut entries = Vec::new();
+    let mut contents = HashMap::new();
+    let mut failures = Vec::new();
+    let mut stream = JarStream::new(Cursor::new(map.as_slice()), option)?;
+
+    while let Some(next) = stream.next_match() {
+        let (index, entry) = next?;
+        let content = if maps_stored && entry.compression == CompressionMethod::Stored {
+            mapped_range(&map, &mut stream, index, &entry, verify_crc).map(MappedContent::Mapped)
+        } else {
+            stream.decode(index, &entry)
+                .and_then(|mut content| read_content(&mut content, &entry, capped))
+                .map(MappedContent::Owned)
+        };
+        let content = match content {
+            Ok(content) => content,
+            Err(err) if skip_corrupt && err.is_corrupt() => {
+                failures.push((entry.name, err));
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        let content = match (&content_mapper, content) {
+            (None, content) => content,
+            (Some(_), content) => {
+                let content = match content {
+                    MappedContent::Mapped(range) => map.as_slice()[range].to_vec(),
+                    MappedContent::Owned(content) => content,
+                };
+
+                match map_content(&content_mapper, &entry.name, content) {
+                    Some(content) => MappedContent::Owned(content),
+                    None => continue,
+                }
+            }
+        };
+
+        contents.insert(entry.name.clone(), (entries.len(), content));
+        entries.push(entry);
+    }
+
+    drop(stream);
+
+    Ok(MappedJar {
+        map,
+        entries,
+        contents,
+        failures,
+    })
+}
+
+/// Locates the content of stored entry `entry` in the mapping, validating its CRC-32 if
+/// `verify_crc`.
+fn mapped_range(map: &Mmap, stream: &mut JarStream<Cursor<&[u8]>>, index: usize, entry: &JarEntry, verify_crc: bool) -> Result<Range<usize>, JarError> {
+    let start = stream.archive_mut().by_index_raw(index)?.data_start() as usize;
+    let range = start..start.saturating_add(entry.size as usize);
+    let content = map.as_slice().get(range.clone())
+        .ok_or(JarError::Zip(ZipError::InvalidArchive("stored entry exceeds the archive")))?;
+
+    if verify_crc && crc32fast::hash(content) != entry.crc32 {
+        return Err(JarError::Zip(ZipError::InvalidArchive("invalid checksum")));
+    }
+
+    Ok(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Write};
+
+    use zip::write::FileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    use crate::fixture::temp_path;
+    use crate::{jar_mmap, JarOptionBuilder};
+
+    fn mixed_jar() -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+        writer.start_file("stored.bin", FileOptions::default().compression_method(CompressionMethod::Stored)).unwrap();
+        writer.write_all(b"stored content").unwrap();
+        writer.start_file("deflated.txt", FileOptions::default()).unwrap();
+        writer.write_all(b"deflated content").unwrap();
+
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_jar_mmap() {
+        let path = temp_path("mapped.jar");
+        std::fs::write(&path, mixed_jar()).unwrap();
+
+        let jar = unsafe { jar_mmap(&path, JarOptionBuilder::default()) }.unwrap();
+
+        assert_eq!(jar.get("stored.bin"), Some(b"stored content".as_slice()));
+        assert_eq!(jar.get("deflated.txt"), Some(b"deflated content".as_slice()));
+        assert!(jar.is_mapped("stored.bin"));
+        assert!(!jar.is_mapped("deflated.txt"));
+        assert_eq!(jar.entries().map(|(entry, _)| entry.name.as_str()).collect::<Vec<_>>(), vec!["stored.bin", "deflated.txt"]);
+    }
+
+    #[test]
+    fn test_jar_mmap_crc() {
+        let mut bytes = mixed_jar();
+        let central_directory = bytes.windows(4).position(|window| window == b"PK\x01\x02").unwrap();
+        // Corrupts the CRC-32 of `stored.bin` declared in the central directory.
+        bytes[central_directory + 16] ^= 0xFF;
+        let path = temp_path("corrupt.jar");
+        std::fs::write(&path, bytes).unwrap();
+
+        assert!(unsafe { jar_mmap(&path, JarOptionBuilder::default()) }.is_err());
+        assert!(unsafe { jar_mmap(&path, JarOptionBuilder::builder().verify_crc(false).build()) }.is_ok());
+
+        let jar = unsafe { jar_mmap(&path, JarOptionBuilder::builder().skip_corrupt().build()) }.unwrap();
+        assert_eq!(jar.failures().iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), vec!["stored.bin"]);
+        assert_eq!(jar.get("deflated.txt"), Some(b"deflated content".as_slice()));
+    }
+
+    #[test]
+    fn test_jar_mmap_options() {
+        let path = temp_path("options.jar");
+        std::fs::write(&path, mixed_jar()).unwrap();
+
+        let option = JarOptionBuilder::builder()
+            .map_content(|name, content| (name != "deflated.txt").then(|| content.to_ascii_uppercase()))
+            .build();
+        let jar = unsafe { jar_mmap(&path, option) }.unwrap();
+        assert_eq!(jar.get("stored.bin"), Some(b"STORED CONTENT".as_slice()));
+        assert!(!jar.is_mapped("stored.bin"));
+        assert_eq!(jar.get("deflated.txt"), None);
+
+        let option = JarOptionBuilder::builder()
+            .decompressor(0, |mut raw| {
+                let mut content = Vec::new();
+                raw.read_to_end(&mut content)?;
+                Ok(Box::new(Cursor::new(content.to_ascii_uppercase())))
+            })
+            .verify_crc(false)
+            .build();
+        let jar = unsafe { jar_mmap(&path, option) }.unwrap();
+        assert_eq!(jar.get("stored.bin"), Some(b"STORED CONTENT".as_slice()));
+        assert_eq!(jar.get("deflated.txt"), Some(b"deflated content".as_slice()));
+    }
+}
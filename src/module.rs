@@ -0,0 +1,525 @@
+//! Java Platform Module System descriptors, read from `module-info.class` or derived for automatic
+//! modules.
+
+use std::collections::{BTreeSet, HashMap};
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::classfile::{ClassFile, ClassReader};
+use crate::{Jar, JarError};
+#[cfg(feature = "fs")]
+use crate::{jar, JarOptionBuilder, MANIFEST_PATH};
+
+/// Path of the compiled module declaration inside a modular jar.
+pub const MODULE_INFO_PATH: &str = "module-info.class";
+
+const ACC_OPEN: u16 = 0x0020;
+const ACC_TRANSITIVE: u16 = 0x0020;
+const ACC_STATIC_PHASE: u16 = 0x0040;
+
+/// How a module is defined.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ModuleKind {
+    /// Declared by `module-info.class`.
+    Explicit,
+    /// Named by the `Automatic-Module-Name` manifest attribute or derived from the jar file name.
+    Automatic,
+}
+
+/// A `requires` directive.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Requires {
+    pub name: String,
+    /// `requires transitive`.
+    pub transitive: bool,
+    /// `requires static`.
+    pub static_phase: bool,
+    /// Version of the required module recorded at compile time.
+    pub version: Option<String>,
+}
+
+/// An `exports` or `opens` directive. Package names are in binary form, e.g. `java.lang`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PackageAccess {
+    pub package: String,
+    /// Modules the package is qualified to, empty if unqualified.
+    pub targets: Vec<String>,
+}
+
+/// A `provides` directive.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Provides {
+    pub service: String,
+    pub providers: Vec<String>,
+}
+
+/// Descriptor of a named module. Class and package names are in binary form.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ModuleDescriptor {
+    pub name: String,
+    pub kind: ModuleKind,
+    /// Whether this is an `open module`, which is always true for automatic modules.
+    pub open: bool,
+    pub version: Option<String>,
+    pub requires: Vec<Requires>,
+    pub exports: Vec<PackageAccess>,
+    pub opens: Vec<PackageAccess>,
+    pub uses: Vec<String>,
+    pub provides: Vec<Provides>,
+}
+
+impl Jar {
+    /// Reads the module descriptor from `module-info.class`, falling back to an automatic module
+    /// named by the `Automatic-Module-Name` manifest attribute. Returns [None] when neither exists;
+    /// use [module_descriptor] to also derive the name from the jar's file name.
+    ///
+    /// An automatic module exports and opens all packages containing classes, and provides the
+    /// services declared in `META-INF/services`.
+    pub fn module_descriptor(&self) -> Result<Option<ModuleDescriptor>, JarError> {
+        if let Some(module_info) = self.files.get(MODULE_INFO_PATH) {
+            return parse_module_info(module_info).map(Some);
+        }
+
+        let name = self.manifest()?
+            .and_then(|manifest| manifest.main_attributes().get("Automatic-Module-Name").map(str::to_string));
+
+        Ok(name.map(|name| self.automatic_module(name.trim().to_string(), None)))
+    }
+
+    fn automatic_module(&self, name: String, version: Option<String>) -> ModuleDescriptor {
+        let packages = self.files.keys()
+            .filter(|path| path.ends_with(".class") && !path.starts_with("META-INF/"))
+            .filter_map(|path| path.rsplit_once('/').map(|(package, _)| package.replace('/', ".")))
+            .collect::<BTreeSet<_>>();
+        let mut provides = self.services().into_iter()
+            .map(|(service, providers)| Provides {
+                service,
+                providers,
+            })
+            .collect::<Vec<_>>();
+        provides.sort_by(|a, b| a.service.cmp(&b.service));
+
+        let packages = packages.into_iter()
+            .map(|package| PackageAccess {
+                package,
+                targets: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        ModuleDescriptor {
+            name,
+            kind: ModuleKind::Automatic,
+            open: true,
+            version,
+            requires: Vec::new(),
+            exports: packages.clone(),
+            opens: packages,
+            uses: Vec::new(),
+            provides,
+        }
+    }
+}
+
+/// Reads the module descriptor of the jar at `path`, like `jar --describe-module`. Besides
+/// [Jar::module_descriptor], falls back to an automatic module named after the file name, as
+/// `java.lang.module.ModuleFinder` does. Returns [None] if no valid module name can be derived.
+///
+/// # Example
+///
+/// ```rs
+/// let descriptor = module_descriptor("lib/guava-31.1-jre.jar")?;
+/// ```
+#[cfg(feature = "fs")]
+pub fn module_descriptor<P>(path: P) -> Result<Option<ModuleDescriptor>, JarError> where P: AsRef<Path> {
+    let path = path.as_ref();
+    let jar = jar(path, JarOptionBuilder::builder().ext("class").target(MANIFEST_PATH).target("META-INF/services/").build())?;
+
+    if let Some(descriptor) = jar.module_descriptor()? {
+        return Ok(Some(descriptor));
+    }
+
+    let file_name = path.file_name().map(|file_name| file_name.to_string_lossy()).unwrap_or_default();
+
+    Ok(automatic_module_name(&file_name).map(|(name, version)| jar.automatic_module(name, version)))
+}
+
+/// A jar resolved into a [ModuleGraph].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GraphModule {
+    pub jar: PathBuf,
+    /// Descriptor of the explicit or automatic module, or [None] for an unnamed module.
+    pub descriptor: Option<ModuleDescriptor>,
+    /// Indices into [ModuleGraph::modules] of the modules resolving this module's `requires`
+    /// directives, in directive order. Automatic modules declare no requirements, although the
+    /// module system lets them read every other module.
+    pub reads: Vec<usize>,
+}
+
+impl GraphModule {
+    /// Returns the module name, or [None] for an unnamed module.
+    pub fn name(&self) -> Option<&str> {
+        self.descriptor.as_ref().map(|descriptor| descriptor.name.as_str())
+    }
+}
+
+/// A `requires` directive which no module of a [ModuleGraph] resolves.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MissingModule {
+    /// Name of the requiring module.
+    pub module: String,
+    pub jar: PathBuf,
+    /// Name of the required module.
+    pub requires: String,
+    /// `requires static`, which is only mandatory at compile time.
+    pub static_phase: bool,
+}
+
+/// Dependency graph of the modules in a set of jars, see [module_graph].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ModuleGraph {
+    /// Modules in the order given, one per jar.
+    pub modules: Vec<GraphModule>,
+    /// Unresolved requirements, in module and directive order.
+    pub missing: Vec<MissingModule>,
+    /// Module names defined by more than one jar, sorted. Requirements resolve to the first one,
+    /// as on a module path.
+    pub duplicates: Vec<String>,
+}
+
+impl ModuleGraph {
+    /// Resolves the `requires` directives of `modules` against each other, given as jars along
+    /// with their descriptors, [None] making a jar an unnamed module, e.g. for jars on the class
+    /// path. Modules named `java.*` or `jdk.*` are assumed to be provided by the platform, and
+    /// unnamed modules cannot be required by name.
+    pub fn resolve(modules: Vec<(PathBuf, Option<ModuleDescriptor>)>) -> ModuleGraph {
+        let mut names = HashMap::new();
+        let mut duplicates = BTreeSet::new();
+
+        for (index, (_, descriptor)) in modules.iter().enumerate() {
+            if let Some(descriptor) = descriptor {
+                if *names.entry(descriptor.name.as_str()).or_insert(index) != index {
+                    duplicates.insert(descriptor.name.clone());
+                }
+            }
+        }
+
+        let mut missing = Vec::new();
+        let reads = modules.iter()
+            .map(|(jar, descriptor)| {
+                let Some(descriptor) = descriptor else {
+                    return Vec::new();
+                };
+
+                descriptor.requires.iter()
+                    .filter(|requires| !is_platform_module(&requires.name))
+                    .filter_map(|requires| {
+                        let index = names.get(requires.name.as_str()).copied();
+
+                        if index.is_none() {
+                            missing.push(MissingModule {
+                                module: descriptor.name.clone(),
+                                jar: jar.clone(),
+                                requires: requires.name.clone(),
+                                static_phase: requires.static_phase,
+                            });
+                        }
+
+                        index
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        let modules = modules.into_iter()
+            .zip(reads)
+            .map(|((jar, descriptor), reads)| GraphModule {
+                jar,
+                descriptor,
+                reads,
+            })
+            .collect();
+
+        ModuleGraph {
+            modules,
+            missing,
+            duplicates: duplicates.into_iter().collect(),
+        }
+    }
+
+    /// Returns the first module named `name`.
+    pub fn module(&self, name: &str) -> Option<&GraphModule> {
+        self.modules.iter().find(|module| module.name() == Some(name))
+    }
+
+    /// Checks whether every requirement but `requires static` ones is resolved, i.e. whether the
+    /// modules can be launched together.
+    pub fn is_resolved(&self) -> bool {
+        self.missing.iter().all(|missing| missing.static_phase)
+    }
+}
+
+/// Builds the module dependency graph of the jars at `paths` as a module path, reading each jar's
+/// descriptor with [module_descriptor] so jars are explicit or automatic modules, or unnamed if no
+/// module name can be derived. See [ModuleGraph::resolve].
+///
+/// # Example
+///
+/// ```rs
+/// let graph = module_graph(&["lib/app.jar", "lib/guava-31.1-jre.jar"])?;
+///
+/// for missing in &graph.missing {
+///     eprintln!("{} requires missing module {}", missing.module, missing.requires);
+/// }
+/// ```
+#[cfg(feature = "fs")]
+pub fn module_graph<P>(paths: &[P]) -> Result<ModuleGraph, JarError> where P: AsRef<Path> {
+    let modules = paths.iter()
+        .map(|path| Ok((path.as_ref().to_path_buf(), module_descriptor(path)?)))
+        .collect::<Result<Vec<_>, JarError>>()?;
+
+    Ok(ModuleGraph::resolve(modules))
+}
+
+/// Checks whether a module is part of the Java platform rather than a library.
+fn is_platform_module(name: &str) -> bool {
+    name.starts_with("java.") || name.starts_with("jdk.")
+}
+
+/// Derives an automatic module name and version from a jar file name following
+/// `java.lang.module.ModuleFinder`, e.g. `foo-bar-1.2.3.jar` becomes `foo.bar` with version `1.2.3`.
+pub fn automatic_module_name(file_name: &str) -> Option<(String, Option<String>)> {
+    let name = file_name.strip_suffix(".jar").unwrap_or(file_name);
+    let bytes = name.as_bytes();
+    // Version starts at the first `-` followed by a digit, then `.` or the end of the name.
+    let version_start = (0..bytes.len()).find(|i| {
+        let digits = bytes[i + 1..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+
+        bytes[*i] == b'-' && digits > 0 && matches!(bytes.get(i + 1 + digits), None | Some(b'.'))
+    });
+    let (name, version) = match version_start {
+        Some(start) => (&name[..start], Some(name[start + 1..].to_string())),
+        None => (name, None),
+    };
+    let name = name.split(|char: char| !char.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(".");
+
+    if name.is_empty() {
+        None
+    } else {
+        Some((name, version))
+    }
+}
+
+/// Parses the `Module` attribute of a `module-info.class`.
+pub(crate) fn parse_module_info(bytes: &[u8]) -> Result<ModuleDescriptor, JarError> {
+    let class = ClassFile::parse(bytes)?;
+    let attribute = class.attribute("Module")
+        .ok_or_else(|| JarError::ClassFormat("module-info.class has no Module attribute".to_string()))?;
+    let mut reader = ClassReader::new(attribute);
+    let optional_utf8 = |index: u16| -> Result<Option<String>, JarError> {
+        if index == 0 {
+            Ok(None)
+        } else {
+            class.utf8(index).map(|value| Some(value.to_string()))
+        }
+    };
+    let binary_name = |name: &str| name.replace('/', ".");
+
+    let name = class.module_name(reader.u2()?)?.to_string();
+    let flags = reader.u2()?;
+    let version = optional_utf8(reader.u2()?)?;
+
+    let mut requires = Vec::new();
+    for _ in 0..reader.u2()? {
+        let name = class.module_name(reader.u2()?)?.to_string();
+        let flags = reader.u2()?;
+
+        requires.push(Requires {
+            name,
+            transitive: flags & ACC_TRANSITIVE != 0,
+            static_phase: flags & ACC_STATIC_PHASE != 0,
+            version: optional_utf8(reader.u2()?)?,
+        });
+    }
+
+    let mut package_accesses = [Vec::new(), Vec::new()];
+    for accesses in &mut package_accesses {
+        for _ in 0..reader.u2()? {
+            let package = binary_name(class.package_name(reader.u2()?)?);
+            reader.u2()?;
+            let targets = (0..reader.u2()?)
+                .map(|_| class.module_name(reader.u2()?).map(str::to_string))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            accesses.push(PackageAccess {
+                package,
+                targets,
+            });
+        }
+    }
+    let [exports, opens] = package_accesses;
+
+    let uses = (0..reader.u2()?)
+        .map(|_| class.class_name(reader.u2()?).map(binary_name))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut provides = Vec::new();
+    for _ in 0..reader.u2()? {
+        let service = binary_name(class.class_name(reader.u2()?)?);
+        let providers = (0..reader.u2()?)
+            .map(|_| class.class_name(reader.u2()?).map(binary_name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        provides.push(Provides {
+            service,
+            providers,
+        });
+    }
+
+    Ok(ModuleDescriptor {
+        name,
+        kind: ModuleKind::Explicit,
+        open: flags & ACC_OPEN != 0,
+        version,
+        requires,
+        exports,
+        opens,
+        uses,
+        provides,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::classfile::tests::{class_bytes, reference, utf8};
+    use std::path::PathBuf;
+
+    use crate::fixture::jar_bytes;
+    use crate::{automatic_module_name, jar_from_bytes, JarOptionBuilder, ModuleDescriptor, ModuleGraph, ModuleKind, Requires};
+
+    fn module_info_bytes() -> Vec<u8> {
+        let mut attribute = Vec::new();
+        // Module name, flags, no version.
+        attribute.extend([0, 3, 0, 0, 0, 0]);
+        // requires transitive java.base.
+        attribute.extend([0, 1, 0, 5, 0, 0x20, 0, 0]);
+        // exports com.example to other.module.
+        attribute.extend([0, 1, 0, 7, 0, 0, 0, 1, 0, 9]);
+        // No opens, uses com.example.Service, provides com.example.Service with com.example.Impl.
+        attribute.extend([0, 0, 0, 1, 0, 11, 0, 1, 0, 11, 0, 1, 0, 13]);
+
+        class_bytes(53, &[
+            utf8("Module"),
+            utf8("com.example"),
+            reference(19, 2),
+            utf8("java.base"),
+            reference(19, 4),
+            utf8("com/example"),
+            reference(20, 6),
+            utf8("other.module"),
+            reference(19, 8),
+            utf8("com/example/Service"),
+            reference(7, 10),
+            utf8("com/example/Impl"),
+            reference(7, 12),
+        ], 0, 0, &[(1, attribute)])
+    }
+
+    #[test]
+    fn test_explicit_module() {
+        let bytes = jar_bytes(&[("module-info.class", &module_info_bytes())]);
+        let descriptor = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap().module_descriptor().unwrap().unwrap();
+
+        assert_eq!(descriptor.name, "com.example");
+        assert_eq!(descriptor.kind, ModuleKind::Explicit);
+        assert!(descriptor.requires[0].transitive);
+        assert_eq!(descriptor.exports[0].package, "com.example");
+        assert_eq!(descriptor.exports[0].targets, vec!["other.module"]);
+        assert_eq!(descriptor.uses, vec!["com.example.Service"]);
+        assert_eq!(descriptor.provides[0].providers, vec!["com.example.Impl"]);
+    }
+
+    #[test]
+    fn test_automatic_module() {
+        let bytes = jar_bytes(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nAutomatic-Module-Name: org.example.lib\r\n"),
+            ("org/example/lib/A.class", b""),
+        ]);
+        let descriptor = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap().module_descriptor().unwrap().unwrap();
+
+        assert_eq!(descriptor.name, "org.example.lib");
+        assert_eq!(descriptor.kind, ModuleKind::Automatic);
+        assert_eq!(descriptor.exports[0].package, "org.example.lib");
+    }
+
+    #[test]
+    fn test_automatic_module_name() {
+        assert_eq!(automatic_module_name("foo-bar-1.2.3.jar"), Some(("foo.bar".to_string(), Some("1.2.3".to_string()))));
+        assert_eq!(automatic_module_name("guava-31.1-jre.jar"), Some(("guava".to_string(), Some("31.1-jre".to_string()))));
+        assert_eq!(automatic_module_name("commons--io_.jar"), Some(("commons.io".to_string(), None)));
+        assert_eq!(automatic_module_name("-1.0.jar"), None);
+    }
+
+    fn descriptor(name: &str, kind: ModuleKind, requires: &[(&str, bool)]) -> ModuleDescriptor {
+        ModuleDescriptor {
+            name: name.to_string(),
+            kind,
+            open: false,
+            version: None,
+            requires: requires.iter()
+                .map(|(name, static_phase)| Requires {
+                    name: name.to_string(),
+                    transitive: false,
+                    static_phase: *static_phase,
+                    version: None,
+                })
+                .collect(),
+            exports: Vec::new(),
+            opens: Vec::new(),
+            uses: Vec::new(),
+            provides: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_module_graph() {
+        let graph = ModuleGraph::resolve(vec![
+            (PathBuf::from("app.jar"), Some(descriptor("app", ModuleKind::Explicit, &[("java.base", false), ("lib", false), ("annotations", true)]))),
+            (PathBuf::from("lib-1.0.jar"), Some(descriptor("lib", ModuleKind::Automatic, &[]))),
+            (PathBuf::from("legacy.jar"), None),
+            (PathBuf::from("lib-2.0.jar"), Some(descriptor("lib", ModuleKind::Explicit, &[("missing", false)]))),
+        ]);
+
+        assert_eq!(graph.modules[0].reads, vec![1]);
+        assert_eq!(graph.modules[2].name(), None);
+        assert_eq!(graph.module("lib").unwrap().jar, PathBuf::from("lib-1.0.jar"));
+        assert_eq!(graph.duplicates, vec!["lib"]);
+        assert_eq!(graph.missing.iter().map(|missing| (missing.module.as_str(), missing.requires.as_str())).collect::<Vec<_>>(), vec![
+            ("app", "annotations"),
+            ("lib", "missing"),
+        ]);
+        assert!(!graph.is_resolved());
+        assert!(ModuleGraph::resolve(graph.modules.into_iter().take(3).map(|module| (module.jar, module.descriptor)).collect()).is_resolved());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_module_graph_files() {
+        let dir = crate::fixture::temp_path("modules");
+        let app = dir.join("app.jar");
+        let lib = dir.join("commons-lib-1.2.jar");
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&app, jar_bytes(&[("module-info.class", &module_info_bytes())])).unwrap();
+        std::fs::write(&lib, jar_bytes(&[("org/commons/lib/A.class", b"")])).unwrap();
+
+        let graph = crate::module_graph(&[&app, &lib]).unwrap();
+
+        assert_eq!(graph.modules.iter().map(|module| module.name()).collect::<Vec<_>>(), vec![Some("com.example"), Some("commons.lib")]);
+        assert_eq!(graph.modules[1].descriptor.as_ref().unwrap().kind, ModuleKind::Automatic);
+        assert!(graph.is_resolved());
+        assert!(crate::module_graph(&[dir.join("missing.jar")]).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+//! Discovery of JNI native libraries bundled in jars, classified by operating system and
+//! architecture.
+
+#[cfg(feature = "fs")]
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::Write;
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+
+use crate::Jar;
+#[cfg(feature = "fs")]
+use crate::JarError;
+
+/// Operating system a native library is built for.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Os {
+    Linux,
+    Windows,
+    MacOs,
+    FreeBsd,
+    Android,
+}
+
+impl Os {
+    /// Returns the operating system this crate is compiled for, if supported.
+    pub fn current() -> Option<Os> {
+        match std::env::consts::OS {
+            "linux" => Some(Os::Linux),
+            "windows" => Some(Os::Windows),
+            "macos" => Some(Os::MacOs),
+            "freebsd" => Some(Os::FreeBsd),
+            "android" => Some(Os::Android),
+            _ => None,
+        }
+    }
+
+    fn from_token(token: &str) -> Option<Os> {
+        match token {
+            "linux" => Some(Os::Linux),
+            "windows" | "win" | "win32" | "win64" => Some(Os::Windows),
+            "macos" | "macosx" | "mac" | "osx" | "darwin" => Some(Os::MacOs),
+            "freebsd" => Some(Os::FreeBsd),
+            "android" => Some(Os::Android),
+            _ => None,
+        }
+    }
+}
+
+/// Processor architecture a native library is built for.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Arch {
+    X86,
+    X86_64,
+    Arm,
+    Aarch64,
+    Ppc64Le,
+    Riscv64,
+}
+
+impl Arch {
+    /// Returns the architecture this crate is compiled for, if supported.
+    pub fn current() -> Option<Arch> {
+        match std::env::consts::ARCH {
+            "x86" => Some(Arch::X86),
+            "x86_64" => Some(Arch::X86_64),
+            "arm" => Some(Arch::Arm),
+            "aarch64" => Some(Arch::Aarch64),
+            "powerpc64" if cfg!(target_endian = "little") => Some(Arch::Ppc64Le),
+            "riscv64" => Some(Arch::Riscv64),
+            _ => None,
+        }
+    }
+
+    fn from_token(token: &str) -> Option<Arch> {
+        match token {
+            "x86_64" | "amd64" | "x64" => Some(Arch::X86_64),
+            "x86" | "i386" | "i486" | "i586" | "i686" => Some(Arch::X86),
+            "aarch64" | "arm64" => Some(Arch::Aarch64),
+            "arm" | "arm32" | "armv7" | "armv7l" | "armhf" | "armel" | "armeabi" => Some(Arch::Arm),
+            "ppc64le" => Some(Arch::Ppc64Le),
+            "riscv64" => Some(Arch::Riscv64),
+            _ => None,
+        }
+    }
+}
+
+/// A native library bundled in a jar, see [Jar::native_libraries].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NativeLibrary {
+    /// Full qualified path of the entry.
+    pub path: String,
+    /// Operating system, from the path or else the file extension.
+    pub os: Os,
+    /// Architecture, if the path names one. Libraries without architecture are often universal
+    /// macOS binaries.
+    pub arch: Option<Arch>,
+}
+
+impl NativeLibrary {
+    /// Classifies the entry at `path`, returning `None` if it is not a native library, i.e. has
+    /// none of the extensions `.so`, `.dll`, `.dylib` or `.jnilib`. Versioned shared objects such
+    /// as `libfoo.so.1` are recognized as well.
+    ///
+    /// The operating system and architecture are taken from the last path segments naming them,
+    /// which covers layouts such as `natives/linux-x86_64/`, `com/sun/jna/win32-x86-64/`,
+    /// `linux/arm64/org/lwjgl/` and `org/sqlite/native/Mac/aarch64/`.
+    pub fn classify(path: &str) -> Option<NativeLibrary> {
+        let file_name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+        let extension_os = if file_name.ends_with(".dll") {
+            Os::Windows
+        } else if file_name.ends_with(".dylib") || file_name.ends_with(".jnilib") {
+            Os::MacOs
+        } else if file_name.ends_with(".so") || file_name.contains(".so.") {
+            Os::Linux
+        } else {
+            return None;
+        };
+
+        let lowercase = path.to_lowercase();
+        let tokens = tokens(&lowercase);
+        let os = tokens.iter().rev().find_map(|token| Os::from_token(token)).unwrap_or(extension_os);
+        let arch = tokens.iter().rev().find_map(|token| Arch::from_token(token));
+
+        Some(NativeLibrary { path: path.to_string(), os, arch })
+    }
+
+    /// Returns the file name the library is loaded by.
+    pub fn file_name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    #[cfg(feature = "fs")]
+    fn supports(&self, os: Os, arch: Arch) -> bool {
+        self.os == os && self.arch.is_none_or(|library_arch| library_arch == arch)
+    }
+}
+
+/// Splits a lowercase path into words separated by `/`, `-`, `_` or `.`, joining `x86` followed by
+/// `64` which separators split.
+fn tokens(path: &str) -> Vec<String> {
+    let words = path.split(['/', '-', '_', '.']).filter(|word| !word.is_empty()).collect::<Vec<_>>();
+    let mut tokens = Vec::with_capacity(words.len());
+    let mut index = 0;
+
+    while index < words.len() {
+        match (words[index], words.get(index + 1)) {
+            ("x86", Some(&"64")) => {
+                tokens.push("x86_64".to_string());
+                index += 2;
+            }
+            (word, _) => {
+                tokens.push(word.to_string());
+                index += 1;
+            }
+        }
+    }
+
+    tokens
+}
+
+impl Jar {
+    /// Finds all native libraries, sorted by path. See [NativeLibrary::classify] for how libraries
+    /// are recognized.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("jna.jar", JarOptionBuilder::default())?;
+    ///
+    /// for library in jar.native_libraries() {
+    ///     println!("{} {:?} {:?}", library.path, library.os, library.arch);
+    /// }
+    /// ```
+    pub fn native_libraries(&self) -> Vec<NativeLibrary> {
+        let mut libraries = self.files.keys()
+            .filter_map(|path| NativeLibrary::classify(path))
+            .collect::<Vec<_>>();
+
+        libraries.sort_by(|a, b| a.path.cmp(&b.path));
+        libraries
+    }
+
+    /// Extracts the native libraries for given operating system and architecture directly into
+    /// `dest_dir`, e.g. a directory on `java.library.path`, returning the created files. Libraries
+    /// without architecture are extracted for any architecture, unless a library of the same file
+    /// name names the architecture. Fails with [JarError::DuplicateEntry] if two libraries would be
+    /// extracted to the same file.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("jna.jar", JarOptionBuilder::default())?;
+    ///
+    /// jar.extract_natives_to("natives", Os::current().unwrap(), Arch::current().unwrap())?;
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn extract_natives_to<P>(&self, dest_dir: P, os: Os, arch: Arch) -> Result<Vec<PathBuf>, JarError> where P: AsRef<Path> {
+        let mut selected = HashMap::<&str, NativeLibrary>::new();
+        let libraries = self.native_libraries();
+
+        for library in libraries.iter().filter(|library| library.supports(os, arch)) {
+            match selected.get(library.file_name()) {
+                Some(other) if other.arch.is_some() == library.arch.is_some() => {
+                    return Err(JarError::DuplicateEntry(library.file_name().to_string()));
+                }
+                Some(other) if other.arch.is_some() => {}
+                _ => {
+                    selected.insert(library.file_name(), library.clone());
+                }
+            }
+        }
+
+        let dest_dir = dest_dir.as_ref();
+        let mut selected = selected.into_values().collect::<Vec<_>>();
+        let mut paths = Vec::with_capacity(selected.len());
+
+        selected.sort_by(|a, b| a.path.cmp(&b.path));
+        std::fs::create_dir_all(dest_dir)?;
+
+        for library in selected {
+            let path = dest_dir.join(library.file_name());
+
+            File::create(&path)?.write_all(&self.files[&library.path])?;
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use crate::fixture::{jar_file, temp_path};
+    use crate::{jar, Arch, JarError, JarOptionBuilder, NativeLibrary, Os};
+
+    #[test]
+    fn test_classify() {
+        let classify = |path| NativeLibrary::classify(path).map(|library| (library.os, library.arch));
+
+        assert_eq!(classify("natives/linux-x86_64/libfoo.so"), Some((Os::Linux, Some(Arch::X86_64))));
+        assert_eq!(classify("com/sun/jna/win32-x86-64/jnidispatch.dll"), Some((Os::Windows, Some(Arch::X86_64))));
+        assert_eq!(classify("com/sun/jna/win32-x86/jnidispatch.dll"), Some((Os::Windows, Some(Arch::X86))));
+        assert_eq!(classify("com/sun/jna/darwin-aarch64/libjnidispatch.jnilib"), Some((Os::MacOs, Some(Arch::Aarch64))));
+        assert_eq!(classify("linux/arm64/org/lwjgl/liblwjgl.so"), Some((Os::Linux, Some(Arch::Aarch64))));
+        assert_eq!(classify("org/sqlite/native/Mac/x86_64/libsqlitejdbc.dylib"), Some((Os::MacOs, Some(Arch::X86_64))));
+        assert_eq!(classify("org/sqlite/native/FreeBSD/x86/libsqlitejdbc.so"), Some((Os::FreeBsd, Some(Arch::X86))));
+        assert_eq!(classify("lib/arm64-v8a/libfoo.so"), Some((Os::Linux, Some(Arch::Aarch64))));
+        assert_eq!(classify("libfoo.dylib"), Some((Os::MacOs, None)));
+        assert_eq!(classify("libfoo.so.1"), Some((Os::Linux, None)));
+        assert_eq!(classify("natives/linux-x86_64/README.txt"), None);
+        assert_eq!(classify("org/example/Solver.class"), None);
+    }
+
+    #[test]
+    fn test_extract_natives() {
+        let path = jar_file(&[
+            ("natives/linux-x86_64/libfoo.so", b"linux x64"),
+            ("natives/linux-aarch64/libfoo.so", b"linux arm64"),
+            ("natives/windows-x86_64/foo.dll", b"windows x64"),
+            ("natives/macos/libfoo.dylib", b"macos"),
+            ("natives/linux/libbar.so", b"linux any"),
+            ("natives/linux-x86_64/libbar.so", b"linux bar x64"),
+            ("org/example/Foo.class", b"class"),
+        ]);
+        let bundle = jar(&path, JarOptionBuilder::default()).unwrap();
+
+        assert_eq!(bundle.native_libraries().len(), 6);
+
+        let dest_dir = temp_path("natives");
+        let paths = bundle.extract_natives_to(&dest_dir, Os::Linux, Arch::X86_64).unwrap();
+
+        assert_eq!(paths, vec![dest_dir.join("libbar.so"), dest_dir.join("libfoo.so")]);
+        assert_eq!(std::fs::read(dest_dir.join("libfoo.so")).unwrap(), b"linux x64");
+        assert_eq!(std::fs::read(dest_dir.join("libbar.so")).unwrap(), b"linux bar x64");
+        assert_eq!(bundle.extract_natives_to(&dest_dir, Os::MacOs, Arch::Aarch64).unwrap(), vec![dest_dir.join("libfoo.dylib")]);
+
+        let path = jar_file(&[("a/linux/libfoo.so", b"a"), ("b/linux/libfoo.so", b"b")]);
+        let conflicting = jar(&path, JarOptionBuilder::default()).unwrap();
+
+        assert!(matches!(conflicting.extract_natives_to(&dest_dir, Os::Linux, Arch::X86_64), Err(JarError::DuplicateEntry(_))));
+    }
+}
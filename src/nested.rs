@@ -0,0 +1,210 @@
+//! Class path order of jars nesting their dependencies as jar entries, as established by the
+//! launchers of such jars.
+
+use crate::spring::{directory, parse_classpath_index};
+use crate::{jar_from_bytes, Jar, JarError, JarOptionBuilder, BOOT_INF_CLASSES, BOOT_INF_LIB};
+
+/// A launcher loading classes from jars nested in the launched jar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NestedLauncher {
+    /// Spring Boot's `JarLauncher` or `WarLauncher`, ordering dependencies by `classpath.idx`.
+    SpringBoot,
+    /// One-JAR, loading `main/main.jar` followed by the jars under `lib/`.
+    OneJar,
+    /// Eclipse's jar-in-jar loader, loading the jars listed by the `Rsrc-Class-Path` manifest
+    /// attribute.
+    EclipseJarInJar,
+}
+
+/// Class path of a jar with nested jars, see [Jar::nested_classpath].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NestedClasspath {
+    pub launcher: NestedLauncher,
+    /// Path of the index file declaring the order, e.g. `BOOT-INF/classpath.idx`, [None] if the
+    /// order follows the launcher's defaults.
+    pub index: Option<String>,
+    /// Full qualified paths of class path elements in lookup order. Directories end with `/`, the
+    /// jar root being the empty path; other paths are nested jars.
+    pub entries: Vec<String>,
+}
+
+impl NestedClasspath {
+    /// Finds the resource at full qualified path `resource`, e.g. `com/example/App.class`, in the
+    /// first class path element containing it, returning the element and the resource's content,
+    /// as the launcher's class loader would. Nested jars are only extracted for the resource.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let archive = jar("app.jar", JarOptionBuilder::default())?;
+    /// let classpath = archive.nested_classpath()?.unwrap();
+    ///
+    /// if let Some((element, _)) = classpath.find(&archive, "org/slf4j/LoggerFactory.class")? {
+    ///     println!("loaded from {}", element);
+    /// }
+    /// ```
+    pub fn find(&self, archive: &Jar, resource: &str) -> Result<Option<(&str, Vec<u8>)>, JarError> {
+        for element in &self.entries {
+            if element.is_empty() || element.ends_with('/') {
+                if let Some(content) = archive.files.get(&format!("{}{}", element, resource)) {
+                    return Ok(Some((element, content.clone())));
+                }
+            } else if let Some(nested) = archive.files.get(element) {
+                let mut nested = jar_from_bytes(nested, JarOptionBuilder::builder().target(resource).build())?;
+
+                if let Some(content) = nested.files.remove(resource) {
+                    return Ok(Some((element, content)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl Jar {
+    /// Detects a launcher loading classes from nested jars and returns the class path it builds, so
+    /// classes can be resolved in the same order as at run time. Spring Boot jars list their own
+    /// classes first and then the jars of `classpath.idx`, or of `BOOT-INF/lib` in archive order
+    /// without an index. Returns [None] for jars of other launchers. The manifest must be extracted,
+    /// and nested jars must be extracted to be listed without an index.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let archive = jar("app.jar", JarOptionBuilder::default())?;
+    ///
+    /// if let Some(classpath) = archive.nested_classpath()? {
+    ///     println!("{:?}: {:?}", classpath.launcher, classpath.entries);
+    /// }
+    /// ```
+    pub fn nested_classpath(&self) -> Result<Option<NestedClasspath>, JarError> {
+        let manifest = self.manifest()?.unwrap_or_default();
+        let attributes = manifest.main_attributes();
+        let attribute = |name: &str| attributes.get(name).map(str::trim).filter(|value| !value.is_empty());
+        let war = attribute("Main-Class").is_some_and(|main_class| main_class.ends_with(".WarLauncher"));
+
+        if attribute("Spring-Boot-Version").is_some() || attribute("Start-Class").is_some() {
+            let (classes, lib, index) = if war {
+                ("WEB-INF/classes/", "WEB-INF/lib/", "WEB-INF/classpath.idx")
+            } else {
+                (BOOT_INF_CLASSES, BOOT_INF_LIB, "BOOT-INF/classpath.idx")
+            };
+            let classes = directory(attribute("Spring-Boot-Classes").unwrap_or(classes));
+            let lib = directory(attribute("Spring-Boot-Lib").unwrap_or(lib));
+            let index = attribute("Spring-Boot-Classpath-Index").unwrap_or(index);
+            let (index, libraries) = match self.files.get(index) {
+                Some(content) => (Some(index.to_string()), parse_classpath_index(&String::from_utf8_lossy(content))?),
+                None => (None, self.nested_jars(&lib, war.then_some("WEB-INF/lib-provided/"))),
+            };
+
+            return Ok(Some(NestedClasspath {
+                launcher: NestedLauncher::SpringBoot,
+                index,
+                entries: std::iter::once(classes).chain(libraries).collect(),
+            }));
+        }
+
+        if attribute("One-Jar-Main-Class").is_some() {
+            return Ok(Some(NestedClasspath {
+                launcher: NestedLauncher::OneJar,
+                index: None,
+                entries: std::iter::once("main/main.jar".to_string()).chain(self.nested_jars("lib/", None)).collect(),
+            }));
+        }
+
+        if let Some(classpath) = attribute("Rsrc-Class-Path") {
+            let entries = classpath.split_whitespace()
+                .map(|element| match element.trim_start_matches("./") {
+                    "" => String::new(),
+                    path => path.to_string(),
+                })
+                .collect();
+
+            return Ok(Some(NestedClasspath {
+                launcher: NestedLauncher::EclipseJarInJar,
+                index: None,
+                entries,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns jars directly under directory `lib` in central directory order, followed by those
+    /// under `provided` if given.
+    fn nested_jars(&self, lib: &str, provided: Option<&str>) -> Vec<String> {
+        let jars_under = |directory: &str| {
+            self.order.iter()
+                .filter(|path| path.strip_prefix(directory).is_some_and(|name| !name.contains('/') && name.ends_with(".jar")))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let mut jars = jars_under(lib);
+
+        if let Some(provided) = provided {
+            jars.extend(jars_under(provided));
+        }
+        jars
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use crate::fixture::{jar_bytes, jar_file};
+    use crate::{jar, jar_from_bytes, JarOptionBuilder, NestedLauncher};
+
+    #[test]
+    fn test_spring_boot_nested_classpath() {
+        let library = |content: &[u8]| jar_bytes(&[("org/lib/Lib.class", content)]);
+        let (first, second) = (library(b"first"), library(b"second"));
+        let manifest = b"Manifest-Version: 1.0\r\nMain-Class: org.springframework.boot.loader.JarLauncher\r\nStart-Class: a.App\r\n";
+        let entries: [(&str, &[u8]); 5] = [
+            ("META-INF/MANIFEST.MF", manifest),
+            ("BOOT-INF/classes/a/App.class", b"app"),
+            ("BOOT-INF/lib/b.jar", &second),
+            ("BOOT-INF/lib/a.jar", &first),
+            ("BOOT-INF/classpath.idx", b"- \"BOOT-INF/lib/a.jar\"\n- \"BOOT-INF/lib/b.jar\"\n"),
+        ];
+        let archive = jar(jar_file(&entries), JarOptionBuilder::default()).unwrap();
+        let classpath = archive.nested_classpath().unwrap().unwrap();
+
+        assert_eq!(classpath.launcher, NestedLauncher::SpringBoot);
+        assert_eq!(classpath.index.as_deref(), Some("BOOT-INF/classpath.idx"));
+        assert_eq!(classpath.entries, vec!["BOOT-INF/classes/", "BOOT-INF/lib/a.jar", "BOOT-INF/lib/b.jar"]);
+        assert_eq!(classpath.find(&archive, "a/App.class").unwrap(), Some(("BOOT-INF/classes/", b"app".to_vec())));
+        assert_eq!(classpath.find(&archive, "org/lib/Lib.class").unwrap(), Some(("BOOT-INF/lib/a.jar", b"first".to_vec())));
+        assert_eq!(classpath.find(&archive, "org/lib/Missing.class").unwrap(), None);
+
+        // Without an index, nested jars follow the archive order.
+        let archive = jar(jar_file(&entries[..4]), JarOptionBuilder::default()).unwrap();
+        let classpath = archive.nested_classpath().unwrap().unwrap();
+
+        assert_eq!(classpath.index, None);
+        assert_eq!(classpath.entries, vec!["BOOT-INF/classes/", "BOOT-INF/lib/b.jar", "BOOT-INF/lib/a.jar"]);
+        assert_eq!(classpath.find(&archive, "org/lib/Lib.class").unwrap(), Some(("BOOT-INF/lib/b.jar", b"second".to_vec())));
+    }
+
+    #[test]
+    fn test_other_nested_classpaths() {
+        let one_jar = jar_bytes(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nOne-Jar-Main-Class: a.App\r\n"),
+            ("main/main.jar", b""),
+            ("lib/z.jar", b""),
+            ("lib/a.jar", b""),
+        ]);
+        let classpath = jar_from_bytes(&one_jar, JarOptionBuilder::default()).unwrap().nested_classpath().unwrap().unwrap();
+
+        assert_eq!(classpath.launcher, NestedLauncher::OneJar);
+        assert_eq!(classpath.entries, vec!["main/main.jar", "lib/z.jar", "lib/a.jar"]);
+
+        let eclipse = jar_bytes(&[("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nRsrc-Class-Path: ./ lib/a.jar b.jar\r\n")]);
+        let classpath = jar_from_bytes(&eclipse, JarOptionBuilder::default()).unwrap().nested_classpath().unwrap().unwrap();
+
+        assert_eq!(classpath.launcher, NestedLauncher::EclipseJarInJar);
+        assert_eq!(classpath.entries, vec!["", "lib/a.jar", "b.jar"]);
+
+        let plain = jar_bytes(&[("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nMain-Class: a.App\r\n")]);
+        assert_eq!(jar_from_bytes(&plain, JarOptionBuilder::default()).unwrap().nested_classpath().unwrap(), None);
+    }
+}
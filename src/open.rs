@@ -0,0 +1,222 @@
+//! Random access to entries of a jar kept open, without extracting everything up front.
+
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{Read, Seek};
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+
+use zip::ZipArchive;
+
+use crate::compression::Decompressors;
+use crate::integrity::EntryDecoder;
+use crate::limits::read_content;
+use crate::option::Callback;
+use crate::{JarEntry, JarEntryReader, JarError, JarOption, JarStream, RawEntry};
+
+/// An open jar whose entries are decompressed on demand. Entries passing the extraction rules are
+/// indexed from the central directory once when opened, so lookups do not scan the archive.
+///
+/// # Example
+///
+/// ```rs
+/// let mut jar = jar_open("sample/rt.jar", JarOptionBuilder::default())?;
+///
+/// if jar.contains("java/lang/Object.class") {
+///     let content = jar.get("java/lang/Object.class")?;
+/// }
+/// ```
+pub struct OpenJar<R> {
+    archive: ZipArchive<R>,
+    entries: Vec<(usize, JarEntry)>,
+    positions: HashMap<String, usize>,
+    capped: bool,
+    verify_crc: bool,
+    decompressors: Decompressors,
+    cancel_token: Option<Callback<AtomicBool>>,
+}
+
+impl<R> OpenJar<R> where R: Read + Seek {
+    /// Opens a jar from any seekable source. See [crate::jar] for the extraction rules.
+    pub fn new(reader: R, option: JarOption) -> Result<Self, JarError> {
+        let capped = option.limits.is_set();
+        let verify_crc = !option.skip_crc;
+        let decompressors = option.decompressors.clone();
+        let cancel_token = option.cancel_token.clone();
+        let mut stream = JarStream::new(reader, option)?;
+        let mut entries = Vec::new();
+
+        while let Some(next) = stream.next_match() {
+            entries.push(next?);
+        }
+
+        let positions = entries.iter()
+            .enumerate()
+            .map(|(position, (_, entry))| (entry.name.clone(), position))
+            .collect();
+
+        Ok(Self {
+            archive: stream.into_archive(),
+            entries,
+            positions,
+            capped,
+            verify_crc,
+            decompressors,
+            cancel_token,
+        })
+    }
+
+    /// Reads the decompressed content of entry `name`, returning [JarError::EntryNotFound] if the
+    /// entry does not exist or is excluded.
+    pub fn get(&mut self, name: &str) -> Result<Vec<u8>, JarError> {
+        let (index, entry) = self.positions.get(name)
+            .map(|position| &self.entries[*position])
+            .ok_or_else(|| JarError::EntryNotFound(name.to_string()))?;
+
+        let mut content = EntryDecoder::open(&mut self.archive, *index, entry.compression, self.verify_crc, &self.decompressors)?;
+
+        read_content(&mut content, entry, self.capped)
+    }
+
+    /// Opens a reader streaming the decompressed content of entry `name`, so large entries can be
+    /// piped into a parser or a file without buffering them, returning [JarError::EntryNotFound] if
+    /// the entry does not exist or is excluded. The CRC-32 is verified once the reader reaches the
+    /// end of the content.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let mut jar = jar_open("app.jar", JarOptionBuilder::default())?;
+    /// let mut reader = jar.open("data/model.bin")?;
+    ///
+    /// std::io::copy(&mut reader, &mut File::create("model.bin")?)?;
+    /// ```
+    pub fn open(&mut self, name: &str) -> Result<JarEntryReader<'_>, JarError> {
+        let (index, entry) = self.positions.get(name)
+            .map(|position| &self.entries[*position])
+            .ok_or_else(|| JarError::EntryNotFound(name.to_string()))?;
+        let content = EntryDecoder::open(&mut self.archive, *index, entry.compression, self.verify_crc, &self.decompressors)?;
+
+        Ok(JarEntryReader::new(content, entry.clone(), *index, self.capped, self.cancel_token.clone()))
+    }
+
+    /// Reads the still compressed content of entry `name`, e.g. to copy it into another jar with
+    /// [crate::JarWriter::write_raw], returning [JarError::EntryNotFound] if the entry does not
+    /// exist or is excluded.
+    pub fn get_raw(&mut self, name: &str) -> Result<RawEntry, JarError> {
+        let (index, entry) = self.positions.get(name)
+            .map(|position| &self.entries[*position])
+            .ok_or_else(|| JarError::EntryNotFound(name.to_string()))?;
+
+        RawEntry::read(entry.clone(), &mut self.archive.by_index_raw(*index)?)
+    }
+
+    /// Checks whether entry `name` exists and passes the extraction rules.
+    pub fn contains(&self, name: &str) -> bool {
+        self.positions.contains_key(name)
+    }
+
+    /// Returns the metadata of entry `name`.
+    pub fn entry(&self, name: &str) -> Option<&JarEntry> {
+        self.positions.get(name).map(|position| &self.entries[*position].1)
+    }
+
+    /// Iterates over names of all accessible entries in archive order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(_, entry)| entry.name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Opens the jar file at `path` as an [OpenJar] for random access to its entries.
+///
+/// # Example
+///
+/// ```rs
+/// let mut jar = jar_open("sample/rt.jar", JarOptionBuilder::builder().ext("class").build())?;
+/// ```
+#[cfg(feature = "fs")]
+pub fn jar_open<P>(path: P, option: JarOption) -> Result<OpenJar<File>, JarError> where P: AsRef<Path> {
+    OpenJar::new(File::open(path)?, option)
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use flate2::read::DeflateDecoder;
+
+    use crate::fixture::{jar_bytes, jar_file};
+    use crate::{jar_open, CompressionMethod, JarError, JarOptionBuilder, LazyJar, OpenJar};
+
+    #[test]
+    fn test_random_access() {
+        let path = jar_file(&[
+            ("java/lang/Object.class", b"object"),
+            ("java/util/List.class", b"list"),
+            ("java/lang/messages.properties", b"key=value"),
+        ]);
+        let mut jar = jar_open(&path, JarOptionBuilder::builder().ext("class").build()).unwrap();
+
+        assert_eq!(jar.names().collect::<Vec<_>>(), vec!["java/lang/Object.class", "java/util/List.class"]);
+        assert!(jar.contains("java/util/List.class"));
+        assert!(!jar.contains("java/lang/messages.properties"));
+        assert_eq!(jar.get("java/util/List.class").unwrap(), b"list");
+        assert_eq!(jar.get("java/lang/Object.class").unwrap(), b"object");
+        assert!(matches!(jar.get("java/lang/messages.properties"), Err(JarError::EntryNotFound(_))));
+    }
+
+    #[test]
+    fn test_decompressor_registry() {
+        let bytes = jar_bytes(&[("a.txt", b"hello")]);
+        let uppercase = || JarOptionBuilder::builder()
+            .decompressor(8, |raw| {
+                let mut content = Vec::new();
+                DeflateDecoder::new(raw).read_to_end(&mut content)?;
+                Ok(Box::new(Cursor::new(content.to_ascii_uppercase())))
+            })
+            .verify_crc(false)
+            .build();
+        let mut jar = OpenJar::new(Cursor::new(bytes.clone()), uppercase()).unwrap();
+        let mut opened = Vec::new();
+
+        jar.open("a.txt").unwrap().read_to_end(&mut opened).unwrap();
+        assert_eq!(opened, b"HELLO");
+        assert_eq!(jar.get("a.txt").unwrap(), b"HELLO");
+        assert_eq!(LazyJar::new(Cursor::new(bytes.clone()), uppercase()).unwrap().get("a.txt").unwrap(), b"HELLO");
+
+        let mut bytes = bytes;
+        for (signature, offset) in [(b"PK\x03\x04", 8), (b"PK\x01\x02", 10)] {
+            let header = bytes.windows(4).position(|window| window == signature).unwrap();
+            bytes[header + offset..header + offset + 2].copy_from_slice(&200u16.to_le_bytes());
+        }
+
+        let mut jar = OpenJar::new(Cursor::new(bytes), JarOptionBuilder::default()).unwrap();
+        assert!(matches!(jar.get("a.txt"), Err(JarError::UnsupportedCompression(CompressionMethod::Other(200)))));
+    }
+
+    #[test]
+    fn test_open_reader() {
+        let content = (0..64 * 1024).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let path = jar_file(&[("data/model.bin", &content), ("a.txt", b"a")]);
+        let mut jar = jar_open(&path, JarOptionBuilder::default()).unwrap();
+        let mut reader = jar.open("data/model.bin").unwrap();
+        let mut read = Vec::new();
+
+        assert_eq!(reader.size(), content.len() as u64);
+        reader.read_to_end(&mut read).unwrap();
+        assert_eq!(read, content);
+
+        drop(reader);
+        assert!(matches!(jar.open("missing.bin"), Err(JarError::EntryNotFound(_))));
+    }
+}
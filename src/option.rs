@@ -0,0 +1,1456 @@
+//! Extraction rules shared by every extraction entry point.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::{Debug, Formatter};
+use std::io::{Read, Write};
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::compression::Decompressors;
+use crate::duplicate::DuplicatePolicy;
+use crate::encoding::{NameDecoder, NameEncoding, UnsafeNamePolicy};
+use crate::explain::{Decision, MatchRule};
+use crate::limits::Limits;
+use crate::manifest::strip_manifest_signatures;
+use crate::pattern::Pattern;
+use crate::progress::{Progress, ProgressCallback};
+use crate::signature::is_signature_file;
+use crate::spill::Spill;
+use crate::symlink::SymlinkPolicy;
+use crate::trace::{SkipReason, TraceCallback, TraceEvent};
+use crate::{CompressionMethod, ContentKind, DateTime, JarEntry, JarError, HIGH_COMPRESSION_RATIO, MANIFEST_PATH};
+
+/// A shared user-provided closure or flag. Two callbacks are equal only when they share the same
+/// value.
+pub(crate) struct Callback<F: ?Sized>(pub(crate) Arc<F>);
+
+impl<F: ?Sized> Clone for Callback<F> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<F: ?Sized> Debug for Callback<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+impl<F: ?Sized> PartialEq for Callback<F> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<F: ?Sized> Eq for Callback<F> {}
+
+type EntryFilter = dyn Fn(&JarEntry) -> bool + Send + Sync;
+
+type Renamer = dyn Fn(&str) -> String + Send + Sync;
+
+pub(crate) type ContentMapper = dyn Fn(&str, Vec<u8>) -> Option<Vec<u8>> + Send + Sync;
+
+pub(crate) type TeeFactory = dyn Fn(&JarEntry) -> Result<Option<Box<dyn Write + Send>>, JarError> + Send + Sync;
+
+/// Applies the content mapper of [JarOptionBuilder::map_content] to a file entry's content,
+/// returning [None] if the entry is dropped. Directories are passed through unchanged.
+pub(crate) fn map_content(mapper: &Option<Callback<ContentMapper>>, name: &str, content: Vec<u8>) -> Option<Vec<u8>> {
+    match mapper {
+        Some(mapper) if !name.ends_with('/') => (mapper.0)(name, content),
+        _ => Some(content),
+    }
+}
+
+/// Returns the number of segments of an entry path, where a directory's trailing `/` does not
+/// start a segment.
+fn depth(path: &str) -> usize {
+    path.trim_end_matches('/').matches('/').count() + 1
+}
+
+/// An option that indicates the extraction behaviour used in [crate::jar].
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct JarOption {
+    extract_targets: HashSet<String>,
+    extension_targets: HashSet<String>,
+    pattern_targets: Vec<Pattern>,
+    excluded_targets: HashSet<String>,
+    excluded_extensions: HashSet<String>,
+    filters: Vec<Callback<EntryFilter>>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    max_depth: Option<usize>,
+    modified_after: Option<SystemTime>,
+    modified_before: Option<SystemTime>,
+    compressions: HashSet<CompressionMethod>,
+    content_kinds: HashSet<ContentKind>,
+    strip_signatures: bool,
+    case_insensitive: bool,
+    normalize_paths: bool,
+    prefix_targets: bool,
+    stripped_prefixes: Vec<String>,
+    renamer: Option<Callback<Renamer>>,
+    pub(crate) release: Option<u32>,
+    pub(crate) preserve_permissions: bool,
+    pub(crate) threads: usize,
+    pub(crate) limits: Limits,
+    pub(crate) progress: Option<Callback<ProgressCallback>>,
+    pub(crate) trace: Option<Callback<TraceCallback>>,
+    pub(crate) cancel_token: Option<Callback<AtomicBool>>,
+    pub(crate) skip_corrupt: bool,
+    pub(crate) skip_crc: bool,
+    pub(crate) name_encoding: NameEncoding,
+    pub(crate) name_decoder: Option<Callback<NameDecoder>>,
+    pub(crate) duplicate_policy: DuplicatePolicy,
+    pub(crate) unsafe_name_policy: UnsafeNamePolicy,
+    pub(crate) symlink_policy: SymlinkPolicy,
+    pub(crate) decompressors: Decompressors,
+    pub(crate) include_dirs: bool,
+    pub(crate) content_mapper: Option<Callback<ContentMapper>>,
+    /// Whether [JarOption::content_mapper] includes a mapper of [JarOptionBuilder::map_content],
+    /// rather than only those built for [JarOptionBuilder::strip_signatures] and
+    /// [JarOptionBuilder::content_type], which cache keys can describe.
+    custom_mapper: bool,
+    /// Whether [JarOption::content_mapper] only strips signatures from the manifest, see
+    /// [JarOptionBuilder::strip_signatures], so other entries need not be decompressed.
+    pub(crate) maps_manifest_only: bool,
+    pub(crate) timestamp: Option<DateTime>,
+    pub(crate) tee: Option<Callback<TeeFactory>>,
+    pub(crate) spill: Option<Spill>,
+}
+
+impl JarOption {
+    /// Returns the filter rejecting an entry, checking path filters, then metadata filters,
+    /// then custom filters, or [None] if the entry passes all of them. Directories have no content
+    /// and always pass the metadata filters.
+    pub(crate) fn skip_reason(&self, entry: &JarEntry) -> Option<SkipReason> {
+        if let Decision::Skipped(reason) = self.matches(&entry.name) {
+            Some(reason)
+        } else if self.max_depth.is_some_and(|max_depth| depth(&entry.name) > max_depth) {
+            Some(SkipReason::Depth)
+        } else if !entry.is_dir() && !self.size_match(entry.size) {
+            Some(SkipReason::Size)
+        } else if !self.modified_match(entry.last_modified.to_system_time()) {
+            Some(SkipReason::Modified)
+        } else if !entry.is_dir() && !self.compressions.is_empty() && !self.compressions.contains(&entry.compression) {
+            Some(SkipReason::Compression)
+        } else if !self.filters.iter().all(|filter| (filter.0)(entry)) {
+            Some(SkipReason::Filter)
+        } else {
+            None
+        }
+    }
+
+    /// Maps the path of an extracted entry to its key in [crate::Jar::files], stripping the first
+    /// matching prefix of [JarOptionBuilder::strip_prefix] and then applying
+    /// [JarOptionBuilder::rename]. Returns [None] if nothing is left of the path.
+    pub(crate) fn remap(&self, name: String) -> Option<String> {
+        let name = match self.stripped_prefixes.iter().find_map(|prefix| name.strip_prefix(prefix.as_str())) {
+            Some(stripped) => stripped.to_string(),
+            None => name,
+        };
+        let name = match &self.renamer {
+            Some(renamer) => renamer.0(&name),
+            None => name,
+        };
+
+        (!name.is_empty()).then_some(name)
+    }
+
+    fn size_match(&self, size: u64) -> bool {
+        self.min_size.is_none_or(|min_size| size >= min_size) && self.max_size.is_none_or(|max_size| size <= max_size)
+    }
+
+    fn modified_match(&self, modified: SystemTime) -> bool {
+        self.modified_after.is_none_or(|after| modified > after) && self.modified_before.is_none_or(|before| modified < before)
+    }
+
+    /// Describes all options affecting which entries are extracted and their content, stable
+    /// across processes so it can key persisted results. Returns [None] when custom filters, name
+    /// decoders, content mappers of [JarOptionBuilder::map_content], tee writers, decompressors or renamers are set, since closures
+    /// cannot be described, or when entries are spilled to disk.
+    #[cfg(feature = "fs")]
+    pub(crate) fn cache_key(&self) -> Option<String> {
+        if !self.filters.is_empty() || self.name_decoder.is_some() || self.custom_mapper || self.tee.is_some()
+            || !self.decompressors.is_empty() || self.renamer.is_some() || self.spill.is_some() {
+            return None;
+        }
+
+        let sorted = |set: &HashSet<String>| {
+            let mut values = set.iter().cloned().collect::<Vec<_>>();
+            values.sort();
+            values
+        };
+        let mut compressions = self.compressions.iter().map(|method| method.id()).collect::<Vec<_>>();
+        compressions.sort();
+        let mut content_kinds = self.content_kinds.iter().map(|kind| format!("{:?}", kind)).collect::<Vec<_>>();
+        content_kinds.sort();
+
+        Some(format!(
+            "targets={:?} extensions={:?} patterns={:?} excluded={:?} excluded_extensions={:?} min_size={:?} max_size={:?} max_depth={:?} \
+            modified_after={:?} modified_before={:?} compressions={:?} case_insensitive={} normalize={} prefix={} stripped={:?} release={:?} limits={:?} skip_corrupt={} skip_crc={} encoding={:?} \
+            duplicates={:?} unsafe_names={:?} symlinks={:?} dirs={} timestamp={:?} strip_signatures={} \
+            content_kinds={:?}",
+            sorted(&self.extract_targets), sorted(&self.extension_targets), self.pattern_targets,
+            sorted(&self.excluded_targets), sorted(&self.excluded_extensions), self.min_size, self.max_size, self.max_depth,
+            self.modified_after, self.modified_before, compressions, self.case_insensitive, self.normalize_paths, self.prefix_targets, self.stripped_prefixes, self.release, self.limits, self.skip_corrupt,
+            self.skip_crc, self.name_encoding, self.duplicate_policy, self.unsafe_name_policy, self.symlink_policy, self.include_dirs, self.timestamp,
+            self.strip_signatures, content_kinds,
+        ))
+    }
+
+    /// Checks whether an entry at full qualified path `path` passes the path filters, with the
+    /// same semantics as extraction: exclusions first, then targets, patterns and extensions.
+    /// Returns [Decision::Extracted] with the first rule the path satisfies, or
+    /// [Decision::Skipped] with [SkipReason::Excluded] or [SkipReason::NotTargeted]. Filters
+    /// needing the entry itself, e.g. [JarOptionBuilder::min_size] or [JarOptionBuilder::filter],
+    /// are not checked.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let option = JarOptionBuilder::builder().target("com/example").exclude_ext("properties").build();
+    ///
+    /// assert_eq!(option.matches("com/example/Main.class"), Decision::Extracted(MatchRule::Target("com/example".to_string())));
+    /// assert_eq!(option.matches("com/example/app.properties"), Decision::Skipped(SkipReason::Excluded));
+    /// ```
+    pub fn matches(&self, path: &str) -> Decision {
+        let stripped = self.strip_signatures && path != MANIFEST_PATH && is_signature_file(path);
+        let path = self.comparable_path(path);
+
+        if stripped || self.exclude_match(&path) {
+            return Decision::Skipped(SkipReason::Excluded);
+        }
+
+        match self.include_rule(&path) {
+            Some(rule) => Decision::Extracted(rule),
+            None => Decision::Skipped(SkipReason::NotTargeted),
+        }
+    }
+
+    /// Checks whether an entry with the given qualified path passes the path filters, see
+    /// [JarOption::matches].
+    #[cfg(test)]
+    pub(crate) fn path_match(&self, qualified_target_path: &str) -> bool {
+        matches!(self.matches(qualified_target_path), Decision::Extracted(_))
+    }
+
+    /// Returns the first target, pattern or extension filter a path satisfies, [MatchRule::All] if
+    /// no such filter is specified, or [None] if the path matches none.
+    fn include_rule(&self, qualified_target_path: &str) -> Option<MatchRule> {
+        if self.extract_targets.is_empty() && self.extension_targets.is_empty() && self.pattern_targets.is_empty() {
+            return Some(MatchRule::All);
+        }
+
+        self.extract_targets.iter()
+            .find(|target| self.under_target(qualified_target_path, target))
+            .map(|target| MatchRule::Target(target.clone()))
+            .or_else(|| {
+                self.pattern_targets.iter()
+                    .find(|pattern| pattern.is_match(qualified_target_path))
+                    .map(|pattern| MatchRule::Pattern(pattern.source().to_string()))
+            })
+            .or_else(|| {
+                matching_ext(&self.extension_targets, qualified_target_path).map(|ext| MatchRule::Extension(ext.clone()))
+            })
+    }
+
+    /// Applies [JarOptionBuilder::normalize_paths] and [JarOptionBuilder::case_insensitive] to a
+    /// path, so it compares with targets of the same form.
+    fn comparable_path<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        let mut path = Cow::Borrowed(path);
+
+        if self.normalize_paths {
+            path = normalize_path(path);
+        }
+        if self.case_insensitive && path.chars().any(char::is_uppercase) {
+            path = Cow::Owned(path.to_lowercase());
+        }
+
+        path
+    }
+
+    /// Checks whether a path is the target or lies under it. Unless [JarOptionBuilder::prefix_targets]
+    /// is set, the target must end at a path segment, so `java/lang` does not match
+    /// `java/language/Foo.class`.
+    fn under_target(&self, qualified_target_path: &str, target: &str) -> bool {
+        match qualified_target_path.strip_prefix(target) {
+            Some(rest) => self.prefix_targets || rest.is_empty() || rest.starts_with('/') || target.ends_with('/') || target.is_empty(),
+            None => false,
+        }
+    }
+
+    fn exclude_match(&self, qualified_target_path: &str) -> bool {
+        self.excluded_targets.iter().any(|target| self.under_target(qualified_target_path, target))
+            || matching_ext(&self.excluded_extensions, qualified_target_path).is_some()
+    }
+}
+
+fn matching_ext<'a>(extensions: &'a HashSet<String>, qualified_target_path: &str) -> Option<&'a String> {
+    let (_, extension) = qualified_target_path.rsplit_once(".")?;
+
+    extensions.iter().find(|ext| extension.ends_with(ext.as_str()))
+}
+
+/// Replaces `\` separators with `/` and strips leading `./` components.
+fn normalize_path(path: Cow<'_, str>) -> Cow<'_, str> {
+    let path = if path.contains('\\') {
+        Cow::Owned(path.replace('\\', "/"))
+    } else {
+        path
+    };
+    let mut stripped = path.as_ref();
+
+    while let Some(rest) = stripped.strip_prefix("./") {
+        stripped = rest.trim_start_matches('/');
+    }
+
+    let offset = path.len() - stripped.len();
+
+    match path {
+        path if offset == 0 => path,
+        Cow::Borrowed(path) => Cow::Borrowed(&path[offset..]),
+        Cow::Owned(path) => Cow::Owned(path[offset..].to_string()),
+    }
+}
+
+/// Total uncompressed size limit of [JarOptionBuilder::safe], 1 GiB.
+pub const SAFE_MAX_TOTAL_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Uncompressed entry size limit of [JarOptionBuilder::safe], 256 MiB.
+pub const SAFE_MAX_ENTRY_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Entry count limit of [JarOptionBuilder::safe], above the 65535 entries of a jar without ZIP64.
+pub const SAFE_MAX_ENTRIES: usize = 100_000;
+
+/// A simple option builder for [JarOption] to build in a easy way.
+///
+/// Targets, glob or regex patterns and extensions are alternatives: an entry is extracted if it
+/// satisfies any of them, and every entry is extracted only when none is specified. Since 0.2, an
+/// option with only targets or only extensions extracts just the matching entries; 0.1 extracted
+/// every entry unless both targets and extensions were specified.
+#[derive(Debug)]
+pub struct JarOptionBuilder {
+    option: JarOption,
+}
+
+impl JarOptionBuilder {
+    /// Creates a [JarOption] which allows any file extraction by default.
+    #[allow(clippy::should_implement_trait)]
+    pub fn default() -> JarOption {
+        JarOption::default()
+    }
+
+    /// Creates a [JarOptionBuilder] to build up extraction options.
+    pub fn builder() -> Self {
+        Self {
+            option: JarOption::default(),
+        }
+    }
+
+    /// Creates a [JarOptionBuilder] tuned for speed on trusted jars: CRC-32 checks are skipped and
+    /// entries are decompressed on all available cores. See the `presets` benchmark for the
+    /// difference to other presets.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("build/libs/app.jar", JarOptionBuilder::fast().ext("class").build())?;
+    /// ```
+    pub fn fast() -> Self {
+        let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+
+        Self::builder().verify_crc(false).parallel(threads)
+    }
+
+    /// Creates a [JarOptionBuilder] tuned for untrusted jars, e.g. uploads: CRC-32 checks are
+    /// enabled, extraction fails on duplicate entries, unsafe names and symbolic links, and is
+    /// limited to [SAFE_MAX_TOTAL_SIZE] in total, [SAFE_MAX_ENTRY_SIZE] per entry,
+    /// [SAFE_MAX_ENTRIES] entries and [HIGH_COMPRESSION_RATIO] as compression ratio. Limits
+    /// can be overridden by calling their setters afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar_from_bytes(&upload, JarOptionBuilder::safe().max_total_size(64 * 1024 * 1024).build())?;
+    /// ```
+    pub fn safe() -> Self {
+        Self::builder()
+            .verify_crc(true)
+            .duplicate_policy(DuplicatePolicy::Error)
+            .unsafe_name_policy(UnsafeNamePolicy::Error)
+            .symlink_policy(SymlinkPolicy::Error)
+            .max_total_size(SAFE_MAX_TOTAL_SIZE)
+            .max_entry_size(SAFE_MAX_ENTRY_SIZE)
+            .max_entries(SAFE_MAX_ENTRIES)
+            .max_compression_ratio(HIGH_COMPRESSION_RATIO)
+    }
+
+    /// Keeps `META-INF` folder on extraction.
+    pub fn keep_meta_info(mut self) -> Self {
+        self.option.extract_targets.insert("META-INF".to_string());
+        self
+    }
+
+    /// Filters extraction target with providing target path, matching the entry at that path and
+    /// entries under it by whole path segments. Note that [crate::jar] extracts all files when
+    /// there's no extraction target specified.
+    /// 
+    /// # Example
+    /// 
+    /// ```rs
+    /// JarOptionBuilder::builder().target("java/lang").build();
+    /// ```
+    pub fn target(mut self, target: &str) -> Self {
+        self.option.extract_targets.insert(target.to_string());
+        self
+    }
+
+    /// Filters multiple extraction targets with providing target path. Note that [crate::jar] extracts all 
+    /// files when there's no extraction target specified.
+    /// 
+    /// # Example
+    /// 
+    /// ```rs
+    /// JarOptionBuilder::builder().targets(vec!["java/lang"]).build();
+    /// ```
+    pub fn targets(mut self, targets: &Vec<&str>) -> Self {
+        for target in targets {
+            self.option.extract_targets.insert(target.to_string());
+        }
+        self
+    }
+
+    /// Filters extraction targets with providing file extension. Note that [crate::jar] extracts all 
+    /// files when there's no extraction target specified.
+    ///
+    /// # Example
+    /// 
+    /// ```rs
+    /// JarOptionBuilder::builder().ext("class").build();
+    /// ```
+    pub fn ext(mut self, ext: &str) -> Self {
+        self.option.extension_targets.insert(ext.to_string());
+        self
+    }
+
+    /// Filters multiple extraction targets with providing file extension. Note that [crate::jar] extracts 
+    /// all files when there's no extraction target specified.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().exts(vec!["class"]).build();
+    /// ```
+    pub fn exts(mut self, exts: &Vec<&str>) -> Self {
+        for ext in exts {
+            self.option.extension_targets.insert(ext.to_string());
+        }
+        self
+    }
+
+    /// Filters extraction targets with a glob pattern matched against the full qualified path.
+    /// `*` and `?` match within a single path segment, `**` matches across any number of segments,
+    /// and `[...]` and `{a,b}` match character classes and alternatives. Note that [crate::jar]
+    /// extracts all files when there's no extraction target specified.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().target_glob("com/**/internal/*.class").build();
+    /// ```
+    pub fn target_glob(mut self, glob: &str) -> Self {
+        self.option.pattern_targets.push(Pattern::glob(glob));
+        self
+    }
+
+    /// Filters extraction targets with a regular expression searched in the full qualified path,
+    /// use `^` and `$` to anchor it. Fails when `regex` is not a valid regular expression. Note that
+    /// [crate::jar] extracts all files when there's no extraction target specified.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().target_regex(r"^com/.+/internal/[^/]+\.class$")?.build();
+    /// ```
+    pub fn target_regex(mut self, regex: &str) -> Result<Self, JarError> {
+        self.option.pattern_targets.push(Pattern::regex(regex)?);
+        Ok(self)
+    }
+
+    /// Excludes entries under given target path from extraction, even if they match other filters.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().target("com/example").exclude_target("com/example/generated").build();
+    /// ```
+    pub fn exclude_target(mut self, target: &str) -> Self {
+        self.option.excluded_targets.insert(target.to_string());
+        self
+    }
+
+    /// Excludes entries with given file extension from extraction, even if they match other filters.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().target("com/example").exclude_ext("properties").build();
+    /// ```
+    pub fn exclude_ext(mut self, ext: &str) -> Self {
+        self.option.excluded_extensions.insert(ext.to_string());
+        self
+    }
+
+    /// Filters extraction targets with a custom predicate, which receives the entry's metadata before
+    /// any content is read. Entries must pass every predicate in addition to other filters.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().filter(|entry| entry.size < 1024 * 1024).build();
+    /// ```
+    pub fn filter<F>(mut self, filter: F) -> Self where F: Fn(&JarEntry) -> bool + Send + Sync + 'static {
+        self.option.filters.push(Callback(Arc::new(filter)));
+        self
+    }
+
+    /// Filters extraction targets by content type detected from magic bytes, see
+    /// [ContentKind::detect], so entries are matched regardless of their extension, e.g. class files
+    /// renamed by an obfuscator. When called more than once, entries of any given type pass. Unlike
+    /// other filters the content is checked after decompression, before [JarOptionBuilder::map_content]
+    /// receives it. Directories and entries spilled by [JarOptionBuilder::spill_to_disk] are not checked.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let classes = jar("obfuscated.jar", JarOptionBuilder::builder().content_type(ContentKind::ClassFile).build())?;
+    /// ```
+    pub fn content_type(mut self, kind: ContentKind) -> Self {
+        self.option.content_kinds.insert(kind);
+        self
+    }
+
+    /// Removes signatures, e.g. before modifying a signed jar with [crate::repack], since stale
+    /// signatures make modified jars fail to load. Signature files such as `META-INF/*.SF`,
+    /// `META-INF/*.RSA`, `META-INF/*.DSA` and `META-INF/*.EC` are excluded, and digest attributes
+    /// are stripped from `META-INF/MANIFEST.MF` as by [crate::Manifest::strip_signatures].
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let option = JarOptionBuilder::builder().strip_signatures().exclude_target("com/vendor/internal").build();
+    ///
+    /// repack("lib/signed.jar", "out/unsigned.jar", option)?;
+    /// ```
+    pub fn strip_signatures(mut self) -> Self {
+        self.option.strip_signatures = true;
+        self
+    }
+
+    /// Rewrites or drops file entries as they are extracted. `mapper` receives the full qualified
+    /// path and decompressed content of each matching entry, and returns the content to keep, or
+    /// [None] to leave the entry out. Entry metadata, e.g. [JarEntry::size], still describes the
+    /// original entry.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// // Redacts passwords and drops source files.
+    /// JarOptionBuilder::builder()
+    ///     .map_content(|name, content| match name {
+    ///         "application.properties" => Some(redact(content)),
+    ///         name if name.ends_with(".java") => None,
+    ///         _ => Some(content),
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn map_content<F>(mut self, mapper: F) -> Self where F: Fn(&str, Vec<u8>) -> Option<Vec<u8>> + Send + Sync + 'static {
+        self.option.content_mapper = Some(Callback(Arc::new(mapper)));
+        self
+    }
+
+    /// Strips `prefix` from the paths of extracted entries starting with it, e.g. `BOOT-INF/classes/`
+    /// so classes of a Spring Boot jar are keyed like those of a plain jar. Filters still match the
+    /// original paths. When called more than once, the first matching prefix is stripped. Entries
+    /// whose path equals the prefix are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let option = JarOptionBuilder::builder().target("BOOT-INF/classes").strip_prefix("BOOT-INF/classes/").build();
+    ///
+    /// let main = &jar("app.jar", option)?.files["com/example/Main.class"];
+    /// ```
+    pub fn strip_prefix(mut self, prefix: &str) -> Self {
+        self.option.stripped_prefixes.push(prefix.to_string());
+        self
+    }
+
+    /// Maps the paths of extracted entries with `renamer`, after [JarOptionBuilder::strip_prefix]
+    /// is applied, e.g. to undo the relocation of a shaded jar. Filters still match the original
+    /// paths, and entries renamed to the empty path are skipped. Entries renamed to the same path
+    /// replace each other in archive order.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder()
+    ///     .rename(|path| path.replace("shaded/com/google/", "com/google/"))
+    ///     .build();
+    /// ```
+    pub fn rename<F>(mut self, renamer: F) -> Self where F: Fn(&str) -> String + Send + Sync + 'static {
+        self.option.renamer = Some(Callback(Arc::new(renamer)));
+        self
+    }
+
+    /// Streams the decompressed content of every file entry extracted into a [crate::Jar] to a
+    /// writer as it is read, e.g. a file on disk or a hasher, so entries are collected and written
+    /// in a single pass. `factory` creates the writer of each entry, or returns [None] to only
+    /// collect it. Writers receive the content before [JarOptionBuilder::map_content] is applied,
+    /// and are flushed and dropped once the entry is read. A corrupt entry's writer may have
+    /// received part of its content before extraction fails.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let option = JarOptionBuilder::builder()
+    ///     .tee(|entry| {
+    ///         let path = Path::new("out").join(&entry.name);
+    ///
+    ///         std::fs::create_dir_all(path.parent().unwrap())?;
+    ///         Ok(Some(Box::new(File::create(path)?)))
+    ///     })
+    ///     .build();
+    /// let jar = jar("app.jar", option)?;
+    /// ```
+    pub fn tee<F>(mut self, factory: F) -> Self
+        where F: Fn(&JarEntry) -> Result<Option<Box<dyn Write + Send>>, JarError> + Send + Sync + 'static {
+        self.option.tee = Some(Callback(Arc::new(factory)));
+        self
+    }
+
+    /// Writes the content of entries whose declared uncompressed size exceeds `threshold` bytes to
+    /// new files under `dir` instead of keeping it in memory, so extracting jars with huge resources
+    /// does not hold them all at once. Spilled entries are listed by [crate::Jar::spilled] rather
+    /// than [crate::Jar::files], skip [JarOptionBuilder::map_content], and their files are left for
+    /// the caller to remove.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let option = JarOptionBuilder::builder().spill_to_disk(64 * 1024 * 1024, std::env::temp_dir()).build();
+    /// let jar = jar("model.jar", option)?;
+    ///
+    /// for (name, path) in jar.spilled() {
+    ///     // ...
+    /// }
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn spill_to_disk<P>(mut self, threshold: u64, dir: P) -> Self where P: AsRef<Path> {
+        self.option.spill = Some(Spill::new(threshold, dir.as_ref()));
+        self
+    }
+
+    /// Only extracts entries whose declared uncompressed size is at least `bytes`.
+    pub fn min_size(mut self, bytes: u64) -> Self {
+        self.option.min_size = Some(bytes);
+        self
+    }
+
+    /// Skips entries whose declared uncompressed size exceeds `bytes`, checked before decompression.
+    /// Unlike [JarOptionBuilder::max_entry_size], larger entries are left out rather than failing
+    /// extraction.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// // Leaves out embedded models while extracting the rest of the jar.
+    /// JarOptionBuilder::builder().max_size(64 * 1024 * 1024).build();
+    /// ```
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.option.max_size = Some(bytes);
+        self
+    }
+
+    /// Only extracts entries at most `depth` path segments deep, checked before decompression, e.g.
+    /// `1` for files and directories at the jar root only, or `2` to also include files of
+    /// top-level directories such as `META-INF/MANIFEST.MF`.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// // Lists top-level resources of a deeply nested artifact.
+    /// let entries = jar_list("app.jar", JarOptionBuilder::builder().max_depth(1).build())?;
+    /// ```
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.option.max_depth = Some(depth);
+        self
+    }
+
+    /// Only extracts entries last modified after `time`, e.g. for incremental builds only processing
+    /// entries changed since the previous build. Zip timestamps have a 2-second resolution and no
+    /// time zone, so they are interpreted as UTC. Directories are filtered as well.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().modified_after(last_build).build();
+    /// ```
+    pub fn modified_after(mut self, time: SystemTime) -> Self {
+        self.option.modified_after = Some(time);
+        self
+    }
+
+    /// Only extracts entries last modified before `time`, see [JarOptionBuilder::modified_after].
+    pub fn modified_before(mut self, time: SystemTime) -> Self {
+        self.option.modified_before = Some(time);
+        self
+    }
+
+    /// Reports [JarEntry::last_modified] of every extracted entry as `epoch`, and writes it with
+    /// [crate::repack], so builds of the same content produce identical jars, e.g. with the time
+    /// of `SOURCE_DATE_EPOCH`. Filters still see the original timestamps.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let epoch = UNIX_EPOCH + Duration::from_secs(std::env::var("SOURCE_DATE_EPOCH")?.parse()?);
+    ///
+    /// repack("app.jar", "app-reproducible.jar", JarOptionBuilder::builder().normalize_timestamps(epoch).build())?;
+    /// ```
+    pub fn normalize_timestamps(mut self, epoch: SystemTime) -> Self {
+        self.option.timestamp = Some(DateTime::from_system_time(epoch));
+        self
+    }
+
+    /// Only extracts entries compressed with `method`. Calling it multiple times allows any of the
+    /// given methods.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().compression(CompressionMethod::Stored).build();
+    /// ```
+    pub fn compression(mut self, method: CompressionMethod) -> Self {
+        self.option.compressions.insert(method);
+        self
+    }
+
+    /// Resolves multi-release jars for given Java release. When the manifest declares
+    /// `Multi-Release: true`, each entry is taken from the highest `META-INF/versions/<N>/` variant
+    /// with `N <= release` and exposed without the versioned prefix, and entries only available to
+    /// newer releases are dropped. Other filters are matched against the effective path.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().release(17).build();
+    /// ```
+    pub fn release(mut self, release: u32) -> Self {
+        self.option.release = Some(release);
+        self
+    }
+
+    /// Restores Unix permissions stored in the jar when extracting onto the file system with
+    /// [crate::extract_to_dir]. Setuid, setgid and sticky bits are never restored, nor write
+    /// permission of group and others on directories, whose permissions are applied once all
+    /// entries are written. Has no effect on other platforms.
+    pub fn preserve_permissions(mut self) -> Self {
+        self.option.preserve_permissions = true;
+        self
+    }
+
+    /// Fails extraction with [JarError::LimitExceeded] once matching entries exceed `bytes` in total
+    /// uncompressed size.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().max_total_size(512 * 1024 * 1024).build();
+    /// ```
+    pub fn max_total_size(mut self, bytes: u64) -> Self {
+        self.option.limits.max_total_size = Some(bytes);
+        self
+    }
+
+    /// Fails extraction with [JarError::LimitExceeded] when a matching entry exceeds `bytes` in
+    /// uncompressed size.
+    pub fn max_entry_size(mut self, bytes: u64) -> Self {
+        self.option.limits.max_entry_size = Some(bytes);
+        self
+    }
+
+    /// Fails extraction with [JarError::LimitExceeded] when more than `count` entries match.
+    pub fn max_entries(mut self, count: usize) -> Self {
+        self.option.limits.max_entries = Some(count);
+        self
+    }
+
+    /// Fails extraction with [JarError::LimitExceeded] when a matching entry's uncompressed size is
+    /// more than `ratio` times its compressed size. Deflate rarely exceeds a ratio of 100 on real
+    /// content, while zip bombs reach ratios above 1000.
+    pub fn max_compression_ratio(mut self, ratio: u64) -> Self {
+        self.option.limits.max_compression_ratio = Some(ratio);
+        self
+    }
+
+    /// Reports a [TraceEvent] to `callback` when the archive is opened, for every entry matched or
+    /// skipped with the [SkipReason], and with totals once all entries are inspected, e.g. to find
+    /// out why an expected entry is missing. Events are reported in archive order from the thread
+    /// walking the central directory, also with [JarOptionBuilder::parallel].
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder()
+    ///     .ext("class")
+    ///     .on_trace(|event| {
+    ///         if let TraceEvent::Skipped { name, reason, .. } = event {
+    ///             eprintln!("skipped {}: {}", name, reason);
+    ///         }
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn on_trace<F>(mut self, callback: F) -> Self where F: Fn(&TraceEvent) + Send + Sync + 'static {
+        self.option.trace = Some(Callback(Arc::new(callback)));
+        self
+    }
+
+    /// Reports [Progress] to `callback` after each entry is read by [crate::jar] and the other eager
+    /// entry points. Returning `false` from `callback` aborts extraction with [JarError::Cancelled].
+    /// With [JarOptionBuilder::parallel], `callback` is called from multiple threads in no
+    /// particular order.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder()
+    ///     .on_progress(|progress| {
+    ///         println!("{}/{} {}", progress.index + 1, progress.total, progress.name);
+    ///         true
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn on_progress<F>(mut self, callback: F) -> Self where F: Fn(&Progress) -> bool + Send + Sync + 'static {
+        self.option.progress = Some(Callback(Arc::new(callback)));
+        self
+    }
+
+    /// Aborts extraction with [JarError::Cancelled] once `token` is set to `true`, e.g. from another
+    /// thread. The token is checked before each entry and while reading entry content, so large
+    /// entries are interrupted as well.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let token = Arc::new(AtomicBool::new(false));
+    /// let option = JarOptionBuilder::builder().cancel_token(token.clone()).build();
+    /// let worker = std::thread::spawn(move || jar("sample/rt.jar", option));
+    ///
+    /// token.store(true, Ordering::Relaxed);
+    /// ```
+    pub fn cancel_token(mut self, token: Arc<AtomicBool>) -> Self {
+        self.option.cancel_token = Some(Callback(token));
+        self
+    }
+
+    /// Skips entries whose content is corrupted, e.g. failing the CRC-32 check or decompression,
+    /// instead of failing the whole extraction with [crate::jar] and the other eager entry points.
+    /// Skipped entries and their errors are available from [crate::Jar::failures].
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("damaged.jar", JarOptionBuilder::builder().skip_corrupt().build())?;
+    ///
+    /// for (file_path, err) in jar.failures() {
+    ///     eprintln!("{}: {}", file_path, err);
+    /// }
+    /// ```
+    pub fn skip_corrupt(mut self) -> Self {
+        self.option.skip_corrupt = true;
+        self
+    }
+
+    /// Sets whether entry contents are validated against their stored CRC-32 while extracting, which
+    /// is enabled by default. Disabling it speeds up extraction of trusted jars; use
+    /// [crate::Jar::integrity_report] to check the result afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().verify_crc(false).build();
+    /// ```
+    pub fn verify_crc(mut self, verify: bool) -> Self {
+        self.option.skip_crc = !verify;
+        self
+    }
+
+    /// Decompresses entries concurrently on given number of threads when extracting with
+    /// [crate::jar] or [crate::jar_from_bytes], each thread reading the archive independently.
+    /// [crate::jar_from_reader] cannot reopen its reader and always extracts sequentially.
+    /// [crate::jars_all] instead extracts this many jars at once, each on a single thread.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let threads = std::thread::available_parallelism()?.get();
+    ///
+    /// JarOptionBuilder::builder().parallel(threads).build();
+    /// ```
+    pub fn parallel(mut self, threads: usize) -> Self {
+        self.option.threads = threads;
+        self
+    }
+
+    /// Sets how entry names are decoded, [NameEncoding::Auto] by default. Filters are matched
+    /// against decoded names.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().name_encoding(NameEncoding::Cp437).build();
+    /// ```
+    pub fn name_encoding(mut self, encoding: NameEncoding) -> Self {
+        self.option.name_encoding = encoding;
+        self
+    }
+
+    /// Decodes raw entry names with a custom decoder, e.g. for a legacy platform charset, instead of
+    /// [JarOptionBuilder::name_encoding]. Decoded names escaping the jar root are still skipped.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().name_decoder(|raw| encoding_rs::SHIFT_JIS.decode(raw).0.into_owned()).build();
+    /// ```
+    pub fn name_decoder<F>(mut self, decoder: F) -> Self where F: Fn(&[u8]) -> String + Send + Sync + 'static {
+        self.option.name_decoder = Some(Callback(Arc::new(decoder)));
+        self
+    }
+
+    /// Includes directory entries, named with a trailing `/` and extracted with empty content, so
+    /// empty directories and their attributes are kept. Directories are matched by the same rules
+    /// as files, e.g. they are excluded by extension filters.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("sample/rt.jar", JarOptionBuilder::builder().include_dirs().build())?;
+    ///
+    /// for (entry, _) in jar.entries().filter(|(entry, _)| entry.is_dir()) {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn include_dirs(mut self) -> Self {
+        self.option.include_dirs = true;
+        self
+    }
+
+    /// Sets how entries sharing a name are extracted, [DuplicatePolicy::LastWins] by default.
+    /// Duplicated names are reported by [crate::Jar::duplicates] regardless of the policy.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().duplicate_policy(DuplicatePolicy::Error).build();
+    /// ```
+    pub fn duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.option.duplicate_policy = policy;
+        self
+    }
+
+    /// Sets how entries with names unsafe to use as relative paths are extracted, e.g.
+    /// `../../etc/passwd`, [UnsafeNamePolicy::Skip] by default. Unsafe names are reported by
+    /// [crate::Jar::unsafe_names] regardless of the policy, so malicious jars can be flagged.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().unsafe_name_policy(UnsafeNamePolicy::Sanitize).build();
+    /// ```
+    pub fn unsafe_name_policy(mut self, policy: UnsafeNamePolicy) -> Self {
+        self.option.unsafe_name_policy = policy;
+        self
+    }
+
+    /// Registers a decompressor for entries compressed with zip method id `method`, e.g. `14` for
+    /// LZMA or `95` for XZ, which wraps the raw content of an entry into a reader of its
+    /// decompressed content. A registered decompressor takes precedence over built-in support,
+    /// and its output is validated against the stored CRC-32 unless
+    /// [JarOptionBuilder::verify_crc] is disabled. Entries of other unsupported methods fail with
+    /// [JarError::UnsupportedCompression].
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder()
+    ///     .decompressor(95, |raw| Ok(Box::new(xz2::read::XzDecoder::new(raw))))
+    ///     .build();
+    /// ```
+    pub fn decompressor<F>(mut self, method: u16, decompressor: F) -> Self
+    where F: for<'r> Fn(Box<dyn Read + 'r>) -> std::io::Result<Box<dyn Read + 'r>> + Send + Sync + 'static {
+        self.option.decompressors.insert(method, Callback(Arc::new(decompressor)));
+        self
+    }
+
+    /// Sets how symbolic link entries are extracted, [SymlinkPolicy::Content] by default. Links
+    /// are recognized by the Unix mode of the entry, see [JarEntry::is_symlink].
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let option = JarOptionBuilder::builder().symlink_policy(SymlinkPolicy::Materialize).preserve_permissions().build();
+    ///
+    /// extract_to_dir("dist.jar", "out", option)?;
+    /// ```
+    pub fn symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.option.symlink_policy = policy;
+        self
+    }
+
+    /// Matches target paths, exclusions, extensions and patterns ignoring case, e.g. so
+    /// `target("com/example")` also extracts `COM/Example/Main.class`.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// JarOptionBuilder::builder().ext("class").case_insensitive().build();
+    /// ```
+    pub fn case_insensitive(mut self) -> Self {
+        self.option.case_insensitive = true;
+        self
+    }
+
+    /// Matches target paths and exclusions after normalizing both them and entry names, replacing
+    /// `\` separators with `/` and stripping leading `./` components, as written by some Windows
+    /// tooling. Extracted entries keep their original names.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// // Also extracts `.\com\example\Main.class`.
+    /// JarOptionBuilder::builder().target("com/example").normalize_paths().build();
+    /// ```
+    pub fn normalize_paths(mut self) -> Self {
+        self.option.normalize_paths = true;
+        self
+    }
+
+    /// Matches target paths and exclusions as plain string prefixes, so `target("java/lang")` also
+    /// extracts `java/language/Foo.class`, as in earlier versions. By default targets only match
+    /// whole path segments.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// // Extracts `com/example/Main.class` and `com/example/Main$Inner.class`.
+    /// JarOptionBuilder::builder().target("com/example/Main").prefix_targets().build();
+    /// ```
+    pub fn prefix_targets(mut self) -> Self {
+        self.option.prefix_targets = true;
+        self
+    }
+
+    /// Like [JarOptionBuilder::build], but fails with [JarError::InvalidOption] describing the first
+    /// contradictory or unsatisfiable rule instead of silently extracting nothing or ignoring it:
+    ///
+    /// - a path or extension both targeted and excluded,
+    /// - an empty target, extension or glob pattern,
+    /// - a [JarOptionBuilder::release] below 9, the first release supporting multi-release jars,
+    /// - a size, depth or entry count limit of zero,
+    /// - a minimum size above the maximum size, or a modification time range that is empty.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let option = JarOptionBuilder::builder().target("com/example").exclude_target("com/example").try_build();
+    ///
+    /// assert!(matches!(option, Err(JarError::InvalidOption(_))));
+    /// ```
+    pub fn try_build(self) -> Result<JarOption, JarError> {
+        self.validate().map_err(JarError::InvalidOption)?;
+
+        Ok(self.build())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let option = &self.option;
+        let comparable = |paths: &HashSet<String>| paths.iter().map(|path| option.comparable_path(path).into_owned()).collect::<HashSet<_>>();
+        let lowercase = |extension: &String| if option.case_insensitive { extension.to_lowercase() } else { extension.clone() };
+
+        if option.extract_targets.iter().chain(&option.excluded_targets).any(String::is_empty) {
+            return Err("target path is empty".to_string());
+        }
+        if option.extension_targets.iter().chain(&option.excluded_extensions).any(String::is_empty) {
+            return Err("extension is empty".to_string());
+        }
+        if option.pattern_targets.iter().any(|pattern| pattern.source().is_empty()) {
+            return Err("pattern is empty".to_string());
+        }
+        if let Some(path) = comparable(&option.extract_targets).intersection(&comparable(&option.excluded_targets)).min() {
+            return Err(format!("`{}` is both targeted and excluded", path));
+        }
+
+        let excluded_extensions = option.excluded_extensions.iter().map(lowercase).collect::<HashSet<_>>();
+
+        if let Some(extension) = option.extension_targets.iter().map(lowercase).filter(|extension| excluded_extensions.contains(extension)).min() {
+            return Err(format!("extension `{}` is both targeted and excluded", extension));
+        }
+        if let Some(release) = option.release.filter(|release| *release < 9) {
+            return Err(format!("release {} predates multi-release jars introduced in release 9", release));
+        }
+
+        let limits = [
+            ("max_depth", option.max_depth.map(|depth| depth as u64)),
+            ("max_total_size", option.limits.max_total_size),
+            ("max_entry_size", option.limits.max_entry_size),
+            ("max_entries", option.limits.max_entries.map(|count| count as u64)),
+            ("max_compression_ratio", option.limits.max_compression_ratio),
+        ];
+
+        if let Some((name, _)) = limits.iter().find(|(_, limit)| *limit == Some(0)) {
+            return Err(format!("{} is zero", name));
+        }
+        if let (Some(min), Some(max)) = (option.min_size, option.max_size) {
+            if min > max {
+                return Err(format!("min_size {} exceeds max_size {}", min, max));
+            }
+        }
+        if let (Some(after), Some(before)) = (option.modified_after, option.modified_before) {
+            if after >= before {
+                return Err("modified_after is not before modified_before".to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finalize current [JarOptionBuilder] and construct a [JarOption] from current builder. Use
+    /// [JarOptionBuilder::try_build] to reject contradictory rules.
+    pub fn build(mut self) -> JarOption {
+        if self.option.normalize_paths || self.option.case_insensitive {
+            let comparable = |paths: &HashSet<String>| paths.iter().map(|path| self.option.comparable_path(path).into_owned()).collect();
+            let (extract_targets, excluded_targets) = (comparable(&self.option.extract_targets), comparable(&self.option.excluded_targets));
+
+            self.option.extract_targets = extract_targets;
+            self.option.excluded_targets = excluded_targets;
+        }
+        if self.option.case_insensitive {
+            let lowercase = |extensions: &HashSet<String>| extensions.iter().map(|extension| extension.to_lowercase()).collect();
+
+            self.option.extension_targets = lowercase(&self.option.extension_targets);
+            self.option.excluded_extensions = lowercase(&self.option.excluded_extensions);
+            self.option.pattern_targets = self.option.pattern_targets.iter().map(Pattern::case_insensitive).collect();
+        }
+
+        self.option.custom_mapper = self.option.content_mapper.is_some();
+
+        if self.option.strip_signatures {
+            let mapper = self.option.content_mapper.take();
+
+            self.option.maps_manifest_only = mapper.is_none() && self.option.content_kinds.is_empty();
+            self.option.content_mapper = Some(Callback(Arc::new(move |name: &str, content: Vec<u8>| {
+                let content = if name == MANIFEST_PATH { strip_manifest_signatures(content) } else { content };
+
+                map_content(&mapper, name, content)
+            })));
+        }
+
+        if !self.option.content_kinds.is_empty() {
+            let kinds = self.option.content_kinds.clone();
+            let mapper = self.option.content_mapper.take();
+
+            self.option.content_mapper = Some(Callback(Arc::new(move |name: &str, content: Vec<u8>| {
+                if !ContentKind::detect(&content).is_some_and(|kind| kinds.contains(&kind)) {
+                    return None;
+                }
+
+                map_content(&mapper, name, content)
+            })));
+        }
+
+        self.option
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, CompressionMethod, DateTime, Decision, JarError, JarOptionBuilder, JarStream, JarWriter, MatchRule, SkipReason};
+
+    #[test]
+    fn test_filters_restrict_on_their_own() {
+        assert!(JarOptionBuilder::default().path_match("a/b.txt"));
+        assert!(!JarOptionBuilder::builder().target("java/lang").build().path_match("java/util/List.class"));
+        assert!(!JarOptionBuilder::builder().ext("class").build().path_match("java/lang/messages.properties"));
+
+        let option = JarOptionBuilder::builder().target("java/lang").ext("properties").build();
+
+        assert!(option.path_match("java/lang/Object.class"));
+        assert!(option.path_match("java/util/messages.properties"));
+        assert!(!option.path_match("java/util/List.class"));
+    }
+
+    #[test]
+    fn test_exclusions_override_targets() {
+        let option = JarOptionBuilder::builder()
+            .target("com/example")
+            .exclude_target("com/example/generated")
+            .exclude_ext("properties")
+            .build();
+
+        assert!(option.path_match("com/example/Main.class"));
+        assert!(!option.path_match("com/example/generated/Stub.class"));
+        assert!(!option.path_match("com/example/messages.properties"));
+        assert!(!option.path_match("org/other/Main.class"));
+        assert!(!JarOptionBuilder::builder().exclude_ext("class").build().path_match("Main.class"));
+    }
+
+    #[test]
+    fn test_try_build() {
+        let invalid = |builder: JarOptionBuilder| match builder.try_build() {
+            Err(JarError::InvalidOption(message)) => message,
+            result => panic!("expected invalid option, got {:?}", result.map(|_| ())),
+        };
+
+        assert!(JarOptionBuilder::builder().target("com").exclude_target("com/generated").ext("class").max_depth(3).release(17).try_build().is_ok());
+        assert!(JarOptionBuilder::safe().min_size(1).max_size(1).try_build().is_ok());
+        assert_eq!(invalid(JarOptionBuilder::builder().target("com/example").exclude_target("com/example")), "`com/example` is both targeted and excluded");
+        assert_eq!(invalid(JarOptionBuilder::builder().case_insensitive().target("Com").exclude_target("com")), "`com` is both targeted and excluded");
+        assert_eq!(invalid(JarOptionBuilder::builder().case_insensitive().ext("CLASS").exclude_ext("class")), "extension `class` is both targeted and excluded");
+        assert_eq!(invalid(JarOptionBuilder::builder().target_glob("")), "pattern is empty");
+        assert_eq!(invalid(JarOptionBuilder::builder().ext("")), "extension is empty");
+        assert_eq!(invalid(JarOptionBuilder::builder().release(8)), "release 8 predates multi-release jars introduced in release 9");
+        assert_eq!(invalid(JarOptionBuilder::builder().max_entries(0)), "max_entries is zero");
+        assert_eq!(invalid(JarOptionBuilder::builder().max_depth(0)), "max_depth is zero");
+        assert_eq!(invalid(JarOptionBuilder::builder().min_size(2).max_size(1)), "min_size 2 exceeds max_size 1");
+        assert_eq!(invalid(JarOptionBuilder::builder().modified_after(UNIX_EPOCH).modified_before(UNIX_EPOCH)), "modified_after is not before modified_before");
+    }
+
+    #[test]
+    fn test_segment_aware_targets() {
+        let option = JarOptionBuilder::builder().target("java/lang").exclude_target("java/lang/ref").build();
+
+        assert!(option.path_match("java/lang/Object.class"));
+        assert!(option.path_match("java/lang"));
+        assert!(option.path_match("java/lang/reflect/Method.class"));
+        assert!(!option.path_match("java/language/Foo.class"));
+        assert!(!option.path_match("java/lang/ref/Reference.class"));
+        assert!(JarOptionBuilder::builder().target("java/").build().path_match("java/language/Foo.class"));
+
+        let option = JarOptionBuilder::builder().target("java/lang").exclude_target("java/lang/ref").prefix_targets().build();
+
+        assert!(option.path_match("java/language/Foo.class"));
+        assert!(!option.path_match("java/lang/reflect/Method.class"));
+    }
+
+    #[test]
+    fn test_presets() {
+        let bytes = jar_bytes(&[("a.txt", b"first"), ("a.txt", b"second"), ("b.bin", &[0; 64 * 1024])]);
+
+        assert_eq!(jar_from_bytes(&bytes, JarOptionBuilder::fast().build()).unwrap().files["a.txt"], b"second");
+        assert!(matches!(jar_from_bytes(&bytes, JarOptionBuilder::safe().build()), Err(JarError::DuplicateEntry(_))));
+
+        let bytes = jar_bytes(&[("b.bin", &[0; 64 * 1024])]);
+
+        assert!(matches!(jar_from_bytes(&bytes, JarOptionBuilder::safe().build()), Err(JarError::LimitExceeded(_))));
+        assert!(jar_from_bytes(&bytes, JarOptionBuilder::safe().max_compression_ratio(u64::MAX).build()).is_ok());
+    }
+
+    #[test]
+    fn test_matches() {
+        let option = JarOptionBuilder::builder().target("com/example").ext("txt").exclude_ext("properties").normalize_paths().build();
+
+        assert_eq!(option.matches("com/example/Main.class"), Decision::Extracted(MatchRule::Target("com/example".to_string())));
+        assert_eq!(option.matches("./docs/README.txt"), Decision::Extracted(MatchRule::Extension("txt".to_string())));
+        assert_eq!(option.matches("com/example/app.properties"), Decision::Skipped(SkipReason::Excluded));
+        assert_eq!(option.matches("org/Main.class"), Decision::Skipped(SkipReason::NotTargeted));
+        assert_eq!(JarOptionBuilder::default().matches("anything"), Decision::Extracted(MatchRule::All));
+    }
+
+    #[test]
+    fn test_case_insensitive_and_normalized_paths() {
+        let option = JarOptionBuilder::builder()
+            .target("com/Example")
+            .exclude_target("com/example/Generated")
+            .ext("CLASS")
+            .target_glob("**/*.Txt")
+            .case_insensitive()
+            .build();
+
+        assert!(option.path_match("COM/EXAMPLE/messages.properties"));
+        assert!(option.path_match("org/Main.Class"));
+        assert!(option.path_match("org/README.TXT"));
+        assert!(!option.path_match("com/example/generated/Stub.class"));
+        assert!(!JarOptionBuilder::builder().target("com/Example").build().path_match("com/example/Main.class"));
+
+        let option = JarOptionBuilder::builder()
+            .target(".\\com\\example")
+            .exclude_target("./com/example/generated")
+            .normalize_paths()
+            .build();
+
+        assert!(option.path_match("com/example/Main.class"));
+        assert!(option.path_match("././/com\\example\\Main.class"));
+        assert!(!option.path_match("com\\example\\generated\\Stub.class"));
+        assert!(!option.path_match("Com/example/Main.class"));
+        assert!(!JarOptionBuilder::builder().target("com/example").build().path_match("./com/example/Main.class"));
+    }
+
+    #[test]
+    fn test_custom_filter() {
+        let bytes = jar_bytes(&[("small.txt", b"a"), ("large.txt", &[0; 64]), ("large.bin", &[0; 64])]);
+        let option = JarOptionBuilder::builder()
+            .ext("txt")
+            .filter(|entry| entry.size < 16)
+            .build();
+        let jar = jar_from_bytes(&bytes, option).unwrap();
+
+        assert_eq!(jar.files.keys().collect::<Vec<_>>(), vec!["small.txt"]);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let bytes = jar_bytes(&[("a.txt", b"a"), ("b/", b""), ("b/c.txt", b"c"), ("b/d/", b""), ("b/d/e.txt", b"e")]);
+        let names = |depth: usize| {
+            let mut names = jar_from_bytes(&bytes, JarOptionBuilder::builder().include_dirs().max_depth(depth).build()).unwrap().files.into_keys().collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+
+        assert!(names(0).is_empty());
+        assert_eq!(names(1), vec!["a.txt", "b/"]);
+        assert_eq!(names(2), vec!["a.txt", "b/", "b/c.txt", "b/d/"]);
+
+        let explanations = crate::jar_explain_reader(Cursor::new(bytes.clone()), JarOptionBuilder::builder().max_depth(1).build()).unwrap();
+
+        assert_eq!((explanations[2].name.as_str(), &explanations[2].decision), ("b/c.txt", &Decision::Skipped(SkipReason::Depth)));
+    }
+
+    #[test]
+    fn test_metadata_filters() {
+        let mut writer = JarWriter::new(Cursor::new(Vec::new())).compression(CompressionMethod::Stored);
+        writer.write_entry("small.txt", b"s").unwrap();
+        writer.write_entry("large.bin", &[0; 1024]).unwrap();
+        let mut writer = writer.compression(CompressionMethod::Deflated);
+        writer.write_entry("medium.txt", &[0; 100]).unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let names = |option: JarOptionBuilder| {
+            let mut names = jar_from_bytes(&bytes, option.ext("txt").ext("bin").build()).unwrap().files.into_keys().collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names(JarOptionBuilder::builder().max_size(100)), vec!["medium.txt", "small.txt"]);
+        assert_eq!(names(JarOptionBuilder::builder().min_size(100)), vec!["large.bin", "medium.txt"]);
+        assert_eq!(names(JarOptionBuilder::builder().compression(CompressionMethod::Stored)), vec!["large.bin", "small.txt"]);
+        assert_eq!(names(JarOptionBuilder::builder().max_size(100).max_entry_size(100)), vec!["medium.txt", "small.txt"]);
+    }
+
+    #[test]
+    fn test_modified_filters() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+        for (name, year) in [("old.txt", 2000), ("new.txt", 2020)] {
+            let modified = zip::DateTime::from_date_and_time(year, 1, 1, 0, 0, 0).unwrap();
+
+            writer.start_file(name, FileOptions::default().last_modified_time(modified)).unwrap();
+        }
+
+        let bytes = writer.finish().unwrap().into_inner();
+        let names = |option: JarOptionBuilder| {
+            let mut stream = JarStream::new(Cursor::new(&bytes), option.build()).unwrap();
+
+            std::iter::from_fn(|| stream.next_match())
+                .map(|next| next.map(|(_, entry)| (entry.name, entry.last_modified.year)).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let (epoch, year_2010) = (UNIX_EPOCH + Duration::from_secs(951868801), UNIX_EPOCH + Duration::from_secs(1262304000));
+
+        assert_eq!(DateTime::from_system_time(year_2010), DateTime { year: 2010, month: 1, day: 1, hour: 0, minute: 0, second: 0 });
+        assert_eq!(DateTime::from_system_time(epoch), DateTime { year: 2000, month: 3, day: 1, hour: 0, minute: 0, second: 0 });
+        assert_eq!(DateTime::from_unix_timestamp(0).year, 1980);
+        assert_eq!(DateTime::from_system_time(year_2010).to_system_time(), year_2010);
+
+        assert_eq!(names(JarOptionBuilder::builder().modified_after(year_2010)), vec![("new.txt".to_string(), 2020)]);
+        assert_eq!(names(JarOptionBuilder::builder().modified_before(year_2010)), vec![("old.txt".to_string(), 2000)]);
+        assert_eq!(names(JarOptionBuilder::builder().modified_after(year_2010).normalize_timestamps(epoch)), vec![("new.txt".to_string(), 2000)]);
+    }
+
+    #[test]
+    fn test_map_content() {
+        let bytes = jar_bytes(&[("a.txt", b"a"), ("b.txt", b"b"), ("c.txt", b"c")]);
+
+        for threads in [1, 2] {
+            let option = JarOptionBuilder::builder()
+                .map_content(|name, content| match name {
+                    "a.txt" => Some(content.to_ascii_uppercase()),
+                    "b.txt" => None,
+                    _ => Some(content),
+                })
+                .parallel(threads)
+                .build();
+            let jar = jar_from_bytes(&bytes, option).unwrap();
+
+            assert_eq!(jar.files.len(), 2);
+            assert_eq!(jar.files["a.txt"], b"A");
+            assert_eq!(jar.files["c.txt"], b"c");
+            assert!(jar.entry("b.txt").is_none());
+        }
+    }
+
+    #[test]
+    fn test_strip_prefix_and_rename() {
+        let bytes = jar_bytes(&[
+            ("BOOT-INF/classes/", b""),
+            ("BOOT-INF/classes/a/A.class", b"a"),
+            ("BOOT-INF/lib/b.jar", b"b"),
+            ("shaded/c/C.class", b"c"),
+        ]);
+
+        for threads in [1, 2] {
+            let option = JarOptionBuilder::builder()
+                .exclude_target("BOOT-INF/lib")
+                .strip_prefix("BOOT-INF/classes/")
+                .rename(|path| path.replace("shaded/", ""))
+                .include_dirs()
+                .parallel(threads)
+                .build();
+            let jar = jar_from_bytes(&bytes, option).unwrap();
+            let mut names = jar.files.keys().cloned().collect::<Vec<_>>();
+
+            names.sort();
+            assert_eq!(names, vec!["a/A.class", "c/C.class"]);
+            assert_eq!(jar.entry("a/A.class").unwrap().name, "a/A.class");
+        }
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_cache_key_mappers() {
+        use crate::ContentKind;
+
+        let key = |builder: JarOptionBuilder| builder.build().cache_key();
+        let plain = key(JarOptionBuilder::builder()).unwrap();
+        let stripped = key(JarOptionBuilder::builder().strip_signatures()).unwrap();
+        let classes = key(JarOptionBuilder::builder().content_type(ContentKind::ClassFile)).unwrap();
+
+        assert!(stripped.contains("strip_signatures=true"));
+        assert!(classes.contains("content_kinds=[\"ClassFile\"]"));
+        assert_ne!(plain, classes);
+        assert!(key(JarOptionBuilder::builder().strip_signatures().map_content(|_, content| Some(content))).is_none());
+    }
+}
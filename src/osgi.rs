@@ -0,0 +1,401 @@
+//! Parsing of OSGi bundle headers based on the
+//! [OSGi Core Specification](https://docs.osgi.org/specification/osgi.core/8.0.0/framework.module.html#framework.module.bundlemanifest).
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+use crate::{Jar, JarError, Manifest};
+
+/// A clause of an OSGi manifest header, e.g. `com.example;version="[1.0,2)";resolution:=optional`
+/// has path `com.example`, attribute `version` and directive `resolution`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Clause {
+    /// Paths sharing the clause's parameters, usually package names.
+    pub paths: Vec<String>,
+    /// Attributes declared with `name=value`, unquoted.
+    pub attributes: Vec<(String, String)>,
+    /// Directives declared with `name:=value`, unquoted.
+    pub directives: Vec<(String, String)>,
+}
+
+impl Clause {
+    /// Returns the value of attribute `name`.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        find(&self.attributes, name)
+    }
+
+    /// Returns the value of directive `name`.
+    pub fn directive(&self, name: &str) -> Option<&str> {
+        find(&self.directives, name)
+    }
+}
+
+fn find<'a>(parameters: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    parameters.iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// An OSGi version `major.minor.micro.qualifier`, where missing numeric parts are 0 and the
+/// qualifier is compared lexicographically.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct OsgiVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub micro: u32,
+    pub qualifier: String,
+}
+
+impl OsgiVersion {
+    /// Parses a version, where an empty string is `0.0.0`.
+    pub fn parse(version: &str) -> Result<Self, JarError> {
+        let version = version.trim();
+
+        if version.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut parts = version.splitn(4, '.');
+        let mut number = || parts.next()
+            .map(|part| part.parse::<u32>().map_err(|_| osgi_error(format!("invalid version `{}`", version))))
+            .transpose()
+            .map(Option::unwrap_or_default);
+
+        Ok(Self {
+            major: number()?,
+            minor: number()?,
+            micro: number()?,
+            qualifier: parts.next().unwrap_or_default().to_string(),
+        })
+    }
+}
+
+impl Ord for OsgiVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.micro, &self.qualifier)
+            .cmp(&(other.major, other.minor, other.micro, &other.qualifier))
+    }
+}
+
+impl PartialOrd for OsgiVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for OsgiVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.micro)?;
+
+        if !self.qualifier.is_empty() {
+            write!(f, ".{}", self.qualifier)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An OSGi version range, e.g. `[1.0,2.0)`. A single version `1.0` means at least that version.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VersionRange {
+    pub floor: OsgiVersion,
+    pub floor_inclusive: bool,
+    /// Upper bound, [None] if unbounded.
+    pub ceiling: Option<OsgiVersion>,
+    pub ceiling_inclusive: bool,
+}
+
+impl VersionRange {
+    /// Parses a version range, where an empty string matches every version.
+    pub fn parse(range: &str) -> Result<Self, JarError> {
+        let range = range.trim();
+        let invalid = || osgi_error(format!("invalid version range `{}`", range));
+
+        let (floor_inclusive, rest) = match range.chars().next() {
+            Some('[') => (true, &range[1..]),
+            Some('(') => (false, &range[1..]),
+            _ => {
+                return Ok(Self {
+                    floor: OsgiVersion::parse(range)?,
+                    floor_inclusive: true,
+                    ceiling: None,
+                    ceiling_inclusive: false,
+                });
+            }
+        };
+        let (ceiling_inclusive, rest) = match rest.chars().last() {
+            Some(']') => (true, &rest[..rest.len() - 1]),
+            Some(')') => (false, &rest[..rest.len() - 1]),
+            _ => return Err(invalid()),
+        };
+        let (floor, ceiling) = rest.split_once(',').ok_or_else(invalid)?;
+
+        Ok(Self {
+            floor: OsgiVersion::parse(floor)?,
+            floor_inclusive,
+            ceiling: Some(OsgiVersion::parse(ceiling)?),
+            ceiling_inclusive,
+        })
+    }
+
+    /// Checks whether `version` is within the range.
+    pub fn contains(&self, version: &OsgiVersion) -> bool {
+        let above_floor = if self.floor_inclusive { version >= &self.floor } else { version > &self.floor };
+        let below_ceiling = match &self.ceiling {
+            Some(ceiling) if self.ceiling_inclusive => version <= ceiling,
+            Some(ceiling) => version < ceiling,
+            None => true,
+        };
+
+        above_floor && below_ceiling
+    }
+}
+
+/// A package exported by a bundle, declared in `Export-Package`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedPackage {
+    pub name: String,
+    pub version: OsgiVersion,
+    /// Packages used by the exported package, declared by the `uses` directive.
+    pub uses: Vec<String>,
+}
+
+/// A package imported by a bundle, declared in `Import-Package`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedPackage {
+    pub name: String,
+    pub version: VersionRange,
+    /// Whether the bundle resolves without the package, declared by `resolution:=optional`.
+    pub optional: bool,
+}
+
+/// Identity and package wiring of an OSGi bundle, parsed from its manifest headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OsgiBundle {
+    /// `Bundle-SymbolicName` without its parameters.
+    pub symbolic_name: String,
+    /// Whether the bundle is declared with `singleton:=true`.
+    pub singleton: bool,
+    /// `Bundle-Version`, `0.0.0` if absent.
+    pub version: OsgiVersion,
+    /// `Bundle-ManifestVersion`, 1 if absent, 2 for bundles following OSGi R4 and later.
+    pub manifest_version: u32,
+    pub exports: Vec<ExportedPackage>,
+    pub imports: Vec<ImportedPackage>,
+}
+
+impl OsgiBundle {
+    /// Parses OSGi headers of `manifest`. Returns [None] if the manifest does not declare
+    /// `Bundle-SymbolicName`, i.e. the jar is not a bundle.
+    pub fn from_manifest(manifest: &Manifest) -> Result<Option<Self>, JarError> {
+        let attributes = manifest.main_attributes();
+        let Some(symbolic_name) = attributes.get("Bundle-SymbolicName") else {
+            return Ok(None);
+        };
+        let symbolic_name = parse_osgi_header(symbolic_name)?.into_iter().next()
+            .filter(|clause| clause.paths.len() == 1)
+            .ok_or_else(|| osgi_error("invalid Bundle-SymbolicName".to_string()))?;
+        let manifest_version = attributes.get("Bundle-ManifestVersion")
+            .map(|version| version.trim().parse().map_err(|_| osgi_error(format!("invalid Bundle-ManifestVersion `{}`", version))))
+            .transpose()?
+            .unwrap_or(1);
+
+        let mut exports = Vec::new();
+        for clause in parse_osgi_header(attributes.get("Export-Package").unwrap_or_default())? {
+            let version = OsgiVersion::parse(clause.attribute("version").or(clause.attribute("specification-version")).unwrap_or_default())?;
+            let uses: Vec<String> = clause.directive("uses")
+                .map(|uses| uses.split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect())
+                .unwrap_or_default();
+
+            exports.extend(clause.paths.iter().map(|name| ExportedPackage {
+                name: name.clone(),
+                version: version.clone(),
+                uses: uses.clone(),
+            }));
+        }
+
+        let mut imports = Vec::new();
+        for clause in parse_osgi_header(attributes.get("Import-Package").unwrap_or_default())? {
+            let version = VersionRange::parse(clause.attribute("version").or(clause.attribute("specification-version")).unwrap_or_default())?;
+            let optional = clause.directive("resolution") == Some("optional");
+
+            imports.extend(clause.paths.iter().map(|name| ImportedPackage {
+                name: name.clone(),
+                version: version.clone(),
+                optional,
+            }));
+        }
+
+        Ok(Some(Self {
+            singleton: symbolic_name.directive("singleton") == Some("true"),
+            symbolic_name: symbolic_name.paths[0].clone(),
+            version: OsgiVersion::parse(attributes.get("Bundle-Version").unwrap_or_default())?,
+            manifest_version,
+            exports,
+            imports,
+        }))
+    }
+}
+
+impl Jar {
+    /// Parses OSGi bundle headers of the manifest. Returns [None] when the manifest is absent or is
+    /// not a bundle manifest. See [Jar::manifest] for extraction requirements.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// if let Some(bundle) = jar("bundle.jar", JarOptionBuilder::builder().keep_meta_info().build())?.osgi_bundle()? {
+    ///     for import in bundle.imports.iter().filter(|import| !import.optional) {
+    ///         // ...
+    ///     }
+    /// }
+    /// ```
+    pub fn osgi_bundle(&self) -> Result<Option<OsgiBundle>, JarError> {
+        match self.manifest()? {
+            Some(manifest) => OsgiBundle::from_manifest(&manifest),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Parses an OSGi header value into clauses separated by `,`, whose paths and parameters are
+/// separated by `;`. Separators inside quoted strings are ignored.
+///
+/// # Example
+///
+/// ```rs
+/// let clauses = parse_osgi_header(r#"a.b;a.c;version="[1,2)",d.e"#)?;
+///
+/// assert_eq!(clauses[0].paths, vec!["a.b", "a.c"]);
+/// ```
+pub fn parse_osgi_header(value: &str) -> Result<Vec<Clause>, JarError> {
+    let mut clauses = Vec::new();
+
+    for clause in split_unquoted(value, ',') {
+        let mut parsed = Clause::default();
+
+        for part in split_unquoted(clause, ';') {
+            let part = part.trim();
+
+            if let Some((name, value)) = part.split_once(":=") {
+                parsed.directives.push((name.trim().to_string(), unquote(value.trim())));
+            } else if let Some((name, value)) = part.split_once('=') {
+                parsed.attributes.push((name.trim().to_string(), unquote(value.trim())));
+            } else if parsed.attributes.is_empty() && parsed.directives.is_empty() && !part.is_empty() {
+                parsed.paths.push(part.to_string());
+            } else {
+                return Err(osgi_error(format!("unexpected `{}` in clause `{}`", part, clause.trim())));
+            }
+        }
+
+        if parsed.paths.is_empty() {
+            if parsed.attributes.is_empty() && parsed.directives.is_empty() {
+                continue;
+            }
+
+            return Err(osgi_error(format!("clause `{}` has no path", clause.trim())));
+        }
+
+        clauses.push(parsed);
+    }
+
+    Ok(clauses)
+}
+
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (index, char) in value.char_indices() {
+        match char {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            _ if char == separator && !quoted => {
+                parts.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&value[start..]);
+    parts
+}
+
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+        Some(quoted) => quoted.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => value.to_string(),
+    }
+}
+
+fn osgi_error(message: String) -> JarError {
+    JarError::ManifestParse(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, parse_osgi_header, JarOptionBuilder, OsgiVersion, VersionRange};
+
+    #[test]
+    fn test_parse_osgi_header() {
+        let clauses = parse_osgi_header(r#"a.b;a.c;version="[1.0,2)";uses:="x.y,x.z", d.e"#).unwrap();
+
+        assert_eq!(clauses.len(), 2);
+        assert_eq!(clauses[0].paths, vec!["a.b", "a.c"]);
+        assert_eq!(clauses[0].attribute("version"), Some("[1.0,2)"));
+        assert_eq!(clauses[0].directive("uses"), Some("x.y,x.z"));
+        assert_eq!(clauses[1].paths, vec!["d.e"]);
+        assert!(parse_osgi_header("a;version=1;b").is_err());
+    }
+
+    #[test]
+    fn test_versions() {
+        let version = |version: &str| OsgiVersion::parse(version).unwrap();
+        let range = VersionRange::parse("[1.2,2.0)").unwrap();
+
+        assert_eq!(version("1.2"), OsgiVersion { major: 1, minor: 2, micro: 0, qualifier: String::new() });
+        assert_eq!(version("1.2.3.RELEASE").to_string(), "1.2.3.RELEASE");
+        assert!(version("1.2.3.a") < version("1.2.3.b"));
+        assert!(range.contains(&version("1.2")) && range.contains(&version("1.9.9")));
+        assert!(!range.contains(&version("2.0")) && !range.contains(&version("1.1")));
+        assert!(VersionRange::parse("1.0").unwrap().contains(&version("99")));
+        assert!(OsgiVersion::parse("1.x").is_err());
+        assert!(VersionRange::parse("[1.0,2.0").is_err());
+    }
+
+    #[test]
+    fn test_osgi_bundle() {
+        let manifest = b"Manifest-Version: 1.0\r\n\
+            Bundle-ManifestVersion: 2\r\n\
+            Bundle-SymbolicName: com.example.core;singleton:=true\r\n\
+            Bundle-Version: 1.4.0.v2023\r\n\
+            Export-Package: com.example.api;version=\"1.4.0\";uses:=\"org.osgi.framework\",com.example.spi\r\n\
+            Import-Package: org.osgi.framework;version=\"[1.8,2)\",org.slf4j;resolution:=optional\r\n";
+        let bytes = jar_bytes(&[("META-INF/MANIFEST.MF", manifest)]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().keep_meta_info().build()).unwrap();
+        let bundle = jar.osgi_bundle().unwrap().unwrap();
+
+        assert_eq!(bundle.symbolic_name, "com.example.core");
+        assert!(bundle.singleton);
+        assert_eq!(bundle.version.to_string(), "1.4.0.v2023");
+        assert_eq!(bundle.manifest_version, 2);
+        assert_eq!(bundle.exports.iter().map(|export| (export.name.as_str(), export.version.to_string())).collect::<Vec<_>>(), vec![
+            ("com.example.api", "1.4.0".to_string()),
+            ("com.example.spi", "0.0.0".to_string()),
+        ]);
+        assert_eq!(bundle.exports[0].uses, vec!["org.osgi.framework"]);
+        assert_eq!(bundle.imports.iter().map(|import| (import.name.as_str(), import.optional)).collect::<Vec<_>>(), vec![
+            ("org.osgi.framework", false),
+            ("org.slf4j", true),
+        ]);
+        assert!(bundle.imports[0].version.contains(&OsgiVersion::parse("1.9").unwrap()));
+
+        let plain = jar_from_bytes(&jar_bytes(&[("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n")]), JarOptionBuilder::builder().keep_meta_info().build()).unwrap();
+        assert_eq!(plain.osgi_bundle().unwrap(), None);
+    }
+}
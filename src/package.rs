@@ -0,0 +1,167 @@
+//! Packages of a jar's classes with their manifest metadata, as exposed by `java.lang.Package`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{Jar, JarError, Manifest};
+
+/// A package containing classes of a jar, see [Jar::packages]. Attributes are taken from the
+/// manifest section of the package, e.g. `Name: com/example/`, falling back to the main section.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Package {
+    /// Binary name of the package, e.g. `com.example`.
+    pub name: String,
+    /// Number of classes in the package.
+    pub classes: usize,
+    /// Whether all classes of the package must come from this jar.
+    pub sealed: bool,
+    pub specification_title: Option<String>,
+    pub specification_version: Option<String>,
+    pub specification_vendor: Option<String>,
+    pub implementation_title: Option<String>,
+    pub implementation_version: Option<String>,
+    pub implementation_vendor: Option<String>,
+}
+
+/// A package sealed in one jar but containing classes of other jars as well, see
+/// [sealing_violations]. Jars are identified by their index in the given list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SealingViolation {
+    /// Binary name of the package.
+    pub package: String,
+    /// Index of the jar sealing the package.
+    pub sealed_in: usize,
+    /// Indices of other jars with classes in the package.
+    pub also_in: Vec<usize>,
+}
+
+impl Jar {
+    /// Lists the packages of all extracted classes, sorted by name, with attributes from
+    /// `META-INF/MANIFEST.MF` if extracted. Classes in the unnamed package and under `META-INF`,
+    /// including versioned classes of multi-release jars, are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let jar = jar("app.jar", JarOptionBuilder::builder().keep_meta_info().ext("class").build())?;
+    ///
+    /// for package in jar.packages()?.iter().filter(|package| package.sealed) {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn packages(&self) -> Result<Vec<Package>, JarError> {
+        let manifest = self.manifest()?.unwrap_or_default();
+        let mut classes = BTreeMap::<&str, usize>::new();
+
+        for path in self.files.keys().filter(|path| !path.starts_with("META-INF/") && path.ends_with(".class")) {
+            if let Some((package, _)) = path.rsplit_once('/') {
+                *classes.entry(package).or_default() += 1;
+            }
+        }
+
+        Ok(classes.into_iter()
+            .map(|(package, classes)| package_attributes(&manifest, package, classes))
+            .collect())
+    }
+}
+
+fn package_attributes(manifest: &Manifest, package: &str, classes: usize) -> Package {
+    let section = manifest.entry(&format!("{}/", package));
+    let attribute = |name: &str| {
+        section.and_then(|attributes| attributes.get(name))
+            .or_else(|| manifest.main_attributes().get(name))
+            .map(str::to_string)
+    };
+
+    Package {
+        name: package.replace('/', "."),
+        classes,
+        sealed: attribute("Sealed").is_some_and(|sealed| sealed.trim().eq_ignore_ascii_case("true")),
+        specification_title: attribute("Specification-Title"),
+        specification_version: attribute("Specification-Version"),
+        specification_vendor: attribute("Specification-Vendor"),
+        implementation_title: attribute("Implementation-Title"),
+        implementation_version: attribute("Implementation-Version"),
+        implementation_vendor: attribute("Implementation-Vendor"),
+    }
+}
+
+/// Finds packages sealed in one of `jars` which other jars contribute classes to, e.g. before
+/// merging jars or putting them on one classpath, where the JVM fails loading such classes with a
+/// `SecurityException`. Violations are sorted by package.
+///
+/// # Example
+///
+/// ```rs
+/// let option = || JarOptionBuilder::builder().keep_meta_info().ext("class").build();
+/// let jars = [jar("a.jar", option())?, jar("b.jar", option())?];
+///
+/// for violation in sealing_violations(&jars)? {
+///     eprintln!("{} is sealed in jar {}", violation.package, violation.sealed_in);
+/// }
+/// ```
+pub fn sealing_violations(jars: &[Jar]) -> Result<Vec<SealingViolation>, JarError> {
+    let mut packages = BTreeMap::<String, Vec<(usize, bool)>>::new();
+
+    for (index, jar) in jars.iter().enumerate() {
+        for package in jar.packages()? {
+            packages.entry(package.name).or_default().push((index, package.sealed));
+        }
+    }
+
+    let mut violations = Vec::new();
+
+    for (package, jars) in packages {
+        for (sealed_in, _) in jars.iter().filter(|(_, sealed)| *sealed) {
+            let also_in = jars.iter()
+                .map(|(index, _)| *index)
+                .filter(|index| index != sealed_in)
+                .collect::<BTreeSet<_>>();
+
+            if !also_in.is_empty() {
+                violations.push(SealingViolation {
+                    package: package.clone(),
+                    sealed_in: *sealed_in,
+                    also_in: also_in.into_iter().collect(),
+                });
+            }
+        }
+    }
+
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, sealing_violations, JarOptionBuilder, SealingViolation};
+
+    #[test]
+    fn test_packages() {
+        let manifest = b"Manifest-Version: 1.0\r\nImplementation-Version: 1.0\r\nSealed: false\r\n\r\n\
+            Name: a/b/\r\nSealed: true\r\nImplementation-Version: 2.0\r\n";
+        let bytes = jar_bytes(&[
+            ("META-INF/MANIFEST.MF", manifest),
+            ("META-INF/versions/11/c/C.class", b""),
+            ("Main.class", b""),
+            ("a/A.class", b""),
+            ("a/b/B.class", b""),
+            ("a/b/C.class", b""),
+            ("a/b/c.properties", b""),
+        ]);
+        let sealed = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap();
+        let packages = sealed.packages().unwrap();
+
+        assert_eq!(packages.iter().map(|package| (package.name.as_str(), package.classes, package.sealed)).collect::<Vec<_>>(), vec![("a", 1, false), ("a.b", 2, true)]);
+        assert_eq!(packages[0].implementation_version.as_deref(), Some("1.0"));
+        assert_eq!(packages[1].implementation_version.as_deref(), Some("2.0"));
+
+        let other = jar_from_bytes(&jar_bytes(&[("a/A.class", b""), ("a/b/D.class", b"")]), JarOptionBuilder::default()).unwrap();
+
+        assert_eq!(sealing_violations(&[other, sealed]).unwrap(), vec![SealingViolation {
+            package: "a.b".to_string(),
+            sealed_in: 1,
+            also_in: vec![0],
+        }]);
+    }
+}
@@ -0,0 +1,191 @@
+//! Multi-threaded extraction, where every thread decompresses entries from its own reader.
+
+use std::collections::HashMap;
+use std::io::{BufReader, Read, Seek};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use zip::ZipArchive;
+
+use crate::integrity::EntryDecoder;
+use crate::option::map_content;
+use crate::progress::{check_cancelled, report, Progress};
+use crate::sink::TeeReader;
+use crate::spill::read_entry;
+use crate::{EntryContent, Jar, JarError, JarOption, JarStream};
+
+/// Extracts a jar on `option.threads` threads. `open` is called once to list matching entries and
+/// once per thread, so every thread seeks independently through its own buffer. Entries are handed
+/// out one at a time, which keeps threads busy when entry sizes vary widely.
+pub(crate) fn jar_parallel<R, F>(open: F, option: JarOption) -> Result<Jar, JarError>
+where
+    R: Read + Seek,
+    F: Fn() -> std::io::Result<R> + Sync,
+{
+    let threads = option.threads;
+    let capped = option.limits.is_set();
+    let progress = option.progress.clone();
+    let cancel_token = option.cancel_token.clone();
+    let skip_corrupt = option.skip_corrupt;
+    let verify_crc = !option.skip_crc;
+    let decompressors = option.decompressors.clone();
+    let content_mapper = option.content_mapper.clone();
+    let tee = option.tee.clone();
+    let spill = option.spill.clone();
+    let mut stream = JarStream::new(BufReader::new(open()?), option)?;
+    let total = stream.len();
+    let duplicates = stream.duplicates().to_vec();
+    let mut entries = Vec::new();
+
+    while let Some(next) = stream.next_match() {
+        entries.push(next?);
+    }
+
+    let unsafe_names = stream.unsafe_names().to_vec();
+    let warnings = stream.warnings().to_vec();
+    let comment = stream.comment().to_vec();
+
+    let next = AtomicUsize::new(0);
+    let results = std::thread::scope(|scope| {
+        let workers = (0..threads.min(entries.len()))
+            .map(|_| scope.spawn(|| {
+                let mut results = Vec::new();
+                let result = (|| {
+                    let mut archive = ZipArchive::new(BufReader::new(open()?))?;
+
+                    loop {
+                        let position = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((index, entry)) = entries.get(position) else {
+                            break;
+                        };
+
+                        check_cancelled(&cancel_token)?;
+
+                        let (content, bytes_read) = match EntryDecoder::open(&mut archive, *index, entry.compression, verify_crc, &decompressors)
+                            .and_then(|content| TeeReader::new(&tee, entry, content))
+                            .and_then(|mut content| read_entry(&spill, entry, &mut content, capped)) {
+                            Ok(content) => content,
+                            Err(err) if skip_corrupt && err.is_corrupt() => {
+                                results.push((position, Err(err)));
+                                continue;
+                            }
+                            Err(err) => return Err(err),
+                        };
+
+                        report(&progress, Progress {
+                            index: *index,
+                            total,
+                            name: &entry.name,
+                            bytes_read,
+                        })?;
+
+                        let content = match content {
+                            EntryContent::InMemory(content) => match map_content(&content_mapper, &entry.name, content) {
+                                Some(content) => EntryContent::InMemory(content),
+                                None => continue,
+                            },
+                            content => content,
+                        };
+
+                        results.push((position, Ok(content)));
+                    }
+
+                    Ok::<_, JarError>(())
+                })();
+
+                // Stops other threads from taking further entries.
+                if result.is_err() {
+                    next.store(entries.len(), Ordering::Relaxed);
+                }
+
+                result.map(|_| results)
+            }))
+            .collect::<Vec<_>>();
+
+        workers.into_iter()
+            .map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    let mut results = results.into_iter().flatten().collect::<Vec<_>>();
+    let mut files = HashMap::with_capacity(entries.len());
+    let mut metadata = HashMap::with_capacity(entries.len());
+    let mut order = Vec::with_capacity(entries.len());
+    let mut spilled = HashMap::new();
+    let mut failures = Vec::new();
+
+    // Workers finish in any order; merging by archive position lets later entries win over
+    // earlier ones renamed to the same path, like sequential extraction does.
+    results.sort_unstable_by_key(|(position, _)| *position);
+
+    for (position, result) in results {
+        let entry = &entries[position].1;
+
+        match result {
+            Ok(EntryContent::InMemory(content)) => {
+                files.insert(entry.name.clone(), content);
+            }
+            Ok(EntryContent::OnDisk(path)) => {
+                spilled.insert(entry.name.clone(), path);
+            }
+            Err(err) => {
+                failures.push((entry.name.clone(), err));
+                continue;
+            }
+        }
+
+        metadata.insert(entry.name.clone(), entry.clone());
+        order.push(entry.name.clone());
+    }
+
+    Ok(Jar {
+        files,
+        metadata,
+        order,
+        failures,
+        duplicates,
+        unsafe_names,
+        warnings,
+        spilled,
+        comment,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, JarOptionBuilder};
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let contents = (0..64).map(|i| (format!("a/{}.class", i), vec![i as u8; i * 100])).collect::<Vec<_>>();
+        let entries = contents.iter().map(|(name, content)| (name.as_str(), content.as_slice())).collect::<Vec<_>>();
+        let bytes = jar_bytes(&entries);
+
+        let sequential = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap();
+        let parallel = jar_from_bytes(&bytes, JarOptionBuilder::builder().parallel(4).build()).unwrap();
+
+        assert_eq!(parallel.files, sequential.files);
+        assert_eq!(parallel.entry("a/63.class"), sequential.entry("a/63.class"));
+    }
+
+    #[test]
+    fn test_parallel_renamed_collisions_match_sequential() {
+        let contents = (0..32).map(|i| (format!("v{}/a/Same.class", i), vec![i as u8; (32 - i) * 1000])).collect::<Vec<_>>();
+        let entries = contents.iter().map(|(name, content)| (name.as_str(), content.as_slice())).collect::<Vec<_>>();
+        let bytes = jar_bytes(&entries);
+        let option = || JarOptionBuilder::builder()
+            .rename(|path| path.split_once('/').map_or(path, |(_, rest)| rest).to_string());
+
+        let sequential = jar_from_bytes(&bytes, option().build()).unwrap();
+
+        assert_eq!(sequential.files["a/Same.class"], vec![31u8; 1000]);
+
+        for _ in 0..8 {
+            let parallel = jar_from_bytes(&bytes, option().parallel(4).build()).unwrap();
+
+            assert_eq!(parallel.files, sequential.files);
+            assert_eq!(parallel.order, sequential.order);
+            assert_eq!(parallel.entry("a/Same.class"), sequential.entry("a/Same.class"));
+        }
+    }
+}
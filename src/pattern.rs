@@ -0,0 +1,155 @@
+//! Glob and regular expression path patterns used by extraction targets.
+
+use std::hash::{Hash, Hasher};
+use regex::{Regex, RegexBuilder};
+
+use crate::JarError;
+
+/// A compiled path pattern. Two patterns are equal when they are compiled from the same source.
+#[derive(Debug, Clone)]
+pub(crate) struct Pattern {
+    source: String,
+    regex: Regex,
+}
+
+impl Pattern {
+    /// Compiles a glob pattern, see [crate::JarOptionBuilder::target_glob] for its syntax.
+    pub(crate) fn glob(glob: &str) -> Self {
+        let regex = Regex::new(&glob_to_regex(glob)).expect("translated glob is always a valid regex");
+
+        Self {
+            source: glob.to_string(),
+            regex,
+        }
+    }
+
+    /// Compiles a regular expression pattern.
+    pub(crate) fn regex(regex: &str) -> Result<Self, JarError> {
+        let compiled = Regex::new(regex).map_err(|err| JarError::InvalidPattern(err.to_string()))?;
+
+        Ok(Self {
+            source: regex.to_string(),
+            regex: compiled,
+        })
+    }
+
+    /// Recompiles the pattern to match ignoring case. Since paths are lowercased before matching
+    /// case-insensitively, the pattern itself must be case-insensitive as well.
+    pub(crate) fn case_insensitive(&self) -> Self {
+        let regex = RegexBuilder::new(self.regex.as_str())
+            .case_insensitive(true)
+            .build()
+            .expect("compiled regex is always valid");
+
+        Self {
+            source: self.source.clone(),
+            regex,
+        }
+    }
+
+    pub(crate) fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub(crate) fn is_match(&self, path: &str) -> bool {
+        self.regex.is_match(path)
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source && self.regex.as_str() == other.regex.as_str()
+    }
+}
+
+impl Eq for Pattern {}
+
+impl Hash for Pattern {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.regex.as_str().hash(state);
+    }
+}
+
+/// Translates a glob into an anchored regular expression. Unbalanced `[` and `{` are matched
+/// literally, so every glob is valid.
+fn glob_to_regex(glob: &str) -> String {
+    let chars = glob.chars().collect::<Vec<_>>();
+    let mut regex = String::from("^");
+    let mut alternation_depth = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                i += 1;
+                if chars.get(i + 1) == Some(&'/') {
+                    // `**/` also matches no directory at all.
+                    i += 1;
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => match chars[i + 1..].iter().position(|char| *char == ']') {
+                Some(length) if length > 0 => {
+                    let class = chars[i + 1..i + 1 + length].iter().collect::<String>();
+
+                    regex.push('[');
+                    if let Some(negated) = class.strip_prefix('!') {
+                        regex.push('^');
+                        regex.push_str(&negated.replace('\\', "\\\\"));
+                    } else {
+                        regex.push_str(&class.replace('\\', "\\\\"));
+                    }
+                    regex.push(']');
+                    i += length + 1;
+                }
+                _ => regex.push_str("\\["),
+            },
+            '{' if chars[i + 1..].contains(&'}') => {
+                alternation_depth += 1;
+                regex.push_str("(?:");
+            }
+            ',' if alternation_depth > 0 => regex.push('|'),
+            '}' if alternation_depth > 0 => {
+                alternation_depth -= 1;
+                regex.push(')');
+            }
+            char => regex.push_str(&regex::escape(&char.to_string())),
+        }
+
+        i += 1;
+    }
+
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pattern::Pattern;
+
+    #[test]
+    fn test_glob_match() {
+        let glob = Pattern::glob("com/**/internal/*.class");
+
+        assert!(glob.is_match("com/internal/A.class"));
+        assert!(glob.is_match("com/example/deep/internal/A.class"));
+        assert!(!glob.is_match("com/example/internal/sub/A.class"));
+        assert!(!glob.is_match("org/internal/A.class"));
+
+        let glob = Pattern::glob("**/*.{png,jp[!e]g}");
+
+        assert!(glob.is_match("icon.png"));
+        assert!(glob.is_match("a/b/photo.jpgg"));
+        assert!(!glob.is_match("a/b/photo.jpeg"));
+        assert!(Pattern::glob("weird[name").is_match("weird[name"));
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        assert!(Pattern::regex("(unclosed").is_err());
+    }
+}
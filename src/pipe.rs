@@ -0,0 +1,638 @@
+//! Reading jars from non-seekable streams, e.g. standard input or an HTTP response body, by walking
+//! local file headers in order instead of reading the central directory.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+
+use flate2::bufread::DeflateDecoder;
+use zip::result::ZipError;
+
+use crate::duplicate::suffixed;
+use crate::encoding::{decode_raw_name, is_raw_lossy, UnsafeName, UnsafeNamePolicy};
+use crate::option::map_content;
+use crate::progress::check_cancelled;
+use crate::trace::{trace, SkipReason, TraceEvent};
+use crate::{CompressionMethod, DateTime, Decision, DuplicatePolicy, Jar, JarEntry, JarError, JarOption, JarWarning};
+
+const LOCAL_HEADER_SIGNATURE: [u8; 4] = *b"PK\x03\x04";
+const DATA_DESCRIPTOR_SIGNATURE: [u8; 4] = *b"PK\x07\x08";
+
+/// Signatures of the central directory and end of central directory records, ending the entries.
+const CENTRAL_SIGNATURES: [[u8; 4]; 3] = [*b"PK\x01\x02", *b"PK\x05\x06", *b"PK\x06\x06"];
+
+const FLAG_ENCRYPTED: u16 = 1;
+const FLAG_DATA_DESCRIPTOR: u16 = 1 << 3;
+const FLAG_UTF8: u16 = 1 << 11;
+
+const ZIP64_EXTRA_FIELD: u16 = 1;
+
+/// A jar read front to back from a non-seekable source, see [jar_from_pipe].
+///
+/// Unlike [crate::JarStream], no central directory is read, so:
+/// - entries are found by their local headers, and entries listed only in the central directory,
+///   or removed from it by an update, are not noticed;
+/// - Unix modes are unknown, so symbolic links are extracted as regular files and permissions are
+///   not restored;
+/// - [JarEntry::extra] holds the extra field of the local header, and the archive comment is not
+///   read;
+/// - duplicates are only known once the second copy is reached, so with
+///   [DuplicatePolicy::LastWins] every copy is yielded, and with [DuplicatePolicy::KeepAll] later
+///   copies are renamed without checking names of entries not read yet;
+/// - entries whose size is only recorded in a trailing data descriptor must be deflated, since the
+///   end of stored content cannot be found otherwise;
+/// - BZIP2 and Zstandard entries require a [crate::JarOptionBuilder::decompressor];
+/// - [crate::JarOptionBuilder::release], [crate::JarOptionBuilder::on_progress],
+///   [crate::JarOptionBuilder::tee] and [crate::JarOptionBuilder::spill_to_disk] have no effect.
+///
+/// # Example
+///
+/// ```rs
+/// let mut pipe = JarPipe::new(std::io::stdin().lock(), JarOptionBuilder::builder().ext("class").build());
+///
+/// while let Some(next) = pipe.next_entry() {
+///     let (entry, content) = next?;
+///     // ...
+/// }
+/// ```
+pub struct JarPipe<R> {
+    reader: BufReader<R>,
+    option: JarOption,
+    index: usize,
+    seen: HashMap<String, usize>,
+    duplicates: Vec<String>,
+    unsafe_names: Vec<UnsafeName>,
+    warnings: Vec<JarWarning>,
+    matched: usize,
+    matched_size: u64,
+    finished: bool,
+    /// Whether the stream is at a local header or the end of the entries, so reading can continue
+    /// after a failed entry.
+    synced: bool,
+    failed_name: Option<String>,
+}
+
+/// Fields of a local file header needed to read the entry following it.
+struct LocalHeader {
+    flags: u16,
+    compression: CompressionMethod,
+    last_modified: DateTime,
+    crc32: u32,
+    compressed_size: u64,
+    size: u64,
+    name: Vec<u8>,
+    extra: Vec<u8>,
+    zip64: bool,
+}
+
+/// Content of an entry as read from the stream, with sizes and checksum as actually found.
+struct EntryData {
+    content: Vec<u8>,
+    compressed_size: u64,
+    size: u64,
+    crc32: u32,
+}
+
+impl<R> JarPipe<R> where R: Read {
+    /// Creates a [JarPipe] reading entries from `reader` with extraction rules `option`.
+    pub fn new(reader: R, option: JarOption) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            option,
+            index: 0,
+            seen: HashMap::new(),
+            duplicates: Vec::new(),
+            unsafe_names: Vec::new(),
+            warnings: Vec::new(),
+            matched: 0,
+            matched_size: 0,
+            finished: false,
+            synced: true,
+            failed_name: None,
+        }
+    }
+
+    /// Advances to the next matching entry, returning its metadata, with the name it is extracted
+    /// under, and its decompressed content. Returns [None] once the central directory or the end of
+    /// the stream is reached, or after a failure leaving the stream at an unknown position. A
+    /// checksum mismatch fails only its entry.
+    pub fn next_entry(&mut self) -> Option<Result<(JarEntry, Vec<u8>), JarError>> {
+        while !self.finished {
+            match self.read_entry() {
+                Ok(Some(next)) => return Some(Ok(next)),
+                Ok(None) => {}
+                Err(err) => {
+                    self.finished = !self.synced;
+                    return Some(Err(err));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns names found more than once so far, in the order their second copy is reached.
+    pub fn duplicates(&self) -> &[String] {
+        &self.duplicates
+    }
+
+    /// Returns entry names rejected as unsafe so far, in archive order.
+    pub fn unsafe_names(&self) -> &[UnsafeName] {
+        &self.unsafe_names
+    }
+
+    /// Returns non-fatal oddities found in entries read so far, in archive order.
+    pub fn warnings(&self) -> &[JarWarning] {
+        &self.warnings
+    }
+
+    /// Reads the next entry, returning [None] for skipped entries. Sets `finished` at the end of
+    /// the entries.
+    fn read_entry(&mut self) -> Result<Option<(JarEntry, Vec<u8>)>, JarError> {
+        check_cancelled(&self.option.cancel_token)?;
+        self.synced = false;
+
+        let Some(header) = self.read_local_header()? else {
+            self.finished = true;
+            trace(&self.option.trace, || TraceEvent::Finished {
+                entries: self.index,
+                matched: self.matched,
+                matched_size: self.matched_size,
+            });
+            return Ok(None);
+        };
+        let index = self.index;
+        self.index += 1;
+
+        if header.flags & FLAG_ENCRYPTED != 0 {
+            self.skip_data(&header)?;
+            return Err(ZipError::UnsupportedArchive("encrypted entries cannot be read from a stream").into());
+        }
+
+        let skipped = |option: &JarOption, name: &str, reason| trace(&option.trace, || TraceEvent::Skipped { index, name, reason });
+        let flagged_utf8 = header.flags & FLAG_UTF8 != 0;
+
+        if is_raw_lossy(&header.name, flagged_utf8, &self.option) {
+            self.warnings.push(JarWarning::UndecodableName { index, name: String::from_utf8_lossy(&header.name).into_owned() });
+        }
+
+        let name = match decode_raw_name(&header.name, flagged_utf8, &self.option) {
+            Ok(name) => Some(name),
+            Err(unsafe_name) if self.option.unsafe_name_policy == UnsafeNamePolicy::Error => {
+                self.skip_data(&header)?;
+                return Err(JarError::InvalidEntryName(unsafe_name.name));
+            }
+            Err(unsafe_name) => {
+                if unsafe_name.sanitized.is_none() {
+                    skipped(&self.option, &unsafe_name.name, SkipReason::UnsafeName);
+                }
+                self.warnings.push(JarWarning::UnsafeName(unsafe_name.clone()));
+                self.unsafe_names.push(unsafe_name.clone());
+                unsafe_name.sanitized
+            }
+        };
+        let Some(mut name) = name else {
+            self.skip_data(&header)?;
+            return Ok(None);
+        };
+        let is_dir = name.ends_with('/');
+
+        if is_dir && !self.option.include_dirs {
+            skipped(&self.option, &name, SkipReason::Directory);
+            self.skip_data(&header)?;
+            return Ok(None);
+        }
+        if let Decision::Skipped(reason) = self.option.matches(&name) {
+            skipped(&self.option, &name, reason);
+            self.skip_data(&header)?;
+            return Ok(None);
+        }
+
+        self.failed_name = Some(name.clone());
+
+        let data = self.read_data(&header, &name)?;
+
+        if is_dir && data.size > 0 {
+            self.warnings.push(JarWarning::DirectoryContent { name: name.clone(), size: data.size });
+        }
+        if !self.option.skip_crc && data.crc32 != crc32fast::hash(&data.content) {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid checksum").into());
+        }
+
+        self.failed_name = None;
+
+        if !is_dir {
+            let copies = self.seen.entry(name.clone()).or_default();
+
+            *copies += 1;
+            if *copies == 2 {
+                self.duplicates.push(name.clone());
+            }
+            if *copies > 1 {
+                match self.option.duplicate_policy {
+                    DuplicatePolicy::FirstWins => {
+                        skipped(&self.option, &name, SkipReason::Duplicate);
+                        self.warnings.push(JarWarning::DuplicateEntry { index, name });
+                        return Ok(None);
+                    }
+                    DuplicatePolicy::LastWins => {}
+                    DuplicatePolicy::Error => return Err(JarError::DuplicateEntry(name)),
+                    DuplicatePolicy::KeepAll => {
+                        let mut suffix = *copies - 1;
+
+                        while self.seen.contains_key(&suffixed(&name, suffix)) {
+                            suffix += 1;
+                        }
+                        name = suffixed(&name, suffix);
+                        self.seen.insert(name.clone(), 1);
+                    }
+                }
+            }
+        }
+
+        let mut entry = JarEntry {
+            name,
+            size: data.size,
+            compressed_size: data.compressed_size,
+            compression: header.compression,
+            last_modified: header.last_modified,
+            crc32: data.crc32,
+            unix_mode: None,
+            extra: header.extra.clone(),
+        };
+
+        if let Some(reason) = self.option.skip_reason(&entry) {
+            skipped(&self.option, &entry.name, reason);
+            return Ok(None);
+        }
+
+        match self.option.remap(entry.name.clone()) {
+            Some(name) => entry.name = name,
+            None => {
+                skipped(&self.option, &entry.name, SkipReason::Renamed);
+                return Ok(None);
+            }
+        }
+
+        self.matched += 1;
+        self.matched_size = self.matched_size.saturating_add(entry.size);
+        self.option.limits.check(&entry, self.matched, self.matched_size)?;
+
+        if let Some(timestamp) = self.option.timestamp {
+            entry.last_modified = timestamp;
+        }
+
+        trace(&self.option.trace, || TraceEvent::Matched { index, entry: &entry });
+
+        Ok(Some((entry, data.content)))
+    }
+
+    /// Reads the local header at the current position, or returns [None] at the central directory
+    /// or the end of the stream.
+    fn read_local_header(&mut self) -> Result<Option<LocalHeader>, JarError> {
+        if self.reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+
+        let signature = read_array::<_, 4>(&mut self.reader)?;
+
+        if CENTRAL_SIGNATURES.contains(&signature) {
+            return Ok(None);
+        }
+        if signature != LOCAL_HEADER_SIGNATURE {
+            return Err(ZipError::InvalidArchive("Invalid local file header").into());
+        }
+
+        let fixed = read_array::<_, 26>(&mut self.reader)?;
+        let u16_at = |offset: usize| u16::from_le_bytes([fixed[offset], fixed[offset + 1]]);
+        let u32_at = |offset: usize| u32::from_le_bytes([fixed[offset], fixed[offset + 1], fixed[offset + 2], fixed[offset + 3]]);
+        let mut name = vec![0; u16_at(22) as usize];
+        let mut extra = vec![0; u16_at(24) as usize];
+
+        self.reader.read_exact(&mut name)?;
+        self.reader.read_exact(&mut extra)?;
+
+        let mut header = LocalHeader {
+            flags: u16_at(2),
+            compression: CompressionMethod::from_id(u16_at(4)),
+            last_modified: zip::DateTime::from_msdos(u16_at(8), u16_at(6)).into(),
+            crc32: u32_at(10),
+            compressed_size: u32_at(14) as u64,
+            size: u32_at(18) as u64,
+            name,
+            extra: Vec::new(),
+            zip64: false,
+        };
+        let mut fields = extra.as_slice();
+
+        while fields.len() >= 4 {
+            let (id, len) = (u16::from_le_bytes([fields[0], fields[1]]), u16::from_le_bytes([fields[2], fields[3]]) as usize);
+            let data = &fields[4..fields.len().min(4 + len)];
+
+            if id == ZIP64_EXTRA_FIELD {
+                header.zip64 = true;
+
+                let mut values = data.chunks_exact(8).map(|value| u64::from_le_bytes(value.try_into().expect("chunks have 8 bytes")));
+
+                if header.size == u32::MAX as u64 {
+                    header.size = values.next().unwrap_or(header.size);
+                }
+                if header.compressed_size == u32::MAX as u64 {
+                    header.compressed_size = values.next().unwrap_or(header.compressed_size);
+                }
+            }
+
+            fields = &fields[data.len() + 4..];
+        }
+
+        header.extra = extra;
+        Ok(Some(header))
+    }
+
+    /// Reads and decompresses the content of the entry following `header`. With
+    /// [crate::JarOptionBuilder::max_entry_size], reading stops once the content exceeds it.
+    fn read_data(&mut self, header: &LocalHeader, name: &str) -> Result<EntryData, JarError> {
+        let max_size = self.option.limits.max_entry_size;
+        let decompressor = self.option.decompressors.get(&header.compression.id()).cloned();
+        let read_capped = |reader: &mut dyn Read| -> Result<Vec<u8>, JarError> {
+            let mut content = Vec::new();
+
+            match max_size {
+                Some(max_size) => {
+                    reader.take(max_size + 1).read_to_end(&mut content)?;
+                    if content.len() as u64 > max_size {
+                        return Err(JarError::LimitExceeded(format!("entry `{}` has more than {} bytes", name, max_size)));
+                    }
+                }
+                None => {
+                    reader.read_to_end(&mut content)?;
+                }
+            }
+
+            Ok(content)
+        };
+
+        if header.flags & FLAG_DATA_DESCRIPTOR == 0 {
+            let mut raw = (&mut self.reader).take(header.compressed_size);
+            let content = match (&decompressor, header.compression) {
+                (Some(decompressor), _) => decompressor.0(Box::new(&mut raw)).map_err(JarError::from).and_then(|mut reader| read_capped(&mut reader)),
+                (None, CompressionMethod::Stored) => read_capped(&mut raw),
+                (None, CompressionMethod::Deflated) => read_capped(&mut flate2::read::DeflateDecoder::new(&mut raw)),
+                (None, compression) => Err(JarError::UnsupportedCompression(compression)),
+            };
+
+            // Consumes what the decompressor left, so the next header can be read even if this
+            // entry fails.
+            std::io::copy(&mut raw, &mut std::io::sink())?;
+
+            if raw.limit() > 0 {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+
+            self.synced = true;
+
+            let content = content?;
+
+            return Ok(EntryData {
+                size: content.len() as u64,
+                content,
+                compressed_size: header.compressed_size,
+                crc32: header.crc32,
+            });
+        }
+
+        if header.compression != CompressionMethod::Deflated || decompressor.is_some() {
+            return Err(ZipError::UnsupportedArchive("only deflated entries with a data descriptor can be read from a stream").into());
+        }
+
+        let mut decoder = DeflateDecoder::new(&mut self.reader);
+        let content = read_capped(&mut decoder)?;
+        let compressed_size = decoder.total_in();
+
+        drop(decoder);
+
+        let (crc32, declared_compressed_size, declared_size) = self.read_data_descriptor(header.zip64)?;
+
+        self.synced = true;
+
+        if declared_compressed_size != compressed_size || declared_size != content.len() as u64 {
+            return Err(ZipError::InvalidArchive("Data descriptor does not match the entry").into());
+        }
+
+        Ok(EntryData {
+            size: content.len() as u64,
+            content,
+            compressed_size,
+            crc32,
+        })
+    }
+
+    /// Reads the data descriptor following deflated content, returning its CRC-32, compressed size
+    /// and uncompressed size.
+    fn read_data_descriptor(&mut self, zip64: bool) -> Result<(u32, u64, u64), JarError> {
+        let mut first = read_array::<_, 4>(&mut self.reader)?;
+
+        // The signature is optional.
+        if first == DATA_DESCRIPTOR_SIGNATURE {
+            first = read_array::<_, 4>(&mut self.reader)?;
+        }
+
+        let crc32 = u32::from_le_bytes(first);
+        let sizes = if zip64 {
+            let sizes = read_array::<_, 16>(&mut self.reader)?;
+
+            (u64::from_le_bytes(sizes[..8].try_into().expect("8 bytes")), u64::from_le_bytes(sizes[8..].try_into().expect("8 bytes")))
+        } else {
+            let sizes = read_array::<_, 8>(&mut self.reader)?;
+
+            (u32::from_le_bytes(sizes[..4].try_into().expect("4 bytes")) as u64, u32::from_le_bytes(sizes[4..].try_into().expect("4 bytes")) as u64)
+        };
+
+        Ok((crc32, sizes.0, sizes.1))
+    }
+
+    /// Skips the content of the entry following `header` without keeping it. Content whose size is
+    /// only recorded in a data descriptor is decompressed to find its end.
+    fn skip_data(&mut self, header: &LocalHeader) -> Result<(), JarError> {
+        if header.flags & FLAG_DATA_DESCRIPTOR == 0 {
+            let skipped = std::io::copy(&mut (&mut self.reader).take(header.compressed_size), &mut std::io::sink())?;
+
+            if skipped < header.compressed_size {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+
+            self.synced = true;
+            return Ok(());
+        }
+        if header.compression != CompressionMethod::Deflated {
+            return Err(ZipError::UnsupportedArchive("only deflated entries with a data descriptor can be read from a stream").into());
+        }
+
+        std::io::copy(&mut DeflateDecoder::new(&mut self.reader), &mut std::io::sink())?;
+        self.read_data_descriptor(header.zip64)?;
+        self.synced = true;
+        Ok(())
+    }
+}
+
+fn read_array<R, const N: usize>(reader: &mut R) -> Result<[u8; N], JarError> where R: Read {
+    let mut bytes = [0; N];
+
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Extracts a jar from a source without random access, e.g. standard input, walking local file
+/// headers front to back. See [JarPipe] for the limitations compared to [crate::jar_from_reader],
+/// which should be preferred whenever the source can seek. Entries failing their checksum are
+/// recorded in [Jar::failures] with [crate::JarOptionBuilder::skip_corrupt].
+///
+/// # Example
+///
+/// ```rs
+/// // curl -s https://example.com/app.jar | indexer
+/// let jar = jar_from_pipe(std::io::stdin().lock(), JarOptionBuilder::builder().ext("class").build())?;
+/// ```
+pub fn jar_from_pipe<R>(reader: R, option: JarOption) -> Result<Jar, JarError> where R: Read {
+    let skip_corrupt = option.skip_corrupt;
+    let content_mapper = option.content_mapper.clone();
+    let mut pipe = JarPipe::new(reader, option);
+    let mut files = HashMap::new();
+    let mut metadata = HashMap::new();
+    let mut order = Vec::new();
+    let mut failures = Vec::new();
+
+    while let Some(next) = pipe.next_entry() {
+        let (entry, content) = match next {
+            Ok(next) => next,
+            Err(err) if skip_corrupt && !pipe.finished && err.is_corrupt() => match pipe.failed_name.take() {
+                Some(name) => {
+                    failures.push((name, err));
+                    continue;
+                }
+                None => return Err(err),
+            },
+            Err(err) => return Err(err),
+        };
+        let Some(content) = map_content(&content_mapper, &entry.name, content) else {
+            continue;
+        };
+
+        if files.insert(entry.name.clone(), content).is_some() {
+            order.retain(|name| *name != entry.name);
+        }
+        order.push(entry.name.clone());
+        metadata.insert(entry.name.clone(), entry);
+    }
+
+    let mut duplicates = pipe.duplicates.clone();
+
+    duplicates.sort();
+
+    Ok(Jar {
+        files,
+        metadata,
+        order,
+        failures,
+        duplicates,
+        unsafe_names: pipe.unsafe_names,
+        warnings: pipe.warnings,
+        spilled: HashMap::new(),
+        comment: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+
+    use crate::fixture::{jar_bytes, zip64_jar_bytes};
+    use crate::{jar_from_bytes, jar_from_pipe, DuplicatePolicy, JarEntry, JarOptionBuilder, JarPipe};
+
+    /// Builds a jar of deflated entries whose sizes and checksums follow their content in data
+    /// descriptors, as written by `java.util.zip.ZipOutputStream`. The central directory is left
+    /// out, since streaming stops at its start.
+    fn data_descriptor_jar_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        for (name, content) in entries {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+
+            encoder.write_all(content).unwrap();
+
+            let compressed = encoder.finish().unwrap();
+
+            bytes.extend(b"PK\x03\x04");
+            bytes.extend([20, 0, 0x08, 0x08, 8, 0, 0, 0, 0x21, 0]);
+            bytes.extend([0; 12]);
+            bytes.extend((name.len() as u16).to_le_bytes());
+            bytes.extend([0, 0]);
+            bytes.extend(name.as_bytes());
+            bytes.extend(&compressed);
+            bytes.extend(b"PK\x07\x08");
+            bytes.extend(crc32fast::hash(content).to_le_bytes());
+            bytes.extend((compressed.len() as u32).to_le_bytes());
+            bytes.extend((content.len() as u32).to_le_bytes());
+        }
+
+        bytes.extend(b"PK\x05\x06");
+        bytes
+    }
+
+    #[test]
+    fn test_jar_from_pipe() {
+        let bytes = jar_bytes(&[("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n"), ("a/A.class", b"a"), ("a/b.txt", b"b"), ("c/", b"")]);
+        let option = || JarOptionBuilder::builder().target("a").include_dirs().build();
+        let jar = jar_from_pipe(bytes.as_slice(), option()).unwrap();
+        let expected = jar_from_bytes(&bytes, option()).unwrap();
+
+        assert_eq!(jar.files, expected.files);
+        // Unix modes are only recorded in the central directory.
+        let entries = |jar: &crate::Jar| jar.ordered_entries().map(|(entry, _)| JarEntry { unix_mode: None, ..entry.clone() }).collect::<Vec<_>>();
+
+        assert_eq!(entries(&jar), entries(&expected));
+
+        let jar = jar_from_pipe(zip64_jar_bytes(&[("a.bin", &[1; 100]), ("b.bin", b"b")]).as_slice(), JarOptionBuilder::default()).unwrap();
+
+        assert_eq!(jar.files["a.bin"], vec![1; 100]);
+        assert_eq!(jar.entry("b.bin").unwrap().size, 1);
+    }
+
+    #[test]
+    fn test_data_descriptors() {
+        let bytes = data_descriptor_jar_bytes(&[("skipped.txt", &[7; 4096]), ("a/A.class", &[1; 1000]), ("a/A.class", b"second")]);
+        let option = JarOptionBuilder::builder().ext("class").duplicate_policy(DuplicatePolicy::FirstWins).build();
+        let mut pipe = JarPipe::new(bytes.as_slice(), option);
+        let (entry, content) = pipe.next_entry().unwrap().unwrap();
+
+        assert_eq!((entry.name.as_str(), entry.size, entry.crc32), ("a/A.class", 1000, crc32fast::hash(&[1; 1000])));
+        assert!(entry.compressed_size < 1000);
+        assert_eq!(content, vec![1; 1000]);
+        assert!(pipe.next_entry().is_none());
+        assert_eq!(pipe.duplicates(), ["a/A.class"]);
+
+        let jar = jar_from_pipe(bytes.as_slice(), JarOptionBuilder::default()).unwrap();
+
+        assert_eq!(jar.files["a/A.class"], b"second");
+        assert_eq!(jar.files.len(), 2);
+    }
+
+    #[test]
+    fn test_corrupt_entries() {
+        let mut bytes = data_descriptor_jar_bytes(&[("a.txt", b"aaaa"), ("b.txt", b"bbbb")]);
+        let descriptor = bytes.windows(4).position(|window| window == b"PK\x07\x08").unwrap();
+        // Corrupts the CRC-32 of `a.txt` recorded in its data descriptor.
+        bytes[descriptor + 4] ^= 0xFF;
+
+        assert!(jar_from_pipe(bytes.as_slice(), JarOptionBuilder::default()).is_err());
+
+        let jar = jar_from_pipe(bytes.as_slice(), JarOptionBuilder::builder().skip_corrupt().build()).unwrap();
+
+        assert_eq!(jar.files.keys().collect::<Vec<_>>(), vec!["b.txt"]);
+        assert_eq!(jar.failures().iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), vec!["a.txt"]);
+        assert!(jar_from_pipe(b"not a jar".as_slice(), JarOptionBuilder::default()).is_err());
+        assert!(jar_from_pipe(b"".as_slice(), JarOptionBuilder::default()).unwrap().files.is_empty());
+    }
+}
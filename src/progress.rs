@@ -0,0 +1,93 @@
+//! Progress reporting and cancellation of extraction.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::option::Callback;
+use crate::JarError;
+
+/// Progress of an extraction, reported to [crate::JarOptionBuilder::on_progress] after each entry is
+/// read.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Progress<'a> {
+    /// Index of the entry in the archive, directories and entries not matching included.
+    pub index: usize,
+    /// Number of all entries in the archive.
+    pub total: usize,
+    /// Full qualified path of the entry.
+    pub name: &'a str,
+    /// Uncompressed bytes read from the entry.
+    pub bytes_read: u64,
+}
+
+pub(crate) type ProgressCallback = dyn Fn(&Progress) -> bool + Send + Sync;
+
+/// Reports progress to `callback` if present, failing with [JarError::Cancelled] when it requests to
+/// abort.
+pub(crate) fn report(callback: &Option<Callback<ProgressCallback>>, progress: Progress) -> Result<(), JarError> {
+    match callback {
+        Some(callback) if !(callback.0)(&progress) => Err(JarError::Cancelled),
+        _ => Ok(()),
+    }
+}
+
+/// Fails with [JarError::Cancelled] if `token` is present and set.
+pub(crate) fn check_cancelled(token: &Option<Callback<AtomicBool>>) -> Result<(), JarError> {
+    match token {
+        Some(token) if token.0.load(Ordering::Relaxed) => Err(JarError::Cancelled),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use crate::fixture::{jar_bytes, jar_file, temp_path};
+    use crate::{extract_to_dir, jar_from_bytes, jar_stream, JarError, JarOptionBuilder};
+
+    #[test]
+    fn test_progress() {
+        let bytes = jar_bytes(&[("a/", b""), ("a/A.class", b"a"), ("a/B.class", b"bb")]);
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let reported_by_callback = reported.clone();
+        let option = JarOptionBuilder::builder()
+            .on_progress(move |progress| {
+                reported_by_callback.lock().unwrap().push((progress.index, progress.total, progress.name.to_string(), progress.bytes_read));
+                true
+            })
+            .build();
+
+        jar_from_bytes(&bytes, option).unwrap();
+
+        assert_eq!(*reported.lock().unwrap(), vec![
+            (1, 3, "a/A.class".to_string(), 1),
+            (2, 3, "a/B.class".to_string(), 2),
+        ]);
+    }
+
+    #[test]
+    fn test_progress_abort() {
+        let entries: [(&str, &[u8]); 3] = [("a/A.class", b"a"), ("a/B.class", b"b"), ("a/C.class", b"c")];
+        let option = JarOptionBuilder::builder().on_progress(|progress| progress.index < 1).build();
+
+        assert!(matches!(jar_from_bytes(&jar_bytes(&entries), option.clone()), Err(JarError::Cancelled)));
+
+        let dest = temp_path("aborted");
+        assert!(matches!(extract_to_dir(jar_file(&entries), &dest, option), Err(JarError::Cancelled)));
+        assert!(dest.join("a/B.class").exists());
+        assert!(!dest.join("a/C.class").exists());
+    }
+
+    #[test]
+    fn test_cancel_token() {
+        let token = Arc::new(AtomicBool::new(false));
+        let option = JarOptionBuilder::builder().cancel_token(token.clone()).build();
+        let mut stream = jar_stream(jar_file(&[("a.txt", b"a"), ("b.txt", b"b")]), option.clone()).unwrap();
+
+        assert!(stream.next_entry().unwrap().is_ok());
+        token.store(true, Ordering::Relaxed);
+        assert!(matches!(stream.next_entry(), Some(Err(JarError::Cancelled))));
+        assert!(matches!(jar_from_bytes(&jar_bytes(&[("a.txt", b"a")]), option), Err(JarError::Cancelled)));
+    }
+}
@@ -0,0 +1,256 @@
+//! Reading jars from sources supporting random access by byte range, e.g. HTTP range requests or
+//! blob stores, fetching only the central directory and the entries actually read.
+
+use std::io::{Error, ErrorKind, Read, Result as IoResult, Seek, SeekFrom};
+
+use crate::{JarError, JarOption, OpenJar};
+
+/// Default number of bytes fetched at once by [RangeReader], enough to cover the end of central
+/// directory and the central directory of most jars with a single request.
+pub const DEFAULT_BLOCK_SIZE: usize = 64 * 1024;
+
+/// Number of bytes fetched by [RangeReader] for a read outside the kept ranges, enough for a local
+/// header and its name.
+const MIN_READAHEAD: usize = 1024;
+
+/// A source of bytes read by offset and length, see [jar_open_range].
+///
+/// # Example
+///
+/// ```rs
+/// struct Blob { client: Client, key: String }
+///
+/// impl RangeSource for Blob {
+///     fn size(&self) -> std::io::Result<u64> {
+///         self.client.head(&self.key).map(|head| head.content_length)
+///     }
+///
+///     fn read_at(&self, offset: u64, len: usize) -> std::io::Result<Vec<u8>> {
+///         self.client.get_range(&self.key, offset..offset + len as u64)
+///     }
+/// }
+/// ```
+pub trait RangeSource {
+    /// Returns the total number of bytes of the source.
+    fn size(&self) -> IoResult<u64>;
+
+    /// Reads `len` bytes starting at `offset`. Requests never exceed [RangeSource::size].
+    fn read_at(&self, offset: u64, len: usize) -> IoResult<Vec<u8>>;
+}
+
+impl RangeSource for [u8] {
+    fn size(&self) -> IoResult<u64> {
+        Ok(self.len() as u64)
+    }
+
+    fn read_at(&self, offset: u64, len: usize) -> IoResult<Vec<u8>> {
+        Ok(self[offset as usize..offset as usize + len].to_vec())
+    }
+}
+
+impl<S> RangeSource for &S where S: RangeSource + ?Sized {
+    fn size(&self) -> IoResult<u64> {
+        (**self).size()
+    }
+
+    fn read_at(&self, offset: u64, len: usize) -> IoResult<Vec<u8>> {
+        (**self).read_at(offset, len)
+    }
+}
+
+/// A seekable reader over a [RangeSource]. The last block of the source, holding the end of
+/// central directory and usually the central directory, is fetched once and kept. Other reads fetch
+/// a small range, doubled up to the block size while reads continue sequentially, so header reads
+/// stay cheap while entry contents are fetched in few requests.
+///
+/// The zip reader checks the local header of every entry when listing, which costs a small request
+/// per entry not covered by the kept block or a previous fetch.
+pub struct RangeReader<S> {
+    source: S,
+    size: u64,
+    position: u64,
+    block_size: usize,
+    readahead: usize,
+    tail: Option<(u64, Vec<u8>)>,
+    block: Option<(u64, Vec<u8>)>,
+    requests: usize,
+    fetched: u64,
+}
+
+impl<S> RangeReader<S> where S: RangeSource {
+    /// Creates a reader over `source`, querying its size once.
+    pub fn new(source: S) -> IoResult<Self> {
+        Ok(Self {
+            size: source.size()?,
+            source,
+            position: 0,
+            block_size: DEFAULT_BLOCK_SIZE,
+            readahead: MIN_READAHEAD,
+            tail: None,
+            block: None,
+            requests: 0,
+            fetched: 0,
+        })
+    }
+
+    /// Sets the size of the kept last block and the largest range fetched for sequential reads,
+    /// [DEFAULT_BLOCK_SIZE] by default.
+    pub fn block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.max(MIN_READAHEAD);
+        self.tail = None;
+        self
+    }
+
+    /// Returns the number of [RangeSource::read_at] calls made so far.
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    /// Returns the number of bytes fetched from the source so far.
+    pub fn fetched(&self) -> u64 {
+        self.fetched
+    }
+
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+
+    fn fetch(&mut self, offset: u64, len: usize) -> IoResult<Vec<u8>> {
+        let bytes = self.source.read_at(offset, len)?;
+
+        if bytes.len() != len {
+            return Err(Error::new(ErrorKind::UnexpectedEof, format!("range source returned {} of {} bytes at offset {}", bytes.len(), len, offset)));
+        }
+
+        self.requests += 1;
+        self.fetched += len as u64;
+        Ok(bytes)
+    }
+
+    /// Copies bytes at the current position from the kept ranges into `buf`, if they cover it.
+    fn read_cached(&mut self, buf: &mut [u8]) -> Option<usize> {
+        let (start, bytes) = [&self.tail, &self.block].into_iter()
+            .flatten()
+            .find(|(start, bytes)| (*start..*start + bytes.len() as u64).contains(&self.position))?;
+        let offset = (self.position - start) as usize;
+        let len = buf.len().min(bytes.len() - offset);
+
+        buf[..len].copy_from_slice(&bytes[offset..offset + len]);
+        self.position += len as u64;
+        Some(len)
+    }
+}
+
+impl<S> Read for RangeReader<S> where S: RangeSource {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        if self.position >= self.size || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let len = (self.size - self.position).min(buf.len() as u64) as usize;
+        let buf = &mut buf[..len];
+        let tail_start = self.size.saturating_sub(self.block_size as u64);
+
+        if self.position >= tail_start && self.tail.is_none() {
+            self.tail = Some((tail_start, self.fetch(tail_start, (self.size - tail_start) as usize)?));
+        }
+        if let Some(len) = self.read_cached(buf) {
+            return Ok(len);
+        }
+
+        if len >= self.block_size {
+            buf.copy_from_slice(&self.fetch(self.position, len)?);
+            self.position += len as u64;
+            return Ok(len);
+        }
+
+        let sequential = matches!(&self.block, Some((start, bytes)) if start + bytes.len() as u64 == self.position);
+
+        self.readahead = if sequential { (self.readahead * 2).min(self.block_size) } else { MIN_READAHEAD };
+        self.block = Some((self.position, self.fetch(self.position, (self.size - self.position).min(self.readahead.max(len) as u64) as usize)?));
+
+        Ok(self.read_cached(buf).unwrap_or_default())
+    }
+}
+
+impl<S> Seek for RangeReader<S> where S: RangeSource {
+    fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        self.position = position.ok_or_else(|| Error::new(ErrorKind::InvalidInput, "seek to a negative or overflowing position"))?;
+        Ok(self.position)
+    }
+}
+
+/// Opens a jar from a [RangeSource] as an [OpenJar]. Only the end of the archive holding the
+/// central directory is fetched when opening, and the byte ranges of entries when they are read.
+/// See [RangeReader] to tune the fetched block size.
+///
+/// # Example
+///
+/// ```rs
+/// let mut jar = jar_open_range(Blob::new(client, "releases/app.jar"), JarOptionBuilder::default())?;
+/// let names = jar.names().map(str::to_string).collect::<Vec<_>>();
+/// let manifest = jar.get("META-INF/MANIFEST.MF")?;
+/// ```
+pub fn jar_open_range<S>(source: S, option: JarOption) -> Result<OpenJar<RangeReader<S>>, JarError> where S: RangeSource {
+    OpenJar::new(RangeReader::new(source)?, option)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::{Cursor, Read};
+
+    use crate::{jar_open_range, CompressionMethod, JarOptionBuilder, JarWriter, OpenJar, RangeReader, RangeSource};
+
+    struct Recorded {
+        bytes: Vec<u8>,
+        ranges: RefCell<Vec<(u64, usize)>>,
+    }
+
+    impl RangeSource for Recorded {
+        fn size(&self) -> std::io::Result<u64> {
+            Ok(self.bytes.len() as u64)
+        }
+
+        fn read_at(&self, offset: u64, len: usize) -> std::io::Result<Vec<u8>> {
+            self.ranges.borrow_mut().push((offset, len));
+            self.bytes.as_slice().read_at(offset, len)
+        }
+    }
+
+    #[test]
+    fn test_jar_open_range() {
+        let mut writer = JarWriter::new(Cursor::new(Vec::new())).compression(CompressionMethod::Stored);
+
+        for index in 0..8 {
+            writer.write_entry(&format!("large/{}.bin", index), &vec![index as u8; 100_000]).unwrap();
+        }
+        writer.write_entry("small.txt", b"small").unwrap();
+
+        let source = Recorded { bytes: writer.finish().unwrap().into_inner(), ranges: RefCell::new(Vec::new()) };
+        let mut jar = jar_open_range(&source, JarOptionBuilder::default()).unwrap();
+
+        let fetched = || source.ranges.borrow().iter().map(|(_, len)| *len as u64).sum::<u64>();
+
+        assert_eq!(jar.len(), 10);
+        assert!(fetched() < 100_000);
+        assert_eq!(jar.get("small.txt").unwrap(), b"small");
+        assert_eq!(jar.get("large/3.bin").unwrap(), vec![3; 100_000]);
+        assert!(fetched() < 200_000);
+
+        let mut reader = RangeReader::new(source.bytes.as_slice()).unwrap().block_size(4096);
+        let mut jar = OpenJar::new(&mut reader, JarOptionBuilder::default()).unwrap();
+        let mut content = Vec::new();
+
+        jar.open("small.txt").unwrap().read_to_end(&mut content).unwrap();
+        drop(jar);
+        assert_eq!(content, b"small");
+        assert!(reader.fetched() < 32 * 1024);
+    }
+}
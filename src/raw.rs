@@ -0,0 +1,174 @@
+//! Access to the still compressed content of jar entries, for copying entries between archives
+//! without decompressing and recompressing them.
+
+use std::io::{Cursor, Read};
+
+use zip::read::ZipFile;
+use zip::ZipArchive;
+
+use crate::limits::content_buffer;
+use crate::{DateTime, JarEntry, JarError};
+
+/// An entry's content as stored in the archive, i.e. still compressed with
+/// [JarEntry::compression], together with the metadata of its headers. Obtained from
+/// [crate::OpenJar::get_raw] and written as is by [crate::JarWriter::write_raw].
+///
+/// # Example
+///
+/// ```rs
+/// let mut source = jar_open("sample/rt.jar", JarOptionBuilder::default())?;
+/// let mut writer = JarWriter::create("object.jar")?;
+///
+/// writer.write_raw(&source.get_raw("java/lang/Object.class")?)?;
+/// writer.finish()?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawEntry {
+    entry: JarEntry,
+    data: Vec<u8>,
+}
+
+impl RawEntry {
+    pub(crate) fn read(entry: JarEntry, file: &mut ZipFile) -> Result<Self, JarError> {
+        let mut data = content_buffer(&JarEntry { size: entry.compressed_size, ..entry.clone() });
+
+        file.read_to_end(&mut data)?;
+        Ok(Self { entry, data })
+    }
+
+    /// Returns the metadata of the entry, where [JarEntry::compression], [JarEntry::crc32] and the
+    /// sizes describe [RawEntry::data].
+    pub fn entry(&self) -> &JarEntry {
+        &self.entry
+    }
+
+    /// Returns the compressed content.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the compressed content, consuming the entry.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Decompresses the content, verifying its CRC-32 checksum.
+    pub fn content(&self) -> Result<Vec<u8>, JarError> {
+        let mut archive = self.archive()?;
+        let mut file = archive.by_index(0)?;
+        let mut content = content_buffer(&self.entry);
+
+        file.read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    /// Wraps the entry into a single-entry archive, since the zip implementation can only copy
+    /// compressed content from an archive being read.
+    pub(crate) fn archive(&self) -> Result<ZipArchive<Cursor<Vec<u8>>>, JarError> {
+        self.archive_at(self.entry.last_modified)
+    }
+
+    /// Wraps the entry into a single-entry archive like [RawEntry::archive], with its modification
+    /// time replaced by `last_modified`.
+    pub(crate) fn archive_at(&self, last_modified: DateTime) -> Result<ZipArchive<Cursor<Vec<u8>>>, JarError> {
+        if self.data.len() as u64 >= u32::MAX as u64 || self.entry.size >= u32::MAX as u64 {
+            return Err(JarError::LimitExceeded(format!("raw entry `{}` is 4 GiB or larger", self.entry.name)));
+        }
+
+        let method = self.entry.compression.id();
+        let date_time = last_modified.to_zip();
+        let name = self.entry.name.as_bytes();
+        let mut fields = Vec::with_capacity(26);
+
+        fields.extend_from_slice(&20u16.to_le_bytes());
+        fields.extend_from_slice(&0x0800u16.to_le_bytes());
+        fields.extend_from_slice(&method.to_le_bytes());
+        fields.extend_from_slice(&date_time.timepart().to_le_bytes());
+        fields.extend_from_slice(&date_time.datepart().to_le_bytes());
+        fields.extend_from_slice(&self.entry.crc32.to_le_bytes());
+        fields.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+        fields.extend_from_slice(&(self.entry.size as u32).to_le_bytes());
+        fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        fields.extend_from_slice(&0u16.to_le_bytes());
+
+        let mut bytes = Vec::with_capacity(self.data.len() + 2 * name.len() + 128);
+
+        bytes.extend_from_slice(b"PK\x03\x04");
+        bytes.extend_from_slice(&fields);
+        bytes.extend_from_slice(name);
+        bytes.extend_from_slice(&self.data);
+
+        let central_directory_offset = bytes.len() as u32;
+        let (version_made_by, external_attributes) = match self.entry.unix_mode {
+            Some(mode) => (3 << 8 | 20u16, mode << 16),
+            None => (20, 0),
+        };
+
+        bytes.extend_from_slice(b"PK\x01\x02");
+        bytes.extend_from_slice(&version_made_by.to_le_bytes());
+        bytes.extend_from_slice(&fields);
+        bytes.extend_from_slice(&[0; 6]);
+        bytes.extend_from_slice(&external_attributes.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(name);
+
+        let central_directory_size = bytes.len() as u32 - central_directory_offset;
+
+        bytes.extend_from_slice(b"PK\x05\x06");
+        bytes.extend_from_slice(&[0, 0, 0, 0, 1, 0, 1, 0]);
+        bytes.extend_from_slice(&central_directory_size.to_le_bytes());
+        bytes.extend_from_slice(&central_directory_offset.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+
+        Ok(ZipArchive::new(Cursor::new(bytes))?)
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use flate2::read::DeflateDecoder;
+
+    use crate::fixture::jar_file;
+    use crate::{jar_from_bytes, jar_open, CompressionMethod, JarOptionBuilder, JarWriter};
+
+    #[test]
+    fn test_raw_copy() {
+        let class = [7; 1000];
+        let path = jar_file(&[("a/A.class", &class), ("a/", b""), ("b.txt", b"b")]);
+        let mut source = jar_open(&path, JarOptionBuilder::builder().include_dirs().build()).unwrap();
+        let raw = source.get_raw("a/A.class").unwrap();
+        let mut decompressed = Vec::new();
+
+        assert_eq!(raw.entry().compression, CompressionMethod::Deflated);
+        assert_eq!(raw.data().len() as u64, raw.entry().compressed_size);
+        DeflateDecoder::new(raw.data()).read_to_end(&mut decompressed).unwrap();
+        assert_eq!(decompressed, class);
+        assert_eq!(raw.content().unwrap(), class);
+
+        let mut writer = JarWriter::new(Cursor::new(Vec::new())).compression(CompressionMethod::Stored);
+        writer.write_raw(&raw).unwrap();
+        writer.write_raw(&source.get_raw("a/").unwrap()).unwrap();
+        writer.write_raw(&source.get_raw("b.txt").unwrap()).unwrap();
+
+        let bytes = writer.finish().unwrap().into_inner();
+        let copied = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap();
+
+        assert_eq!(copied.files["a/A.class"], class);
+        assert_eq!(copied.files["b.txt"], b"b");
+        assert_eq!(copied.entry("a/A.class").unwrap().compressed_size, raw.entry().compressed_size);
+        assert_eq!(copied.entry("a/A.class").unwrap().last_modified, raw.entry().last_modified);
+        assert!(jar_open(&path, JarOptionBuilder::default()).unwrap().get_raw("missing").is_err());
+    }
+
+    #[test]
+    fn test_raw_content_detects_corruption() {
+        let path = jar_file(&[("a.txt", b"content")]);
+        let raw = jar_open(&path, JarOptionBuilder::default()).unwrap().get_raw("a.txt").unwrap();
+        let mut corrupted = raw.clone();
+
+        corrupted.entry.crc32 ^= 1;
+        assert!(corrupted.content().is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! Resolution of multi-release jar (MRJAR) entries for a target Java release.
+
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+use crate::encoding::extracted_name;
+use crate::{parse_manifest, JarError, JarOption, MANIFEST_PATH};
+
+/// Directory prefix of versioned entries inside a multi-release jar.
+pub const VERSIONS_PREFIX: &str = "META-INF/versions/";
+
+/// Resolves the effective entries of a multi-release jar for Java `release`. Returns [None] when the
+/// manifest does not declare `Multi-Release: true`, otherwise a map from entry index to effective
+/// name for every visible entry; entries shadowed by a higher applicable version, or only available
+/// for releases above `release`, are absent.
+pub(crate) fn resolve_release<R>(
+    archive: &mut ZipArchive<R>,
+    release: u32,
+    option: &JarOption,
+) -> Result<Option<HashMap<usize, String>>, JarError> where R: Read + Seek {
+    let mut manifest = Vec::new();
+
+    match archive.by_name(MANIFEST_PATH) {
+        Ok(mut file) => file.read_to_end(&mut manifest)?,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let multi_release = parse_manifest(&manifest)?
+        .main_attributes()
+        .get("Multi-Release")
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("true"));
+
+    if !multi_release {
+        return Ok(None);
+    }
+
+    // Effective name -> (version, index), where base entries have version 0.
+    let mut resolved = HashMap::<String, (u32, usize)>::new();
+
+    for index in 0..archive.len() {
+        let Some(name) = extracted_name(&archive.by_index_raw(index)?, option) else {
+            continue;
+        };
+        let (version, name) = match versioned_name(&name) {
+            Some((version, _)) if version > release => continue,
+            Some((version, name)) => (version, name.to_string()),
+            None => (0, name),
+        };
+
+        match resolved.get(&name) {
+            Some((resolved_version, _)) if *resolved_version >= version => {}
+            _ => {
+                resolved.insert(name, (version, index));
+            }
+        }
+    }
+
+    Ok(Some(resolved.into_iter().map(|(name, (_, index))| (index, name)).collect()))
+}
+
+/// Splits a versioned entry name into its release and effective name. Versions below 9 are not
+/// recognized, as the specification only defines versioned directories starting from Java 9.
+fn versioned_name(name: &str) -> Option<(u32, &str)> {
+    let (version, name) = name.strip_prefix(VERSIONS_PREFIX)?.split_once('/')?;
+    let version = version.parse::<u32>().ok().filter(|version| *version >= 9)?;
+
+    if name.is_empty() {
+        None
+    } else {
+        Some((version, name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, JarOptionBuilder};
+
+    #[test]
+    fn test_release_resolution() {
+        let bytes = jar_bytes(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nMulti-Release: true\r\n"),
+            ("a/A.class", b"8"),
+            ("a/B.class", b"8"),
+            ("META-INF/versions/9/a/A.class", b"9"),
+            ("META-INF/versions/11/a/A.class", b"11"),
+            ("META-INF/versions/17/a/B.class", b"17"),
+            ("META-INF/versions/11/a/C.class", b"11"),
+        ]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().ext("class").release(11).build()).unwrap();
+
+        assert_eq!(jar.files.len(), 3);
+        assert_eq!(jar.files["a/A.class"], b"11");
+        assert_eq!(jar.files["a/B.class"], b"8");
+        assert_eq!(jar.files["a/C.class"], b"11");
+    }
+
+    #[test]
+    fn test_release_ignored_without_multi_release() {
+        let bytes = jar_bytes(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n"),
+            ("a/A.class", b"8"),
+            ("META-INF/versions/9/a/A.class", b"9"),
+        ]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().release(17).build()).unwrap();
+
+        assert_eq!(jar.files["a/A.class"], b"8");
+        assert!(jar.files.contains_key("META-INF/versions/9/a/A.class"));
+    }
+}
@@ -0,0 +1,343 @@
+//! Package relocation, also known as shading, which moves classes into another package and
+//! rewrites references to them.
+
+use std::fmt::{Debug, Formatter};
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::{Read, Write};
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::sync::Arc;
+
+#[cfg(feature = "fs")]
+use zip::write::FileOptions;
+#[cfg(feature = "fs")]
+use zip::ZipWriter;
+
+use crate::classfile::map_utf8_constants;
+#[cfg(feature = "fs")]
+use crate::limits::content_buffer;
+use crate::option::Callback;
+use crate::{JarError, SERVICES_PREFIX, VERSIONS_PREFIX};
+#[cfg(feature = "fs")]
+use crate::JarEntry;
+#[cfg(feature = "fs")]
+use crate::{jar_stream, JarOption, JarStream};
+
+type ClassRewriter = dyn Fn(&str, &[u8], &Relocator) -> Result<Vec<u8>, JarError> + Send + Sync;
+
+/// Rules moving packages to new names, applied to entry paths, class files and
+/// `META-INF/services` provider-configuration files.
+///
+/// By default class files are rewritten by relocating every string in their constant pool, which
+/// covers class references, descriptors, signatures and string literals naming relocated classes.
+/// A custom rewriter, e.g. one based on a full bytecode library, can replace it with
+/// [Relocator::class_rewriter].
+///
+/// # Example
+///
+/// ```rs
+/// let relocator = Relocator::new()
+///     .relocate("com.google.common", "shaded.com.google.common")
+///     .relocate("org.slf4j", "shaded.org.slf4j");
+///
+/// relocate_jar("app-all.jar", "app-shaded.jar", JarOptionBuilder::default(), &relocator)?;
+/// ```
+#[derive(Clone, Default)]
+pub struct Relocator {
+    /// Pairs of package prefixes in internal form, e.g. `com/google/common/`.
+    rules: Vec<(String, String)>,
+    class_rewriter: Option<Callback<ClassRewriter>>,
+}
+
+impl Debug for Relocator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Relocator").field("rules", &self.rules).finish()
+    }
+}
+
+impl Relocator {
+    /// Creates a [Relocator] without rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves package `from` and all its subpackages to `to`, both given as binary names, e.g.
+    /// `com.google.common`. Rules are tried in the order they are added.
+    pub fn relocate(mut self, from: &str, to: &str) -> Self {
+        let internal = |package: &str| format!("{}/", package.trim_matches('.').replace('.', "/"));
+
+        self.rules.push((internal(from), internal(to)));
+        self
+    }
+
+    /// Replaces the built-in rewriting of class files. `rewriter` receives the relocated entry path,
+    /// the original class file and this [Relocator], e.g. to call [Relocator::relocate_name] for
+    /// every class reference, and returns the rewritten class file.
+    pub fn class_rewriter<F>(mut self, rewriter: F) -> Self
+        where F: Fn(&str, &[u8], &Relocator) -> Result<Vec<u8>, JarError> + Send + Sync + 'static {
+        self.class_rewriter = Some(Callback(Arc::new(rewriter)));
+        self
+    }
+
+    /// Relocates a class or package name given in internal form, e.g. `com/google/common/Lists`,
+    /// or as a binary name, e.g. `com.google.common.Lists`. Returns [None] if no rule applies.
+    pub fn relocate_name(&self, name: &str) -> Option<String> {
+        if name.contains('/') || !name.contains('.') {
+            return self.relocate_internal(name);
+        }
+
+        self.relocate_internal(&name.replace('.', "/")).map(|relocated| relocated.replace('/', "."))
+    }
+
+    /// Relocates an entry path, e.g. `com/google/common/Lists.class`, including versioned entries
+    /// of multi-release jars and provider-configuration files named after a relocated service.
+    /// Returns [None] if no rule applies.
+    pub fn relocate_path(&self, path: &str) -> Option<String> {
+        if let Some(service) = path.strip_prefix(SERVICES_PREFIX) {
+            return Some(format!("{}{}", SERVICES_PREFIX, self.relocate_name(service)?));
+        }
+
+        if let Some((version, versioned)) = path.strip_prefix(VERSIONS_PREFIX).and_then(|path| path.split_once('/')) {
+            return Some(format!("{}{}/{}", VERSIONS_PREFIX, version, self.relocate_internal(versioned)?));
+        }
+
+        self.relocate_internal(path)
+    }
+
+    /// Relocates every occurrence of a relocated package inside `value`, in internal form or as a
+    /// binary name, e.g. within descriptors like `(Lcom/google/common/Lists;)V`. Returns [None] if
+    /// nothing is relocated.
+    pub fn relocate_string(&self, value: &str) -> Option<String> {
+        let mut relocated = value.to_string();
+        let mut changed = false;
+
+        for (from, to) in &self.rules {
+            let (dotted_from, dotted_to) = (from.replace('/', "."), to.replace('/', "."));
+
+            for (from, to) in [(from.as_str(), to.as_str()), (dotted_from.as_str(), dotted_to.as_str())] {
+                if let Some(replaced) = replace_at_boundaries(&relocated, from, to) {
+                    relocated = replaced;
+                    changed = true;
+                }
+            }
+        }
+
+        changed.then_some(relocated)
+    }
+
+    /// Checks whether the content of the entry at `path` may need rewriting, i.e. it is a class file
+    /// or provider-configuration file and there is any rule.
+    fn rewrites(&self, path: &str) -> bool {
+        !self.rules.is_empty()
+            && (path.ends_with(".class") || path.strip_prefix(SERVICES_PREFIX).is_some_and(|service| !service.is_empty() && !service.contains('/')))
+    }
+
+    /// Rewrites the content of an entry at original path `path` whose relocated path is `relocated`.
+    /// Class files are rewritten by the class rewriter, and provider-configuration files have their
+    /// implementation class names relocated. Returns [None] if the content is unchanged.
+    pub fn relocate_content(&self, path: &str, relocated: &str, content: &[u8]) -> Result<Option<Vec<u8>>, JarError> {
+        if !self.rewrites(path) {
+            return Ok(None);
+        }
+
+        if path.ends_with(".class") {
+            return match &self.class_rewriter {
+                Some(rewriter) => (rewriter.0)(relocated, content, self).map(Some),
+                None => {
+                    let mut changed = false;
+                    let rewritten = map_utf8_constants(content, |value| {
+                        let relocated = self.relocate_string(value);
+                        changed |= relocated.is_some();
+                        relocated
+                    })?;
+
+                    Ok(changed.then_some(rewritten))
+                }
+            };
+        }
+
+        let mut changed = false;
+        let rewritten = String::from_utf8_lossy(content)
+            .lines()
+            .map(|line| {
+                let (provider, comment) = line.split_once('#').map_or((line, None), |(provider, comment)| (provider, Some(comment)));
+                let relocated = self.relocate_name(provider.trim());
+                changed |= relocated.is_some();
+
+                match (relocated, comment) {
+                    (Some(relocated), Some(comment)) => format!("{} #{}\n", relocated, comment),
+                    (Some(relocated), None) => format!("{}\n", relocated),
+                    (None, _) => format!("{}\n", line),
+                }
+            })
+            .collect::<String>();
+
+        Ok(changed.then(|| rewritten.into_bytes()))
+    }
+
+    fn relocate_internal(&self, name: &str) -> Option<String> {
+        self.rules.iter().find_map(|(from, to)| {
+            let rest = name.strip_prefix(from.as_str())
+                .or_else(|| (name == &from[..from.len() - 1]).then_some(""))?;
+
+            if rest.is_empty() && !name.ends_with('/') {
+                Some(to[..to.len() - 1].to_string())
+            } else {
+                Some(format!("{}{}", to, rest))
+            }
+        })
+    }
+}
+
+/// Replaces occurrences of package prefix `from` which start a name, i.e. are not preceded by a
+/// name character, or only by the `L` of a type descriptor.
+fn replace_at_boundaries(value: &str, from: &str, to: &str) -> Option<String> {
+    let is_name_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '$' | '/' | '.');
+    let mut replaced = String::with_capacity(value.len());
+    let mut last = 0;
+
+    for (start, _) in value.match_indices(from) {
+        let mut preceding = value[..start].chars().rev();
+        let boundary = match preceding.next() {
+            None => true,
+            Some('L') => preceding.next().is_none_or(|c| !is_name_char(c)),
+            Some(c) => !is_name_char(c),
+        };
+
+        if boundary && start >= last {
+            replaced.push_str(&value[last..start]);
+            replaced.push_str(to);
+            last = start + from.len();
+        }
+    }
+
+    if last == 0 {
+        return None;
+    }
+
+    replaced.push_str(&value[last..]);
+    Some(replaced)
+}
+
+/// Writes a new jar at `dest` containing the entries of the jar at `src` which pass the extraction
+/// rules of `option`, relocated by `relocator`. Entries whose content does not need rewriting are
+/// copied in compressed form, others are recompressed with their original compression method and
+/// timestamp. Returns metadata of all source entries, in their original order, paired with their
+/// written paths.
+///
+/// Note that relocating entries of a signed jar invalidates its signature.
+///
+/// # Example
+///
+/// ```rs
+/// let relocator = Relocator::new().relocate("com.google.common", "shaded.com.google.common");
+///
+/// relocate_jar("app.jar", "app-shaded.jar", JarOptionBuilder::default(), &relocator)?;
+/// ```
+#[cfg(feature = "fs")]
+pub fn relocate_jar<P, D>(src: P, dest: D, option: JarOption, relocator: &Relocator) -> Result<Vec<(JarEntry, String)>, JarError>
+    where P: AsRef<Path>, D: AsRef<Path> {
+    let mut stream = jar_stream(src, option)?;
+    let mut writer = ZipWriter::new(File::create(dest)?);
+    let mut written = Vec::new();
+
+    while let Some(next) = stream.next_match() {
+        let (index, entry) = next?;
+
+        let name = write_relocated(&mut writer, &mut stream, index, &entry, relocator)?;
+        written.push((entry, name));
+    }
+
+    writer.finish()?;
+    Ok(written)
+}
+
+/// Writes entry `index` of `archive` under its relocated path, returning the path.
+#[cfg(feature = "fs")]
+pub(crate) fn write_relocated<W, R>(
+    writer: &mut ZipWriter<W>,
+    stream: &mut JarStream<R>,
+    index: usize,
+    entry: &JarEntry,
+    relocator: &Relocator,
+) -> Result<String, JarError>
+where
+    W: Write + std::io::Seek,
+    R: Read + std::io::Seek,
+{
+    let name = relocator.relocate_path(&entry.name).unwrap_or_else(|| entry.name.clone());
+    let mut content = content_buffer(entry);
+
+    if relocator.rewrites(&entry.name) {
+        stream.decode(index, entry)?.read_to_end(&mut content)?;
+    }
+
+    match relocator.relocate_content(&entry.name, &name, &content)? {
+        Some(content) => {
+            let mut options = FileOptions::default()
+                .compression_method(entry.compression.into())
+                .last_modified_time(entry.last_modified.to_zip())
+                .large_file(content.len() as u64 >= u32::MAX as u64);
+
+            if let Some(mode) = entry.unix_mode {
+                options = options.unix_permissions(mode);
+            }
+
+            writer.start_file(name.as_str(), options)?;
+            writer.write_all(&content)?;
+        }
+        None => writer.raw_copy_file_rename(stream.archive_mut().by_index_raw(index)?, name.as_str())?,
+    }
+
+    Ok(name)
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use crate::classfile::tests::{class_bytes, reference, utf8};
+    use crate::classfile::ClassFile;
+    use crate::fixture::{jar_file, temp_path};
+    use crate::{jar, relocate_jar, JarOptionBuilder, Relocator};
+
+    #[test]
+    fn test_relocate_names() {
+        let relocator = Relocator::new().relocate("com.google.common", "shaded.guava");
+
+        assert_eq!(relocator.relocate_name("com/google/common/base/Strings").as_deref(), Some("shaded/guava/base/Strings"));
+        assert_eq!(relocator.relocate_name("com.google.common.base.Strings").as_deref(), Some("shaded.guava.base.Strings"));
+        assert_eq!(relocator.relocate_name("com/google/commonx/A"), None);
+        assert_eq!(relocator.relocate_path("META-INF/versions/9/com/google/common/A.class").as_deref(), Some("META-INF/versions/9/shaded/guava/A.class"));
+        assert_eq!(
+            relocator.relocate_string("(Lcom/google/common/A;[Lcom/google/common/B;)Lxcom/google/common/C;").as_deref(),
+            Some("(Lshaded/guava/A;[Lshaded/guava/B;)Lxcom/google/common/C;"),
+        );
+        assert_eq!(relocator.relocate_string("load com.google.common.A").as_deref(), Some("load shaded.guava.A"));
+        assert_eq!(relocator.relocate_string("java/lang/Object"), None);
+    }
+
+    #[test]
+    fn test_relocate_jar() {
+        let class = class_bytes(61, &[utf8("com/google/common/A"), reference(7, 1), utf8("java/lang/Object"), reference(7, 3)], 2, 4, &[]);
+        let main = class_bytes(61, &[utf8("app/Main"), reference(7, 1), utf8("com/google/common/A"), reference(7, 3)], 2, 4, &[]);
+        let src = jar_file(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n"),
+            ("META-INF/services/com.google.common.Service", b"com.google.common.Impl # default\n"),
+            ("com/google/common/A.class", &class),
+            ("app/Main.class", &main),
+        ]);
+        let dest = temp_path("relocated.jar");
+        let relocator = Relocator::new().relocate("com.google.common", "shaded.com.google.common");
+        let written = relocate_jar(&src, &dest, JarOptionBuilder::default(), &relocator).unwrap();
+
+        assert_eq!(written[2].1, "shaded/com/google/common/A.class");
+
+        let relocated = jar(&dest, JarOptionBuilder::builder().keep_meta_info().ext("class").build()).unwrap();
+        let class = ClassFile::parse(&relocated.files["shaded/com/google/common/A.class"]).unwrap();
+
+        assert_eq!(class.class_name(2).unwrap(), "shaded/com/google/common/A");
+        assert_eq!(ClassFile::parse(&relocated.files["app/Main.class"]).unwrap().class_name(4).unwrap(), "shaded/com/google/common/A");
+        assert_eq!(relocated.services()["shaded.com.google.common.Service"], vec!["shaded.com.google.common.Impl"]);
+    }
+}
@@ -0,0 +1,561 @@
+//! Downloading jars from URLs and Maven repositories, built with feature `remote`.
+
+use std::fmt::{Debug, Formatter};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ring::digest::{digest, Algorithm, SHA1_FOR_LEGACY_USE_ONLY, SHA256};
+
+use crate::{jar_from_bytes, Jar, JarError, JarOption};
+
+/// Environment variable overriding the default cache directory of [Remote].
+pub const CACHE_DIR_VAR: &str = "JARS_CACHE_DIR";
+
+const MAX_REDIRECTS: usize = 5;
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Largest response the built-in fetcher reads, so a hostile server cannot exhaust memory.
+const MAX_RESPONSE_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Checksum files published next to Maven artifacts, strongest first.
+const MAVEN_CHECKSUMS: [(&str, &Algorithm); 2] = [("sha256", &SHA256), ("sha1", &SHA1_FOR_LEGACY_USE_ONLY)];
+
+/// Downloads the body of a URL for [Remote], set with [Remote::with_fetcher], e.g. to wrap an
+/// HTTPS client. Implemented by functions and closures taking the URL.
+pub trait Fetcher: Send + Sync {
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, JarError>;
+}
+
+impl<F> Fetcher for F where F: Fn(&str) -> Result<Vec<u8>, JarError> + Send + Sync {
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, JarError> {
+        self(url)
+    }
+}
+
+/// Downloads jars and caches them on disk, so each URL is fetched once. Cached files are never
+/// refreshed, so remove the cache directory to download again.
+///
+/// The built-in fetcher supports `http://` and `file://` URLs only, since this crate ships no TLS
+/// implementation. Fetching `https://` URLs, including Maven Central, requires a [Fetcher]
+/// wrapping an HTTPS client, set with [Remote::fetcher]. It follows redirects to `http://` URLs
+/// only, connects and reads with a timeout of 60 seconds, and rejects responses above 1 GiB.
+///
+/// # Example
+///
+/// ```rs
+/// let remote = Remote::new()
+///     .cache_dir("target/jars")
+///     .fetcher(|url| {
+///         let response = reqwest::blocking::get(url).and_then(|response| response.error_for_status());
+///
+///         response.and_then(|response| response.bytes())
+///             .map(|bytes| bytes.to_vec())
+///             .map_err(|err| JarError::Fetch(err.to_string()))
+///     });
+/// let guava = remote.jar_from_maven("com.google.guava:guava:32.1.2-jre", "https://repo.maven.apache.org/maven2", JarOptionBuilder::default())?;
+/// ```
+#[derive(Clone)]
+pub struct Remote {
+    cache_dir: Option<PathBuf>,
+    fetcher: Arc<dyn Fetcher>,
+}
+
+impl Default for Remote {
+    fn default() -> Self {
+        let cache_dir = std::env::var_os(CACHE_DIR_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| std::env::temp_dir().join("jars-cache"));
+
+        Self {
+            cache_dir: Some(cache_dir),
+            fetcher: Arc::new(fetch),
+        }
+    }
+}
+
+impl Debug for Remote {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Remote").field("cache_dir", &self.cache_dir).finish_non_exhaustive()
+    }
+}
+
+impl Remote {
+    /// Creates a [Remote] with the built-in fetcher, caching into the directory named by
+    /// [CACHE_DIR_VAR], or `jars-cache` in the temporary directory if unset.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caches downloaded files into `dir`, which is created on demand.
+    pub fn cache_dir<P>(mut self, dir: P) -> Self where P: AsRef<Path> {
+        self.cache_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Disables caching, so every call downloads again.
+    pub fn no_cache(mut self) -> Self {
+        self.cache_dir = None;
+        self
+    }
+
+    /// Downloads with given function instead of the built-in fetcher, which receives the URL and
+    /// returns the response body.
+    pub fn fetcher<F>(self, fetcher: F) -> Self where F: Fn(&str) -> Result<Vec<u8>, JarError> + Send + Sync + 'static {
+        self.with_fetcher(fetcher)
+    }
+
+    /// Downloads with given [Fetcher] instead of the built-in fetcher, e.g. a type holding a
+    /// reusable HTTPS client.
+    pub fn with_fetcher<F>(mut self, fetcher: F) -> Self where F: Fetcher + 'static {
+        self.fetcher = Arc::new(fetcher);
+        self
+    }
+
+    /// Downloads `url`, or reads it from the cache if downloaded before. `file://` URLs are never
+    /// cached.
+    pub fn fetch(&self, url: &str) -> Result<Vec<u8>, JarError> {
+        let cache_path = match &self.cache_dir {
+            Some(dir) if !url.starts_with("file://") => dir.join(cache_file_name(url)),
+            _ => return self.fetcher.fetch(url),
+        };
+
+        if cache_path.is_file() {
+            return Ok(std::fs::read(&cache_path)?);
+        }
+
+        let content = self.fetcher.fetch(url)?;
+        let temp_path = cache_path.with_extension(format!("{}.tmp", std::process::id()));
+
+        std::fs::create_dir_all(cache_path.parent().expect("cache path is inside the cache directory"))?;
+        std::fs::write(&temp_path, &content)?;
+        std::fs::rename(&temp_path, &cache_path)?;
+
+        Ok(content)
+    }
+
+    /// Downloads the jar at `url` and extracts it with given [JarOption].
+    pub fn jar_from_url(&self, url: &str, option: JarOption) -> Result<Jar, JarError> {
+        jar_from_bytes(&self.fetch(url)?, option)
+    }
+
+    /// Downloads the artifact with given coordinates from the Maven repository at `repository`,
+    /// e.g. `file:///home/me/.m2/repository`, and extracts it with given [JarOption]. See [maven_url] for the
+    /// supported coordinates.
+    ///
+    /// The artifact is verified against the `.sha256` or else `.sha1` checksum file published next
+    /// to it, failing with [JarError::Fetch] on a mismatch or if neither can be fetched. Local
+    /// `file://` repositories may lack checksum files, since `mvn install` does not write them.
+    /// Artifacts failing verification are removed from the cache along with their checksum files,
+    /// so the next call downloads them again.
+    pub fn jar_from_maven(&self, coordinates: &str, repository: &str, option: JarOption) -> Result<Jar, JarError> {
+        let url = maven_url(coordinates, repository)?;
+        let content = self.fetch(&url)?;
+
+        if let Err(err) = self.verify_checksum(&url, &content) {
+            self.evict(&url)?;
+            for (extension, _) in MAVEN_CHECKSUMS {
+                self.evict(&format!("{}.{}", url, extension))?;
+            }
+
+            return Err(err);
+        }
+
+        jar_from_bytes(&content, option)
+    }
+
+    /// Removes the cached file of `url`, if any.
+    fn evict(&self, url: &str) -> Result<(), JarError> {
+        let Some(dir) = &self.cache_dir else {
+            return Ok(());
+        };
+
+        match std::fs::remove_file(dir.join(cache_file_name(url))) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(JarError::Io(err)),
+            _ => Ok(()),
+        }
+    }
+
+    fn verify_checksum(&self, url: &str, content: &[u8]) -> Result<(), JarError> {
+        for (extension, algorithm) in MAVEN_CHECKSUMS {
+            let Ok(checksum) = self.fetch(&format!("{}.{}", url, extension)) else {
+                continue;
+            };
+            let expected = String::from_utf8_lossy(&checksum).split_whitespace().next().unwrap_or_default().to_ascii_lowercase();
+            let actual = hex(digest(algorithm, content).as_ref());
+
+            if expected != actual {
+                return Err(JarError::Fetch(format!("{} checksum of `{}` is {}, expected {}", extension, url, actual, expected)));
+            }
+
+            return Ok(());
+        }
+
+        if url.starts_with("file://") {
+            return Ok(());
+        }
+
+        Err(JarError::Fetch(format!("no checksum found for `{}`", url)))
+    }
+}
+
+/// Downloads the jar at `url` with the default [Remote] and extracts it with given [JarOption].
+///
+/// # Example
+///
+/// ```rs
+/// let jar = jar_from_url("http://repository.example.com/app.jar", JarOptionBuilder::default())?;
+/// ```
+pub fn jar_from_url(url: &str, option: JarOption) -> Result<Jar, JarError> {
+    Remote::default().jar_from_url(url, option)
+}
+
+/// Downloads a Maven artifact with the default [Remote] and extracts it with given [JarOption].
+///
+/// # Example
+///
+/// ```rs
+/// let jar = jar_from_maven("org.slf4j:slf4j-api:2.0.9", "file:///home/me/.m2/repository", JarOptionBuilder::default())?;
+/// ```
+pub fn jar_from_maven(coordinates: &str, repository: &str, option: JarOption) -> Result<Jar, JarError> {
+    Remote::default().jar_from_maven(coordinates, repository, option)
+}
+
+/// Resolves Maven coordinates `group:artifact:version[:classifier][@extension]` to the URL of the
+/// artifact in the Maven repository at `repository`. The extension defaults to `jar`. Snapshot
+/// versions are not resolved to timestamped versions, so they only resolve in local repositories.
+///
+/// # Example
+///
+/// ```rs
+/// let url = maven_url("org.lwjgl:lwjgl:3.3.3:natives-linux", "https://repo.maven.apache.org/maven2")?;
+/// // https://repo.maven.apache.org/maven2/org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3-natives-linux.jar
+/// ```
+pub fn maven_url(coordinates: &str, repository: &str) -> Result<String, JarError> {
+    let (coordinates, extension) = coordinates.split_once('@').unwrap_or((coordinates, "jar"));
+    let parts = coordinates.split(':').collect::<Vec<_>>();
+    let (group_id, artifact_id, version, classifier) = match parts[..] {
+        [group_id, artifact_id, version] => (group_id, artifact_id, version, None),
+        [group_id, artifact_id, version, classifier] => (group_id, artifact_id, version, Some(classifier)),
+        _ => return Err(invalid_coordinates(coordinates)),
+    };
+
+    if ![artifact_id, version, extension].into_iter().chain(classifier).chain(group_id.split('.')).all(is_coordinate_part) {
+        return Err(invalid_coordinates(coordinates));
+    }
+
+    let classifier = classifier.map(|classifier| format!("-{}", classifier)).unwrap_or_default();
+
+    Ok(format!(
+        "{}/{}/{}/{}/{}-{}{}.{}",
+        repository.trim_end_matches('/'), group_id.replace('.', "/"), artifact_id, version,
+        artifact_id, version, classifier, extension,
+    ))
+}
+
+/// Checks whether `part` of Maven coordinates is safe to use as a URL path segment, rejecting
+/// empty parts, `.` and `..` as well as separators and query characters.
+fn is_coordinate_part(part: &str) -> bool {
+    !part.is_empty()
+        && part != "." && part != ".."
+        && part.chars().all(|char| char.is_ascii_alphanumeric() || matches!(char, '.' | '-' | '_' | '+'))
+}
+
+fn invalid_coordinates(coordinates: &str) -> JarError {
+    JarError::Fetch(format!("invalid Maven coordinates `{}`, expected group:artifact:version", coordinates))
+}
+
+/// Names the cache file of `url` by a hash of the URL followed by its last path segment, so cached
+/// files stay recognizable.
+fn cache_file_name(url: &str) -> String {
+    let hash = hex(&digest(&SHA256, url.as_bytes()).as_ref()[..8]);
+    let file_name = url.split(['?', '#']).next().unwrap_or(url).rsplit('/').next().unwrap_or_default()
+        .chars()
+        .map(|char| if char.is_ascii_alphanumeric() || matches!(char, '.' | '-' | '_') { char } else { '_' })
+        .collect::<String>();
+
+    format!("{}-{}", hash, file_name)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The built-in fetcher, reading `file://` URLs from the file system and `http://` URLs with a
+/// minimal HTTP/1.1 client following redirects to `http://` URLs only. Redirects never lead to
+/// `file://` URLs, so a server cannot make it read local files.
+fn fetch(url: &str) -> Result<Vec<u8>, JarError> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return Ok(std::fs::read(path)?);
+    }
+
+    let mut url = url.to_string();
+
+    for _ in 0..=MAX_REDIRECTS {
+        let Some(location) = url.strip_prefix("http://") else {
+            let scheme = url.split_once("://").map_or("", |(scheme, _)| scheme);
+            return Err(JarError::Fetch(format!("unsupported URL scheme `{}` of `{}`, configure a fetcher with Remote::fetcher", scheme, url)));
+        };
+
+        match http_get(location)? {
+            Response::Body(body) => return Ok(body),
+            Response::Redirect(target) if target.starts_with('/') => {
+                let authority = location.split('/').next().unwrap_or_default();
+                url = format!("http://{}{}", authority, target);
+            }
+            Response::Redirect(target) if target.starts_with("http://") => url = target,
+            Response::Redirect(target) => return Err(JarError::Fetch(format!("`{}` redirects to unsupported location `{}`", url, target))),
+            Response::Status(status) => return Err(JarError::Fetch(format!("`{}` returned HTTP status {}", url, status))),
+        }
+    }
+
+    Err(JarError::Fetch(format!("more than {} redirects fetching `{}`", MAX_REDIRECTS, url)))
+}
+
+enum Response {
+    Body(Vec<u8>),
+    Redirect(String),
+    Status(u16),
+}
+
+/// Sends a GET request to `location`, an `http://` URL without scheme, and reads the response
+/// until the server closes the connection.
+fn http_get(location: &str) -> Result<Response, JarError> {
+    let (authority, path) = location.find(['/', '?'])
+        .map_or((location, "/"), |index| (&location[..index], &location[index..]));
+    let address = if authority.rsplit_once(':').is_some_and(|(_, port)| port.bytes().all(|byte| byte.is_ascii_digit())) {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+    let path = if path.starts_with('?') { format!("/{}", path) } else { path.to_string() };
+    let mut stream = connect(&address)?;
+
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: jars/{}\r\nAccept: */*\r\nConnection: close\r\n\r\n",
+        path, authority, env!("CARGO_PKG_VERSION"),
+    )?;
+
+    let mut response = Vec::new();
+    (&mut stream).take(MAX_RESPONSE_SIZE + 1).read_to_end(&mut response)?;
+
+    if response.len() as u64 > MAX_RESPONSE_SIZE {
+        return Err(JarError::Fetch(format!("response from `{}` exceeds {} bytes", authority, MAX_RESPONSE_SIZE)));
+    }
+
+    let malformed = || JarError::Fetch(format!("malformed HTTP response from `{}`", authority));
+    let header_end = response.windows(4).position(|window| window == b"\r\n\r\n").ok_or_else(malformed)?;
+    let head = String::from_utf8_lossy(&response[..header_end]).into_owned();
+    let mut body = response.split_off(header_end + 4);
+    let mut lines = head.split("\r\n");
+    let status = lines.next()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or_else(malformed)?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim()))
+        .collect::<Vec<_>>();
+    let header = |name: &str| headers.iter().find(|(header, _)| header == name).map(|(_, value)| *value);
+
+    match status {
+        200 => {}
+        301 | 302 | 303 | 307 | 308 => return header("location").map(|location| Response::Redirect(location.to_string())).ok_or_else(malformed),
+        status => return Ok(Response::Status(status)),
+    }
+
+    if header("transfer-encoding").is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked")) {
+        body = decode_chunked(&body).ok_or_else(malformed)?;
+    } else if let Some(length) = header("content-length") {
+        let length = length.parse::<usize>().map_err(|_| malformed())?;
+
+        if body.len() < length {
+            return Err(JarError::Fetch(format!("response from `{}` ended after {} of {} bytes", authority, body.len(), length)));
+        }
+        body.truncate(length);
+    }
+
+    Ok(Response::Body(body))
+}
+
+/// Connects to the first reachable address `address` resolves to, waiting at most [TIMEOUT] for
+/// each.
+fn connect(address: &str) -> Result<TcpStream, JarError> {
+    let mut last_error = None;
+
+    for address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    Err(last_error.map_or_else(|| JarError::Fetch(format!("`{}` resolves to no address", address)), JarError::Io))
+}
+
+fn decode_chunked(mut bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut body = Vec::with_capacity(bytes.len());
+
+    loop {
+        let line_end = bytes.windows(2).position(|window| window == b"\r\n")?;
+        let size = std::str::from_utf8(&bytes[..line_end]).ok()?.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+
+        bytes = &bytes[line_end + 2..];
+        if size == 0 {
+            return Some(body);
+        }
+
+        body.extend_from_slice(bytes.get(..size)?);
+        bytes = bytes.get(size + 2..)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use ring::digest::{digest, SHA256};
+
+    use crate::fixture::{jar_bytes, temp_path};
+    use crate::{maven_url, JarError, JarOptionBuilder, Remote};
+
+    const MAVEN_CENTRAL: &str = "https://repo.maven.apache.org/maven2";
+
+    /// Serves `count` requests: `/app.jar` chunked, `/latest.jar` as a redirect to `/app.jar`,
+    /// `/local.jar` as a redirect to a local file and everything else as not found.
+    fn serve(content: Vec<u8>, count: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = stream.read(&mut buffer).unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                }
+
+                let request = String::from_utf8(request).unwrap();
+                let path = request.split(' ').nth(1).unwrap();
+
+                match path {
+                    "/app.jar" => {
+                        let (first, second) = content.split_at(content.len() / 2);
+
+                        write!(stream, "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n").unwrap();
+                        for chunk in [first, second] {
+                            write!(stream, "{:x}\r\n", chunk.len()).unwrap();
+                            stream.write_all(chunk).unwrap();
+                            write!(stream, "\r\n").unwrap();
+                        }
+                        write!(stream, "0\r\n\r\n").unwrap();
+                    }
+                    "/latest.jar" => write!(stream, "HTTP/1.1 302 Found\r\nLocation: /app.jar\r\nContent-Length: 0\r\n\r\n").unwrap(),
+                    "/local.jar" => write!(stream, "HTTP/1.1 302 Found\r\nLocation: file:///etc/hostname\r\nContent-Length: 0\r\n\r\n").unwrap(),
+                    _ => write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n").unwrap(),
+                }
+            }
+        });
+
+        format!("http://{}", address)
+    }
+
+    #[test]
+    fn test_jar_from_url() {
+        let server = serve(jar_bytes(&[("a/A.class", b"a")]), 4);
+        let remote = Remote::new().cache_dir(temp_path("cache"));
+
+        assert_eq!(remote.jar_from_url(&format!("{}/latest.jar", server), JarOptionBuilder::default()).unwrap().files["a/A.class"], b"a");
+        assert!(matches!(remote.fetch(&format!("{}/missing.jar", server)), Err(JarError::Fetch(_))));
+        assert!(remote.fetch(&format!("{}/local.jar", server)).is_err_and(|err| err.to_string().contains("unsupported location `file:///etc/hostname`")));
+        // Served from the cache, since the server only answers 4 requests.
+        assert_eq!(remote.jar_from_url(&format!("{}/latest.jar", server), JarOptionBuilder::default()).unwrap().files["a/A.class"], b"a");
+        assert!(matches!(Remote::new().no_cache().fetch("https://example.com/app.jar"), Err(JarError::Fetch(_))));
+    }
+
+    #[test]
+    fn test_jar_from_maven() {
+        let repository = temp_path("repository");
+        let artifact_dir = repository.join("org/example/app/1.0");
+
+        std::fs::create_dir_all(&artifact_dir).unwrap();
+        std::fs::write(artifact_dir.join("app-1.0.jar"), jar_bytes(&[("a/A.class", b"a")])).unwrap();
+
+        let repository = format!("file://{}", repository.display());
+        let jar = Remote::new().jar_from_maven("org.example:app:1.0", &repository, JarOptionBuilder::default()).unwrap();
+
+        assert_eq!(jar.files["a/A.class"], b"a");
+
+        std::fs::write(artifact_dir.join("app-1.0.jar.sha1"), "0000000000000000000000000000000000000000  app-1.0.jar\n").unwrap();
+        assert!(Remote::new().no_cache().jar_from_maven("org.example:app:1.0", &repository, JarOptionBuilder::default())
+            .is_err_and(|err| err.to_string().contains("sha1 checksum")));
+
+        let checksum = digest(&SHA256, &std::fs::read(artifact_dir.join("app-1.0.jar")).unwrap()).as_ref().iter().map(|byte| format!("{:02X}", byte)).collect::<String>();
+        std::fs::write(artifact_dir.join("app-1.0.jar.sha256"), checksum).unwrap();
+        assert!(Remote::new().no_cache().jar_from_maven("org.example:app:1.0", &repository, JarOptionBuilder::default()).is_ok());
+
+        // A corrupted download and its checksum are evicted, so a later call recovers.
+        let cache = temp_path("maven-cache");
+        let served = Arc::new(AtomicUsize::new(0));
+        let flaky = {
+            let served = served.clone();
+            let artifact = jar_bytes(&[("a/A.class", b"a")]);
+            let checksum = digest(&SHA256, &artifact).as_ref().iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+
+            move |url: &str| match url.rsplit('.').next() {
+                Some("jar") if served.fetch_add(1, Ordering::Relaxed) == 0 => Ok(artifact[..artifact.len() / 2].to_vec()),
+                Some("jar") => Ok(artifact.clone()),
+                Some("sha256") => Ok(checksum.clone().into_bytes()),
+                _ => Err(JarError::Fetch(url.to_string())),
+            }
+        };
+        let remote = Remote::new().cache_dir(&cache).fetcher(flaky);
+
+        assert!(remote.jar_from_maven("org.example:app:1.0", "http://repository.example.com", JarOptionBuilder::default())
+            .is_err_and(|err| err.to_string().contains("sha256 checksum")));
+        assert_eq!(std::fs::read_dir(&cache).unwrap().count(), 0);
+        assert_eq!(remote.jar_from_maven("org.example:app:1.0", "http://repository.example.com", JarOptionBuilder::default()).unwrap().files["a/A.class"], b"a");
+        assert_eq!(served.load(Ordering::Relaxed), 2);
+
+        let unverified = |url: &str| match url.ends_with(".jar") {
+            true => Ok(jar_bytes(&[])),
+            false => Err(JarError::Fetch(url.to_string())),
+        };
+        assert!(Remote::new().no_cache().fetcher(unverified)
+            .jar_from_maven("org.example:app:1.0", "http://repository.example.com", JarOptionBuilder::default())
+            .is_err_and(|err| err.to_string().contains("no checksum")));
+        assert!(Remote::new().no_cache()
+            .fetcher(|url| Err(JarError::Fetch(url.to_string())))
+            .jar_from_maven("org.example:app:1.0", "https://repository.example.com/", JarOptionBuilder::default())
+            .is_err_and(|err| err.to_string().ends_with("https://repository.example.com/org/example/app/1.0/app-1.0.jar")));
+    }
+
+    #[test]
+    fn test_maven_url() {
+        assert_eq!(
+            maven_url("org.lwjgl:lwjgl:3.3.3:natives-linux", MAVEN_CENTRAL).unwrap(),
+            "https://repo.maven.apache.org/maven2/org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3-natives-linux.jar",
+        );
+        assert_eq!(maven_url("a.b:c:1@pom", "https://repo/").unwrap(), "https://repo/a/b/c/1/c-1.pom");
+        assert!(maven_url("a.b:c", MAVEN_CENTRAL).is_err());
+        assert!(maven_url("a.b::1", MAVEN_CENTRAL).is_err());
+        assert!(maven_url("a.b:c:1:x:y", MAVEN_CENTRAL).is_err());
+        assert!(maven_url("..:c:1", MAVEN_CENTRAL).is_err());
+        assert!(maven_url("a.b:..:1", MAVEN_CENTRAL).is_err());
+        assert!(maven_url("a.b:c:1@jar?x", MAVEN_CENTRAL).is_err());
+        assert!(maven_url("a..b:c:1", MAVEN_CENTRAL).is_err());
+    }
+}
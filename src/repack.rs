@@ -0,0 +1,208 @@
+//! Filtering an existing jar into a new jar without recompressing entries.
+
+use std::fs::File;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use crate::limits::content_buffer;
+use crate::option::map_content;
+use crate::{jar_stream, JarEntry, JarError, JarOption, RawEntry, MANIFEST_PATH};
+
+/// Writes a new jar at `dest` containing only the entries of the jar at `src` which pass the
+/// extraction rules of `option`, in their original order. Entries are copied in compressed form,
+/// so compression method, timestamps and Unix permissions are preserved and nothing is
+/// recompressed. Entries of multi-release jars resolved with [crate::JarOptionBuilder::release] are
+/// written under their resolved path. Returns metadata of all written entries.
+///
+/// With [crate::JarOptionBuilder::map_content], file entries are decompressed and passed through
+/// the mapper in the same pass: mapped entries are recompressed with their original compression
+/// method and timestamp, and dropped entries are not written. Returned metadata then still
+/// describes the source entries.
+///
+/// With [crate::JarOptionBuilder::normalize_timestamps], entries copied in compressed form are
+/// written with the normalized timestamp as well.
+///
+/// Note that removing entries from a signed jar invalidates its signature, which
+/// [crate::JarOptionBuilder::strip_signatures] removes. Only the manifest is then recompressed.
+///
+/// # Example
+///
+/// ```rs
+/// let option = JarOptionBuilder::builder().exclude_target("test-resources").exclude_ext("properties").build();
+///
+/// repack("lib/dependency.jar", "out/dependency.jar", option)?;
+/// ```
+pub fn repack<P, D>(src: P, dest: D, option: JarOption) -> Result<Vec<JarEntry>, JarError>
+    where P: AsRef<Path>, D: AsRef<Path> {
+    let (src, dest) = (src.as_ref(), dest.as_ref());
+
+    if dest.exists() && src.canonicalize()? == dest.canonicalize()? {
+        return Err(JarError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "source and destination are the same file",
+        )));
+    }
+
+    let content_mapper = option.content_mapper.clone();
+    let maps_manifest_only = option.maps_manifest_only;
+    let normalized = option.timestamp.is_some();
+    let mut stream = jar_stream(src, option)?;
+    let mut writer = ZipWriter::new(File::create(dest)?);
+    let mut written = Vec::new();
+
+    while let Some(next) = stream.next_match() {
+        let (index, entry) = next?;
+        let mapped = content_mapper.is_some() && !entry.is_dir() && (!maps_manifest_only || entry.name == MANIFEST_PATH);
+
+        if normalized && !mapped {
+            // Raw copies keep the headers of the source entry, so they are rebuilt with the timestamp.
+            let raw = RawEntry::read(entry.clone(), &mut stream.archive_mut().by_index_raw(index)?)?;
+
+            writer.raw_copy_file(raw.archive()?.by_index_raw(0)?)?;
+            written.push(entry);
+            continue;
+        }
+        if !mapped {
+            copy_raw(&mut writer, stream.archive_mut().by_index_raw(index)?, &entry.name)?;
+            written.push(entry);
+            continue;
+        }
+
+        let mut content = content_buffer(&entry);
+        stream.decode(index, &entry)?.read_to_end(&mut content)?;
+
+        if let Some(content) = map_content(&content_mapper, &entry.name, content) {
+            let mut options = FileOptions::default()
+                .compression_method(entry.compression.into())
+                .last_modified_time(entry.last_modified.to_zip())
+                .large_file(content.len() as u64 >= u32::MAX as u64);
+
+            if let Some(mode) = entry.unix_mode {
+                options = options.unix_permissions(mode);
+            }
+
+            writer.start_file(entry.name.as_str(), options)?;
+            writer.write_all(&content)?;
+            written.push(entry);
+        }
+    }
+
+    writer.finish()?;
+    Ok(written)
+}
+
+fn copy_raw<W>(writer: &mut ZipWriter<W>, file: zip::read::ZipFile, name: &str) -> Result<(), JarError> where W: Write + Seek {
+    if file.name() == name {
+        writer.raw_copy_file(file)?;
+    } else {
+        writer.raw_copy_file_rename(file, name)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::fixture::{jar_file, temp_path};
+    use crate::{jar, jar_list, repack, DateTime, JarOptionBuilder};
+
+    #[test]
+    fn test_repack() {
+        let src = jar_file(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n"),
+            ("a/A.class", b"a"),
+            ("a/messages_fr.properties", b"fr"),
+            ("test-resources/data.txt", b"data"),
+        ]);
+        let dest = temp_path("repacked.jar");
+        let option = JarOptionBuilder::builder().exclude_target("test-resources").exclude_ext("properties").build();
+        let written = repack(&src, &dest, option).unwrap();
+
+        assert_eq!(written.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(), vec!["META-INF/MANIFEST.MF", "a/A.class"]);
+
+        let source = jar(&src, JarOptionBuilder::default()).unwrap();
+        let repacked = jar(&dest, JarOptionBuilder::default()).unwrap();
+        let mut paths = repacked.files.keys().collect::<Vec<_>>();
+        paths.sort();
+
+        assert_eq!(paths, vec!["META-INF/MANIFEST.MF", "a/A.class"]);
+        let (source_entry, repacked_entry) = (source.entry("a/A.class").unwrap(), repacked.entry("a/A.class").unwrap());
+
+        assert_eq!(repacked_entry.compressed_size, source_entry.compressed_size);
+        assert_eq!(repacked_entry.last_modified, source_entry.last_modified);
+        assert_eq!(repacked_entry.unix_mode.map(|mode| mode & 0o777), source_entry.unix_mode.map(|mode| mode & 0o777));
+        assert!(repack(&dest, &dest, JarOptionBuilder::default()).is_err());
+    }
+
+    #[test]
+    fn test_repack_map_content() {
+        let src = jar_file(&[
+            ("application.properties", b"password=secret"),
+            ("a/A.java", b"class A {}"),
+            ("a/A.class", b"a"),
+        ]);
+        let dest = temp_path("mapped.jar");
+        let option = JarOptionBuilder::builder()
+            .map_content(|name, content| match name {
+                "application.properties" => Some(b"password=***".to_vec()),
+                name if name.ends_with(".java") => None,
+                _ => Some(content),
+            })
+            .build();
+        let written = repack(&src, &dest, option).unwrap();
+
+        assert_eq!(written.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(), vec!["application.properties", "a/A.class"]);
+
+        let repacked = jar(&dest, JarOptionBuilder::default()).unwrap();
+        assert_eq!(repacked.files["application.properties"], b"password=***");
+        assert_eq!(repacked.files["a/A.class"], b"a");
+        assert!(!repacked.files.contains_key("a/A.java"));
+    }
+
+    #[test]
+    fn test_repack_strip_signatures() {
+        let src = concat!(env!("CARGO_MANIFEST_DIR"), "/sample/signed-rsa.jar");
+        let dest = temp_path("unsigned.jar");
+
+        repack(src, &dest, JarOptionBuilder::builder().strip_signatures().build()).unwrap();
+
+        let (source, repacked) = (jar(src, JarOptionBuilder::default()).unwrap(), jar(&dest, JarOptionBuilder::default()).unwrap());
+        let mut paths = repacked.files.keys().collect::<Vec<_>>();
+        paths.sort();
+
+        assert!(source.verify().unwrap().is_verified());
+        assert_eq!(paths, vec!["META-INF/MANIFEST.MF", "a/A.class", "a/messages.properties"]);
+        assert_eq!(repacked.manifest().unwrap().unwrap().entries().count(), 0);
+        assert_eq!(repacked.entry("a/A.class").unwrap().compressed_size, source.entry("a/A.class").unwrap().compressed_size);
+        assert!(repacked.verify().unwrap().signers.is_empty());
+    }
+
+    #[test]
+    fn test_repack_normalize_timestamps() {
+        let src = jar_file(&[("a/", b""), ("a/A.class", b"a"), ("a/a.properties", b"a")]);
+        let dest = temp_path("normalized.jar");
+        let epoch = UNIX_EPOCH + Duration::from_secs(1262304000);
+        let option = JarOptionBuilder::builder()
+            .include_dirs()
+            .normalize_timestamps(epoch)
+            .map_content(|name, content| name.ends_with(".properties").then_some(content))
+            .build();
+
+        let raw = temp_path("raw.jar");
+
+        repack(&src, &dest, option).unwrap();
+        repack(&src, &raw, JarOptionBuilder::builder().include_dirs().normalize_timestamps(epoch).build()).unwrap();
+
+        let mapped = jar_list(&dest, JarOptionBuilder::builder().include_dirs().build()).unwrap();
+        let copied = jar_list(&raw, JarOptionBuilder::builder().include_dirs().build()).unwrap();
+
+        assert_eq!(mapped.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(), vec!["a/", "a/a.properties"]);
+        assert_eq!(copied.len(), 3);
+        assert!(mapped.iter().chain(&copied).all(|entry| entry.last_modified == DateTime::from_system_time(epoch)));
+    }
+}
@@ -0,0 +1,163 @@
+//! Searching entry contents for byte patterns or regular expressions.
+
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::Read;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use regex::bytes::Regex;
+
+use crate::text::is_text_name;
+use crate::{Jar, JarError};
+#[cfg(feature = "fs")]
+use crate::{JarOption, JarStream};
+
+/// A match found by [Jar::search] or [jar_search].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Full qualified path of the entry.
+    pub name: String,
+    /// Byte offset of the match in the entry's uncompressed content.
+    pub offset: usize,
+    /// Matched bytes.
+    pub bytes: Vec<u8>,
+}
+
+/// A compiled search pattern along with the entries to search.
+///
+/// # Example
+///
+/// ```rs
+/// let searcher = Searcher::regex(r"jdbc:[a-z]+://")?.text_only();
+///
+/// for found in jar("app.jar", JarOptionBuilder::default())?.search(&searcher) {
+///     println!("{}@{}", found.name, found.offset);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Searcher {
+    regex: Regex,
+    text_only: bool,
+}
+
+impl Searcher {
+    /// Searches for a regular expression, which is matched against raw bytes so it also finds
+    /// UTF-8 strings in binary entries such as class constant pools.
+    pub fn regex(pattern: &str) -> Result<Self, JarError> {
+        let regex = Regex::new(pattern).map_err(|err| JarError::InvalidPattern(err.to_string()))?;
+
+        Ok(Self {
+            regex,
+            text_only: false,
+        })
+    }
+
+    /// Searches for an exact byte sequence.
+    pub fn bytes(needle: &[u8]) -> Self {
+        let pattern = needle.iter().map(|byte| format!("\\x{:02x}", byte)).collect::<String>();
+
+        Self {
+            regex: Regex::new(&format!("(?-u){}", pattern)).expect("escaped bytes always compile"),
+            text_only: false,
+        }
+    }
+
+    /// Restricts the search to text-like entries, recognized by extensions such as `properties`,
+    /// `xml` or `json`, and to provider-configuration files under `META-INF/services`.
+    pub fn text_only(mut self) -> Self {
+        self.text_only = true;
+        self
+    }
+
+    fn accepts(&self, name: &str) -> bool {
+        !self.text_only || is_text_name(name)
+    }
+
+    fn find<'a>(&'a self, name: &'a str, content: &'a [u8]) -> impl Iterator<Item = SearchMatch> + 'a {
+        self.regex.find_iter(content).map(move |found| SearchMatch {
+            name: name.to_string(),
+            offset: found.start(),
+            bytes: found.as_bytes().to_vec(),
+        })
+    }
+}
+
+impl Jar {
+    /// Searches extracted files for `searcher`'s pattern, returning matches in central directory
+    /// order and by offset within each entry.
+    pub fn search(&self, searcher: &Searcher) -> Vec<SearchMatch> {
+        self.order.iter()
+            .filter(|name| searcher.accepts(name))
+            .filter_map(|name| Some((name, self.files.get(name)?)))
+            .flat_map(|(name, content)| searcher.find(name, content))
+            .collect()
+    }
+}
+
+/// Searches entries of the jar at `path` matching `option` for `searcher`'s pattern, reading one
+/// entry at a time like [crate::jar_stream] instead of extracting the whole jar.
+///
+/// # Example
+///
+/// ```rs
+/// let matches = jar_search("sample/rt.jar", JarOptionBuilder::builder().ext("class").build(), &Searcher::bytes(b"sun/misc/Unsafe"))?;
+/// ```
+#[cfg(feature = "fs")]
+pub fn jar_search<P>(path: P, option: JarOption, searcher: &Searcher) -> Result<Vec<SearchMatch>, JarError>
+    where P: AsRef<Path> {
+    let mut stream = JarStream::new(File::open(path)?, option)?;
+    let mut matches = Vec::new();
+    let mut content = Vec::new();
+
+    while let Some(next) = stream.next_entry() {
+        let (name, mut reader) = next?;
+
+        if searcher.accepts(&name) {
+            content.clear();
+            reader.read_to_end(&mut content)?;
+            matches.extend(searcher.find(&name, &content));
+        }
+    }
+
+    Ok(matches)
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use crate::fixture::{jar_bytes, jar_file};
+    use crate::{jar_from_bytes, jar_search, JarOptionBuilder, Searcher};
+
+    const ENTRIES: [(&str, &[u8]); 3] = [
+        ("a/A.class", b"\xCA\xFE\xBA\xBEjdbc:h2:mem"),
+        ("app.properties", b"url=jdbc:postgresql://db\nbackup=jdbc:mysql://db"),
+        ("META-INF/services/java.sql.Driver", b"org.h2.Driver"),
+    ];
+
+    #[test]
+    fn test_search() {
+        let jar = jar_from_bytes(&jar_bytes(&ENTRIES), JarOptionBuilder::builder().keep_meta_info().ext("class").ext("properties").build()).unwrap();
+        let matches = jar.search(&Searcher::regex("jdbc:[a-z0-9]+").unwrap());
+
+        assert_eq!(matches.iter().map(|found| (found.name.as_str(), found.offset)).collect::<Vec<_>>(), vec![
+            ("a/A.class", 4),
+            ("app.properties", 4),
+            ("app.properties", 32),
+        ]);
+        assert_eq!(matches[1].bytes, b"jdbc:postgresql");
+
+        assert_eq!(jar.search(&Searcher::regex("jdbc").unwrap().text_only()).len(), 2);
+        assert_eq!(jar.search(&Searcher::bytes(b"\xCA\xFE")).len(), 1);
+        assert_eq!(jar.search(&Searcher::regex("Driver").unwrap().text_only()).len(), 1);
+        assert!(Searcher::regex("(").is_err());
+    }
+
+    #[test]
+    fn test_jar_search() {
+        let path = jar_file(&ENTRIES);
+        let matches = jar_search(&path, JarOptionBuilder::builder().ext("properties").build(), &Searcher::bytes(b"://")).unwrap();
+
+        assert_eq!(matches.iter().map(|found| found.offset).collect::<Vec<_>>(), vec![19, 42]);
+    }
+}
@@ -0,0 +1,260 @@
+//! Parsing of `META-INF/services` provider-configuration files used by `java.util.ServiceLoader`.
+
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::collections::HashSet;
+use std::path::PathBuf;
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::option::Callback;
+use crate::Jar;
+#[cfg(feature = "fs")]
+use crate::class_path;
+
+/// Directory of provider-configuration files inside a jar.
+pub const SERVICES_PREFIX: &str = "META-INF/services/";
+
+impl Jar {
+    /// Maps service interface names to their implementation class names, parsed from
+    /// `META-INF/services/*` files. `META-INF` must be extracted, e.g. with
+    /// [crate::JarOptionBuilder::keep_meta_info].
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let services = jar("sample/rt.jar", JarOptionBuilder::builder().keep_meta_info().build())?.services();
+    ///
+    /// for provider in &services["java.sql.Driver"] {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn services(&self) -> HashMap<String, Vec<String>> {
+        self.files.iter()
+            .filter_map(|(path, content)| {
+                let service = path.strip_prefix(SERVICES_PREFIX)?;
+
+                if service.is_empty() || service.contains('/') {
+                    None
+                } else {
+                    Some((service.to_string(), parse_service_providers(content)))
+                }
+            })
+            .collect()
+    }
+}
+
+/// Parses a provider-configuration file into implementation class names in declaration order. As
+/// `ServiceLoader` does, `#` starts a comment, surrounding whitespace is ignored, and duplicated
+/// names are only kept once.
+pub(crate) fn parse_service_providers(content: &[u8]) -> Vec<String> {
+    let mut providers = Vec::<String>::new();
+
+    for line in String::from_utf8_lossy(content).lines() {
+        let provider = line.split('#').next().unwrap_or_default().trim();
+
+        if !provider.is_empty() && !providers.iter().any(|existing| existing == provider) {
+            providers.push(provider.to_string());
+        }
+    }
+
+    providers
+}
+
+/// A problem with provider-configuration files found by [ServiceDescriptorMerger].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ServiceWarning {
+    /// A provider is listed by more than one jar and only written once.
+    DuplicateProvider {
+        service: String,
+        provider: String,
+        /// Jars listing the provider, in merge order.
+        jars: Vec<PathBuf>,
+    },
+    /// More than one jar contributes providers of a service, so `ServiceLoader` loads providers of
+    /// all of them, which breaks callers expecting a single provider, e.g. of a logging backend.
+    ConflictingProviders {
+        service: String,
+        /// Jars with the providers they contribute first, in merge order.
+        providers: Vec<(PathBuf, Vec<String>)>,
+    },
+    /// A provider class is not part of the merged jar, so iterating the `ServiceLoader` fails with
+    /// a `ServiceConfigurationError`.
+    MissingProvider {
+        service: String,
+        provider: String,
+    },
+}
+
+type ServiceWarningCallback = dyn Fn(&ServiceWarning) + Send + Sync;
+
+/// Merges provider-configuration files of the same service from several jars, used by
+/// [crate::merge] for `META-INF/services/*` with [crate::MergeStrategy::Services]. Providers are
+/// written once each in order of first appearance, and comments are kept unless stripped.
+///
+/// # Example
+///
+/// ```rs
+/// let merger = ServiceDescriptorMerger::new()
+///     .strip_comments()
+///     .on_warning(|warning| eprintln!("{:?}", warning));
+///
+/// merge(&["app.jar", "lib/dependency.jar"], "app-all.jar", MergeOptions::default().service_merger(merger))?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ServiceDescriptorMerger {
+    strip_comments: bool,
+    on_warning: Option<Callback<ServiceWarningCallback>>,
+}
+
+impl ServiceDescriptorMerger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drops comments and blank lines, writing only provider names.
+    pub fn strip_comments(mut self) -> Self {
+        self.strip_comments = true;
+        self
+    }
+
+    /// Reports each [ServiceWarning] to `callback`.
+    pub fn on_warning<F>(mut self, callback: F) -> Self where F: Fn(&ServiceWarning) + Send + Sync + 'static {
+        self.on_warning = Some(Callback(Arc::new(callback)));
+        self
+    }
+
+    #[cfg(feature = "fs")]
+    fn warn(&self, warning: ServiceWarning) {
+        if let Some(callback) = &self.on_warning {
+            (callback.0)(&warning);
+        }
+    }
+
+    /// Merges the files of `service` from `descriptors`, given with their jars in merge order.
+    #[cfg(feature = "fs")]
+    pub(crate) fn merge(&self, service: &str, descriptors: &[(&Path, Vec<u8>)]) -> Vec<u8> {
+        let mut merged = String::new();
+        let mut listed_by = Vec::<(String, Vec<PathBuf>)>::new();
+        let mut contributions = Vec::<(PathBuf, Vec<String>)>::new();
+
+        for (jar, content) in descriptors {
+            let mut contributed = Vec::new();
+
+            for line in String::from_utf8_lossy(content).lines() {
+                let provider = line.split('#').next().unwrap_or_default().trim();
+
+                if provider.is_empty() {
+                    if !self.strip_comments && !line.trim().is_empty() {
+                        merged.push_str(line.trim_end());
+                        merged.push('\n');
+                    }
+                    continue;
+                }
+
+                match listed_by.iter_mut().find(|(listed, _)| listed == provider) {
+                    Some((_, jars)) => {
+                        if !jars.iter().any(|listing| listing == jar) {
+                            jars.push(jar.to_path_buf());
+                        }
+                    }
+                    None => {
+                        listed_by.push((provider.to_string(), vec![jar.to_path_buf()]));
+                        contributed.push(provider.to_string());
+                        merged.push_str(if self.strip_comments { provider } else { line.trim_end() });
+                        merged.push('\n');
+                    }
+                }
+            }
+
+            if !contributed.is_empty() {
+                contributions.push((jar.to_path_buf(), contributed));
+            }
+        }
+
+        for (provider, jars) in listed_by.into_iter().filter(|(_, jars)| jars.len() > 1) {
+            self.warn(ServiceWarning::DuplicateProvider {
+                service: service.to_string(),
+                provider,
+                jars,
+            });
+        }
+        if contributions.len() > 1 {
+            self.warn(ServiceWarning::ConflictingProviders {
+                service: service.to_string(),
+                providers: contributions,
+            });
+        }
+
+        merged.into_bytes()
+    }
+
+    /// Reports providers of a merged file whose classes are not among the merged entry `names`.
+    #[cfg(feature = "fs")]
+    pub(crate) fn check_providers(&self, service: &str, content: &[u8], names: &HashSet<&str>) {
+        for provider in parse_service_providers(content) {
+            if !names.contains(class_path(&provider).as_str()) {
+                self.warn(ServiceWarning::MissingProvider {
+                    service: service.to_string(),
+                    provider,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::collections::HashSet;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, JarOptionBuilder, ServiceDescriptorMerger, ServiceWarning};
+
+    #[test]
+    fn test_services() {
+        let bytes = jar_bytes(&[
+            ("META-INF/services/java.sql.Driver", b"# drivers\n com.example.Driver  # primary\n\ncom.example.Other\r\ncom.example.Driver\n"),
+            ("META-INF/services/nested/ignored", b"com.example.Ignored"),
+            ("com/example/Driver.class", b""),
+        ]);
+        let services = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap().services();
+
+        assert_eq!(services.len(), 1);
+        assert_eq!(services["java.sql.Driver"], vec!["com.example.Driver", "com.example.Other"]);
+    }
+
+    #[test]
+    fn test_service_descriptor_merger() {
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let reported = warnings.clone();
+        let merger = ServiceDescriptorMerger::new().on_warning(move |warning| reported.lock().unwrap().push(warning.clone()));
+        let (first, second) = (Path::new("first.jar"), Path::new("second.jar"));
+        let merged = merger.merge("a.Service", &[
+            (first, b"# first\na.FirstImpl # primary\n\n".to_vec()),
+            (second, b"a.SecondImpl\r\na.FirstImpl\n".to_vec()),
+        ]);
+
+        assert_eq!(merged, b"# first\na.FirstImpl # primary\na.SecondImpl\n");
+        assert_eq!(ServiceDescriptorMerger::new().strip_comments().merge("a.Service", &[(first, b"# first\na.FirstImpl # primary\n".to_vec())]), b"a.FirstImpl\n");
+
+        merger.check_providers("a.Service", &merged, &HashSet::from(["a/FirstImpl.class"]));
+        assert_eq!(*warnings.lock().unwrap(), vec![
+            ServiceWarning::DuplicateProvider {
+                service: "a.Service".to_string(),
+                provider: "a.FirstImpl".to_string(),
+                jars: vec![first.to_path_buf(), second.to_path_buf()],
+            },
+            ServiceWarning::ConflictingProviders {
+                service: "a.Service".to_string(),
+                providers: vec![(first.to_path_buf(), vec!["a.FirstImpl".to_string()]), (second.to_path_buf(), vec!["a.SecondImpl".to_string()])],
+            },
+            ServiceWarning::MissingProvider {
+                service: "a.Service".to_string(),
+                provider: "a.SecondImpl".to_string(),
+            },
+        ]);
+    }
+}
@@ -0,0 +1,357 @@
+//! Signing of jars, producing the manifest digests, signature file (`.SF`) and PKCS#7 signature
+//! block checked by [crate::Jar::verify].
+
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::Write;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::signature::{self, EcdsaKeyPair, KeyPair, RsaKeyPair};
+#[cfg(feature = "fs")]
+use zip::write::FileOptions;
+#[cfg(feature = "fs")]
+use zip::ZipWriter;
+
+use crate::der::{encode, encode_all, tag, DerReader};
+use crate::manifest::raw_sections;
+use crate::signature::{
+    certificate_issuer_and_serial, is_signature_file, public_key, OID_EC, OID_P256, OID_P384, OID_RSA, OID_SHA1,
+    OID_SHA256, OID_SHA256_ECDSA, OID_SHA384, OID_SHA384_ECDSA, OID_SHA512, OID_SIGNED_DATA, SIGNATURE_BLOCK_EXTENSIONS,
+};
+use crate::{parse_manifest, DigestAlgorithm, JarError, Manifest, MANIFEST_PATH};
+#[cfg(feature = "fs")]
+use crate::{jar, jar_stream, DateTime, JarOptionBuilder};
+
+const OID_DATA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x01];
+
+enum SigningKey {
+    Rsa(RsaKeyPair),
+    /// An ECDSA key pair, whose signature hash is determined by its curve.
+    Ecdsa(EcdsaKeyPair, DigestAlgorithm),
+}
+
+/// A signer producing jars verifiable by `jarsigner -verify` and [crate::Jar::verify], from an RSA or
+/// ECDSA (P-256 or P-384) private key and its X.509 certificate.
+///
+/// Every file gets a `<ALGORITHM>-Digest` attribute in its manifest section, the signature file
+/// `META-INF/<NAME>.SF` digests the manifest and its sections, and the signature block
+/// `META-INF/<NAME>.RSA` or `META-INF/<NAME>.EC` signs the signature file. Signatures of other
+/// signers are kept.
+///
+/// # Example
+///
+/// ```rs
+/// let signer = JarSigner::new(&std::fs::read("key.pk8")?, &std::fs::read("cert.der")?)?.name("RELEASE");
+///
+/// signer.sign_jar("app.jar", "app-signed.jar")?;
+/// ```
+pub struct JarSigner {
+    key: SigningKey,
+    certificate: Vec<u8>,
+    name: String,
+    digest_algorithm: DigestAlgorithm,
+}
+
+impl JarSigner {
+    /// Creates a [JarSigner] from a DER-encoded PKCS#8 private key and the DER-encoded certificate
+    /// of its public key. Signs as `SIGNER` with [DigestAlgorithm::Sha256] digests by default.
+    pub fn new(private_key: &[u8], certificate: &[u8]) -> Result<Self, JarError> {
+        let signing_error = |message: &str| JarError::Signing(message.to_string());
+        let certificate_der = DerReader::new(certificate).read().map_err(signing_error)?;
+        let public_key = public_key(certificate_der).map_err(signing_error)?;
+        let key = match (public_key.algorithm, public_key.curve) {
+            (OID_RSA, _) => RsaKeyPair::from_pkcs8(private_key).map(SigningKey::Rsa).map_err(|_| signing_error("invalid RSA private key"))?,
+            (OID_EC, Some(OID_P256)) => EcdsaKeyPair::from_pkcs8(&signature::ECDSA_P256_SHA256_ASN1_SIGNING, private_key, &SystemRandom::new())
+                .map(|key| SigningKey::Ecdsa(key, DigestAlgorithm::Sha256))
+                .map_err(|_| signing_error("invalid ECDSA private key"))?,
+            (OID_EC, Some(OID_P384)) => EcdsaKeyPair::from_pkcs8(&signature::ECDSA_P384_SHA384_ASN1_SIGNING, private_key, &SystemRandom::new())
+                .map(|key| SigningKey::Ecdsa(key, DigestAlgorithm::Sha384))
+                .map_err(|_| signing_error("invalid ECDSA private key"))?,
+            _ => return Err(signing_error("unsupported key algorithm")),
+        };
+        let key_matches = match &key {
+            SigningKey::Rsa(key) => key.public_key().as_ref() == public_key.key,
+            SigningKey::Ecdsa(key, _) => key.public_key().as_ref() == public_key.key,
+        };
+
+        if !key_matches {
+            return Err(signing_error("private key does not match certificate"));
+        }
+
+        Ok(Self {
+            key,
+            certificate: certificate_der.raw.to_vec(),
+            name: "SIGNER".to_string(),
+            digest_algorithm: DigestAlgorithm::Sha256,
+        })
+    }
+
+    /// Sets the base name of the signature file and block, e.g. `RELEASE` for
+    /// `META-INF/RELEASE.SF`. Names are upper-cased as jar signers expect.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_ascii_uppercase();
+        self
+    }
+
+    /// Sets the algorithm of manifest and signature file digests.
+    pub fn digest_algorithm(mut self, algorithm: DigestAlgorithm) -> Self {
+        self.digest_algorithm = algorithm;
+        self
+    }
+
+    /// Signs a map of full qualified paths to contents, e.g. [crate::Jar::files], in place: digests
+    /// of all files are added to `META-INF/MANIFEST.MF`, which is created if absent, and the
+    /// signature file and block are inserted, replacing any previous ones of the same name.
+    pub fn sign_files(&self, files: &mut HashMap<String, Vec<u8>>) -> Result<(), JarError> {
+        let mut manifest = match files.get(MANIFEST_PATH) {
+            Some(manifest) => parse_manifest(manifest)?,
+            None => Manifest::default(),
+        };
+
+        if !manifest.main_attributes().contains("Manifest-Version") {
+            let mut versioned = Manifest::default();
+
+            versioned.main_attributes_mut().insert("Manifest-Version".to_string(), "1.0".to_string());
+            for (name, value) in manifest.main_attributes().iter() {
+                versioned.main_attributes_mut().insert(name.to_string(), value.to_string());
+            }
+            for (name, attributes) in manifest.entries() {
+                *versioned.entry_mut(name) = attributes.clone();
+            }
+
+            manifest = versioned;
+        }
+
+        for extension in ["SF"].into_iter().chain(SIGNATURE_BLOCK_EXTENSIONS) {
+            files.remove(&format!("META-INF/{}.{}", self.name, extension));
+        }
+
+        let mut paths = files.keys()
+            .filter(|path| !is_signature_file(path) && !path.ends_with('/'))
+            .cloned()
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        let digest_name = format!("{}-Digest", self.digest_algorithm.name());
+        for path in &paths {
+            manifest.entry_mut(path).insert(digest_name.clone(), STANDARD.encode(self.digest_algorithm.digest(&files[path])));
+        }
+
+        let manifest_bytes = manifest.to_bytes();
+        let signature_file = self.signature_file(&manifest, &manifest_bytes);
+        let (extension, block) = self.signature_block(&signature_file)?;
+
+        files.insert(MANIFEST_PATH.to_string(), manifest_bytes);
+        files.insert(format!("META-INF/{}.SF", self.name), signature_file);
+        files.insert(format!("META-INF/{}.{}", self.name, extension), block);
+        Ok(())
+    }
+
+    /// Writes a signed copy of the jar at `src` to `dest`. The manifest and signature files are
+    /// written first, as `java.util.jar.JarInputStream` requires, followed by all other entries
+    /// copied in compressed form in their original order.
+    #[cfg(feature = "fs")]
+    pub fn sign_jar<P, D>(&self, src: P, dest: D) -> Result<(), JarError> where P: AsRef<Path>, D: AsRef<Path> {
+        let (src, dest) = (src.as_ref(), dest.as_ref());
+
+        if dest.exists() && src.canonicalize()? == dest.canonicalize()? {
+            return Err(JarError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "source and destination are the same file",
+            )));
+        }
+
+        let mut files = jar(src, JarOptionBuilder::default())?.files;
+        self.sign_files(&mut files)?;
+
+        let mut signature_files = files.keys()
+            .filter(|path| is_signature_file(path) && *path != MANIFEST_PATH)
+            .collect::<Vec<_>>();
+        // Orders each signature file before its signature block, as `jarsigner` does.
+        signature_files.sort_by_key(|path| (path.rsplit_once('.').map(|(name, _)| name), !path.ends_with(".SF")));
+
+        let mut writer = ZipWriter::new(File::create(dest)?);
+        for path in [MANIFEST_PATH].into_iter().chain(signature_files.into_iter().map(String::as_str)) {
+            writer.start_file(path, FileOptions::default().last_modified_time(DateTime::now().to_zip()))?;
+            writer.write_all(&files[path])?;
+        }
+
+        let mut stream = jar_stream(src, JarOptionBuilder::builder().include_dirs().build())?;
+        while let Some(next) = stream.next_match() {
+            let (index, entry) = next?;
+
+            if !is_signature_file(&entry.name) {
+                writer.raw_copy_file_rename(stream.archive_mut().by_index_raw(index)?, entry.name.as_str())?;
+            }
+        }
+
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Creates the signature file, digesting the whole manifest, its main section, and each
+    /// per-entry section.
+    fn signature_file(&self, manifest: &Manifest, manifest_bytes: &[u8]) -> Vec<u8> {
+        let algorithm = self.digest_algorithm;
+        let digest = |bytes: &[u8]| STANDARD.encode(algorithm.digest(bytes));
+        let sections = raw_sections(manifest_bytes);
+        let mut signature_file = Manifest::default();
+        let attributes = signature_file.main_attributes_mut();
+
+        attributes.insert("Signature-Version".to_string(), "1.0".to_string());
+        attributes.insert("Created-By".to_string(), format!("jars {}", env!("CARGO_PKG_VERSION")));
+        attributes.insert(format!("{}-Digest-Manifest", algorithm.name()), digest(manifest_bytes));
+        attributes.insert(format!("{}-Digest-Manifest-Main-Attributes", algorithm.name()), digest(sections[0]));
+
+        // Sections are serialized in order after the main section.
+        for ((name, _), section) in manifest.entries().zip(sections.into_iter().skip(1)) {
+            signature_file.entry_mut(name).insert(format!("{}-Digest", algorithm.name()), digest(section));
+        }
+
+        signature_file.to_bytes()
+    }
+
+    /// Creates a detached PKCS#7 signed data of `signed` without signed attributes, returning it
+    /// with its file extension.
+    fn signature_block(&self, signed: &[u8]) -> Result<(&'static str, Vec<u8>), JarError> {
+        let signing_error = |message: &str| JarError::Signing(message.to_string());
+        let rng = SystemRandom::new();
+        let (extension, hash, signature_algorithm, signature) = match &self.key {
+            SigningKey::Rsa(key) => {
+                let (hash, padding): (_, &'static dyn signature::RsaEncoding) = match self.digest_algorithm {
+                    DigestAlgorithm::Sha384 => (DigestAlgorithm::Sha384, &signature::RSA_PKCS1_SHA384),
+                    DigestAlgorithm::Sha512 => (DigestAlgorithm::Sha512, &signature::RSA_PKCS1_SHA512),
+                    DigestAlgorithm::Sha1 | DigestAlgorithm::Sha256 => (DigestAlgorithm::Sha256, &signature::RSA_PKCS1_SHA256),
+                };
+                let mut signature = vec![0; key.public().modulus_len()];
+
+                key.sign(padding, &rng, signed, &mut signature).map_err(|_| signing_error("RSA signing failed"))?;
+                ("RSA", hash, encode_all(tag::SEQUENCE, &[&encode(tag::OID, OID_RSA), &encode(tag::NULL, &[])]), signature)
+            }
+            SigningKey::Ecdsa(key, hash) => {
+                let signature = key.sign(&rng, signed).map_err(|_| signing_error("ECDSA signing failed"))?;
+                let oid = if *hash == DigestAlgorithm::Sha384 { OID_SHA384_ECDSA } else { OID_SHA256_ECDSA };
+
+                ("EC", *hash, encode(tag::SEQUENCE, &encode(tag::OID, oid)), signature.as_ref().to_vec())
+            }
+        };
+
+        let certificate = DerReader::new(&self.certificate).read().map_err(signing_error)?;
+        let (issuer, serial) = certificate_issuer_and_serial(&certificate).map_err(signing_error)?;
+        let digest_algorithm = encode_all(tag::SEQUENCE, &[&encode(tag::OID, digest_oid(hash)), &encode(tag::NULL, &[])]);
+        let signer_info = encode_all(tag::SEQUENCE, &[
+            &encode(tag::INTEGER, &[1]),
+            &encode_all(tag::SEQUENCE, &[issuer, &encode(tag::INTEGER, serial)]),
+            &digest_algorithm,
+            &signature_algorithm,
+            &encode(tag::OCTET_STRING, &signature),
+        ]);
+        let signed_data = encode_all(tag::SEQUENCE, &[
+            &encode(tag::INTEGER, &[1]),
+            &encode(tag::SET, &digest_algorithm),
+            &encode(tag::SEQUENCE, &encode(tag::OID, OID_DATA)),
+            &encode(tag::CONTEXT_0, &self.certificate),
+            &encode(tag::SET, &signer_info),
+        ]);
+
+        Ok((extension, encode_all(tag::SEQUENCE, &[&encode(tag::OID, OID_SIGNED_DATA), &encode(tag::CONTEXT_0, &signed_data)])))
+    }
+}
+
+fn digest_oid(algorithm: DigestAlgorithm) -> &'static [u8] {
+    match algorithm {
+        DigestAlgorithm::Sha1 => OID_SHA1,
+        DigestAlgorithm::Sha256 => OID_SHA256,
+        DigestAlgorithm::Sha384 => OID_SHA384,
+        DigestAlgorithm::Sha512 => OID_SHA512,
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::fixture::{jar_file, temp_path};
+    use crate::{jar, jar_from_bytes, EntryStatus, JarError, JarOptionBuilder, JarSigner};
+
+    const RSA_KEY: &[u8] = include_bytes!("../sample/signer-rsa.pk8");
+    const RSA_CERTIFICATE: &[u8] = include_bytes!("../sample/signer-rsa.der");
+    const EC_KEY: &[u8] = include_bytes!("../sample/signer-ec.pk8");
+    const EC_CERTIFICATE: &[u8] = include_bytes!("../sample/signer-ec.der");
+    /// Signed by [JarSigner] and accepted by `jarsigner -verify` of OpenJDK 17, see
+    /// `sample/crate-signed.jarsigner.txt`.
+    const CRATE_SIGNED_RSA: &[u8] = include_bytes!("../sample/crate-signed-rsa.jar");
+    const CRATE_SIGNED_EC: &[u8] = include_bytes!("../sample/crate-signed-ec.jar");
+
+    #[test]
+    fn test_sign_jar() {
+        let src = jar_file(&[
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nMain-Class: a.A\r\n"),
+            ("a/A.class", b"a"),
+            ("a/messages.properties", b"key=value"),
+        ]);
+
+        for (key, certificate, block) in [(RSA_KEY, RSA_CERTIFICATE, "META-INF/RELEASE.RSA"), (EC_KEY, EC_CERTIFICATE, "META-INF/RELEASE.EC")] {
+            let dest = temp_path("signed.jar");
+            JarSigner::new(key, certificate).unwrap().name("release").sign_jar(&src, &dest).unwrap();
+
+            let signed = jar(&dest, JarOptionBuilder::default()).unwrap();
+            let report = signed.verify().unwrap();
+
+            assert!(report.is_verified(), "{:?}", report);
+            assert_eq!(report.entries["a/A.class"], EntryStatus::Signed(vec!["META-INF/RELEASE".to_string()]));
+            assert!(signed.files.contains_key(block));
+            assert_eq!(signed.main_class().unwrap().as_deref(), Some("a.A"));
+            assert_eq!(signed.ordered_entries().next().unwrap().0.name, "META-INF/MANIFEST.MF");
+        }
+    }
+
+    #[test]
+    fn test_sign_files() {
+        let mut files = HashMap::from([("a/A.class".to_string(), b"a".to_vec())]);
+        let signer = JarSigner::new(RSA_KEY, RSA_CERTIFICATE).unwrap();
+
+        signer.sign_files(&mut files).unwrap();
+        signer.sign_files(&mut files).unwrap();
+
+        let mut paths = files.keys().collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(paths, vec!["META-INF/MANIFEST.MF", "META-INF/SIGNER.RSA", "META-INF/SIGNER.SF", "a/A.class"]);
+        assert!(crate::signature::verify(&files, &HashMap::new()).unwrap().is_verified());
+        assert!(matches!(JarSigner::new(EC_KEY, RSA_CERTIFICATE), Err(JarError::Signing(_))));
+    }
+
+    #[test]
+    fn test_sign_matches_jarsigner_verified() {
+        for (bytes, key, certificate, block) in [
+            (CRATE_SIGNED_RSA, RSA_KEY, RSA_CERTIFICATE, "META-INF/CRATE.RSA"),
+            (CRATE_SIGNED_EC, EC_KEY, EC_CERTIFICATE, "META-INF/CRATE.EC"),
+        ] {
+            let signed = jar_from_bytes(bytes, JarOptionBuilder::default()).unwrap();
+            let report = signed.verify().unwrap();
+
+            assert!(report.is_verified(), "{:?}", report);
+
+            // Re-signing must still produce what jarsigner accepted, apart from the crate version
+            // in `Created-By`, which the signature block signs as well.
+            let mut files = signed.files.clone();
+            JarSigner::new(key, certificate).unwrap().name("crate").sign_files(&mut files).unwrap();
+
+            let without_creator = |signature_file: &[u8]| String::from_utf8_lossy(signature_file)
+                .split("\r\n")
+                .filter(|line| !line.starts_with("Created-By: "))
+                .collect::<Vec<_>>()
+                .join("\r\n");
+
+            assert_eq!(files["META-INF/MANIFEST.MF"], signed.files["META-INF/MANIFEST.MF"]);
+            assert_eq!(without_creator(&files["META-INF/CRATE.SF"]), without_creator(&signed.files["META-INF/CRATE.SF"]));
+            assert!(signed.files.contains_key(block));
+        }
+    }
+}
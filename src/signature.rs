@@ -0,0 +1,623 @@
+//! Verification of signed jars, following the
+//! [signed JAR file](https://docs.oracle.com/en/java/javase/17/docs/specs/jar/jar.html#signed-jar-file)
+//! specification: entry contents are checked against manifest digests, manifest sections against
+//! signature file (`.SF`) digests, and signature files against their PKCS#7 signature block.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::signature::{self, UnparsedPublicKey, VerificationAlgorithm};
+
+use crate::der::{tag, Der, DerReader, DerResult};
+use crate::digest::{digest_attributes, DigestAlgorithm, DigestWriter};
+use crate::manifest::raw_sections;
+use crate::{parse_manifest, Attributes, Jar, JarError, MANIFEST_PATH};
+
+pub(crate) const OID_SIGNED_DATA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02];
+const OID_MESSAGE_DIGEST: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x04];
+pub(crate) const OID_SHA1: &[u8] = &[0x2B, 0x0E, 0x03, 0x02, 0x1A];
+pub(crate) const OID_SHA256: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+pub(crate) const OID_SHA384: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+pub(crate) const OID_SHA512: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+pub(crate) const OID_RSA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01];
+const OID_SHA1_RSA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x05];
+const OID_SHA256_RSA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0B];
+const OID_SHA384_RSA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0C];
+const OID_SHA512_RSA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0D];
+pub(crate) const OID_EC: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01];
+pub(crate) const OID_SHA256_ECDSA: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x02];
+pub(crate) const OID_SHA384_ECDSA: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x03];
+const OID_SHA512_ECDSA: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x04];
+pub(crate) const OID_P256: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07];
+pub(crate) const OID_P384: &[u8] = &[0x2B, 0x81, 0x04, 0x00, 0x22];
+
+/// Extensions of signature block files, each signing the `.SF` file with the same base name.
+pub(crate) const SIGNATURE_BLOCK_EXTENSIONS: [&str; 3] = ["RSA", "DSA", "EC"];
+
+/// Verification result of a single jar entry.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum EntryStatus {
+    /// The entry matches its manifest digest, which is covered by the listed signers whose
+    /// signature blocks were verified.
+    Signed(Vec<String>),
+    /// The entry is not covered by any verifiable signer.
+    Unsigned,
+    /// The entry content, its manifest section or the signature covering it does not match.
+    Tampered,
+    /// The entry has a manifest digest but was not extracted, e.g. because of extraction rules or
+    /// [crate::JarOptionBuilder::map_content] dropping it, so its content was never checked.
+    Unchecked,
+}
+
+/// Verification result of a signature block.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SignatureStatus {
+    /// The signature block is a valid signature of the signature file by the embedded certificate.
+    Verified,
+    /// The signature block is malformed or does not match the signature file.
+    Invalid(String),
+    /// The signature file has no accompanying signature block.
+    Missing,
+    /// The signature uses an algorithm this crate cannot verify, e.g. DSA.
+    Unsupported(String),
+}
+
+/// A signer of the jar, identified by the base name of its signature file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Signer {
+    /// Base name of the signer's files, e.g. `META-INF/CERT` for `META-INF/CERT.SF`.
+    pub name: String,
+    pub status: SignatureStatus,
+    /// DER-encoded certificate used to verify the signature block. Note that no trust is
+    /// established in the certificate itself, callers should validate it against their own roots.
+    pub certificate: Option<Vec<u8>>,
+}
+
+/// Structured report of [crate::Jar::verify].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VerificationReport {
+    pub signers: Vec<Signer>,
+    /// Status of every entry, except the manifest and signature-related files.
+    pub entries: BTreeMap<String, EntryStatus>,
+}
+
+impl VerificationReport {
+    /// Checks whether the jar has at least one signer, all signers are verified, and every entry is
+    /// signed. Entries listed in the manifest but not extracted are [EntryStatus::Unchecked], so
+    /// jars extracted with filters only verify if every digested entry passes them.
+    pub fn is_verified(&self) -> bool {
+        !self.signers.is_empty()
+            && self.signers.iter().all(|signer| signer.status == SignatureStatus::Verified)
+            && self.entries.values().all(|status| matches!(status, EntryStatus::Signed(_)))
+    }
+
+    /// Iterates over names of tampered entries.
+    pub fn tampered(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter()
+            .filter(|(_, status)| **status == EntryStatus::Tampered)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+/// Result of [Jar::verify_manifest_digests].
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ManifestDigestReport {
+    /// Number of files checked against a manifest digest.
+    pub checked: usize,
+    /// Files whose content does not match their manifest digest.
+    pub mismatched: Vec<String>,
+    /// Files without a manifest section declaring a digest of a supported algorithm.
+    pub undigested: Vec<String>,
+    /// Manifest sections with digests naming files absent from the jar.
+    pub missing: Vec<String>,
+}
+
+impl ManifestDigestReport {
+    /// Checks whether every file has a matching manifest digest and every digest names a file.
+    pub fn is_intact(&self) -> bool {
+        self.mismatched.is_empty() && self.undigested.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl Jar {
+    /// Recomputes `SHA-256-Digest`, `SHA1-Digest` and other supported `*-Digest` attributes of
+    /// manifest sections against extracted files, without verifying signature files or blocks as
+    /// [Jar::verify] does. Files are reported sorted by path. The manifest must be extracted, e.g.
+    /// with [crate::JarOptionBuilder::keep_meta_info]; files excluded by extraction rules are
+    /// reported as missing.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let report = jar("app.jar", JarOptionBuilder::default())?.verify_manifest_digests()?;
+    ///
+    /// for name in &report.mismatched {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn verify_manifest_digests(&self) -> Result<ManifestDigestReport, JarError> {
+        let manifest = self.manifest()?.unwrap_or_default();
+        let mut report = ManifestDigestReport::default();
+
+        for (path, content) in &self.files {
+            if is_signature_file(path) {
+                continue;
+            }
+
+            match manifest.entry(path).and_then(|attributes| check_digests(attributes, "-Digest", content)) {
+                Some(true) => report.checked += 1,
+                Some(false) => {
+                    report.checked += 1;
+                    report.mismatched.push(path.clone());
+                }
+                None => report.undigested.push(path.clone()),
+            }
+        }
+
+        report.missing = manifest.entries()
+            .filter(|(name, attributes)| !self.files.contains_key(*name) && digest_attributes(attributes, "-Digest").next().is_some())
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        report.mismatched.sort();
+        report.undigested.sort();
+        report.missing.sort();
+        Ok(report)
+    }
+}
+
+/// Checks whether a path is the manifest or a signature-related file, which are not signed
+/// themselves.
+pub(crate) fn is_signature_file(path: &str) -> bool {
+    let Some(name) = path.strip_prefix("META-INF/") else {
+        return false;
+    };
+    let upper = name.to_ascii_uppercase();
+
+    path == MANIFEST_PATH || !name.contains('/') && (
+        upper.starts_with("SIG-")
+            || upper.ends_with(".SF")
+            || SIGNATURE_BLOCK_EXTENSIONS.iter().any(|ext| upper.ends_with(&format!(".{}", ext)))
+    )
+}
+
+/// Checks digest attributes with given suffix against `bytes`. Returns [None] when there's no
+/// digest attribute of a supported algorithm.
+pub(crate) fn check_digests(attributes: &Attributes, suffix: &str, bytes: &[u8]) -> Option<bool> {
+    let mut checked = false;
+
+    for (algorithm, expected) in digest_attributes(attributes, suffix) {
+        if STANDARD.decode(expected).ok() != Some(algorithm.digest(bytes)) {
+            return Some(false);
+        }
+        checked = true;
+    }
+
+    checked.then_some(true)
+}
+
+/// Checks digest attributes of `attributes` with given suffix against the content of the file at
+/// `path`, reading it in chunks, see [check_digests].
+fn check_file_digests(attributes: &Attributes, suffix: &str, path: &Path) -> Result<Option<bool>, JarError> {
+    let mut checked = false;
+
+    for (algorithm, expected) in digest_attributes(attributes, suffix) {
+        let mut writer = DigestWriter(algorithm.context());
+
+        std::io::copy(&mut File::open(path)?, &mut writer)?;
+        if STANDARD.decode(expected).ok().as_deref() != Some(writer.0.finish().as_ref()) {
+            return Ok(Some(false));
+        }
+        checked = true;
+    }
+
+    Ok(checked.then_some(true))
+}
+
+/// Verifies `files` and the files `spilled` to disk, whose content is digested from disk.
+pub(crate) fn verify(files: &HashMap<String, Vec<u8>>, spilled: &HashMap<String, PathBuf>) -> Result<VerificationReport, JarError> {
+    // The manifest and signature files are small, so spilled ones are read back into memory.
+    let spilled_signature_files = spilled.iter()
+        .filter(|(path, _)| is_signature_file(path))
+        .map(|(path, file)| Ok((path.clone(), std::fs::read(file)?)))
+        .collect::<Result<HashMap<_, _>, JarError>>()?;
+    let meta_file = |path: &str| files.get(path).or_else(|| spilled_signature_files.get(path));
+    let manifest_bytes = meta_file(MANIFEST_PATH).map(Vec::as_slice);
+    let manifest = manifest_bytes.map(parse_manifest).transpose()?.unwrap_or_default();
+    let mut manifest_sections = HashMap::new();
+
+    for raw in manifest_bytes.map(raw_sections).unwrap_or_default().into_iter().skip(1) {
+        if let Some(name) = parse_manifest(raw)?.main_attributes().get("Name") {
+            manifest_sections.insert(name.to_string(), raw);
+        }
+    }
+
+    let mut signers = Vec::new();
+    // Entry name -> (signer index, whether the signer's digest of its manifest section matches).
+    let mut coverage = HashMap::<String, Vec<(usize, bool)>>::new();
+    let mut signature_files = files.iter()
+        .chain(&spilled_signature_files)
+        .filter(|(path, _)| is_signature_file(path) && path.to_ascii_uppercase().ends_with(".SF"))
+        .collect::<Vec<_>>();
+    signature_files.sort();
+
+    for (path, signature_file) in signature_files {
+        let name = &path[..path.len() - 3];
+        let block = SIGNATURE_BLOCK_EXTENSIONS.iter()
+            .find_map(|ext| meta_file(&format!("{}.{}", name, ext)));
+        let (status, certificate) = match block {
+            Some(block) => verify_block(block, signature_file),
+            None => (SignatureStatus::Missing, None),
+        };
+        let signature_file = parse_manifest(signature_file)?;
+        let manifest_matches = manifest_bytes.is_some_and(|manifest_bytes| {
+            check_digests(signature_file.main_attributes(), "-Digest-Manifest", manifest_bytes) == Some(true)
+        });
+
+        for (entry, attributes) in signature_file.entries() {
+            let section_matches = manifest_matches || manifest_sections.get(entry).is_some_and(|raw| {
+                check_digests(attributes, "-Digest", raw) == Some(true)
+            });
+
+            coverage.entry(entry.to_string()).or_default().push((signers.len(), section_matches));
+        }
+
+        signers.push(Signer {
+            name: name.to_string(),
+            status,
+            certificate,
+        });
+    }
+
+    let mut entries = BTreeMap::new();
+
+    for (path, content) in files.iter().filter(|(path, _)| !is_signature_file(path)) {
+        let digest_matches = manifest.entry(path).and_then(|attributes| check_digests(attributes, "-Digest", content));
+
+        entries.insert(path.clone(), entry_status(&signers, coverage.get(path.as_str()), digest_matches));
+    }
+    for (path, file) in spilled.iter().filter(|(path, _)| !is_signature_file(path)) {
+        let digest_matches = match manifest.entry(path) {
+            Some(attributes) => check_file_digests(attributes, "-Digest", file)?,
+            None => None,
+        };
+
+        entries.insert(path.clone(), entry_status(&signers, coverage.get(path.as_str()), digest_matches));
+    }
+    for (path, attributes) in manifest.entries() {
+        if !entries.contains_key(path) && !is_signature_file(path) && !path.ends_with('/')
+            && digest_attributes(attributes, "-Digest").next().is_some() {
+            entries.insert(path.to_string(), EntryStatus::Unchecked);
+        }
+    }
+
+    Ok(VerificationReport {
+        signers,
+        entries,
+    })
+}
+
+/// Derives the status of an entry from whether its content matches its manifest digest, [None]
+/// meaning it has none, and the signers covering its manifest section.
+fn entry_status(signers: &[Signer], coverage: Option<&Vec<(usize, bool)>>, digest_matches: Option<bool>) -> EntryStatus {
+    let coverage = coverage.map(Vec::as_slice).unwrap_or_default();
+
+    match digest_matches {
+        Some(false) => EntryStatus::Tampered,
+        None => EntryStatus::Unsigned,
+        Some(true) => {
+            let verified_signers = coverage.iter()
+                .filter(|(signer, matches)| *matches && signers[*signer].status == SignatureStatus::Verified)
+                .map(|(signer, _)| signers[*signer].name.clone())
+                .collect::<Vec<_>>();
+            let tampered = coverage.iter()
+                .any(|(signer, matches)| !*matches || matches!(signers[*signer].status, SignatureStatus::Invalid(_)));
+
+            if !verified_signers.is_empty() {
+                EntryStatus::Signed(verified_signers)
+            } else if tampered {
+                EntryStatus::Tampered
+            } else {
+                EntryStatus::Unsigned
+            }
+        }
+    }
+}
+
+/// Verifies a PKCS#7 signature block against the signature file it signs.
+fn verify_block(block: &[u8], signed: &[u8]) -> (SignatureStatus, Option<Vec<u8>>) {
+    let signed_data = match SignedData::parse(block) {
+        Ok(signed_data) => signed_data,
+        Err(reason) => return (SignatureStatus::Invalid(reason.to_string()), None),
+    };
+    let certificate = signed_data.certificate.map(|certificate| certificate.raw.to_vec());
+
+    (signed_data.verify(signed).unwrap_or_else(|reason| SignatureStatus::Invalid(reason.to_string())), certificate)
+}
+
+/// The parts of a PKCS#7 `SignedData` needed to verify its first signer.
+struct SignedData<'a> {
+    certificate: Option<Der<'a>>,
+    digest_algorithm: &'a [u8],
+    signed_attributes: Option<Der<'a>>,
+    signature_algorithm: &'a [u8],
+    signature: &'a [u8],
+}
+
+impl<'a> SignedData<'a> {
+    fn parse(block: &'a [u8]) -> DerResult<Self> {
+        let mut content_info = DerReader::new(block).expect(tag::SEQUENCE)?.reader();
+
+        if content_info.expect(tag::OID)?.content != OID_SIGNED_DATA {
+            return Err("signature block is not a PKCS#7 signed data");
+        }
+
+        let mut signed_data = content_info.expect(tag::CONTEXT_0)?.reader().expect(tag::SEQUENCE)?.reader();
+        signed_data.expect(tag::INTEGER)?;
+        signed_data.expect(tag::SET)?;
+        signed_data.expect(tag::SEQUENCE)?;
+
+        let mut certificates = Vec::new();
+        if let Some(certificate_set) = signed_data.optional(tag::CONTEXT_0)? {
+            let mut reader = certificate_set.reader();
+            while !reader.is_empty() {
+                certificates.push(reader.read()?);
+            }
+        }
+        signed_data.optional(0xA1)?;
+
+        let mut signer_info = signed_data.expect(tag::SET)?.reader().expect(tag::SEQUENCE)?.reader();
+        signer_info.expect(tag::INTEGER)?;
+
+        let signer_id = signer_info.read()?;
+        let digest_algorithm = signer_info.expect(tag::SEQUENCE)?.reader().expect(tag::OID)?.content;
+        let signed_attributes = signer_info.optional(tag::CONTEXT_0)?;
+        let signature_algorithm = signer_info.expect(tag::SEQUENCE)?.reader().expect(tag::OID)?.content;
+        let signature = signer_info.expect(tag::OCTET_STRING)?.content;
+
+        let certificate = match issuer_and_serial(signer_id) {
+            Some(issuer_and_serial) => certificates.into_iter()
+                .find(|certificate| certificate_issuer_and_serial(certificate).ok() == Some(issuer_and_serial)),
+            None => certificates.into_iter().next(),
+        };
+
+        Ok(Self {
+            certificate,
+            digest_algorithm,
+            signed_attributes,
+            signature_algorithm,
+            signature,
+        })
+    }
+
+    fn verify(&self, signed: &[u8]) -> DerResult<SignatureStatus> {
+        let certificate = self.certificate.ok_or("signer certificate is missing")?;
+        let Some(digest_algorithm) = digest_algorithm(self.digest_algorithm) else {
+            return Ok(SignatureStatus::Unsupported("digest algorithm".to_string()));
+        };
+        let message = match self.signed_attributes {
+            Some(signed_attributes) => {
+                if message_digest(signed_attributes)? != digest_algorithm.digest(signed).as_slice() {
+                    return Ok(SignatureStatus::Invalid("message digest mismatch".to_string()));
+                }
+
+                // Signed attributes are signed as an explicit SET instead of their implicit tag.
+                let mut message = signed_attributes.raw.to_vec();
+                message[0] = tag::SET;
+                message
+            }
+            None => signed.to_vec(),
+        };
+
+        let public_key = public_key(certificate)?;
+        let hash = signature_digest_algorithm(self.signature_algorithm).unwrap_or(digest_algorithm);
+        let algorithm: &dyn VerificationAlgorithm = match (public_key.algorithm, public_key.curve, hash) {
+            (OID_RSA, _, DigestAlgorithm::Sha1) => &signature::RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY,
+            (OID_RSA, _, DigestAlgorithm::Sha256) => &signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY,
+            (OID_RSA, _, DigestAlgorithm::Sha384) => &signature::RSA_PKCS1_2048_8192_SHA384,
+            (OID_RSA, _, DigestAlgorithm::Sha512) => &signature::RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY,
+            (OID_EC, Some(OID_P256), DigestAlgorithm::Sha256) => &signature::ECDSA_P256_SHA256_ASN1,
+            (OID_EC, Some(OID_P256), DigestAlgorithm::Sha384) => &signature::ECDSA_P256_SHA384_ASN1,
+            (OID_EC, Some(OID_P384), DigestAlgorithm::Sha256) => &signature::ECDSA_P384_SHA256_ASN1,
+            (OID_EC, Some(OID_P384), DigestAlgorithm::Sha384) => &signature::ECDSA_P384_SHA384_ASN1,
+            _ => return Ok(SignatureStatus::Unsupported("signature algorithm".to_string())),
+        };
+
+        match UnparsedPublicKey::new(algorithm, public_key.key).verify(&message, self.signature) {
+            Ok(()) => Ok(SignatureStatus::Verified),
+            Err(_) => Ok(SignatureStatus::Invalid("signature mismatch".to_string())),
+        }
+    }
+}
+
+fn digest_algorithm(oid: &[u8]) -> Option<DigestAlgorithm> {
+    match oid {
+        OID_SHA1 => Some(DigestAlgorithm::Sha1),
+        OID_SHA256 => Some(DigestAlgorithm::Sha256),
+        OID_SHA384 => Some(DigestAlgorithm::Sha384),
+        OID_SHA512 => Some(DigestAlgorithm::Sha512),
+        _ => None,
+    }
+}
+
+/// Returns the digest algorithm implied by a combined signature algorithm, e.g. `sha256WithRSA`.
+fn signature_digest_algorithm(oid: &[u8]) -> Option<DigestAlgorithm> {
+    match oid {
+        OID_SHA1_RSA => Some(DigestAlgorithm::Sha1),
+        OID_SHA256_RSA | OID_SHA256_ECDSA => Some(DigestAlgorithm::Sha256),
+        OID_SHA384_RSA | OID_SHA384_ECDSA => Some(DigestAlgorithm::Sha384),
+        OID_SHA512_RSA | OID_SHA512_ECDSA => Some(DigestAlgorithm::Sha512),
+        _ => None,
+    }
+}
+
+/// Extracts the raw issuer name and serial number content of an `IssuerAndSerialNumber` signer
+/// identifier, returns [None] for subject key identifiers.
+fn issuer_and_serial(signer_id: Der<'_>) -> Option<(&[u8], &[u8])> {
+    if signer_id.tag != tag::SEQUENCE {
+        return None;
+    }
+
+    let mut reader = signer_id.reader();
+    let issuer = reader.expect(tag::SEQUENCE).ok()?;
+    let serial = reader.expect(tag::INTEGER).ok()?;
+
+    Some((issuer.raw, serial.content))
+}
+
+/// Reads the `TBSCertificate` of a certificate, positioned after the optional version.
+fn tbs_certificate<'a>(certificate: &Der<'a>) -> DerResult<DerReader<'a>> {
+    let mut tbs = certificate.reader().expect(tag::SEQUENCE)?.reader();
+
+    tbs.optional(tag::CONTEXT_0)?;
+    Ok(tbs)
+}
+
+pub(crate) fn certificate_issuer_and_serial<'a>(certificate: &Der<'a>) -> DerResult<(&'a [u8], &'a [u8])> {
+    let mut tbs = tbs_certificate(certificate)?;
+    let serial = tbs.expect(tag::INTEGER)?;
+    tbs.expect(tag::SEQUENCE)?;
+    let issuer = tbs.expect(tag::SEQUENCE)?;
+
+    Ok((issuer.raw, serial.content))
+}
+
+/// A certificate's subject public key.
+pub(crate) struct PublicKey<'a> {
+    pub(crate) algorithm: &'a [u8],
+    /// Named curve of elliptic curve keys.
+    pub(crate) curve: Option<&'a [u8]>,
+    pub(crate) key: &'a [u8],
+}
+
+pub(crate) fn public_key<'a>(certificate: Der<'a>) -> DerResult<PublicKey<'a>> {
+    let mut tbs = tbs_certificate(&certificate)?;
+
+    for _ in 0..5 {
+        tbs.read()?;
+    }
+
+    let mut key_info = tbs.expect(tag::SEQUENCE)?.reader();
+    let mut algorithm = key_info.expect(tag::SEQUENCE)?.reader();
+    let key_algorithm = algorithm.expect(tag::OID)?.content;
+    let curve = algorithm.optional(tag::OID)?.map(|curve| curve.content);
+    let key = key_info.expect(tag::BIT_STRING)?.content;
+
+    match key.split_first() {
+        Some((0, key)) => Ok(PublicKey {
+            algorithm: key_algorithm,
+            curve,
+            key,
+        }),
+        _ => Err("public key has unused bits"),
+    }
+}
+
+fn message_digest<'a>(signed_attributes: Der<'a>) -> DerResult<&'a [u8]> {
+    let mut attributes = signed_attributes.reader();
+
+    while !attributes.is_empty() {
+        let mut attribute = attributes.expect(tag::SEQUENCE)?.reader();
+
+        if attribute.expect(tag::OID)?.content == OID_MESSAGE_DIGEST {
+            return Ok(attribute.expect(tag::SET)?.reader().expect(tag::OCTET_STRING)?.content);
+        }
+    }
+
+    Err("signed attributes have no message digest")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{jar_from_bytes, EntryStatus, JarOptionBuilder, SignatureStatus};
+
+    const SIGNED_RSA: &[u8] = include_bytes!("../sample/signed-rsa.jar");
+    const SIGNED_EC: &[u8] = include_bytes!("../sample/signed-ec.jar");
+
+    #[test]
+    fn test_verify_signed_jars() {
+        for (bytes, signer) in [(SIGNED_RSA, "META-INF/RSA"), (SIGNED_EC, "META-INF/EC")] {
+            let jar = jar_from_bytes(bytes, JarOptionBuilder::default()).unwrap();
+            let report = jar.verify().unwrap();
+
+            assert!(report.is_verified(), "{:?}", report);
+            assert_eq!(report.signers[0].name, signer);
+            assert_eq!(report.entries["a/A.class"], EntryStatus::Signed(vec![signer.to_string()]));
+        }
+    }
+
+    #[test]
+    fn test_verify_tampered_jar() {
+        let mut jar = jar_from_bytes(SIGNED_RSA, JarOptionBuilder::default()).unwrap();
+
+        jar.files.insert("a/A.class".to_string(), b"class B".to_vec());
+        jar.files.insert("a/Injected.class".to_string(), b"injected".to_vec());
+
+        let report = jar.verify().unwrap();
+
+        assert!(!report.is_verified());
+        assert_eq!(report.tampered().collect::<Vec<_>>(), vec!["a/A.class"]);
+        assert_eq!(report.entries["a/Injected.class"], EntryStatus::Unsigned);
+        assert_eq!(report.entries["a/messages.properties"], EntryStatus::Signed(vec!["META-INF/RSA".to_string()]));
+
+        let signature_file = jar.files.get_mut("META-INF/RSA.SF").unwrap();
+        signature_file.extend_from_slice(b"\r\n");
+
+        let report = jar.verify().unwrap();
+
+        assert!(matches!(report.signers[0].status, SignatureStatus::Invalid(_)));
+        assert_eq!(report.entries["a/messages.properties"], EntryStatus::Tampered);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_verify_spilled_jar() {
+        let option = JarOptionBuilder::builder().spill_to_disk(4, crate::fixture::temp_path("verify-spill")).build();
+        let jar = jar_from_bytes(SIGNED_RSA, option).unwrap();
+
+        assert!(jar.spilled().contains_key("a/messages.properties"));
+        assert!(jar.spilled().contains_key("META-INF/MANIFEST.MF"));
+        assert!(jar.verify().unwrap().is_verified());
+
+        std::fs::write(&jar.spilled()["a/messages.properties"], b"key=tampered").unwrap();
+
+        let report = jar.verify().unwrap();
+
+        assert!(!report.is_verified());
+        assert_eq!(report.tampered().collect::<Vec<_>>(), vec!["a/messages.properties"]);
+    }
+
+    #[test]
+    fn test_verify_filtered_jar() {
+        let jar = jar_from_bytes(SIGNED_RSA, JarOptionBuilder::builder().ext("class").keep_meta_info().build()).unwrap();
+        let report = jar.verify().unwrap();
+
+        assert!(!jar.files.contains_key("a/messages.properties"));
+        assert_eq!(report.entries["a/A.class"], EntryStatus::Signed(vec!["META-INF/RSA".to_string()]));
+        assert_eq!(report.entries["a/messages.properties"], EntryStatus::Unchecked);
+        assert!(!report.is_verified());
+    }
+
+    #[test]
+    fn test_verify_manifest_digests() {
+        let mut jar = jar_from_bytes(SIGNED_RSA, JarOptionBuilder::default()).unwrap();
+        let report = jar.verify_manifest_digests().unwrap();
+
+        assert!(report.is_intact(), "{:?}", report);
+        assert_eq!(report.checked, jar.files.len() - 3);
+
+        jar.files.insert("a/A.class".to_string(), b"class B".to_vec());
+        jar.files.insert("a/Injected.class".to_string(), b"injected".to_vec());
+        jar.files.remove("a/messages.properties");
+
+        let report = jar.verify_manifest_digests().unwrap();
+
+        assert_eq!(report.mismatched, vec!["a/A.class"]);
+        assert_eq!(report.undigested, vec!["a/Injected.class"]);
+        assert_eq!(report.missing, vec!["a/messages.properties"]);
+        assert!(!report.is_intact());
+    }
+}
@@ -0,0 +1,198 @@
+//! Streaming entries into caller-defined destinations without materializing the whole jar.
+
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{Read, Seek, Write};
+use std::ops::ControlFlow;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::option::{Callback, TeeFactory};
+use crate::progress::{self, Progress};
+use crate::{JarEntry, JarError, JarOption, JarStream};
+
+/// A destination of entries visited by [jar_visit], e.g. a database, an archive writer or a hasher.
+///
+/// Closures taking a [JarEntry] and a reader implement [EntrySink] as well.
+///
+/// # Example
+///
+/// ```rs
+/// struct Sizes(u64);
+///
+/// impl EntrySink for Sizes {
+///     fn entry(&mut self, entry: &JarEntry, reader: &mut dyn Read) -> Result<ControlFlow<()>, JarError> {
+///         self.0 += std::io::copy(reader, &mut std::io::sink())?;
+///         Ok(ControlFlow::Continue(()))
+///     }
+/// }
+/// ```
+pub trait EntrySink {
+    /// Receives an entry matching the extraction rules along with a reader of its decompressed
+    /// content. The reader need not be consumed. Returning [ControlFlow::Break] stops visiting
+    /// further entries.
+    fn entry(&mut self, entry: &JarEntry, reader: &mut dyn Read) -> Result<ControlFlow<()>, JarError>;
+}
+
+impl<F> EntrySink for F where F: FnMut(&JarEntry, &mut dyn Read) -> Result<ControlFlow<()>, JarError> {
+    fn entry(&mut self, entry: &JarEntry, reader: &mut dyn Read) -> Result<ControlFlow<()>, JarError> {
+        self(entry, reader)
+    }
+}
+
+/// Streams entries of the jar at `path` matching `option` into `sink` in archive order, holding
+/// no more than the sink itself buffers. See [crate::jar] for the extraction rules. Returns the
+/// number of entries visited.
+///
+/// # Example
+///
+/// ```rs
+/// jar_visit("sample/rt.jar", JarOptionBuilder::builder().ext("class").build(), &mut |entry: &JarEntry, reader: &mut dyn Read| {
+///     database.insert(&entry.name, reader)?;
+///     Ok(ControlFlow::Continue(()))
+/// })?;
+/// ```
+#[cfg(feature = "fs")]
+pub fn jar_visit<P, S>(path: P, option: JarOption, sink: &mut S) -> Result<usize, JarError>
+    where P: AsRef<Path>, S: EntrySink + ?Sized {
+    jar_visit_reader(File::open(path)?, option, sink)
+}
+
+/// Streams entries of a jar from any seekable source into `sink`. See [jar_visit].
+pub fn jar_visit_reader<R, S>(reader: R, option: JarOption, sink: &mut S) -> Result<usize, JarError>
+    where R: Read + Seek, S: EntrySink + ?Sized {
+    let progress = option.progress.clone();
+    let mut stream = JarStream::new(reader, option)?;
+    let total = stream.len();
+    let mut visited = 0;
+
+    while let Some(next) = stream.next_entry() {
+        let (file_path, mut reader) = next?;
+        let entry = reader.entry().clone();
+        let flow = sink.entry(&entry, &mut reader)?;
+
+        visited += 1;
+        progress::report(&progress, Progress {
+            index: reader.index(),
+            total,
+            name: &file_path,
+            bytes_read: reader.bytes_read(),
+        })?;
+
+        if flow.is_break() {
+            break;
+        }
+    }
+
+    Ok(visited)
+}
+
+/// A reader copying everything read to a writer of [crate::JarOptionBuilder::tee], flushing it at
+/// the end of the content.
+pub(crate) struct TeeReader<R> {
+    reader: R,
+    writer: Option<Box<dyn Write + Send>>,
+}
+
+impl<R> TeeReader<R> where R: Read {
+    /// Wraps the content reader of `entry` with the writer `factory` creates for it, if any.
+    pub(crate) fn new(factory: &Option<Callback<TeeFactory>>, entry: &crate::JarEntry, reader: R) -> Result<Self, JarError> {
+        let writer = match factory {
+            Some(factory) => (factory.0)(entry)?,
+            None => None,
+        };
+
+        Ok(Self { reader, writer })
+    }
+}
+
+impl<R> Read for TeeReader<R> where R: Read {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.reader.read(buf)?;
+
+        if let Some(writer) = &mut self.writer {
+            writer.write_all(&buf[..read])?;
+            if read == 0 && !buf.is_empty() {
+                writer.flush()?;
+            }
+        }
+
+        Ok(read)
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::{Read, Write};
+    use std::ops::ControlFlow;
+    use std::sync::{Arc, Mutex};
+
+    use crate::fixture::{jar_bytes, jar_file};
+    use crate::{jar_from_bytes, jar_visit, EntrySink, JarEntry, JarError, JarOptionBuilder};
+
+    struct Collect(Vec<(String, Vec<u8>)>);
+
+    impl EntrySink for Collect {
+        fn entry(&mut self, entry: &JarEntry, reader: &mut dyn Read) -> Result<ControlFlow<()>, JarError> {
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content)?;
+            self.0.push((entry.name.clone(), content));
+
+            Ok(ControlFlow::Continue(()))
+        }
+    }
+
+    #[test]
+    fn test_jar_visit() {
+        let path = jar_file(&[("a/A.class", b"a"), ("a/a.properties", b"p"), ("b/B.class", b"b")]);
+        let mut sink = Collect(Vec::new());
+
+        assert_eq!(jar_visit(&path, JarOptionBuilder::builder().ext("class").build(), &mut sink).unwrap(), 2);
+        assert_eq!(sink.0, vec![("a/A.class".to_string(), b"a".to_vec()), ("b/B.class".to_string(), b"b".to_vec())]);
+
+        let mut names = Vec::new();
+        let visited = jar_visit(&path, JarOptionBuilder::default(), &mut |entry: &JarEntry, _: &mut dyn Read| {
+            names.push(entry.name.clone());
+            Ok(ControlFlow::Break(()))
+        }).unwrap();
+
+        assert_eq!(visited, 1);
+        assert_eq!(names, vec!["a/A.class"]);
+    }
+
+    struct Shared(String, Arc<Mutex<HashMap<String, Vec<u8>>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.1.lock().unwrap().entry(self.0.clone()).or_default().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_tee() {
+        let bytes = jar_bytes(&[("a/A.class", b"a"), ("a/b.txt", &[b'b'; 100_000]), ("c.txt", b"c")]);
+
+        for threads in [1, 2] {
+            let written = Arc::new(Mutex::new(HashMap::new()));
+            let teed = written.clone();
+            let option = JarOptionBuilder::builder()
+                .parallel(threads)
+                .tee(move |entry| Ok(entry.name.starts_with("a/").then(|| Box::new(Shared(entry.name.clone(), teed.clone())) as _)))
+                .map_content(|name, content| (name != "a/A.class").then_some(content))
+                .build();
+            let jar = jar_from_bytes(&bytes, option).unwrap();
+            let written = written.lock().unwrap();
+
+            assert_eq!(jar.files.len(), 2);
+            assert_eq!(written.len(), 2);
+            assert_eq!(written["a/A.class"], b"a");
+            assert_eq!(written["a/b.txt"], jar.files["a/b.txt"]);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Detection of entry content types by magic bytes, telling class files, images, archives and
+//! properties apart regardless of their extension, e.g. in obfuscated jars renaming class files.
+
+use crate::classfile::MAGIC;
+use crate::text::SAMPLE_SIZE;
+
+/// Oldest class file major version, of JDK 1.0.2.
+const MIN_MAJOR_VERSION: u16 = 45;
+
+/// Magic bytes of the supported image formats, see [ContentKind::Image].
+const IMAGE_MAGICS: [&[u8]; 5] = [b"\x89PNG\r\n\x1A\n", b"\xFF\xD8\xFF", b"GIF87a", b"GIF89a", b"\0\0\x01\0"];
+
+/// Magic bytes of a local file header, an empty archive's end of central directory and a spanned
+/// archive marker.
+const ZIP_MAGICS: [&[u8]; 3] = [b"PK\x03\x04", b"PK\x05\x06", b"PK\x07\x08"];
+
+/// Type of entry content detected by [ContentKind::detect], set as filter with
+/// [crate::JarOptionBuilder::content_type].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContentKind {
+    /// A class file, starting with `0xCAFEBABE` followed by a plausible class file version, which
+    /// tells it from a universal Mach-O binary sharing the magic.
+    ClassFile,
+    /// A PNG, JPEG, GIF, BMP, WebP or ICO image.
+    Image,
+    /// A zip archive, e.g. a nested jar.
+    Zip,
+    /// A properties file: text without control characters other than whitespace, where every line
+    /// but blank lines, comments and continuations holds a key and a value.
+    Properties,
+}
+
+impl ContentKind {
+    /// Detects the type of `bytes` by its magic bytes, or for properties by the first 8000 bytes.
+    /// Returns [None] for content of any other type or empty content.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// assert_eq!(ContentKind::detect(b"\xCA\xFE\xBA\xBE\0\0\0\x34"), Some(ContentKind::ClassFile));
+    /// assert_eq!(ContentKind::detect(b"greeting=hello\n"), Some(ContentKind::Properties));
+    /// ```
+    pub fn detect(bytes: &[u8]) -> Option<ContentKind> {
+        if is_class_file(bytes) {
+            Some(ContentKind::ClassFile)
+        } else if is_image(bytes) {
+            Some(ContentKind::Image)
+        } else if ZIP_MAGICS.iter().any(|magic| bytes.starts_with(magic)) {
+            Some(ContentKind::Zip)
+        } else if is_properties(bytes) {
+            Some(ContentKind::Properties)
+        } else {
+            None
+        }
+    }
+}
+
+fn is_class_file(bytes: &[u8]) -> bool {
+    bytes.len() >= 8 && bytes[..4] == MAGIC.to_be_bytes() && u16::from_be_bytes([bytes[6], bytes[7]]) >= MIN_MAJOR_VERSION
+}
+
+fn is_image(bytes: &[u8]) -> bool {
+    IMAGE_MAGICS.iter().any(|magic| bytes.starts_with(magic))
+        // BMP declares its file size after the magic, followed by four reserved zero bytes.
+        || (bytes.len() >= 14 && bytes.starts_with(b"BM") && bytes[6..10] == [0; 4])
+        || (bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP")
+}
+
+fn is_properties(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(SAMPLE_SIZE)];
+
+    if sample.iter().any(|byte| byte.is_ascii_control() && !b"\t\n\r\x0C".contains(byte)) {
+        return false;
+    }
+
+    let mut lines = sample.split(|byte| *byte == b'\n' || *byte == b'\r').collect::<Vec<_>>();
+
+    // The last line of a truncated sample may be cut off before its separator.
+    if sample.len() < bytes.len() {
+        lines.pop();
+    }
+
+    let mut continued = false;
+    let mut pairs = 0;
+
+    for line in lines {
+        let line = line.trim_ascii_start();
+        let was_continued = std::mem::replace(&mut continued, line.ends_with(b"\\"));
+
+        if was_continued || line.is_empty() || line.starts_with(b"#") || line.starts_with(b"!") {
+            continue;
+        }
+        if !line.iter().any(|byte| b"=:".contains(byte)) {
+            return false;
+        }
+
+        pairs += 1;
+    }
+
+    pairs > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, ContentKind, JarOptionBuilder};
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(ContentKind::detect(b"\xCA\xFE\xBA\xBE\0\0\0\x34rest"), Some(ContentKind::ClassFile));
+        assert_eq!(ContentKind::detect(b"\xCA\xFE\xBA\xBE\0\0\0\x02"), None);
+        assert_eq!(ContentKind::detect(b"\x89PNG\r\n\x1A\n\0\0"), Some(ContentKind::Image));
+        assert_eq!(ContentKind::detect(b"RIFF\0\0\0\0WEBPVP8 "), Some(ContentKind::Image));
+        assert_eq!(ContentKind::detect(b"PK\x03\x04"), Some(ContentKind::Zip));
+        assert_eq!(ContentKind::detect(b"# comment\n\nkey=value\nmulti = a, \\\n    b\r\nother: c"), Some(ContentKind::Properties));
+        assert_eq!(ContentKind::detect(b"just some prose\n"), None);
+        assert_eq!(ContentKind::detect(b"a=b\0"), None);
+        assert_eq!(ContentKind::detect(b""), None);
+    }
+
+    #[test]
+    fn test_content_type_filter() {
+        let bytes = jar_bytes(&[
+            ("a/A.class", b"\xCA\xFE\xBA\xBE\0\0\0\x34"),
+            ("a/b.dat", b"\xCA\xFE\xBA\xBE\0\0\0\x3D"),
+            ("a/c.class", b"not a class"),
+            ("app.properties", b"name=app"),
+            ("dir/", b""),
+        ]);
+
+        for option in [JarOptionBuilder::builder(), JarOptionBuilder::builder().parallel(2)] {
+            let option = option.content_type(ContentKind::ClassFile).map_content(|_, content| Some(content[4..].to_vec())).include_dirs();
+            let mut names = jar_from_bytes(&bytes, option.build()).unwrap().files.into_iter().collect::<Vec<_>>();
+
+            names.sort();
+            assert_eq!(names, vec![
+                ("a/A.class".to_string(), b"\0\0\0\x34".to_vec()),
+                ("a/b.dat".to_string(), b"\0\0\0\x3D".to_vec()),
+                ("dir/".to_string(), Vec::new()),
+            ]);
+        }
+
+        let option = JarOptionBuilder::builder().content_type(ContentKind::Properties).content_type(ContentKind::Image).build();
+
+        assert_eq!(jar_from_bytes(&bytes, option).unwrap().files.into_keys().collect::<Vec<_>>(), vec!["app.properties"]);
+    }
+}
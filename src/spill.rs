@@ -0,0 +1,219 @@
+//! Spilling of huge entries to temporary files, bounding memory use when extracting jars with
+//! gigantic embedded resources, e.g. models or datasets.
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::limits::{check_declared_size, read_content};
+use crate::{Jar, JarEntry, JarError};
+
+static SPILLED: AtomicUsize = AtomicUsize::new(0);
+
+/// Attempts to create a file under a fresh random name before giving up.
+const CREATE_ATTEMPTS: usize = 16;
+
+/// Content of an extracted entry, see [Jar::into_contents].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryContent {
+    InMemory(Vec<u8>),
+    /// Content written to a temporary file by [crate::JarOptionBuilder::spill_to_disk].
+    OnDisk(PathBuf),
+}
+
+impl EntryContent {
+    pub fn is_on_disk(&self) -> bool {
+        matches!(self, EntryContent::OnDisk(_))
+    }
+
+    /// Opens a reader over the content, without loading spilled content into memory.
+    #[cfg(feature = "fs")]
+    pub fn reader(&self) -> Result<Box<dyn Read + '_>, JarError> {
+        match self {
+            EntryContent::InMemory(content) => Ok(Box::new(content.as_slice())),
+            EntryContent::OnDisk(path) => Ok(Box::new(File::open(path)?)),
+        }
+    }
+
+    /// Returns the content, reading spilled content from its file.
+    #[cfg(feature = "fs")]
+    pub fn into_bytes(self) -> Result<Vec<u8>, JarError> {
+        match self {
+            EntryContent::InMemory(content) => Ok(content),
+            EntryContent::OnDisk(path) => Ok(std::fs::read(path)?),
+        }
+    }
+}
+
+impl Jar {
+    /// Returns entries written to temporary files by [crate::JarOptionBuilder::spill_to_disk],
+    /// keyed by full qualified path. Spilled entries have metadata but are absent from
+    /// [Jar::files].
+    pub fn spilled(&self) -> &HashMap<String, PathBuf> {
+        &self.spilled
+    }
+
+    /// Returns the content of every extracted file, whether kept in memory or spilled, keyed by
+    /// full qualified path.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let option = JarOptionBuilder::builder().spill_to_disk(64 * 1024 * 1024, std::env::temp_dir()).build();
+    ///
+    /// for (name, content) in jar("model.jar", option)?.into_contents() {
+    ///     std::io::copy(&mut content.reader()?, &mut sink)?;
+    /// }
+    /// ```
+    pub fn into_contents(self) -> HashMap<String, EntryContent> {
+        self.files.into_iter()
+            .map(|(name, content)| (name, EntryContent::InMemory(content)))
+            .chain(self.spilled.into_iter().map(|(name, path)| (name, EntryContent::OnDisk(path))))
+            .collect()
+    }
+}
+
+/// Reads the content of `entry` from `reader` into memory, or into a file if `spill` applies to it,
+/// returning it with the number of bytes read. When `capped`, fails if the content is larger than
+/// declared.
+pub(crate) fn read_entry<R>(spill: &Option<Spill>, entry: &JarEntry, reader: &mut R, capped: bool) -> Result<(EntryContent, u64), JarError>
+    where R: Read {
+    match spill {
+        Some(spill) if spill.applies(entry) => {
+            let (path, written) = spill.write(entry, reader, capped)?;
+
+            Ok((EntryContent::OnDisk(path), written))
+        }
+        _ => {
+            let content = read_content(reader, entry, capped)?;
+            let read = content.len() as u64;
+
+            Ok((EntryContent::InMemory(content), read))
+        }
+    }
+}
+
+/// Spilling rule of [crate::JarOptionBuilder::spill_to_disk].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Spill {
+    threshold: u64,
+    dir: PathBuf,
+}
+
+impl Spill {
+    #[cfg(feature = "fs")]
+    pub(crate) fn new(threshold: u64, dir: &Path) -> Self {
+        Self {
+            threshold,
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// Checks whether the declared size of `entry` exceeds the threshold.
+    fn applies(&self, entry: &JarEntry) -> bool {
+        entry.size > self.threshold
+    }
+
+    /// Streams the content of `entry` from `reader` into a new file under the spill directory,
+    /// returning its path and the number of bytes written. When `capped`, fails if the content is
+    /// larger than declared. The file is removed if writing fails.
+    fn write<R>(&self, entry: &JarEntry, reader: &mut R, capped: bool) -> Result<(PathBuf, u64), JarError> where R: Read {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let (path, file) = self.create(entry.name.rsplit('/').next().unwrap_or_default())?;
+        let written = (|| {
+            let mut file = BufWriter::new(file);
+            let written = if capped {
+                let written = std::io::copy(&mut reader.take(entry.size + 1), &mut file)?;
+
+                check_declared_size(entry, written)?;
+                written
+            } else {
+                std::io::copy(reader, &mut file)?
+            };
+
+            file.flush()?;
+            Ok::<_, JarError>(written)
+        })();
+
+        match written {
+            Ok(written) => Ok((path, written)),
+            Err(err) => {
+                let _ = std::fs::remove_file(&path);
+                Err(err)
+            }
+        }
+    }
+
+    /// Creates a new file under the spill directory named with a random component, see
+    /// [create_new_file].
+    fn create(&self, file_name: &str) -> Result<(PathBuf, File), JarError> {
+        create_new_file(&self.dir, |nonce| {
+            format!("jars-spill-{}-{}-{}-{}", std::process::id(), SPILLED.fetch_add(1, Ordering::Relaxed), nonce, file_name)
+        })
+    }
+}
+
+/// Creates a new file in `dir` named by `name` from a random hex component, never opening an
+/// existing file or following a link planted in a shared directory such as `/tmp`.
+pub(crate) fn create_new_file<F>(dir: &Path, name: F) -> Result<(PathBuf, File), JarError> where F: Fn(&str) -> String {
+    let random = SystemRandom::new();
+    let mut last_error = None;
+
+    for _ in 0..CREATE_ATTEMPTS {
+        let mut nonce = [0; 8];
+
+        random.fill(&mut nonce).map_err(|_| std::io::Error::other("no system randomness available"))?;
+
+        let nonce = nonce.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+        let path = dir.join(name(&nonce));
+
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => last_error = Some(err),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Err(last_error.expect("at least one attempt is made").into())
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::io::Read;
+
+    use crate::fixture::{jar_bytes, jar_file, temp_path};
+    use crate::{jar, jar_from_bytes, EntryContent, JarOptionBuilder};
+
+    #[test]
+    fn test_spill_to_disk() {
+        let dir = temp_path("spill");
+        let path = jar_file(&[("small.txt", b"small"), ("data/model.bin", &[7; 4096])]);
+        let option = JarOptionBuilder::builder().spill_to_disk(1024, &dir).parallel(2).build();
+        let jar = jar(&path, option).unwrap();
+
+        assert!(!jar.files.contains_key("data/model.bin"));
+        assert_eq!(jar.entry("data/model.bin").unwrap().size, 4096);
+        assert!(jar.spilled()["data/model.bin"].starts_with(&dir));
+        // A random component keeps the name unpredictable for other users of a shared directory.
+        let name = jar.spilled()["data/model.bin"].file_name().unwrap().to_str().unwrap().to_string();
+        assert!(name.split('-').any(|part| part.len() == 16 && part.bytes().all(|byte| byte.is_ascii_hexdigit())));
+
+        let mut contents = jar.into_contents();
+        let spilled = contents.remove("data/model.bin").unwrap();
+
+        assert!(spilled.is_on_disk());
+        assert_eq!(spilled.into_bytes().unwrap(), vec![7; 4096]);
+        assert_eq!(contents.remove("small.txt"), Some(EntryContent::InMemory(b"small".to_vec())));
+
+        let jar = jar_from_bytes(&jar_bytes(&[("data/model.bin", &[7; 4096])]), JarOptionBuilder::builder().spill_to_disk(1024, &dir).build()).unwrap();
+        let mut content = Vec::new();
+
+        EntryContent::OnDisk(jar.spilled()["data/model.bin"].clone()).reader().unwrap().read_to_end(&mut content).unwrap();
+        assert_eq!(content.len(), 4096);
+    }
+}
@@ -0,0 +1,254 @@
+//! Splitting a jar into several jars along package boundaries.
+
+use std::path::{Path, PathBuf};
+
+use crate::signature::is_signature_file;
+use crate::{
+    jar_list, jar_open, jar_stream, parse_manifest, JarEntry, JarError, JarOption, JarOptionBuilder, JarWriter, Manifest, RawEntry,
+    MANIFEST_PATH,
+};
+
+/// Rules of [split], assigning entries to output jars by path prefix.
+///
+/// # Example
+///
+/// ```rs
+/// let rules = SplitRules::new()
+///     .bucket("out/api.jar", &["com/example/api"])
+///     .bucket("out/impl.jar", &["com/example/impl", "META-INF/services"])
+///     .default_bucket("out/rest.jar");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SplitRules {
+    buckets: Vec<(PathBuf, Vec<String>)>,
+    default_bucket: Option<PathBuf>,
+    option: JarOption,
+}
+
+impl SplitRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes entries under any of `prefixes`, e.g. `com/example/api` for a package and its
+    /// subpackages, to the jar at `dest`. Prefixes match whole path segments, and an entry matching
+    /// prefixes of several buckets goes to the bucket with the longest one.
+    pub fn bucket<P>(mut self, dest: P, prefixes: &[&str]) -> Self where P: AsRef<Path> {
+        let prefixes = prefixes.iter().map(|prefix| prefix.trim_end_matches('/').to_string()).collect();
+
+        self.buckets.push((dest.as_ref().to_path_buf(), prefixes));
+        self
+    }
+
+    /// Writes entries matching no bucket to the jar at `dest`. Without a default bucket, such
+    /// entries are dropped.
+    pub fn default_bucket<P>(mut self, dest: P) -> Self where P: AsRef<Path> {
+        self.default_bucket = Some(dest.as_ref().to_path_buf());
+        self
+    }
+
+    /// Only splits entries passing the extraction rules of `option`, [JarOptionBuilder::default]
+    /// by default.
+    pub fn option(mut self, option: JarOption) -> Self {
+        self.option = option;
+        self
+    }
+
+    /// Returns the position of the bucket `name` is written to, the default bucket being last.
+    fn bucket_of(&self, name: &str) -> Option<usize> {
+        self.buckets.iter()
+            .enumerate()
+            .filter_map(|(position, (_, prefixes))| {
+                prefixes.iter()
+                    .filter(|prefix| is_under(name, prefix))
+                    .map(|prefix| (prefix.len(), position))
+                    .max()
+            })
+            .max_by_key(|(length, position)| (*length, std::cmp::Reverse(*position)))
+            .map(|(_, position)| position)
+            .or(self.default_bucket.as_ref().map(|_| self.buckets.len()))
+    }
+}
+
+fn is_under(name: &str, prefix: &str) -> bool {
+    name.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || prefix.is_empty())
+}
+
+/// A jar written by [split].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrittenJar {
+    pub path: PathBuf,
+    /// Metadata of the entries copied into the jar, in their original order.
+    pub entries: Vec<JarEntry>,
+}
+
+/// Splits the jar at `src` into the jars of `rules`, copying each entry in compressed form into
+/// the jar of its bucket in original order. Every written jar gets the main attributes of the
+/// source manifest and the per-entry sections of its own entries. Signature files are dropped, as
+/// splitting invalidates signatures, and with them the digests of manifest sections. Buckets
+/// without entries are not written. Returns the written jars in bucket order, the default bucket
+/// last.
+///
+/// # Example
+///
+/// ```rs
+/// let rules = SplitRules::new()
+///     .bucket("out/api.jar", &["com/example/api"])
+///     .default_bucket("out/core.jar");
+///
+/// for written in split("legacy.jar", rules)? {
+///     println!("{}: {} entries", written.path.display(), written.entries.len());
+/// }
+/// ```
+pub fn split<P>(src: P, rules: SplitRules) -> Result<Vec<WrittenJar>, JarError> where P: AsRef<Path> {
+    let src = src.as_ref();
+    let manifest = read_manifest(src)?;
+    let destinations = rules.buckets.iter()
+        .map(|(dest, _)| dest)
+        .chain(&rules.default_bucket)
+        .collect::<Vec<_>>();
+    let mut buckets = vec![Vec::new(); destinations.len()];
+
+    // Manifests are written first but depend on all entries of their jar, so entries are assigned
+    // from the central directory before any content is copied.
+    for entry in jar_list(src, rules.option.clone())? {
+        if let Some(bucket) = rules.bucket_of(&entry.name).filter(|_| !is_signature_file(&entry.name)) {
+            buckets[bucket].push(entry);
+        }
+    }
+
+    let mut writers = buckets.iter()
+        .zip(&destinations)
+        .map(|(entries, dest)| {
+            if entries.is_empty() {
+                return Ok(None);
+            }
+
+            let writer = JarWriter::create(dest)?;
+
+            Ok(Some(match &manifest {
+                Some(manifest) => writer.manifest(bucket_manifest(manifest, entries)),
+                None => writer,
+            }))
+        })
+        .collect::<Result<Vec<_>, JarError>>()?;
+    let mut stream = jar_stream(src, rules.option.clone())?;
+
+    while let Some(next) = stream.next_match() {
+        let (index, entry) = next?;
+        let writer = rules.bucket_of(&entry.name)
+            .filter(|_| !is_signature_file(&entry.name))
+            .and_then(|bucket| writers[bucket].as_mut());
+
+        if let Some(writer) = writer {
+            writer.write_raw(&RawEntry::read(entry, &mut stream.archive_mut().by_index_raw(index)?)?)?;
+        }
+    }
+
+    let mut written = Vec::new();
+
+    for ((writer, entries), dest) in writers.into_iter().zip(buckets).zip(destinations) {
+        if let Some(writer) = writer {
+            writer.finish()?;
+            written.push(WrittenJar {
+                path: dest.clone(),
+                entries,
+            });
+        }
+    }
+
+    Ok(written)
+}
+
+fn read_manifest(path: &Path) -> Result<Option<Manifest>, JarError> {
+    let mut jar = jar_open(path, JarOptionBuilder::builder().target(MANIFEST_PATH).build())?;
+
+    if jar.contains(MANIFEST_PATH) {
+        parse_manifest(&jar.get(MANIFEST_PATH)?).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Keeps the main attributes and the sections of `entries` or their directories, without digests.
+fn bucket_manifest(manifest: &Manifest, entries: &[JarEntry]) -> Manifest {
+    let mut bucket = Manifest::default();
+
+    for (name, value) in manifest.main_attributes().iter() {
+        bucket.main_attributes_mut().insert(name.to_string(), value.to_string());
+    }
+
+    for (section, attributes) in manifest.entries() {
+        let covered = entries.iter().any(|entry| {
+            entry.name == section || section.ends_with('/') && entry.name.starts_with(section)
+        });
+        let attributes = attributes.iter()
+            .filter(|(name, _)| !name.to_ascii_uppercase().ends_with("-DIGEST"))
+            .collect::<Vec<_>>();
+
+        if covered && !attributes.is_empty() {
+            let bucket_section = bucket.entry_mut(section);
+
+            for (name, value) in attributes {
+                bucket_section.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+
+    bucket
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::{jar_file, temp_path};
+    use crate::{jar, split, JarOptionBuilder, SplitRules};
+
+    #[test]
+    fn test_split() {
+        let manifest = b"Manifest-Version: 1.0\r\nImplementation-Version: 1.0\r\n\r\n\
+            Name: com/example/api/\r\nSealed: true\r\n\r\n\
+            Name: com/example/impl/Impl.class\r\nSHA-256-Digest: abc\r\n";
+        let src = jar_file(&[
+            ("META-INF/MANIFEST.MF", manifest),
+            ("META-INF/SIGNER.SF", b""),
+            ("META-INF/services/com.example.api.Api", b"com.example.impl.Impl"),
+            ("com/example/api/Api.class", b"api"),
+            ("com/example/apiutil/Util.class", b"util"),
+            ("com/example/impl/Impl.class", b"impl"),
+            ("com/example/impl/internal/Internal.class", b"internal"),
+            ("log4j.properties", b""),
+        ]);
+        let dir = temp_path("split");
+
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let rules = SplitRules::new()
+            .bucket(dir.join("api.jar"), &["com/example/api"])
+            .bucket(dir.join("impl.jar"), &["com/example", "META-INF/services"])
+            .bucket(dir.join("internal.jar"), &["com/example/impl/internal/"])
+            .bucket(dir.join("empty.jar"), &["org"])
+            .default_bucket(dir.join("rest.jar"));
+        let written = split(&src, rules).unwrap();
+        let names = |index: usize| written[index].entries.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(written.iter().map(|written| written.path.clone()).collect::<Vec<_>>(), vec![
+            dir.join("api.jar"),
+            dir.join("impl.jar"),
+            dir.join("internal.jar"),
+            dir.join("rest.jar"),
+        ]);
+        assert_eq!(names(0), vec!["com/example/api/Api.class"]);
+        assert_eq!(names(1), vec!["META-INF/services/com.example.api.Api", "com/example/apiutil/Util.class", "com/example/impl/Impl.class"]);
+        assert_eq!(names(2), vec!["com/example/impl/internal/Internal.class"]);
+        assert_eq!(names(3), vec!["log4j.properties"]);
+        assert!(!dir.join("empty.jar").exists());
+
+        let api = jar(dir.join("api.jar"), JarOptionBuilder::default()).unwrap().manifest().unwrap().unwrap();
+        let implementation = jar(dir.join("impl.jar"), JarOptionBuilder::default()).unwrap().manifest().unwrap().unwrap();
+
+        assert_eq!(api.main_attributes().get("Implementation-Version"), Some("1.0"));
+        assert_eq!(api.entry("com/example/api/").unwrap().get("Sealed"), Some("true"));
+        assert_eq!(implementation.entries().count(), 0);
+        assert_eq!(jar(dir.join("rest.jar"), JarOptionBuilder::default()).unwrap().files.len(), 2);
+    }
+}
@@ -0,0 +1,247 @@
+//! Layout of Spring Boot executable jars, which nest the application classes and dependency jars
+//! under `BOOT-INF`.
+
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::{jar_from_bytes, Jar, JarError, JarOption};
+#[cfg(feature = "fs")]
+use crate::jar;
+#[cfg(feature = "fs")]
+use crate::JarOptionBuilder;
+
+/// Default directory of a Spring Boot jar's application classes.
+pub const BOOT_INF_CLASSES: &str = "BOOT-INF/classes/";
+/// Default directory of a Spring Boot jar's dependency jars.
+pub const BOOT_INF_LIB: &str = "BOOT-INF/lib/";
+
+/// A layer of a Spring Boot jar declared in `layers.idx`, used to build layered container images.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layer {
+    /// Name of the layer, e.g. `dependencies` or `application`.
+    pub name: String,
+    /// Paths assigned to the layer. A path ending with `/` covers all entries under it.
+    pub paths: Vec<String>,
+}
+
+impl Layer {
+    /// Checks whether the entry at full qualified path `path` belongs to the layer.
+    pub fn contains(&self, path: &str) -> bool {
+        self.paths.iter().any(|layer_path| {
+            if layer_path.ends_with('/') {
+                path.starts_with(layer_path.as_str())
+            } else {
+                path == layer_path
+            }
+        })
+    }
+}
+
+/// A Spring Boot executable jar, whose application classes are under `BOOT-INF/classes` and
+/// dependency jars under `BOOT-INF/lib`. Locations declared by the `Spring-Boot-Classes`,
+/// `Spring-Boot-Lib`, `Spring-Boot-Classpath-Index` and `Spring-Boot-Layers-Index` manifest
+/// attributes take precedence over the defaults.
+///
+/// # Example
+///
+/// ```rs
+/// let boot = spring_boot("app.jar")?;
+///
+/// for library in boot.classpath() {
+///     println!("{} in layer {:?}", library, boot.layer_of(library));
+/// }
+/// ```
+pub struct SpringBootJar {
+    archive: Jar,
+    classes_directory: String,
+    lib_directory: String,
+    classpath_index: Option<Vec<String>>,
+    layers: Vec<Layer>,
+}
+
+impl SpringBootJar {
+    /// Wraps an extracted Spring Boot jar, parsing its classpath and layers indices if present.
+    /// Fails if an index is malformed.
+    pub fn new(archive: Jar) -> Result<Self, JarError> {
+        let manifest = archive.manifest()?.unwrap_or_default();
+        let attribute = |name: &str, default: &str| manifest.main_attributes().get(name)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .unwrap_or(default)
+            .to_string();
+
+        let classes_directory = directory(&attribute("Spring-Boot-Classes", BOOT_INF_CLASSES));
+        let lib_directory = directory(&attribute("Spring-Boot-Lib", BOOT_INF_LIB));
+        let classpath_index = archive.files.get(&attribute("Spring-Boot-Classpath-Index", "BOOT-INF/classpath.idx"))
+            .map(|index| parse_classpath_index(&String::from_utf8_lossy(index)))
+            .transpose()?;
+        let layers = archive.files.get(&attribute("Spring-Boot-Layers-Index", "BOOT-INF/layers.idx"))
+            .map(|index| parse_layers_index(&String::from_utf8_lossy(index)))
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(Self {
+            archive,
+            classes_directory,
+            lib_directory,
+            classpath_index,
+            layers,
+        })
+    }
+
+    /// Returns the extracted archive with all files under their original paths.
+    pub fn archive(&self) -> &Jar {
+        &self.archive
+    }
+
+    /// Returns the `Start-Class` manifest attribute, the application class run by the Spring Boot
+    /// launcher.
+    pub fn start_class(&self) -> Result<Option<String>, JarError> {
+        self.archive.launcher_info().map(|info| info.start_class)
+    }
+
+    /// Iterates over application files under `BOOT-INF/classes` with paths relative to it, as seen
+    /// by the application's class loader, e.g. `com/example/App.class`.
+    pub fn classes(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.archive.files.iter()
+            .filter_map(|(path, content)| Some((path.strip_prefix(self.classes_directory.as_str())?, content.as_slice())))
+    }
+
+    /// Iterates over full qualified paths of dependency jars directly under `BOOT-INF/lib`, sorted.
+    pub fn libraries(&self) -> impl Iterator<Item = &str> {
+        let mut paths = self.archive.files.keys()
+            .filter(|path| path.strip_prefix(self.lib_directory.as_str()).is_some_and(|name| !name.contains('/') && name.ends_with(".jar")))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        paths.sort();
+        paths.into_iter()
+    }
+
+    /// Returns full qualified paths of dependency jars in class path order as declared by
+    /// `BOOT-INF/classpath.idx`, falling back to [SpringBootJar::libraries] for jars without one.
+    pub fn classpath(&self) -> Vec<&str> {
+        match &self.classpath_index {
+            Some(index) => index.iter().map(String::as_str).collect(),
+            None => self.libraries().collect(),
+        }
+    }
+
+    /// Returns the parsed `BOOT-INF/classpath.idx`, if present.
+    pub fn classpath_index(&self) -> Option<&[String]> {
+        self.classpath_index.as_deref()
+    }
+
+    /// Returns layers declared by `BOOT-INF/layers.idx` in declaration order, which is empty for
+    /// jars built without layering.
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Returns the name of the layer the entry at full qualified path `path` is assigned to, which
+    /// is the first declared layer containing it.
+    pub fn layer_of(&self, path: &str) -> Option<&str> {
+        self.layers.iter().find(|layer| layer.contains(path)).map(|layer| layer.name.as_str())
+    }
+
+    /// Extracts the dependency jar at full qualified path `path`, see [SpringBootJar::libraries].
+    pub fn library(&self, path: &str, option: JarOption) -> Result<Jar, JarError> {
+        let content = self.archive.files.get(path).ok_or_else(|| JarError::EntryNotFound(path.to_string()))?;
+
+        jar_from_bytes(content, option)
+    }
+}
+
+/// Extracts the Spring Boot jar at `path` with all its files.
+#[cfg(feature = "fs")]
+pub fn spring_boot<P>(path: P) -> Result<SpringBootJar, JarError> where P: AsRef<Path> {
+    SpringBootJar::new(jar(path, JarOptionBuilder::default())?)
+}
+
+pub(crate) fn directory(path: &str) -> String {
+    format!("{}/", path.trim_matches('/'))
+}
+
+/// Parses an index line of the form `- "path"`, returning the unquoted path.
+fn index_item(line: &str) -> Option<&str> {
+    line.trim().strip_prefix("- ")?.trim().strip_prefix('"')?.strip_suffix('"')
+}
+
+/// Parses `classpath.idx`, a list of quoted dependency jar paths, e.g. `- "BOOT-INF/lib/a.jar"`.
+pub(crate) fn parse_classpath_index(index: &str) -> Result<Vec<String>, JarError> {
+    index.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            index_item(line)
+                .map(str::to_string)
+                .ok_or_else(|| JarError::ManifestParse(format!("malformed classpath index line `{}`", line)))
+        })
+        .collect()
+}
+
+/// Parses `layers.idx`, where each unindented `- "name":` line starts a layer followed by indented
+/// `- "path"` lines.
+fn parse_layers_index(index: &str) -> Result<Vec<Layer>, JarError> {
+    let mut layers: Vec<Layer> = Vec::new();
+
+    for line in index.lines().filter(|line| !line.trim().is_empty()) {
+        let malformed = || JarError::ManifestParse(format!("malformed layers index line `{}`", line));
+
+        if !line.starts_with(char::is_whitespace) {
+            let name = line.strip_suffix(':').and_then(index_item).ok_or_else(malformed)?;
+
+            layers.push(Layer {
+                name: name.to_string(),
+                paths: Vec::new(),
+            });
+        } else {
+            let path = index_item(line).ok_or_else(malformed)?;
+
+            layers.last_mut().ok_or_else(malformed)?.paths.push(path.to_string());
+        }
+    }
+
+    Ok(layers)
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use crate::fixture::{jar_bytes, jar_file};
+    use crate::{spring_boot, JarOptionBuilder};
+
+    #[test]
+    fn test_spring_boot() {
+        let library = jar_bytes(&[("lib/L.class", b"l")]);
+        let boot = spring_boot(jar_file(&[
+            ("META-INF/MANIFEST.MF", b"Main-Class: org.springframework.boot.loader.launch.JarLauncher\r\nStart-Class: com.example.App\r\n"),
+            ("org/springframework/boot/loader/launch/JarLauncher.class", b"launcher"),
+            ("BOOT-INF/classes/com/example/App.class", b"app"),
+            ("BOOT-INF/lib/b.jar", &library),
+            ("BOOT-INF/lib/a.jar", &library),
+            ("BOOT-INF/classpath.idx", b"- \"BOOT-INF/lib/b.jar\"\n- \"BOOT-INF/lib/a.jar\"\n"),
+            ("BOOT-INF/layers.idx", b"- \"dependencies\":\n  - \"BOOT-INF/lib/\"\n- \"spring-boot-loader\":\n  - \"org/\"\n- \"application\":\n  - \"BOOT-INF/classes/\"\n  - \"BOOT-INF/classpath.idx\"\n"),
+        ])).unwrap();
+
+        assert_eq!(boot.start_class().unwrap().as_deref(), Some("com.example.App"));
+        assert_eq!(boot.classes().collect::<Vec<_>>(), vec![("com/example/App.class", b"app".as_slice())]);
+        assert_eq!(boot.libraries().collect::<Vec<_>>(), vec!["BOOT-INF/lib/a.jar", "BOOT-INF/lib/b.jar"]);
+        assert_eq!(boot.classpath(), vec!["BOOT-INF/lib/b.jar", "BOOT-INF/lib/a.jar"]);
+        assert_eq!(boot.layers().iter().map(|layer| layer.name.as_str()).collect::<Vec<_>>(), vec!["dependencies", "spring-boot-loader", "application"]);
+        assert_eq!(boot.layer_of("BOOT-INF/lib/a.jar"), Some("dependencies"));
+        assert_eq!(boot.layer_of("BOOT-INF/classpath.idx"), Some("application"));
+        assert_eq!(boot.layer_of("META-INF/MANIFEST.MF"), None);
+        assert_eq!(boot.library("BOOT-INF/lib/a.jar", JarOptionBuilder::default()).unwrap().files["lib/L.class"], b"l");
+    }
+
+    #[test]
+    fn test_spring_boot_without_indices() {
+        let boot = spring_boot(jar_file(&[
+            ("META-INF/MANIFEST.MF", b"Spring-Boot-Lib: WEB-INF/lib/\r\n"),
+            ("WEB-INF/lib/a.jar", &jar_bytes(&[])),
+        ])).unwrap();
+
+        assert_eq!(boot.classpath(), vec!["WEB-INF/lib/a.jar"]);
+        assert!(boot.layers().is_empty());
+        assert!(spring_boot(jar_file(&[("BOOT-INF/layers.idx", b"  - \"orphan/\"\n")])).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+//! Size and compression statistics of jar entries, computed from central directory metadata.
+
+use std::collections::BTreeMap;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::{Jar, JarEntry};
+#[cfg(feature = "fs")]
+use crate::{jar_list, JarError, JarOption};
+
+/// Entry count and summed sizes of a group of entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SizeTotals {
+    /// Number of entries.
+    pub entries: usize,
+    /// Summed compressed size in bytes.
+    pub compressed_size: u64,
+    /// Summed uncompressed size in bytes.
+    pub size: u64,
+}
+
+impl SizeTotals {
+    /// Returns the compressed size divided by the uncompressed size, where lower is better
+    /// compressed. Returns `1.0` for empty groups.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.size == 0 {
+            1.0
+        } else {
+            self.compressed_size as f64 / self.size as f64
+        }
+    }
+
+    fn add(&mut self, entry: &JarEntry) {
+        self.entries += 1;
+        self.compressed_size = self.compressed_size.saturating_add(entry.compressed_size);
+        self.size = self.size.saturating_add(entry.size);
+    }
+}
+
+/// Statistics of the file entries of a jar, see [Jar::stats] and [jar_stats].
+///
+/// # Example
+///
+/// ```rs
+/// let stats = jar_stats("app.jar", JarOptionBuilder::default())?;
+///
+/// println!("{} entries, ratio {:.2}", stats.total.entries, stats.total.compression_ratio());
+/// for entry in stats.largest(5) {
+///     println!("{} {}", entry.name, entry.size);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JarStats {
+    /// Totals of all file entries.
+    pub total: SizeTotals,
+    /// Totals per package, keyed by the directory of each entry, e.g. `com/example`, or an empty
+    /// string for entries at the archive root.
+    pub packages: BTreeMap<String, SizeTotals>,
+    /// Totals per lowercase file extension, e.g. `class`, or an empty string for entries without
+    /// one.
+    pub extensions: BTreeMap<String, SizeTotals>,
+    entries: Vec<JarEntry>,
+}
+
+impl JarStats {
+    /// Computes statistics of given entries, ignoring directories.
+    pub fn from_entries<'a, I>(entries: I) -> Self where I: IntoIterator<Item = &'a JarEntry> {
+        let mut stats = JarStats::default();
+
+        for entry in entries.into_iter().filter(|entry| !entry.is_dir()) {
+            let (package, file_name) = entry.name.rsplit_once('/').unwrap_or(("", &entry.name));
+            let extension = file_name.rsplit_once('.').map_or(String::new(), |(_, extension)| extension.to_lowercase());
+
+            stats.total.add(entry);
+            stats.packages.entry(package.to_string()).or_default().add(entry);
+            stats.extensions.entry(extension).or_default().add(entry);
+            stats.entries.push(entry.clone());
+        }
+
+        stats.entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        stats
+    }
+
+    /// Returns the `count` largest entries by uncompressed size, largest first.
+    pub fn largest(&self, count: usize) -> &[JarEntry] {
+        &self.entries[..count.min(self.entries.len())]
+    }
+}
+
+impl Jar {
+    /// Computes statistics of all extracted files.
+    pub fn stats(&self) -> JarStats {
+        JarStats::from_entries(self.entries().map(|(entry, _)| entry))
+    }
+}
+
+/// Computes statistics of entries in the jar at `path` matching given `option`. Like [jar_list],
+/// only the central directory is read, so no entry content is decompressed.
+#[cfg(feature = "fs")]
+pub fn jar_stats<P>(path: P, option: JarOption) -> Result<JarStats, JarError> where P: AsRef<Path> {
+    Ok(JarStats::from_entries(&jar_list(path, option)?))
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use crate::fixture::jar_file;
+    use crate::{jar, jar_stats, JarOptionBuilder};
+
+    #[test]
+    fn test_stats() {
+        let path = jar_file(&[
+            ("META-INF/", b""),
+            ("Main.class", b"main"),
+            ("com/example/A.class", &[0; 1000]),
+            ("com/example/b.PNG", b"png"),
+            ("com/example/data/LICENSE", b"license"),
+        ]);
+        let stats = jar_stats(&path, JarOptionBuilder::builder().include_dirs().build()).unwrap();
+
+        assert_eq!(stats.total.entries, 4);
+        assert_eq!(stats.total.size, 1014);
+        assert!(stats.total.compression_ratio() < 1.0);
+        assert_eq!(stats.packages.keys().collect::<Vec<_>>(), vec!["", "com/example", "com/example/data"]);
+        assert_eq!(stats.packages["com/example"].entries, 2);
+        assert_eq!(stats.extensions.iter().map(|(extension, totals)| (extension.as_str(), totals.entries)).collect::<Vec<_>>(), vec![("", 1), ("class", 2), ("png", 1)]);
+        assert_eq!(stats.largest(2).iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(), vec!["com/example/A.class", "com/example/data/LICENSE"]);
+        assert_eq!(stats.largest(10).len(), 4);
+        assert_eq!(jar(&path, JarOptionBuilder::default()).unwrap().stats().total.size, 1014);
+    }
+}
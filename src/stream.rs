@@ -0,0 +1,416 @@
+//! Lazy jar extraction which reads one entry at a time instead of buffering the whole archive.
+
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{Read, Seek};
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+
+use zip::ZipArchive;
+
+use crate::duplicate::{resolve_duplicates, Duplicates};
+use crate::encoding::{decode_name, is_lossy, UnsafeName, UnsafeNamePolicy};
+use crate::integrity::EntryDecoder;
+use crate::limits::check_declared_size;
+use crate::option::Callback;
+use crate::progress::check_cancelled;
+use crate::release::resolve_release;
+use crate::symlink::SymlinkPolicy;
+use crate::trace::{trace, SkipReason, TraceEvent};
+use crate::{JarEntry, JarError, JarOption, JarWarning};
+
+/// A lazy stream over the entries of a jar which pass the extraction rules of a [JarOption].
+///
+/// Since each entry borrows the underlying archive while being read, [JarStream] cannot implement
+/// [Iterator]; use [JarStream::next_entry] in a `while let` loop instead.
+///
+/// # Example
+///
+/// ```rs
+/// let mut stream = jar_stream("sample/rt.jar", JarOptionBuilder::default())?;
+///
+/// while let Some(entry) = stream.next_entry() {
+///     let (file_path, mut reader) = entry?;
+///     // ...
+/// }
+/// ```
+pub struct JarStream<R> {
+    archive: ZipArchive<R>,
+    option: JarOption,
+    index: usize,
+    release_names: Option<HashMap<usize, String>>,
+    duplicates: Duplicates,
+    unsafe_names: Vec<UnsafeName>,
+    warnings: Vec<JarWarning>,
+    matched: usize,
+    matched_size: u64,
+    finished: bool,
+    failed_name: Option<String>,
+}
+
+impl<R> JarStream<R> where R: Read + Seek {
+    pub(crate) fn new(reader: R, option: JarOption) -> Result<Self, JarError> {
+        let mut archive = ZipArchive::new(reader)?;
+        let release_names = match option.release {
+            Some(release) => resolve_release(&mut archive, release, &option)?,
+            None => None,
+        };
+        let duplicates = resolve_duplicates(&mut archive, &option, release_names.as_ref())?;
+
+        trace(&option.trace, || TraceEvent::Opened { entries: archive.len() });
+
+        Ok(Self {
+            archive,
+            option,
+            index: 0,
+            release_names,
+            duplicates,
+            unsafe_names: Vec::new(),
+            warnings: Vec::new(),
+            matched: 0,
+            matched_size: 0,
+            finished: false,
+            failed_name: None,
+        })
+    }
+
+    /// Advances to the next matching entry, returning its full qualified path and a reader of its
+    /// decompressed content. Returns [None] when all entries are consumed.
+    pub fn next_entry(&mut self) -> Option<Result<(String, JarEntryReader<'_>), JarError>> {
+        let (index, entry) = match self.next_match()? {
+            Ok(next) => next,
+            Err(err) => return Some(Err(err)),
+        };
+
+        let capped = self.option.limits.is_set();
+        let cancel_token = self.option.cancel_token.clone();
+
+        match EntryDecoder::open(&mut self.archive, index, entry.compression, !self.option.skip_crc, &self.option.decompressors) {
+            Ok(content) => Some(Ok((entry.name.clone(), JarEntryReader::new(content, entry, index, capped, cancel_token)))),
+            Err(err) => {
+                self.failed_name = Some(entry.name);
+                Some(Err(err))
+            }
+        }
+    }
+
+    /// Returns names shared by more than one entry in the archive, sorted. How such entries are
+    /// extracted is defined by [crate::JarOptionBuilder::duplicate_policy].
+    pub fn duplicates(&self) -> &[String] {
+        &self.duplicates.names
+    }
+
+    /// Returns entry names rejected as unsafe so far, in archive order. How such entries are
+    /// extracted is defined by [crate::JarOptionBuilder::unsafe_name_policy].
+    pub fn unsafe_names(&self) -> &[UnsafeName] {
+        &self.unsafe_names
+    }
+
+    /// Returns non-fatal oddities found in entries walked so far, in archive order.
+    pub fn warnings(&self) -> &[JarWarning] {
+        &self.warnings
+    }
+
+    /// Returns the archive comment stored at the end of the jar, empty if there is none.
+    pub fn comment(&self) -> &[u8] {
+        self.archive.comment()
+    }
+
+    /// Takes the name of the entry whose local header failed to be read by the last call of
+    /// [JarStream::next_entry], if any.
+    pub(crate) fn take_failed_name(&mut self) -> Option<String> {
+        self.failed_name.take()
+    }
+
+    /// Returns the number of all entries in the archive, including directories and entries not
+    /// matching.
+    pub(crate) fn len(&self) -> usize {
+        self.archive.len()
+    }
+
+    #[cfg(feature = "fs")]
+    pub(crate) fn archive_mut(&mut self) -> &mut ZipArchive<R> {
+        &mut self.archive
+    }
+
+    /// Opens the decompressed content of entry `entry` at `index`, decompressed by a registered
+    /// decompressor if any and verified as configured, as entries yielded by the stream are.
+    #[cfg(feature = "fs")]
+    pub(crate) fn decode(&mut self, index: usize, entry: &JarEntry) -> Result<EntryDecoder<'_>, JarError> {
+        EntryDecoder::open(&mut self.archive, index, entry.compression, !self.option.skip_crc, &self.option.decompressors)
+    }
+
+    pub(crate) fn into_archive(self) -> ZipArchive<R> {
+        self.archive
+    }
+
+    /// Finds the next entry to extract by only inspecting central directory records.
+    pub(crate) fn next_match(&mut self) -> Option<Result<(usize, JarEntry), JarError>> {
+        while self.index < self.archive.len() {
+            if let Err(err) = check_cancelled(&self.option.cancel_token) {
+                return Some(Err(err));
+            }
+
+            let index = self.index;
+            self.index += 1;
+
+            let file = match self.archive.by_index_raw(index) {
+                Ok(file) => file,
+                Err(err) => return Some(Err(err.into())),
+            };
+            let skipped = |name: &str, reason| trace(&self.option.trace, || TraceEvent::Skipped { index, name, reason });
+
+            if is_lossy(&file, &self.option) {
+                self.warnings.push(JarWarning::UndecodableName { index, name: String::from_utf8_lossy(file.name_raw()).into_owned() });
+            }
+
+            let mut file_path = match decode_name(&file, &self.option) {
+                Ok(file_path) => file_path,
+                Err(unsafe_name) if self.option.unsafe_name_policy == UnsafeNamePolicy::Error => {
+                    return Some(Err(JarError::InvalidEntryName(unsafe_name.name)));
+                }
+                Err(unsafe_name) => {
+                    let sanitized = unsafe_name.sanitized.clone();
+
+                    if sanitized.is_none() {
+                        skipped(&unsafe_name.name, SkipReason::UnsafeName);
+                    }
+                    self.warnings.push(JarWarning::UnsafeName(unsafe_name.clone()));
+                    self.unsafe_names.push(unsafe_name);
+
+                    match sanitized {
+                        Some(sanitized) => sanitized,
+                        None => continue,
+                    }
+                }
+            };
+
+            if self.duplicates.skipped.contains(&index) {
+                skipped(&file_path, SkipReason::Duplicate);
+                self.warnings.push(JarWarning::DuplicateEntry { index, name: file_path });
+                continue;
+            }
+
+            if file.is_dir() && file.size() > 0 {
+                self.warnings.push(JarWarning::DirectoryContent { name: file_path.clone(), size: file.size() });
+            }
+
+            if file.is_dir() && !self.option.include_dirs {
+                skipped(&file_path, SkipReason::Directory);
+                continue;
+            }
+
+            if let Some(release_names) = &self.release_names {
+                match release_names.get(&index) {
+                    Some(release_name) => file_path = release_name.clone(),
+                    None => {
+                        skipped(&file_path, SkipReason::Release);
+                        continue;
+                    }
+                }
+            }
+
+            if let Some(renamed) = self.duplicates.renamed.get(&index) {
+                file_path = renamed.clone();
+            }
+
+            let mut entry = JarEntry::new(file_path, &file);
+
+            if let Some(reason) = self.option.skip_reason(&entry) {
+                skipped(&entry.name, reason);
+                continue;
+            }
+
+            match self.option.remap(entry.name.clone()) {
+                Some(name) => entry.name = name,
+                None => {
+                    skipped(&entry.name, SkipReason::Renamed);
+                    continue;
+                }
+            }
+
+            if entry.is_symlink() {
+                match self.option.symlink_policy {
+                    SymlinkPolicy::Skip => {
+                        skipped(&entry.name, SkipReason::Symlink);
+                        continue;
+                    }
+                    SymlinkPolicy::Error => return Some(Err(JarError::SymlinkEntry(entry.name))),
+                    SymlinkPolicy::Content | SymlinkPolicy::Materialize => {}
+                }
+            }
+
+            self.matched += 1;
+            self.matched_size = self.matched_size.saturating_add(entry.size);
+
+            if let Err(err) = self.option.limits.check(&entry, self.matched, self.matched_size) {
+                return Some(Err(err));
+            }
+
+            if let Some(timestamp) = self.option.timestamp {
+                entry.last_modified = timestamp;
+            }
+
+            trace(&self.option.trace, || TraceEvent::Matched { index, entry: &entry });
+
+            return Some(Ok((index, entry)));
+        }
+
+        if !self.finished {
+            self.finished = true;
+            trace(&self.option.trace, || TraceEvent::Finished {
+                entries: self.archive.len(),
+                matched: self.matched,
+                matched_size: self.matched_size,
+            });
+        }
+
+        None
+    }
+}
+
+/// A reader of a single jar entry's decompressed content, yielded by [JarStream::next_entry] and
+/// [crate::OpenJar::open].
+pub struct JarEntryReader<'a> {
+    content: EntryDecoder<'a>,
+    entry: JarEntry,
+    index: usize,
+    capped: bool,
+    read: u64,
+    cancel_token: Option<Callback<AtomicBool>>,
+}
+
+impl<'a> JarEntryReader<'a> {
+    pub(crate) fn new(content: EntryDecoder<'a>, entry: JarEntry, index: usize, capped: bool, cancel_token: Option<Callback<AtomicBool>>) -> Self {
+        Self {
+            content,
+            entry,
+            index,
+            capped,
+            read: 0,
+            cancel_token,
+        }
+    }
+
+    /// Returns the metadata of the entry, whose name is the resolved path for multi-release jars.
+    pub fn entry(&self) -> &JarEntry {
+        &self.entry
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the number of decompressed bytes read so far.
+    pub(crate) fn bytes_read(&self) -> u64 {
+        self.read
+    }
+
+    /// Returns the uncompressed size of the entry declared in the archive.
+    pub fn size(&self) -> u64 {
+        self.entry.size
+    }
+
+    /// Returns the Unix mode of the entry, if the jar was created on a Unix-like system.
+    pub fn unix_mode(&self) -> Option<u32> {
+        self.entry.unix_mode
+    }
+}
+
+impl Read for JarEntryReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        check_cancelled(&self.cancel_token)?;
+
+        let read = self.content.read(buf)?;
+        self.read += read as u64;
+
+        if self.capped {
+            check_declared_size(&self.entry, self.read)?;
+        }
+
+        Ok(read)
+    }
+}
+
+/// Opens a jar file from given parameter `path` as a lazy [JarStream]. Unlike [crate::jar], entries are
+/// decompressed only when read, so only one entry's content needs to be held at a time.
+///
+/// # Example
+///
+/// ```rs
+/// let mut stream = jar_stream("sample/rt.jar", JarOptionBuilder::builder().ext("class").build())?;
+/// ```
+#[cfg(feature = "fs")]
+pub fn jar_stream<P>(path: P, option: JarOption) -> Result<JarStream<File>, JarError> where P: AsRef<Path> {
+    JarStream::new(File::open(path)?, option)
+}
+
+/// Lists metadata of entries in the jar at `path` matching given `option`, in archive order. Only
+/// the central directory is read, so no entry content is decompressed.
+///
+/// # Example
+///
+/// ```rs
+/// for entry in jar_list("sample/rt.jar", JarOptionBuilder::builder().ext("class").build())? {
+///     println!("{} {}", entry.name, entry.size);
+/// }
+/// ```
+#[cfg(feature = "fs")]
+pub fn jar_list<P>(path: P, option: JarOption) -> Result<Vec<JarEntry>, JarError> where P: AsRef<Path> {
+    let mut stream = JarStream::new(File::open(path)?, option)?;
+    let mut entries = Vec::new();
+
+    while let Some(next) = stream.next_match() {
+        entries.push(next?.1);
+    }
+
+    Ok(entries)
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::io::Read;
+
+    use crate::fixture::jar_file;
+    use crate::{jar_list, jar_stream, JarOptionBuilder};
+
+    #[test]
+    fn test_stream_filtered_entries() {
+        let path = jar_file(&[
+            ("META-INF/", b""),
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n"),
+            ("java/lang/Object.class", b"object"),
+            ("java/util/List.class", b"list"),
+        ]);
+        let mut stream = jar_stream(&path, JarOptionBuilder::builder().target("java/lang").build()).unwrap();
+        let mut entries = Vec::new();
+
+        while let Some(entry) = stream.next_entry() {
+            let (file_path, mut reader) = entry.unwrap();
+            let mut content = String::new();
+
+            reader.read_to_string(&mut content).unwrap();
+            entries.push((file_path, content));
+        }
+
+        assert_eq!(entries, vec![("java/lang/Object.class".to_string(), "object".to_string())]);
+    }
+
+    #[test]
+    fn test_list_entries() {
+        let path = jar_file(&[
+            ("java/", b""),
+            ("java/util/List.class", b"list"),
+            ("java/lang/Object.class", b"object"),
+            ("java/lang/messages.properties", b"key=value"),
+        ]);
+        let entries = jar_list(&path, JarOptionBuilder::builder().ext("class").build()).unwrap();
+
+        assert_eq!(
+            entries.iter().map(|entry| (entry.name.as_str(), entry.size)).collect::<Vec<_>>(),
+            vec![("java/util/List.class", 4), ("java/lang/Object.class", 6)],
+        );
+    }
+}
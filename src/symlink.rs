@@ -0,0 +1,216 @@
+//! Handling of symbolic link entries, stored by Unix zip tools with the link target as content,
+//! e.g. in jars repackaged from tarballs.
+
+#[cfg(feature = "fs")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "fs")]
+use std::path::{Component, Path, PathBuf};
+
+#[cfg(feature = "fs")]
+use crate::JarError;
+
+/// How symbolic link entries are extracted, set with [crate::JarOptionBuilder::symlink_policy].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SymlinkPolicy {
+    /// Extracts the link as a regular file whose content is the link target, as
+    /// `java.util.zip.ZipFile` does.
+    #[default]
+    Content,
+    /// Creates the link on the file system with [crate::extract_to_dir] on Unix, and otherwise
+    /// behaves like [SymlinkPolicy::Content]. Links whose target escapes the destination directory,
+    /// also through links created before, and entries at or under a created link fail extraction
+    /// with [JarError::InvalidEntryName].
+    Materialize,
+    /// Skips the link.
+    Skip,
+    /// Fails extraction with [JarError::SymlinkEntry].
+    Error,
+}
+
+/// Symbolic links created by [crate::extract_to_dir], guarding against entries written through
+/// them and links escaping the destination directory through them.
+#[cfg(feature = "fs")]
+#[derive(Debug, Default)]
+pub(crate) struct Materialized {
+    /// Created links by relative path, with targets resolved relative to the destination directory.
+    links: HashMap<PathBuf, PathBuf>,
+    /// Paths a `..` component was applied to while resolving targets. Turning one of them into a
+    /// link later would change where the resolved target points.
+    ascended: HashSet<PathBuf>,
+}
+
+#[cfg(feature = "fs")]
+impl Materialized {
+    /// Fails if relative path `path` of entry `file_path` is or is under a link created before,
+    /// since writing through it could escape the destination directory.
+    pub(crate) fn check(&self, file_path: &str, path: &Path) -> Result<(), JarError> {
+        if path.ancestors().any(|ancestor| self.links.contains_key(ancestor)) {
+            return Err(JarError::InvalidEntryName(file_path.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Creates a link at relative path `path` under `dest_dir` pointing to `target`, replacing an
+    /// existing file. Fails if the target is absolute or escapes `dest_dir`, following links
+    /// created before.
+    pub(crate) fn create(&mut self, file_path: &str, dest_dir: &Path, path: &Path, target: &str) -> Result<PathBuf, JarError> {
+        let parent = path.parent().unwrap_or(Path::new(""));
+        let resolved = self.resolve(&parent.join(target))
+            .filter(|_| !self.ascended.contains(path))
+            .ok_or_else(|| JarError::InvalidEntryName(file_path.to_string()))?;
+
+        let dest_path = dest_dir.join(path);
+
+        if let Some(parent) = dest_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if std::fs::symlink_metadata(&dest_path).is_ok() {
+            std::fs::remove_file(&dest_path)?;
+        }
+
+        symlink(target, &dest_path)?;
+        self.links.insert(path.to_path_buf(), resolved);
+
+        Ok(dest_path)
+    }
+
+    /// Resolves relative path `path` against the destination directory, substituting links created
+    /// before by their resolved targets as the file system would. Returns [None] if the path is
+    /// absolute or escapes the destination directory.
+    fn resolve(&mut self, path: &Path) -> Option<PathBuf> {
+        let mut resolved = PathBuf::new();
+
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    self.ascended.extend(resolved.ancestors().filter(|ancestor| !ancestor.as_os_str().is_empty()).map(Path::to_path_buf));
+
+                    if !resolved.pop() {
+                        return None;
+                    }
+                }
+                Component::Normal(name) => {
+                    resolved.push(name);
+
+                    if let Some(target) = self.links.get(&resolved) {
+                        resolved = target.clone();
+                    }
+                }
+                Component::RootDir | Component::Prefix(_) => return None,
+            }
+        }
+
+        Some(resolved)
+    }
+}
+
+#[cfg(all(feature = "fs", unix))]
+fn symlink(target: &str, dest_path: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, dest_path)
+}
+
+#[cfg(all(feature = "fs", not(unix)))]
+fn symlink(target: &str, dest_path: &Path) -> std::io::Result<()> {
+    std::fs::write(dest_path, target)
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    use crate::fixture::temp_path;
+    use crate::{extract_to_dir, jar_from_bytes, JarError, JarOptionBuilder, SymlinkPolicy};
+
+    fn symlink_jar(links: &[(&str, &str)]) -> Vec<u8> {
+        symlink_jar_with(links, &[])
+    }
+
+    /// Builds a jar with an executable script, then `links`, then regular files `files`.
+    fn symlink_jar_with(links: &[(&str, &str)], files: &[&str]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+        writer.start_file("bin/run.sh", FileOptions::default().unix_permissions(0o755)).unwrap();
+        writer.write_all(b"#!/bin/sh\n").unwrap();
+        for (name, target) in links {
+            writer.add_symlink(*name, *target, FileOptions::default()).unwrap();
+        }
+        for name in files {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(b"pwned").unwrap();
+        }
+
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_symlink_policy() {
+        let bytes = symlink_jar(&[("bin/start", "run.sh")]);
+        let extract = |policy| jar_from_bytes(&bytes, JarOptionBuilder::builder().symlink_policy(policy).build());
+
+        let jar = extract(SymlinkPolicy::Content).unwrap();
+        assert!(jar.entry("bin/start").unwrap().is_symlink());
+        assert!(jar.entry("bin/run.sh").unwrap().is_executable());
+        assert_eq!(jar.files["bin/start"], b"run.sh");
+
+        assert!(!extract(SymlinkPolicy::Skip).unwrap().files.contains_key("bin/start"));
+        assert!(matches!(extract(SymlinkPolicy::Error), Err(JarError::SymlinkEntry(name)) if name == "bin/start"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_materialize_symlinks() {
+        let extract_with = |links: &[(&str, &str)], files: &[&str], dest: &str| {
+            let (path, dest) = (temp_path("links.jar"), temp_path(dest));
+            std::fs::write(&path, symlink_jar_with(links, files)).unwrap();
+
+            let option = JarOptionBuilder::builder().symlink_policy(SymlinkPolicy::Materialize).preserve_permissions().build();
+            (extract_to_dir(&path, &dest, option), dest)
+        };
+        let extract = |links: &[(&str, &str)], dest: &str| extract_with(links, &[], dest);
+
+        let (written, dest) = extract(&[("bin/start", "run.sh")], "materialized");
+        assert_eq!(written.unwrap().len(), 2);
+        assert_eq!(std::fs::read_link(dest.join("bin/start")).unwrap().to_str(), Some("run.sh"));
+        assert_eq!(std::fs::read(dest.join("bin/start")).unwrap(), b"#!/bin/sh\n");
+
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(dest.join("bin/run.sh")).unwrap().permissions().mode() & 0o777, 0o755);
+
+        let (written, _) = extract(&[("escape", "../outside")], "materialized-escape");
+        assert!(matches!(written, Err(JarError::InvalidEntryName(name)) if name == "escape"));
+
+        let (written, _) = extract(&[("bin/up", ".."), ("bin/up/escape", "..")], "materialized-through");
+        assert!(matches!(written, Err(JarError::InvalidEntryName(name)) if name == "bin/up/escape"));
+
+        let (written, dest) = extract(&[("x/l", ".."), ("y", "x/l/..")], "materialized-chain");
+        assert!(matches!(written, Err(JarError::InvalidEntryName(name)) if name == "y"));
+        assert!(std::fs::symlink_metadata(dest.join("y")).is_err());
+
+        let (written, dest) = extract_with(&[("x/l", ".."), ("w", "x/l/../pwned")], &["./w"], "materialized-write");
+        assert!(matches!(written, Err(JarError::InvalidEntryName(name)) if name == "w"));
+        assert!(!dest.parent().unwrap().join("pwned").exists());
+
+        let (written, _) = extract_with(&[("w", "bin/run.sh")], &["./w"], "materialized-overwrite");
+        assert!(matches!(written, Err(JarError::InvalidEntryName(name)) if name == "./w"));
+
+        let (written, _) = extract(&[("a", "x/.."), ("x", ".")], "materialized-ascended");
+        assert!(matches!(written, Err(JarError::InvalidEntryName(name)) if name == "x"));
+
+        let (written, dest) = extract(&[("lib/a.so", "a.so.1"), ("lib/a.so.1", "a.so.1.2"), ("up", "lib/../bin")], "materialized-chained");
+        assert_eq!(written.unwrap().len(), 4);
+        assert_eq!(std::fs::read(dest.join("up/run.sh")).unwrap(), b"#!/bin/sh\n");
+
+        let (path, dest, target) = (temp_path("planted.jar"), temp_path("planted"), temp_path("planted-target"));
+        std::fs::write(&path, symlink_jar(&[])).unwrap();
+        std::fs::create_dir_all(dest.join("bin")).unwrap();
+        std::os::unix::fs::symlink(&target, dest.join("bin/run.sh")).unwrap();
+        assert!(matches!(extract_to_dir(&path, &dest, JarOptionBuilder::default()), Err(JarError::InvalidEntryName(name)) if name == "bin/run.sh"));
+        assert!(!target.exists());
+    }
+}
@@ -0,0 +1,158 @@
+//! Telling text resources from binary entries such as class files and images, in the manner of
+//! `grep` and `git diff`.
+
+use crate::{Jar, SERVICES_PREFIX};
+
+/// Extensions of entries considered text regardless of their encoding.
+pub(crate) const TEXT_EXTENSIONS: [&str; 22] = [
+    "txt", "properties", "xml", "json", "yml", "yaml", "mf", "sf", "html", "htm", "css", "js", "java", "kt",
+    "groovy", "scala", "sql", "csv", "conf", "cfg", "ini", "md",
+];
+
+/// Extensions of entries considered binary regardless of their content.
+const BINARY_EXTENSIONS: [&str; 20] = [
+    "class", "jar", "war", "ear", "zip", "gz", "png", "jpg", "jpeg", "gif", "ico", "bmp", "webp", "so", "dll",
+    "dylib", "jnilib", "ttf", "otf", "woff",
+];
+
+/// Number of leading bytes inspected by [is_probably_text], as `git diff` does.
+pub(crate) const SAMPLE_SIZE: usize = 8000;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// Checks whether the entry at `name` with content `bytes` is probably text. Content starting with
+/// a Unicode byte order mark is text, and entries with binary extensions such as `class` or `png`
+/// are not. Otherwise the first 8000 bytes decide: content with null bytes is binary, valid UTF-8
+/// is text, and content in another encoding is only text for text extensions such as
+/// `properties`, which are traditionally ISO-8859-1. Empty content is text.
+///
+/// # Example
+///
+/// ```rs
+/// assert!(is_probably_text("messages.properties", b"greeting=Gr\xFC\xDFe"));
+/// assert!(!is_probably_text("logo.png", b"\x89PNG\r\n\x1A\n"));
+/// ```
+pub fn is_probably_text(name: &str, bytes: &[u8]) -> bool {
+    if [UTF8_BOM, UTF16LE_BOM, UTF16BE_BOM].iter().any(|bom| bytes.starts_with(bom)) {
+        return true;
+    }
+    if has_extension(name, &BINARY_EXTENSIONS) {
+        return false;
+    }
+
+    let sample = &bytes[..bytes.len().min(SAMPLE_SIZE)];
+
+    if sample.contains(&0) {
+        return false;
+    }
+
+    match std::str::from_utf8(sample) {
+        Ok(_) => true,
+        // A sequence cut off at the end of a truncated sample is still valid UTF-8.
+        Err(err) if err.error_len().is_none() && sample.len() < bytes.len() => true,
+        Err(_) => is_text_name(name),
+    }
+}
+
+/// Checks whether the entry at `name` is text by name alone, see [TEXT_EXTENSIONS].
+pub(crate) fn is_text_name(name: &str) -> bool {
+    name.starts_with(SERVICES_PREFIX) || has_extension(name, &TEXT_EXTENSIONS)
+}
+
+fn has_extension(name: &str, extensions: &[&str]) -> bool {
+    name.rsplit_once('.')
+        .is_some_and(|(_, extension)| !extension.contains('/') && extensions.contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+/// Decodes text content, honoring a UTF-8 or UTF-16 byte order mark. Content without one is
+/// decoded as UTF-8, or as ISO-8859-1 if it is not valid UTF-8.
+fn decode_text(bytes: &[u8]) -> String {
+    let utf16 = |bytes: &[u8], decode: fn([u8; 2]) -> u16| {
+        let units = bytes.chunks_exact(2).map(|unit| decode([unit[0], unit[1]])).collect::<Vec<_>>();
+
+        String::from_utf16_lossy(&units)
+    };
+
+    if let Some(bytes) = bytes.strip_prefix(UTF8_BOM) {
+        String::from_utf8_lossy(bytes).into_owned()
+    } else if let Some(bytes) = bytes.strip_prefix(UTF16LE_BOM) {
+        utf16(bytes, u16::from_le_bytes)
+    } else if let Some(bytes) = bytes.strip_prefix(UTF16BE_BOM) {
+        utf16(bytes, u16::from_be_bytes)
+    } else {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => bytes.iter().map(|byte| *byte as char).collect(),
+        }
+    }
+}
+
+impl Jar {
+    /// Iterates over extracted files which are probably text, see [is_probably_text], with their
+    /// decoded content, in no particular order.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// for (name, text) in jar("app.jar", JarOptionBuilder::default())?.text_entries() {
+    ///     println!("{}: {} lines", name, text.lines().count());
+    /// }
+    /// ```
+    pub fn text_entries(&self) -> impl Iterator<Item = (&str, String)> {
+        self.files.iter()
+            .filter(|(name, content)| is_probably_text(name, content))
+            .map(|(name, content)| (name.as_str(), decode_text(content)))
+    }
+
+    /// Returns the decoded content of extracted file `file_path` if it is probably text, see
+    /// [is_probably_text].
+    pub fn text(&self, file_path: &str) -> Option<String> {
+        self.files.get(file_path)
+            .filter(|content| is_probably_text(file_path, content))
+            .map(|content| decode_text(content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{is_probably_text, jar_from_bytes, JarOptionBuilder};
+
+    #[test]
+    fn test_is_probably_text() {
+        assert!(is_probably_text("README", b"plain text\n"));
+        assert!(is_probably_text("empty.bin", b""));
+        assert!(is_probably_text("messages.properties", b"greeting=Gr\xFC\xDFe"));
+        assert!(is_probably_text("notes", b"\xFF\xFEh\0i\0"));
+        assert!(!is_probably_text("notes", b"Gr\xFC\xDFe"));
+        assert!(!is_probably_text("a/A.class", b"\xCA\xFE\xBA\xBE"));
+        assert!(!is_probably_text("data.txt", b"a\0b"));
+        assert!(!is_probably_text("logo.png", b"PNG"));
+
+        let mut long = "a".repeat(7999).into_bytes();
+        long.extend_from_slice("ß".as_bytes());
+        assert!(is_probably_text("long", &long));
+    }
+
+    #[test]
+    fn test_text_entries() {
+        let bytes = jar_bytes(&[
+            ("a/A.class", b"\xCA\xFE\xBA\xBE\0\0\0\x34"),
+            ("messages.properties", b"greeting=Gr\xFC\xDFe"),
+            ("utf16.txt", b"\xFE\xFF\0h\0i"),
+            ("bom.txt", b"\xEF\xBB\xBFhi"),
+        ]);
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap();
+        let mut texts = jar.text_entries().collect::<Vec<_>>();
+
+        texts.sort();
+        assert_eq!(texts, vec![
+            ("bom.txt", "hi".to_string()),
+            ("messages.properties", "greeting=Grüße".to_string()),
+            ("utf16.txt", "hi".to_string()),
+        ]);
+        assert_eq!(jar.text("a/A.class"), None);
+    }
+}
@@ -0,0 +1,149 @@
+//! Diagnostic events of extraction, to find out why an expected entry is missing.
+
+use std::fmt::{Display, Formatter};
+
+use crate::option::Callback;
+use crate::JarEntry;
+
+/// Why an entry of the archive is not extracted, reported with [TraceEvent::Skipped].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SkipReason {
+    /// The entry is a directory and [crate::JarOptionBuilder::include_dirs] is not set.
+    Directory,
+    /// The entry is a symbolic link skipped by [crate::SymlinkPolicy::Skip].
+    Symlink,
+    /// The decoded entry name escapes the jar root, e.g. `../evil.sh`.
+    UnsafeName,
+    /// Another entry of the same name is extracted instead, see [crate::DuplicatePolicy].
+    Duplicate,
+    /// The entry is a variant of a multi-release jar not selected for
+    /// [crate::JarOptionBuilder::release].
+    Release,
+    /// The entry is under an excluded target or has an excluded extension.
+    Excluded,
+    /// Targets, patterns or extensions are set and the entry matches none of them.
+    NotTargeted,
+    /// The entry is nested deeper than [crate::JarOptionBuilder::max_depth].
+    Depth,
+    /// The entry is smaller than the minimum or larger than the maximum size.
+    Size,
+    /// The entry is modified outside the range of [crate::JarOptionBuilder::modified_after] and
+    /// [crate::JarOptionBuilder::modified_before].
+    Modified,
+    /// The entry is not compressed with an accepted compression method.
+    Compression,
+    /// A custom filter of [crate::JarOptionBuilder::filter] rejects the entry.
+    Filter,
+    /// Nothing is left of the path after [crate::JarOptionBuilder::strip_prefix] and
+    /// [crate::JarOptionBuilder::rename].
+    Renamed,
+}
+
+impl Display for SkipReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SkipReason::Directory => "directory",
+            SkipReason::Symlink => "symbolic link",
+            SkipReason::UnsafeName => "name escapes the jar root",
+            SkipReason::Duplicate => "shadowed by a duplicate entry",
+            SkipReason::Release => "not selected for the release",
+            SkipReason::Excluded => "excluded",
+            SkipReason::NotTargeted => "matches no target, pattern or extension",
+            SkipReason::Depth => "nested too deep",
+            SkipReason::Size => "size out of range",
+            SkipReason::Modified => "modification time out of range",
+            SkipReason::Compression => "compression method not accepted",
+            SkipReason::Filter => "rejected by a custom filter",
+            SkipReason::Renamed => "renamed to an empty path",
+        })
+    }
+}
+
+/// An event of extraction, reported to [crate::JarOptionBuilder::on_trace] while the central
+/// directory is walked, before any matching entry is read.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TraceEvent<'a> {
+    /// The archive is opened.
+    Opened {
+        /// Number of all entries in the archive.
+        entries: usize,
+    },
+    /// An entry passes the extraction rules and is extracted.
+    Matched {
+        /// Index of the entry in the archive.
+        index: usize,
+        entry: &'a JarEntry,
+    },
+    /// An entry is skipped.
+    Skipped {
+        /// Index of the entry in the archive.
+        index: usize,
+        /// Decoded name of the entry, or the raw name lossily decoded if it is unsafe.
+        name: &'a str,
+        reason: SkipReason,
+    },
+    /// All entries are inspected.
+    Finished {
+        /// Number of all entries in the archive.
+        entries: usize,
+        /// Number of matching entries.
+        matched: usize,
+        /// Uncompressed size of matching entries in bytes.
+        matched_size: u64,
+    },
+}
+
+pub(crate) type TraceCallback = dyn Fn(&TraceEvent) + Send + Sync;
+
+/// Reports `event` to `callback` if present. The event is only built when traced.
+pub(crate) fn trace<'a, F>(callback: &Option<Callback<TraceCallback>>, event: F) where F: FnOnce() -> TraceEvent<'a> {
+    if let Some(callback) = callback {
+        (callback.0)(&event());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, JarOptionBuilder, SkipReason, TraceEvent};
+
+    #[test]
+    fn test_trace() {
+        let bytes = jar_bytes(&[
+            ("a/", b""),
+            ("a/A.class", b"a"),
+            ("a/A.java", b"a"),
+            ("a/B.class", &[0; 64]),
+            ("a/gen/C.class", b"c"),
+            ("a/A.class", b"last"),
+        ]);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let traced = events.clone();
+        let option = JarOptionBuilder::builder()
+            .ext("class")
+            .exclude_target("a/gen")
+            .max_size(16)
+            .on_trace(move |event| traced.lock().unwrap().push(match event {
+                TraceEvent::Opened { entries } => format!("opened {}", entries),
+                TraceEvent::Matched { index, entry } => format!("{} {}", index, entry.name),
+                TraceEvent::Skipped { index, name, reason } => format!("{} {}: {}", index, name, reason),
+                TraceEvent::Finished { entries, matched, matched_size } => format!("finished {} {} {}", entries, matched, matched_size),
+            }))
+            .build();
+        let jar = jar_from_bytes(&bytes, option).unwrap();
+
+        assert_eq!(jar.files["a/A.class"], b"last");
+        assert_eq!(*events.lock().unwrap(), vec![
+            "opened 6".to_string(),
+            format!("0 a/: {}", SkipReason::Directory),
+            format!("1 a/A.class: {}", SkipReason::Duplicate),
+            format!("2 a/A.java: {}", SkipReason::NotTargeted),
+            format!("3 a/B.class: {}", SkipReason::Size),
+            format!("4 a/gen/C.class: {}", SkipReason::Excluded),
+            "5 a/A.class".to_string(),
+            "finished 6 1 4".to_string(),
+        ]);
+    }
+}
@@ -0,0 +1,255 @@
+//! In-place updates of existing jars, which only recompress changed entries.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::spill::create_new_file;
+use crate::{CompressionMethod, DateTime, JarError, MANIFEST_PATH};
+
+/// Pending changes to an existing jar, applied by [JarUpdater::commit]. Unchanged entries are
+/// copied in compressed form, so updating a single entry of a large jar costs little more than
+/// copying the file.
+///
+/// # Example
+///
+/// ```rs
+/// let mut updater = JarUpdater::open("app.jar")?;
+///
+/// updater.replace("application.properties", b"port=8080")?;
+/// updater.remove("debug.properties")?;
+/// updater.add("NOTICE", b"...")?;
+/// updater.commit()?;
+/// ```
+pub struct JarUpdater {
+    path: PathBuf,
+    archive: ZipArchive<File>,
+    names: HashSet<String>,
+    replaced: HashMap<String, Vec<u8>>,
+    removed: HashSet<String>,
+    added: Vec<(String, Vec<u8>)>,
+    compression: Option<CompressionMethod>,
+}
+
+impl JarUpdater {
+    /// Opens the jar at `path` for updating.
+    pub fn open<P>(path: P) -> Result<Self, JarError> where P: AsRef<Path> {
+        let path = path.as_ref().to_path_buf();
+        let archive = ZipArchive::new(File::open(&path)?)?;
+        let names = archive.file_names().map(str::to_string).collect();
+
+        Ok(Self {
+            path,
+            archive,
+            names,
+            replaced: HashMap::new(),
+            removed: HashSet::new(),
+            added: Vec::new(),
+            compression: None,
+        })
+    }
+
+    /// Sets the compression method of added and replaced entries. By default, replaced entries keep
+    /// their original method and added entries are compressed with [CompressionMethod::Deflated].
+    pub fn compression(mut self, method: CompressionMethod) -> Self {
+        self.compression = Some(method);
+        self
+    }
+
+    /// Checks whether the jar has an entry named `name`, taking pending changes into account.
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name) && !self.removed.contains(name) || self.added.iter().any(|(added, _)| added == name)
+    }
+
+    /// Adds a new entry, which is written after all existing entries, except `META-INF/MANIFEST.MF`
+    /// which is written first. An entry removed before is added anew rather than replaced in
+    /// place. Fails with [JarError::DuplicateEntry] if the entry exists.
+    pub fn add(&mut self, name: &str, content: &[u8]) -> Result<(), JarError> {
+        if self.contains(name) {
+            return Err(JarError::DuplicateEntry(name.to_string()));
+        }
+
+        self.added.push((name.to_string(), content.to_vec()));
+        Ok(())
+    }
+
+    /// Replaces the content of an existing entry, keeping its position and timestamp. Fails with
+    /// [JarError::EntryNotFound] if the entry does not exist.
+    pub fn replace(&mut self, name: &str, content: &[u8]) -> Result<(), JarError> {
+        if !self.contains(name) {
+            return Err(JarError::EntryNotFound(name.to_string()));
+        }
+
+        match self.added.iter_mut().find(|(added, _)| added == name) {
+            Some((_, added)) => *added = content.to_vec(),
+            None => {
+                self.replaced.insert(name.to_string(), content.to_vec());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes an existing entry. Fails with [JarError::EntryNotFound] if the entry does not exist.
+    pub fn remove(&mut self, name: &str) -> Result<(), JarError> {
+        if !self.contains(name) {
+            return Err(JarError::EntryNotFound(name.to_string()));
+        }
+
+        if let Some(index) = self.added.iter().position(|(added, _)| added == name) {
+            self.added.remove(index);
+        } else {
+            self.replaced.remove(name);
+            self.removed.insert(name.to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Applies all pending changes. The updated jar is written to a new temporary file next to the
+    /// original, which then replaces it with the original's permissions, so the original is left
+    /// intact if writing fails.
+    pub fn commit(mut self) -> Result<(), JarError> {
+        let file_name = self.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let dir = self.path.parent().unwrap_or(Path::new(""));
+        let (temp_path, file) = create_new_file(dir, |nonce| format!(".{}.{}.tmp", file_name, nonce))?;
+        let result = self.write(file)
+            .and_then(|()| Ok(std::fs::set_permissions(&temp_path, std::fs::metadata(&self.path)?.permissions())?));
+
+        match result {
+            Ok(()) => Ok(std::fs::rename(&temp_path, &self.path)?),
+            Err(err) => {
+                let _ = std::fs::remove_file(&temp_path);
+                Err(err)
+            }
+        }
+    }
+
+    fn write(&mut self, dest: File) -> Result<(), JarError> {
+        let mut writer = ZipWriter::new(dest);
+        let (manifest, added) = self.added.iter().partition::<Vec<_>, _>(|(name, _)| name == MANIFEST_PATH);
+        let added_options = FileOptions::default()
+            .last_modified_time(DateTime::now().to_zip())
+            .compression_method(self.compression.unwrap_or(CompressionMethod::Deflated).into());
+
+        for (name, content) in manifest {
+            writer.start_file(name.as_str(), added_options)?;
+            writer.write_all(content)?;
+        }
+
+        for index in 0..self.archive.len() {
+            let file = self.archive.by_index_raw(index)?;
+
+            if self.removed.contains(file.name()) {
+                continue;
+            }
+
+            match self.replaced.get(file.name()) {
+                Some(content) => {
+                    let mut options = FileOptions::default()
+                        .compression_method(self.compression.map_or(file.compression(), Into::into))
+                        .last_modified_time(file.last_modified())
+                        .large_file(content.len() as u64 >= u32::MAX as u64);
+
+                    if let Some(mode) = file.unix_mode() {
+                        options = options.unix_permissions(mode);
+                    }
+
+                    let name = file.name().to_string();
+                    writer.start_file(name, options)?;
+                    writer.write_all(content)?;
+                }
+                None => writer.raw_copy_file(file)?,
+            }
+        }
+
+        for (name, content) in added {
+            writer.start_file(name.as_str(), added_options.large_file(content.len() as u64 >= u32::MAX as u64))?;
+            writer.write_all(content)?;
+        }
+
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_file;
+    use crate::{jar, JarError, JarOptionBuilder, JarUpdater};
+
+    #[test]
+    fn test_update() {
+        let path = jar_file(&[
+            ("a/A.class", &[0; 1000]),
+            ("application.properties", b"port=80"),
+            ("debug.properties", b"debug=true"),
+        ]);
+        let original = jar(&path, JarOptionBuilder::default()).unwrap();
+        let mut updater = JarUpdater::open(&path).unwrap();
+
+        updater.replace("application.properties", b"port=8080").unwrap();
+        updater.remove("debug.properties").unwrap();
+        updater.add("NOTICE", b"notice").unwrap();
+        updater.add("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n").unwrap();
+        assert!(matches!(updater.add("a/A.class", b""), Err(JarError::DuplicateEntry(_))));
+        assert!(matches!(updater.remove("debug.properties"), Err(JarError::EntryNotFound(_))));
+        updater.commit().unwrap();
+
+        let updated = jar(&path, JarOptionBuilder::default()).unwrap();
+        let names = updated.ordered_entries().map(|(entry, _)| entry.name.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["META-INF/MANIFEST.MF", "a/A.class", "application.properties", "NOTICE"]);
+        assert_eq!(updated.files["application.properties"], b"port=8080");
+        for name in ["a/A.class", "application.properties"] {
+            let (updated, original) = (updated.entry(name).unwrap(), original.entry(name).unwrap());
+
+            assert_eq!(updated.last_modified, original.last_modified);
+            assert_eq!(updated.compression, original.compression);
+        }
+        assert_eq!(updated.entry("a/A.class").unwrap().compressed_size, original.entry("a/A.class").unwrap().compressed_size);
+    }
+
+    #[test]
+    fn test_update_readd_and_permissions() {
+        let path = jar_file(&[("a.txt", b"a"), ("b.txt", b"b")]);
+        let original = jar(&path, JarOptionBuilder::default()).unwrap();
+        let mut updater = JarUpdater::open(&path).unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        updater.remove("a.txt").unwrap();
+        updater.add("a.txt", b"again").unwrap();
+        updater.commit().unwrap();
+
+        let updated = jar(&path, JarOptionBuilder::default()).unwrap();
+        let names = updated.ordered_entries().map(|(entry, _)| entry.name.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["b.txt", "a.txt"]);
+        assert_eq!(updated.files["a.txt"], b"again");
+        assert_ne!(updated.entry("a.txt").unwrap().last_modified, original.entry("a.txt").unwrap().last_modified);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o640);
+        }
+
+        let temp_prefix = format!(".{}.", path.file_name().unwrap().to_string_lossy());
+        let temp_files = std::fs::read_dir(path.parent().unwrap()).unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with(&temp_prefix))
+            .count();
+
+        assert_eq!(temp_files, 0);
+    }
+}
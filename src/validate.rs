@@ -0,0 +1,247 @@
+//! Well-formedness checks of jars beyond zip integrity, for reviewing untrusted jars.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{Read, Seek};
+#[cfg(feature = "fs")]
+use std::io::BufReader;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use zip::ZipArchive;
+
+use crate::signature::is_signature_file;
+use crate::{parse_manifest, JarError, Manifest, MANIFEST_PATH};
+
+/// Compression ratio above which an entry is reported by [validate], as expected from zip bombs.
+pub const HIGH_COMPRESSION_RATIO: u64 = 100;
+
+/// Entries smaller than this many bytes are not checked for [HIGH_COMPRESSION_RATIO], since small
+/// files of repetitive content legitimately compress that well.
+const MIN_RATIO_CHECKED_SIZE: u64 = 1024 * 1024;
+
+/// A problem found by [validate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// An entry name is absolute, e.g. `/etc/passwd` or `C:\Windows`.
+    AbsolutePath(String),
+    /// An entry name has a `..` component escaping the extraction directory.
+    ParentTraversal(String),
+    /// More than one entry has this name, so tools disagree which one is used.
+    DuplicateName(String),
+    /// An entry name is neither valid UTF-8 nor flagged as another encoding, given lossily decoded.
+    InvalidUtf8Name(String),
+    /// The manifest is neither the first entry nor the second after `META-INF/`, so
+    /// `java.util.jar.JarInputStream` does not find it.
+    ManifestNotFirst {
+        /// Position of the manifest in the central directory.
+        position: usize,
+    },
+    /// The manifest does not follow the manifest format.
+    InvalidManifest(String),
+    /// A file of a signed jar has no digest in the manifest, so it is not covered by signatures.
+    UndigestedEntry(String),
+    /// An entry expands to more than [HIGH_COMPRESSION_RATIO] times its compressed size.
+    HighCompressionRatio {
+        name: String,
+        compressed_size: u64,
+        size: u64,
+    },
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationIssue::AbsolutePath(name) => write!(f, "entry `{}` has an absolute path", name),
+            ValidationIssue::ParentTraversal(name) => write!(f, "entry `{}` escapes its directory with `..`", name),
+            ValidationIssue::DuplicateName(name) => write!(f, "entry name `{}` is used more than once", name),
+            ValidationIssue::InvalidUtf8Name(name) => write!(f, "entry name `{}` is not valid UTF-8", name),
+            ValidationIssue::ManifestNotFirst { position } => write!(f, "manifest is entry {} instead of the first", position),
+            ValidationIssue::InvalidManifest(message) => write!(f, "invalid manifest, {}", message),
+            ValidationIssue::UndigestedEntry(name) => write!(f, "entry `{}` of a signed jar has no manifest digest", name),
+            ValidationIssue::HighCompressionRatio { name, compressed_size, size } => {
+                write!(f, "entry `{}` expands from {} to {} bytes", name, compressed_size, size)
+            }
+        }
+    }
+}
+
+/// Result of [validate].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Number of entries checked.
+    pub entries: usize,
+    /// Problems in central directory order, followed by undigested entries of signed jars.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checks the jar at `path` for problems which extract fine but hint at malicious or broken
+/// archives, see [ValidationIssue]. Only the central directory and the manifest are read. Fails
+/// only if the archive is not a readable zip file at all.
+///
+/// # Example
+///
+/// ```rs
+/// let report = validate("untrusted.jar")?;
+///
+/// for issue in &report.issues {
+///     eprintln!("{}", issue);
+/// }
+/// ```
+#[cfg(feature = "fs")]
+pub fn validate<P>(path: P) -> Result<ValidationReport, JarError> where P: AsRef<Path> {
+    validate_reader(BufReader::new(File::open(path)?))
+}
+
+/// Checks a jar from any seekable source, see [validate].
+pub fn validate_reader<R>(reader: R) -> Result<ValidationReport, JarError> where R: Read + Seek {
+    let mut archive = ZipArchive::new(reader)?;
+    let mut report = ValidationReport {
+        entries: archive.len(),
+        issues: Vec::new(),
+    };
+    let mut names = HashMap::<String, usize>::new();
+    let mut files = Vec::new();
+    let mut manifest_index = None;
+    let mut signed = false;
+    let mut first_is_meta_inf = false;
+
+    for index in 0..archive.len() {
+        let file = archive.by_index_raw(index)?;
+        let name = match std::str::from_utf8(file.name_raw()) {
+            Ok(name) => name.to_string(),
+            Err(_) => {
+                let name = String::from_utf8_lossy(file.name_raw()).into_owned();
+                report.issues.push(ValidationIssue::InvalidUtf8Name(name.clone()));
+                name
+            }
+        };
+
+        if name.starts_with(['/', '\\']) || name.as_bytes().get(1) == Some(&b':') && name.as_bytes()[0].is_ascii_alphabetic() {
+            report.issues.push(ValidationIssue::AbsolutePath(name.clone()));
+        }
+        if name.split(['/', '\\']).any(|component| component == "..") {
+            report.issues.push(ValidationIssue::ParentTraversal(name.clone()));
+        }
+        if file.size() >= MIN_RATIO_CHECKED_SIZE && file.size() > file.compressed_size().max(1).saturating_mul(HIGH_COMPRESSION_RATIO) {
+            report.issues.push(ValidationIssue::HighCompressionRatio {
+                name: name.clone(),
+                compressed_size: file.compressed_size(),
+                size: file.size(),
+            });
+        }
+
+        if index == 0 {
+            first_is_meta_inf = name.eq_ignore_ascii_case("META-INF/");
+        }
+
+        let count = names.entry(name.clone()).or_default();
+        *count += 1;
+        if *count == 2 {
+            report.issues.push(ValidationIssue::DuplicateName(name.clone()));
+        }
+
+        if name.eq_ignore_ascii_case(MANIFEST_PATH) {
+            manifest_index.get_or_insert(index);
+        } else if is_signature_file(&name) && name.to_ascii_uppercase().ends_with(".SF") {
+            signed = true;
+        } else if !file.is_dir() && !is_signature_file(&name) {
+            files.push(name);
+        }
+    }
+
+    let Some(manifest_index) = manifest_index else {
+        return Ok(report);
+    };
+    if manifest_index > 1 || manifest_index == 1 && !first_is_meta_inf {
+        report.issues.push(ValidationIssue::ManifestNotFirst { position: manifest_index });
+    }
+
+    if signed {
+        let mut content = Vec::new();
+
+        archive.by_index(manifest_index)?.read_to_end(&mut content)?;
+        match parse_manifest(&content) {
+            Ok(manifest) => report.issues.extend(undigested(&manifest, files)),
+            Err(err) => report.issues.push(ValidationIssue::InvalidManifest(err.to_string())),
+        }
+    }
+
+    Ok(report)
+}
+
+fn undigested(manifest: &Manifest, mut files: Vec<String>) -> impl Iterator<Item = ValidationIssue> + '_ {
+    files.sort();
+    files.dedup();
+    files.into_iter()
+        .filter(|name| {
+            manifest.entry(name).is_none_or(|attributes| !attributes.iter().any(|(key, _)| key.ends_with("-Digest")))
+        })
+        .map(ValidationIssue::UndigestedEntry)
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::write::FileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    use crate::fixture::{jar_bytes, jar_file};
+    use crate::{validate, validate_reader, ValidationIssue};
+
+    #[test]
+    fn test_validate() {
+        let manifest = b"Manifest-Version: 1.0\r\n\r\nName: a/A.class\r\nSHA-256-Digest: abc\r\n";
+        let path = jar_file(&[
+            ("a/A.class", b"a"),
+            ("META-INF/MANIFEST.MF", manifest),
+            ("META-INF/SIGNER.SF", b""),
+            ("META-INF/SIGNER.RSA", b""),
+            ("a/B.class", b"b"),
+            ("../evil.sh", b""),
+            ("/etc/passwd", b""),
+            ("C:\\evil.bat", b""),
+            ("a/B.class", b"masked"),
+        ]);
+        let report = validate(&path).unwrap();
+
+        assert_eq!(report.entries, 9);
+        assert_eq!(report.issues, vec![
+            ValidationIssue::ParentTraversal("../evil.sh".to_string()),
+            ValidationIssue::AbsolutePath("/etc/passwd".to_string()),
+            ValidationIssue::AbsolutePath("C:\\evil.bat".to_string()),
+            ValidationIssue::DuplicateName("a/B.class".to_string()),
+            ValidationIssue::ManifestNotFirst { position: 1 },
+            ValidationIssue::UndigestedEntry("../evil.sh".to_string()),
+            ValidationIssue::UndigestedEntry("/etc/passwd".to_string()),
+            ValidationIssue::UndigestedEntry("C:\\evil.bat".to_string()),
+            ValidationIssue::UndigestedEntry("a/B.class".to_string()),
+        ]);
+        assert!(!report.is_valid());
+
+        let bytes = jar_bytes(&[("META-INF/", b""), ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n"), ("a/A.class", b"a")]);
+        assert!(validate_reader(Cursor::new(bytes)).unwrap().is_valid());
+    }
+
+    #[test]
+    fn test_validate_compression_ratio() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+        writer.start_file("zeros.bin", FileOptions::default().compression_method(CompressionMethod::Deflated)).unwrap();
+        writer.write_all(&vec![0; 4 * 1024 * 1024]).unwrap();
+
+        let report = validate_reader(writer.finish().unwrap()).unwrap();
+
+        assert!(matches!(&report.issues[..], [ValidationIssue::HighCompressionRatio { name, size: 4194304, .. }] if name == "zeros.bin"));
+        assert!(report.issues[0].to_string().starts_with("entry `zeros.bin` expands from"));
+    }
+}
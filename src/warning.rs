@@ -0,0 +1,73 @@
+//! Non-fatal oddities found while extracting a jar, reported instead of silently ignored.
+
+use std::fmt::{Display, Formatter};
+
+use crate::UnsafeName;
+
+/// A non-fatal oddity found while walking the central directory, see [crate::Jar::warnings] and
+/// [crate::JarStream::warnings].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JarWarning {
+    /// An entry name is unsafe to use as a relative path, so the entry is skipped or sanitized, see
+    /// [crate::UnsafeNamePolicy].
+    UnsafeName(UnsafeName),
+    /// An entry is dropped in favor of another entry of the same name, see
+    /// [crate::DuplicatePolicy].
+    DuplicateEntry {
+        /// Index of the dropped entry in the archive.
+        index: usize,
+        name: String,
+    },
+    /// An entry name is not valid UTF-8 although it is decoded as such, so invalid sequences are
+    /// replaced with `U+FFFD`, see [crate::NameEncoding].
+    UndecodableName {
+        index: usize,
+        name: String,
+    },
+    /// A directory entry has content, which is ignored.
+    DirectoryContent {
+        name: String,
+        /// Declared uncompressed size of the ignored content.
+        size: u64,
+    },
+}
+
+impl Display for JarWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JarWarning::UnsafeName(UnsafeName { name, sanitized: Some(sanitized) }) => {
+                write!(f, "unsafe entry name `{}` extracted as `{}`", name, sanitized)
+            }
+            JarWarning::UnsafeName(UnsafeName { name, sanitized: None }) => write!(f, "unsafe entry name `{}` skipped", name),
+            JarWarning::DuplicateEntry { index, name } => write!(f, "duplicate entry `{}` at index {} dropped", name, index),
+            JarWarning::UndecodableName { index, name } => write!(f, "entry name `{}` at index {} is not valid UTF-8", name, index),
+            JarWarning::DirectoryContent { name, size } => write!(f, "{} bytes of directory entry `{}` ignored", size, name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::jar_bytes;
+    use crate::{jar_from_bytes, JarOptionBuilder, JarWarning, NameEncoding};
+
+    #[test]
+    fn test_warnings() {
+        let mut bytes = jar_bytes(&[("a.txt", b"first"), ("a.txt", b"second"), ("../escape", b""), ("b\u{e9}.txt", b"")]);
+
+        // Corrupts the UTF-8 encoded `é` of the last name in the local and central headers.
+        for position in bytes.windows(3).enumerate().filter(|(_, window)| window == b"b\xC3\xA9").map(|(position, _)| position).collect::<Vec<_>>() {
+            bytes[position + 1] = 0xFF;
+        }
+
+        let jar = jar_from_bytes(&bytes, JarOptionBuilder::builder().name_encoding(NameEncoding::Utf8).build()).unwrap();
+
+        assert_eq!(jar.warnings().len(), 3);
+        assert_eq!(jar.warnings()[0], JarWarning::DuplicateEntry { index: 0, name: "a.txt".to_string() });
+        assert!(matches!(&jar.warnings()[1], JarWarning::UnsafeName(unsafe_name) if unsafe_name.name == "../escape"));
+        assert_eq!(jar.warnings()[2], JarWarning::UndecodableName { index: 3, name: "b\u{FFFD}\u{FFFD}.txt".to_string() });
+        assert_eq!(jar.warnings()[2].to_string(), "entry name `b\u{FFFD}\u{FFFD}.txt` at index 3 is not valid UTF-8");
+
+        assert!(jar_from_bytes(&jar_bytes(&[("a/", b""), ("a/b.txt", b"b")]), JarOptionBuilder::default()).unwrap().warnings().is_empty());
+    }
+}
@@ -0,0 +1,373 @@
+//! Creation of jar files from in-memory entries.
+
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{Seek, Write};
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::time::SystemTime;
+
+use zip::write::FileOptions;
+use zip::{DateTime, ZipWriter};
+
+use crate::{parse_manifest, CompressionMethod, JarError, JarIndex, Manifest, RawEntry, INDEX_PATH, MANIFEST_PATH};
+
+/// Manifest attributes describing the build environment rather than the content, removed by
+/// [JarWriter::deterministic].
+pub(crate) const NONDETERMINISTIC_ATTRIBUTES: [&str; 7] = [
+    "Built-By",
+    "Build-Jdk",
+    "Build-Jdk-Spec",
+    "Build-Date",
+    "Build-Time",
+    "Build-Timestamp",
+    "Bnd-LastModified",
+];
+
+/// A writer which creates a jar entry by entry. `META-INF/MANIFEST.MF` is always written as the
+/// first file entry: either the manifest given to [JarWriter::manifest], the manifest entry passed
+/// to [JarWriter::write_files], or an auto-generated one.
+///
+/// # Example
+///
+/// ```rs
+/// let jar = jar("sample/rt.jar", JarOptionBuilder::builder().keep_meta_info().target("java/lang").build())?;
+/// let mut writer = JarWriter::create("lang.jar")?.level(9);
+///
+/// writer.write_files(&jar.files)?;
+/// writer.finish()?;
+/// ```
+pub struct JarWriter<W> where W: Write + Seek {
+    writer: ZipWriter<W>,
+    compression: CompressionMethod,
+    level: Option<i32>,
+    manifest: Option<Manifest>,
+    manifest_written: bool,
+    index: Option<JarIndex>,
+    deterministic: bool,
+    timestamp: Option<DateTime>,
+    large_files: bool,
+    pending: BTreeMap<String, Vec<u8>>,
+}
+
+#[cfg(feature = "fs")]
+impl JarWriter<File> {
+    /// Creates a [JarWriter] writing to a newly created file at `path`, truncating any existing one.
+    pub fn create<P>(path: P) -> Result<Self, JarError> where P: AsRef<Path> {
+        Ok(Self::new(File::create(path)?))
+    }
+}
+
+impl<W> JarWriter<W> where W: Write + Seek {
+    /// Creates a [JarWriter] on given writer, compressing entries with
+    /// [CompressionMethod::Deflated] at default level.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: ZipWriter::new(writer),
+            compression: CompressionMethod::Deflated,
+            level: None,
+            manifest: None,
+            manifest_written: false,
+            index: None,
+            deterministic: false,
+            timestamp: None,
+            large_files: false,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the compression method of subsequently written entries.
+    pub fn compression(mut self, method: CompressionMethod) -> Self {
+        self.compression = method;
+        self
+    }
+
+    /// Sets the compression level of subsequently written entries, whose valid range depends on the
+    /// compression method, e.g. `0..=9` for [CompressionMethod::Deflated].
+    pub fn level(mut self, level: i32) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// Uses given [Manifest] instead of an auto-generated one.
+    pub fn manifest(mut self, manifest: Manifest) -> Self {
+        self.manifest = Some(manifest);
+        self
+    }
+
+    /// Writes given [JarIndex] as `META-INF/INDEX.LIST` right after the manifest, e.g. one from
+    /// [crate::generate_index].
+    pub fn index(mut self, index: JarIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Makes the output reproducible, so identical entries always produce a byte-identical jar:
+    /// entries are buffered until [JarWriter::finish] and written sorted by path, timestamps are
+    /// fixed to 1980-01-01 00:00:00, and manifest attributes describing the build environment, e.g.
+    /// `Built-By` and `Build-Jdk`, are removed.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let mut writer = JarWriter::create("app.jar")?.deterministic();
+    /// ```
+    pub fn deterministic(mut self) -> Self {
+        self.deterministic = true;
+        self
+    }
+
+    /// Writes all entries with the modification time `epoch` instead of the current time, or of
+    /// 1980-01-01 00:00:00 for a [JarWriter::deterministic] writer, e.g. with the time of
+    /// `SOURCE_DATE_EPOCH` for reproducible builds. Entries written with [JarWriter::write_raw] are
+    /// normalized as well.
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let mut writer = JarWriter::create("app.jar")?.deterministic().normalize_timestamps(epoch);
+    /// ```
+    pub fn normalize_timestamps(mut self, epoch: SystemTime) -> Self {
+        self.timestamp = Some(crate::DateTime::from_system_time(epoch).to_zip());
+        self
+    }
+
+    /// Writes subsequent entries with ZIP64 extra fields, which is required for entries of 4 GiB or
+    /// larger. Writing such an entry without this option fails. Archives of more than 65535 entries
+    /// get a ZIP64 end of central directory regardless.
+    pub fn large_files(mut self) -> Self {
+        self.large_files = true;
+        self
+    }
+
+    /// Writes a single file entry. Writing [MANIFEST_PATH] is only allowed before any other entry.
+    pub fn write_entry(&mut self, name: &str, content: &[u8]) -> Result<(), JarError> {
+        if self.deterministic {
+            if self.pending.insert(name.to_string(), content.to_vec()).is_some() {
+                return Err(JarError::DuplicateEntry(name.to_string()));
+            }
+
+            return Ok(());
+        }
+
+        if name == MANIFEST_PATH {
+            if self.manifest_written {
+                return Err(JarError::DuplicateEntry(MANIFEST_PATH.to_string()));
+            }
+
+            self.write_manifest(content)
+        } else {
+            self.ensure_manifest()?;
+            self.write_file(name, content)
+        }
+    }
+
+    /// Writes an entry from its still compressed content, keeping its compression method,
+    /// timestamp and permissions instead of recompressing it. The timestamp is replaced if set with
+    /// [JarWriter::normalize_timestamps]. The manifest and entries of a
+    /// [JarWriter::deterministic] writer are decompressed and written like [JarWriter::write_entry].
+    pub fn write_raw(&mut self, raw: &RawEntry) -> Result<(), JarError> {
+        let name = raw.entry().name.as_str();
+
+        if self.deterministic || name == MANIFEST_PATH {
+            return self.write_entry(name, &raw.content()?);
+        }
+
+        self.ensure_manifest()?;
+
+        let mut archive = match self.timestamp {
+            Some(timestamp) => raw.archive_at(timestamp.into())?,
+            None => raw.archive()?,
+        };
+        self.writer.raw_copy_file(archive.by_index_raw(0)?)?;
+
+        Ok(())
+    }
+
+    /// Writes all files of a map of full qualified paths to contents, e.g. [crate::Jar::files],
+    /// ordered by path so the output does not depend on map iteration order.
+    pub fn write_files(&mut self, files: &HashMap<String, Vec<u8>>) -> Result<(), JarError> {
+        if let Some(manifest) = files.get(MANIFEST_PATH) {
+            self.write_entry(MANIFEST_PATH, manifest)?;
+        }
+
+        let mut paths = files.keys()
+            .filter(|path| *path != MANIFEST_PATH)
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        for path in paths {
+            self.write_entry(path, &files[path])?;
+        }
+
+        Ok(())
+    }
+
+    /// Finishes the jar by writing the central directory, returning the underlying writer.
+    pub fn finish(mut self) -> Result<W, JarError> {
+        if self.deterministic {
+            let mut pending = std::mem::take(&mut self.pending);
+
+            if let Some(manifest) = pending.remove(MANIFEST_PATH) {
+                self.manifest = Some(parse_manifest(&manifest)?);
+            }
+
+            self.ensure_manifest()?;
+            for (name, content) in pending {
+                self.write_file(&name, &content)?;
+            }
+        }
+
+        self.ensure_manifest()?;
+
+        Ok(self.writer.finish()?)
+    }
+
+    fn ensure_manifest(&mut self) -> Result<(), JarError> {
+        if self.manifest_written {
+            return Ok(());
+        }
+
+        let mut manifest = self.manifest.take().unwrap_or_else(default_manifest);
+
+        if self.deterministic {
+            for name in NONDETERMINISTIC_ATTRIBUTES {
+                manifest.main_attributes_mut().remove(name);
+            }
+        }
+
+        self.write_manifest(&manifest.to_bytes())
+    }
+
+    fn write_manifest(&mut self, content: &[u8]) -> Result<(), JarError> {
+        self.writer.add_directory("META-INF/", self.file_options())?;
+        self.write_file(MANIFEST_PATH, content)?;
+        self.manifest_written = true;
+
+        if let Some(index) = self.index.take() {
+            self.write_file(INDEX_PATH, &index.to_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    fn write_file(&mut self, name: &str, content: &[u8]) -> Result<(), JarError> {
+        self.writer.start_file(name, self.file_options())?;
+        self.writer.write_all(content)?;
+
+        Ok(())
+    }
+
+    fn file_options(&self) -> FileOptions {
+        let options = FileOptions::default()
+            .compression_method(self.compression.into())
+            .compression_level(self.level)
+            .large_file(self.large_files);
+
+        match (self.timestamp, self.deterministic) {
+            (timestamp, true) => options.last_modified_time(timestamp.unwrap_or_default()).unix_permissions(0o644),
+            (Some(timestamp), false) => options.last_modified_time(timestamp),
+            (None, false) => options.last_modified_time(crate::DateTime::now().to_zip()),
+        }
+    }
+}
+
+fn default_manifest() -> Manifest {
+    let mut manifest = Manifest::default();
+    let attributes = manifest.main_attributes_mut();
+
+    attributes.insert("Manifest-Version".to_string(), "1.0".to_string());
+    attributes.insert("Created-By".to_string(), format!("jars {}", env!("CARGO_PKG_VERSION")));
+    manifest
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::Cursor;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::fixture::{jar_file, temp_path};
+    use crate::{
+        jar, jar_from_bytes, jar_open, jar_stream, CompressionMethod, DateTime, JarOptionBuilder, JarWriter, MANIFEST_PATH,
+    };
+
+    #[test]
+    fn test_write_generates_manifest_first() {
+        let path = temp_path("written.jar");
+        let files = HashMap::from([
+            ("b/B.class".to_string(), b"b".to_vec()),
+            ("a/A.class".to_string(), b"a".to_vec()),
+        ]);
+        let mut writer = JarWriter::create(&path).unwrap().compression(CompressionMethod::Stored);
+
+        writer.write_files(&files).unwrap();
+        writer.finish().unwrap();
+
+        let mut stream = jar_stream(&path, JarOptionBuilder::default()).unwrap();
+        let mut paths = Vec::new();
+        while let Some(entry) = stream.next_entry() {
+            paths.push(entry.unwrap().0);
+        }
+        assert_eq!(paths, vec![MANIFEST_PATH, "a/A.class", "b/B.class"]);
+
+        let jar = jar(&path, JarOptionBuilder::default()).unwrap();
+        let manifest = jar.manifest().unwrap().unwrap();
+        assert_eq!(manifest.main_attributes().get("Manifest-Version"), Some("1.0"));
+        assert_eq!(jar.files["a/A.class"], b"a");
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let write = |entries: &[(&str, &[u8])]| {
+            let mut writer = JarWriter::new(Cursor::new(Vec::new())).deterministic();
+
+            for (name, content) in entries {
+                writer.write_entry(name, content).unwrap();
+            }
+
+            writer.finish().unwrap().into_inner()
+        };
+        let manifest = b"Manifest-Version: 1.0\r\nBuilt-By: someone\r\nMain-Class: a.A\r\n";
+        let first = write(&[("b/B.class", b"b"), (MANIFEST_PATH, manifest), ("a/A.class", b"a")]);
+        let second = write(&[("a/A.class", b"a"), ("b/B.class", b"b"), (MANIFEST_PATH, manifest)]);
+
+        assert_eq!(first, second);
+
+        let jar = jar_from_bytes(&first, JarOptionBuilder::default()).unwrap();
+        let manifest = jar.manifest().unwrap().unwrap();
+        assert!(!manifest.main_attributes().contains("Built-By"));
+        assert_eq!(manifest.main_attributes().get("Main-Class"), Some("a.A"));
+        assert_eq!(jar.entry("a/A.class").unwrap().last_modified.year, 1980);
+    }
+
+    #[test]
+    fn test_normalize_timestamps() {
+        let epoch = UNIX_EPOCH + Duration::from_secs(1262304000);
+        let mut source = jar_open(jar_file(&[("a/A.class", b"a")]), JarOptionBuilder::default()).unwrap();
+        let mut writer = JarWriter::new(Cursor::new(Vec::new())).normalize_timestamps(epoch);
+
+        writer.write_entry("b/B.class", b"b").unwrap();
+        writer.write_raw(&source.get_raw("a/A.class").unwrap()).unwrap();
+
+        let jar = jar_from_bytes(&writer.finish().unwrap().into_inner(), JarOptionBuilder::default()).unwrap();
+
+        for name in [MANIFEST_PATH, "a/A.class", "b/B.class"] {
+            assert_eq!(jar.entry(name).unwrap().last_modified, DateTime::from_system_time(epoch));
+        }
+    }
+
+    #[test]
+    fn test_large_files() {
+        let mut writer = JarWriter::new(Cursor::new(Vec::new())).large_files();
+
+        writer.write_entry("a/A.class", b"a").unwrap();
+
+        let bytes = writer.finish().unwrap().into_inner();
+        let local_header = bytes.windows(4).position(|window| window == b"PK\x03\x04").unwrap();
+        // The first local header is `META-INF/` followed by a ZIP64 extra field.
+        assert_eq!(&bytes[local_header + 30 + "META-INF/".len()..][..2], &[1, 0]);
+        assert_eq!(jar_from_bytes(&bytes, JarOptionBuilder::default()).unwrap().files["a/A.class"], b"a");
+    }
+}